@@ -3,16 +3,21 @@ use anyhow::Result;
 use rubato::{
     Resampler, SincFixedIn, SincInterpolationParameters, SincInterpolationType, WindowFunction,
 };
+use serde::{Deserialize, Serialize};
 use std::fs::File;
-use std::io::Write;
 use std::path::Path;
-use symphonia::core::audio::{AudioBufferRef, Signal};
+use symphonia::core::audio::{AudioBuffer, AudioBufferRef, Signal};
 use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
-use symphonia::core::formats::FormatOptions;
-use symphonia::core::io::MediaSourceStream;
+use symphonia::core::conv::IntoSample;
+use symphonia::core::formats::{FormatOptions, FormatReader, Track};
+use symphonia::core::io::{MediaSource, MediaSourceStream};
 use symphonia::core::meta::MetadataOptions;
 use symphonia::core::probe::Hint;
-use tempfile::{NamedTempFile, TempDir};
+use symphonia::core::sample::Sample;
+
+/// リサンプリングで一度に処理する入力フレーム数
+/// - 入力全体ではなく固定長チャンクごとに変換し、メモリ使用量を入力長に依存させない
+const RESAMPLE_CHUNK_FRAMES: usize = 4096;
 
 /// 入力音声ファイルから取得する基本メタデータ
 #[derive(Debug, Clone)]
@@ -24,36 +29,130 @@ pub struct AudioMetadata {
     pub format: String,
 }
 
+/// 多チャンネル音声からどのチャンネルを使うか
+/// - 設定/フォームでは `mix` / `left` / `right` / `separate` / チャンネル番号（0 始まり）で指定する
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum ChannelMode {
+    /// 全チャンネルの単純平均（従来の動作）
+    #[default]
+    Mix,
+    /// 左チャンネル（0 番）
+    Left,
+    /// 右チャンネル（1 番）
+    Right,
+    /// 指定した番号のチャンネル
+    Index(usize),
+    /// チャンネルごとに文字起こしし、結果を時刻順にまとめる
+    Separate,
+}
+
+impl ChannelMode {
+    /// 指定値を解釈（大文字小文字は区別しない）
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        match value.to_ascii_lowercase().as_str() {
+            "mix" | "mono" => Some(ChannelMode::Mix),
+            "left" => Some(ChannelMode::Left),
+            "right" => Some(ChannelMode::Right),
+            "separate" => Some(ChannelMode::Separate),
+            _ => value.parse().ok().map(ChannelMode::Index),
+        }
+    }
+
+    /// 単一チャンネルを選ぶモードで使うチャンネル番号
+    /// - 音声に存在しないチャンネルを指定した場合はエラー
+    pub fn channel_index(&self, channels: usize) -> Result<usize> {
+        let index = match self {
+            ChannelMode::Left => 0,
+            ChannelMode::Right => 1,
+            ChannelMode::Index(index) => *index,
+            ChannelMode::Mix | ChannelMode::Separate => return Ok(0),
+        };
+        if index >= channels {
+            return Err(anyhow::anyhow!(
+                "チャンネル {} は存在しません（音声のチャンネル数: {}）",
+                self,
+                channels
+            ));
+        }
+        Ok(index)
+    }
+
+    /// 出力するサンプル列の数
+    fn output_count(&self, channels: usize) -> Result<usize> {
+        match self {
+            ChannelMode::Separate => Ok(channels.max(1)),
+            mode => mode.channel_index(channels).map(|_| 1),
+        }
+    }
+}
+
+impl std::fmt::Display for ChannelMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChannelMode::Mix => write!(f, "mix"),
+            ChannelMode::Left => write!(f, "left"),
+            ChannelMode::Right => write!(f, "right"),
+            ChannelMode::Index(index) => write!(f, "{}", index),
+            ChannelMode::Separate => write!(f, "separate"),
+        }
+    }
+}
+
+impl TryFrom<String> for ChannelMode {
+    type Error = String;
+
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        ChannelMode::parse(&value).ok_or_else(|| {
+            format!(
+                "channel_mode の値が不正です: {}（mix / left / right / separate / チャンネル番号）",
+                value
+            )
+        })
+    }
+}
+
+impl From<ChannelMode> for String {
+    fn from(mode: ChannelMode) -> Self {
+        mode.to_string()
+    }
+}
+
 /// Whisper に渡す前処理後の音声データ
 #[derive(Debug)]
 pub struct ProcessedAudio {
+    /// モノラル（または選択したチャンネル）のサンプル列（`separate` の場合は空）
     pub samples: Vec<f32>,
+    /// `separate` の場合のチャンネルごとのサンプル列（それ以外は空）
+    pub channel_samples: Vec<Vec<f32>>,
     pub sample_rate: u32,
     pub duration_ms: u64,
     pub original_metadata: AudioMetadata,
 }
 
 /// 音声処理ユーティリティ
-/// - 一時ファイル/ディレクトリの管理
-/// - デコード（symphonia）→ f32 モノラル化
+/// - デコード（symphonia）→ f32 モノラル化（`channel_mode` に応じてチャンネル選択/分離）
 /// - リサンプリング（rubato）でターゲット SR へ
+/// - 入力はファイルに限らず任意の `MediaSource`（メモリ上のバイト列など）から読み、一時ファイルを作らない
 pub struct AudioProcessor {
     config: Config,
-    temp_dir: TempDir,
+    channel_mode: ChannelMode,
 }
 
 impl AudioProcessor {
     pub fn new(config: &Config) -> Result<Self> {
-        // config で指定された temp ディレクトリ配下に、使い捨ての一時ディレクトリを作成
-        // Drop で自動削除されるため、明示的な掃除は不要
-        let temp_dir = TempDir::new_in(&config.paths.temp_dir)?;
-
         Ok(Self {
             config: config.clone(),
-            temp_dir,
+            channel_mode: config.audio.channel_mode,
         })
     }
 
+    /// 使用するチャンネルを指定（既定は設定の `audio.channel_mode`）
+    pub fn set_channel_mode(&mut self, mode: ChannelMode) {
+        self.channel_mode = mode;
+    }
+
     /// 音声ファイルのメタデータを取得
     /// - デコードは行わず、トラック/サンプリングレート/推定再生時間などを確認
     pub fn probe_metadata<P: AsRef<Path>>(&self, file_path: P) -> Result<AudioMetadata> {
@@ -80,10 +179,7 @@ impl AudioProcessor {
         let probed = symphonia::default::get_probe().format(&hint, mss, &fmt_opts, &meta_opts)?;
         let mut format = probed.format;
 
-        let track = format
-            .tracks()
-            .iter()
-            .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+        let track = select_audio_track(format.as_ref())
             .ok_or_else(|| anyhow::anyhow!("音声トラックが見つかりません"))?;
 
         let track_id = track.id;
@@ -156,48 +252,25 @@ impl AudioProcessor {
         })
     }
 
-    /// バイト配列から一時ファイルを作成し、音声データを処理
-    /// - ブラウザからのアップロード（メモリ上のバイト列）に対応する経路
-    pub fn process_audio_from_bytes(
+    /// 任意の `MediaSource` から音声を読み込み、Whisper用のf32サンプルに変換
+    /// - `filename` の拡張子はフォーマット判定のヒントとメタデータに使う
+    /// - パケット単位でデコードし、モノラル化/リサンプリングも逐次行う
+    pub fn process_audio_from_source(
         &mut self,
-        audio_bytes: &[u8],
+        source: Box<dyn MediaSource>,
         filename: &str,
     ) -> Result<ProcessedAudio> {
-        // 一時ファイルを作成
-        let temp_file = self.create_temp_file_from_bytes(audio_bytes, filename)?;
-        let temp_path = temp_file.path();
-
-        self.process_audio_file(temp_path)
+        let extension = Path::new(filename)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_lowercase());
+        self.decode_source(source, extension.as_deref())
     }
 
     /// 音声ファイルを処理してWhisper用のf32サンプルに変換
     /// - デコード→モノラル化→リサンプリング（必要時）→持続時間計算
     pub fn process_audio_file<P: AsRef<Path>>(&mut self, file_path: P) -> Result<ProcessedAudio> {
         let path = file_path.as_ref();
-
-        // メタデータを取得
-        let metadata = self.probe_metadata(path)?;
-
-        // 音声データを読み込み
-        let samples = self.load_audio_file(path)?;
-
-        // 期間を計算（ターゲット SR でのサンプル数から ms を求める）
-        let duration_ms =
-            (samples.len() as f64 / self.config.audio.sample_rate as f64 * 1000.0) as u64;
-
-        Ok(ProcessedAudio {
-            samples,
-            sample_rate: self.config.audio.sample_rate,
-            duration_ms,
-            original_metadata: metadata,
-        })
-    }
-
-    /// 音声ファイルをf32サンプル配列として読み込み
-    /// - symphonia を使って任意フォーマット（wav/mp3/m4a/...）をデコード
-    /// - 多チャンネルは単純平均でモノラル化
-    pub fn load_audio_file<P: AsRef<Path>>(&mut self, file_path: P) -> Result<Vec<f32>> {
-        let path = file_path.as_ref();
         if !path.exists() {
             return Err(anyhow::anyhow!(
                 "音声ファイルが見つかりません: {}",
@@ -206,13 +279,34 @@ impl AudioProcessor {
         }
 
         let file = File::open(path)?;
-        let mss = MediaSourceStream::new(Box::new(file), Default::default());
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_lowercase());
+        self.decode_source(Box::new(file), extension.as_deref())
+    }
+
+    /// 音声ファイルをf32サンプル配列として読み込み
+    /// - symphonia を使って任意フォーマット（wav/mp3/m4a/...）をデコード
+    /// - 多チャンネルは単純平均でモノラル化
+    pub fn load_audio_file<P: AsRef<Path>>(&mut self, file_path: P) -> Result<Vec<f32>> {
+        Ok(self.process_audio_file(file_path)?.samples)
+    }
+
+    /// `MediaSource` をデコードし、ターゲット SR のモノラルサンプルとメタデータを返す
+    /// - 元の SR のサンプル列全体は保持せず、パケットごとにリサンプラーへ流す
+    /// - 設定の最大再生時間を超えた時点でデコードを打ち切る
+    fn decode_source(
+        &self,
+        source: Box<dyn MediaSource>,
+        extension: Option<&str>,
+    ) -> Result<ProcessedAudio> {
+        let file_size_bytes = source.byte_len().unwrap_or(0);
+        let mss = MediaSourceStream::new(source, Default::default());
 
         let mut hint = Hint::new();
-        if let Some(extension) = path.extension() {
-            if let Some(extension_str) = extension.to_str() {
-                hint.with_extension(extension_str);
-            }
+        if let Some(extension) = extension {
+            hint.with_extension(extension);
         }
 
         let meta_opts: MetadataOptions = Default::default();
@@ -222,10 +316,7 @@ impl AudioProcessor {
         let mut format = probed.format;
 
         let (track_id, codec_params) = {
-            let track = format
-                .tracks()
-                .iter()
-                .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+            let track = select_audio_track(format.as_ref())
                 .ok_or_else(|| anyhow::anyhow!("音声トラックが見つかりません"))?;
 
             (track.id, track.codec_params.clone())
@@ -234,7 +325,35 @@ impl AudioProcessor {
         let dec_opts: DecoderOptions = Default::default();
         let mut decoder = symphonia::default::get_codecs().make(&codec_params, &dec_opts)?;
 
-        let mut samples = Vec::new();
+        let original_sample_rate = codec_params
+            .sample_rate
+            .ok_or_else(|| anyhow::anyhow!("サンプリングレートが取得できません"))?;
+        let channels = codec_params
+            .channels
+            .map(|ch| ch.count() as u16)
+            .unwrap_or(1);
+
+        // 再生時間の上限（元の SR でのフレーム数）
+        let max_frames =
+            self.config.limits.max_audio_duration_minutes as u64 * 60 * original_sample_rate as u64;
+        if let Some(n_frames) = codec_params.n_frames {
+            self.check_decoded_duration(n_frames, original_sample_rate, max_frames)?;
+        }
+
+        let target_sample_rate = self.config.audio.sample_rate;
+        let needs_resampling = original_sample_rate.abs_diff(target_sample_rate) > 1;
+        // 出力は長さが分かっていれば先に確保しておく（再確保によるピークを避ける）
+        let expected_len = codec_params.n_frames.map(|n_frames| {
+            let ratio = target_sample_rate as f64 / original_sample_rate as f64;
+            (n_frames as f64 * ratio) as usize + RESAMPLE_CHUNK_FRAMES
+        });
+
+        // 出力ストリーム（`separate` はチャンネル数分、それ以外は 1 本）
+        // - チャンネル数はコンテナによっては最初のパケットをデコードするまで分からないため遅延生成
+        let mut streams: Vec<OutputStream> = Vec::new();
+        let mut packet_samples: Vec<Vec<f32>> = Vec::new();
+        let mut decoded_frames = 0u64;
+        let mut decoded_channels = channels;
 
         // パケットを逐次デコードしてサンプル列を構築
         loop {
@@ -255,7 +374,23 @@ impl AudioProcessor {
 
             match decoder.decode(&packet) {
                 Ok(audio_buf) => {
-                    self.extract_samples_from_buffer(&audio_buf, &mut samples)?;
+                    if streams.is_empty() {
+                        decoded_channels = audio_buf.spec().channels.count() as u16;
+                        let count = self.channel_mode.output_count(decoded_channels as usize)?;
+                        for _ in 0..count {
+                            streams.push(OutputStream::new(
+                                needs_resampling,
+                                original_sample_rate,
+                                target_sample_rate,
+                                expected_len,
+                            )?);
+                        }
+                        packet_samples.resize_with(count, Vec::new);
+                    }
+                    for samples in packet_samples.iter_mut() {
+                        samples.clear();
+                    }
+                    self.extract_samples_from_buffer(&audio_buf, &mut packet_samples)?;
                 }
                 Err(symphonia::core::errors::Error::IoError(ref err))
                     if err.kind() == std::io::ErrorKind::UnexpectedEof =>
@@ -264,49 +399,58 @@ impl AudioProcessor {
                 }
                 Err(err) => return Err(anyhow::anyhow!("デコードエラー: {}", err)),
             }
+
+            decoded_frames += packet_samples[0].len() as u64;
+            self.check_decoded_duration(decoded_frames, original_sample_rate, max_frames)?;
+
+            for (stream, samples) in streams.iter_mut().zip(&packet_samples) {
+                stream.push(samples)?;
+            }
         }
 
-        if samples.is_empty() {
+        if decoded_frames == 0 {
             return Err(anyhow::anyhow!("音声データが空です"));
         }
 
-        // 元のサンプリングレートを取得し、必要に応じてターゲット SR へリサンプリング
-        let original_sample_rate = decoder
-            .codec_params()
-            .sample_rate
-            .or(codec_params.sample_rate)
-            .ok_or_else(|| anyhow::anyhow!("サンプリングレートが取得できません"))?
-            as f64;
+        let mut outputs = streams
+            .into_iter()
+            .map(OutputStream::finish)
+            .collect::<Result<Vec<_>>>()?;
+
+        // 期間を計算（ターゲット SR でのサンプル数から ms を求める）
+        let duration_ms = (outputs[0].len() as f64 / target_sample_rate as f64 * 1000.0) as u64;
 
-        let target_sample_rate = self.config.audio.sample_rate as f64;
-        let resampled = if (original_sample_rate - target_sample_rate).abs() > 1.0 {
-            self.resample_audio(samples, original_sample_rate, target_sample_rate)?
+        let (samples, channel_samples) = if self.channel_mode == ChannelMode::Separate {
+            (Vec::new(), outputs)
         } else {
-            samples
+            (outputs.swap_remove(0), Vec::new())
         };
 
-        Ok(resampled)
+        Ok(ProcessedAudio {
+            samples,
+            channel_samples,
+            sample_rate: target_sample_rate,
+            duration_ms,
+            original_metadata: AudioMetadata {
+                duration_seconds: (decoded_frames as f64 / original_sample_rate as f64) as f32,
+                sample_rate: original_sample_rate,
+                channels: decoded_channels,
+                file_size_bytes,
+                format: extension.unwrap_or("unknown").to_string(),
+            },
+        })
     }
 
-    /// バイト配列から一時ファイルを作成
-    /// - 拡張子は元ファイル名から推測し、デコーダーのヒントに寄与
-    pub fn create_temp_file_from_bytes(
-        &self,
-        bytes: &[u8],
-        filename: &str,
-    ) -> Result<NamedTempFile> {
-        let extension = Path::new(filename)
-            .extension()
-            .and_then(|ext| ext.to_str())
-            .unwrap_or("bin");
-
-        let mut temp_file =
-            NamedTempFile::with_suffix_in(&format!(".{}", extension), &self.temp_dir)?;
-
-        std::io::Write::write_all(&mut temp_file, bytes)?;
-        temp_file.flush()?;
-
-        Ok(temp_file)
+    /// デコード済みのフレーム数が最大再生時間を超えていないか
+    fn check_decoded_duration(&self, frames: u64, sample_rate: u32, max_frames: u64) -> Result<()> {
+        if frames > max_frames {
+            return Err(anyhow::anyhow!(
+                "音声ファイルが長すぎます: {:.1}分超 > {:.1}分",
+                frames as f64 / sample_rate as f64 / 60.0,
+                self.config.limits.max_audio_duration_minutes as f32
+            ));
+        }
+        Ok(())
     }
 
     /// サポートされているファイル形式かチェック
@@ -364,62 +508,150 @@ impl AudioProcessor {
         Ok(())
     }
 
+    /// デコード済みバッファから `channel_mode` に従ってサンプルを取り出す
+    /// - `outputs` は出力ストリームごとのバッファ（`separate` はチャンネル数分）
     fn extract_samples_from_buffer(
         &self,
         audio_buf: &AudioBufferRef,
-        samples: &mut Vec<f32>,
+        outputs: &mut [Vec<f32>],
     ) -> Result<()> {
-        // サンプル型ごとに取り出し、単純平均でモノラル化
+        // サンプル型ごとに f32 へ変換する
+        // - 整数型は符号付き/符号なしともに [-1.0, 1.0) へ正規化される
+        let mode = self.channel_mode;
         match audio_buf {
-            AudioBufferRef::F32(buf) => {
-                let ch = buf.spec().channels.count();
-                let frames = buf.frames();
-                for i in 0..frames {
-                    let mut sum = 0.0f32;
-                    for c in 0..ch {
-                        sum += buf.chan(c)[i];
-                    }
-                    samples.push(sum / ch as f32);
+            AudioBufferRef::U8(buf) => extract_channels(buf, mode, outputs),
+            AudioBufferRef::U16(buf) => extract_channels(buf, mode, outputs),
+            AudioBufferRef::U24(buf) => extract_channels(buf, mode, outputs),
+            AudioBufferRef::U32(buf) => extract_channels(buf, mode, outputs),
+            AudioBufferRef::S8(buf) => extract_channels(buf, mode, outputs),
+            AudioBufferRef::S16(buf) => extract_channels(buf, mode, outputs),
+            AudioBufferRef::S24(buf) => extract_channels(buf, mode, outputs),
+            AudioBufferRef::S32(buf) => extract_channels(buf, mode, outputs),
+            AudioBufferRef::F32(buf) => extract_channels(buf, mode, outputs),
+            AudioBufferRef::F64(buf) => extract_channels(buf, mode, outputs),
+        }
+    }
+}
+
+/// デコードする音声トラックを選ぶ
+/// - 既定トラックにデコーダーがあればそれを使う
+/// - なければデコーダーのある最初のトラック（映像/字幕や未対応コーデックのトラックは飛ばす）
+fn select_audio_track(format: &dyn FormatReader) -> Option<&Track> {
+    let decodable = |track: &Track| {
+        track.codec_params.codec != CODEC_TYPE_NULL
+            && symphonia::default::get_codecs()
+                .get_codec(track.codec_params.codec)
+                .is_some()
+    };
+
+    format
+        .default_track()
+        .filter(|track| decodable(track))
+        .or_else(|| format.tracks().iter().find(|track| decodable(track)))
+}
+
+/// バッファから `mode` に従って f32 サンプルを取り出し、出力ごとに追記する
+/// - `mix` は全チャンネルの単純平均、`left`/`right`/番号指定はそのチャンネルのみ
+/// - `separate` はチャンネルごとに別の出力へ
+fn extract_channels<S>(
+    buf: &AudioBuffer<S>,
+    mode: ChannelMode,
+    outputs: &mut [Vec<f32>],
+) -> Result<()>
+where
+    S: Sample + IntoSample<f32>,
+{
+    let ch = buf.spec().channels.count();
+    match mode {
+        ChannelMode::Mix => {
+            for i in 0..buf.frames() {
+                let mut sum = 0.0f32;
+                for c in 0..ch {
+                    sum += buf.chan(c)[i].into_sample();
                 }
+                outputs[0].push(sum / ch as f32);
             }
-            AudioBufferRef::S32(buf) => {
-                let ch = buf.spec().channels.count();
-                let frames = buf.frames();
-                for i in 0..frames {
-                    let mut sum = 0.0f32;
-                    for c in 0..ch {
-                        sum += buf.chan(c)[i] as f32 / i32::MAX as f32;
-                    }
-                    samples.push(sum / ch as f32);
-                }
+        }
+        ChannelMode::Separate => {
+            for (c, output) in outputs.iter_mut().enumerate().take(ch) {
+                output.extend(
+                    buf.chan(c)
+                        .iter()
+                        .map(|&sample| IntoSample::<f32>::into_sample(sample)),
+                );
             }
-            AudioBufferRef::S16(buf) => {
-                let ch = buf.spec().channels.count();
-                let frames = buf.frames();
-                for i in 0..frames {
-                    let mut sum = 0.0f32;
-                    for c in 0..ch {
-                        sum += buf.chan(c)[i] as f32 / i16::MAX as f32;
-                    }
-                    samples.push(sum / ch as f32);
-                }
+        }
+        mode => {
+            let c = mode.channel_index(ch)?;
+            outputs[0].extend(
+                buf.chan(c)
+                    .iter()
+                    .map(|&sample| IntoSample::<f32>::into_sample(sample)),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// デコード結果の出力先（必要に応じてリサンプリングする）
+struct OutputStream {
+    resampler: Option<ChunkedResampler>,
+    samples: Vec<f32>,
+}
+
+impl OutputStream {
+    fn new(
+        needs_resampling: bool,
+        input_rate: u32,
+        output_rate: u32,
+        expected_len: Option<usize>,
+    ) -> Result<Self> {
+        let resampler = if needs_resampling {
+            Some(ChunkedResampler::new(
+                input_rate as f64,
+                output_rate as f64,
+            )?)
+        } else {
+            None
+        };
+        Ok(Self {
+            resampler,
+            samples: Vec::with_capacity(expected_len.unwrap_or(0)),
+        })
+    }
+
+    fn push(&mut self, samples: &[f32]) -> Result<()> {
+        match self.resampler.as_mut() {
+            Some(resampler) => resampler.push(samples, &mut self.samples),
+            None => {
+                self.samples.extend_from_slice(samples);
+                Ok(())
             }
-            _ => return Err(anyhow::anyhow!("サポートされていない音声フォーマットです")),
         }
-        Ok(())
     }
 
-    fn resample_audio(
-        &self,
-        samples: Vec<f32>,
-        input_rate: f64,
-        output_rate: f64,
-    ) -> Result<Vec<f32>> {
-        // SR がほぼ同等ならそのまま返す
-        if (input_rate - output_rate).abs() < 1.0 {
-            return Ok(samples);
+    fn finish(mut self) -> Result<Vec<f32>> {
+        if let Some(resampler) = self.resampler.take() {
+            resampler.finish(&mut self.samples)?;
         }
+        self.samples.shrink_to_fit();
+        Ok(self.samples)
+    }
+}
+
+/// 固定長チャンクでのリサンプリング（モノラル）
+/// - 入力を `RESAMPLE_CHUNK_FRAMES` ずつ変換して出力へ追記する
+/// - 末尾はフィルタに残ったサンプルを吐き出し、出力長を入力長×変換比に揃える
+struct ChunkedResampler {
+    resampler: SincFixedIn<f32>,
+    pending: Vec<f32>,
+    input_frames: u64,
+    output_frames: u64,
+    ratio: f64,
+}
 
+impl ChunkedResampler {
+    fn new(input_rate: f64, output_rate: f64) -> Result<Self> {
         let params = SincInterpolationParameters {
             sinc_len: 256,
             f_cutoff: 0.95,
@@ -429,24 +661,65 @@ impl AudioProcessor {
         };
 
         // 高品質な Sinc 補間でのリサンプリング
-        let mut resampler = SincFixedIn::<f32>::new(
-            output_rate / input_rate,
+        let ratio = output_rate / input_rate;
+        let resampler = SincFixedIn::<f32>::new(
+            ratio,
             2.0,
             params,
-            samples.len(),
+            RESAMPLE_CHUNK_FRAMES,
             1, // モノラル
         )?;
 
-        let input_channels = vec![samples];
-        let output_channels = resampler.process(&input_channels, None)?;
+        Ok(Self {
+            resampler,
+            pending: Vec::with_capacity(RESAMPLE_CHUNK_FRAMES * 2),
+            input_frames: 0,
+            output_frames: 0,
+            ratio,
+        })
+    }
 
-        Ok(output_channels[0].clone())
+    /// 入力を追加し、チャンクが揃った分だけ変換する
+    fn push(&mut self, samples: &[f32], output: &mut Vec<f32>) -> Result<()> {
+        self.input_frames += samples.len() as u64;
+        self.pending.extend_from_slice(samples);
+
+        let mut offset = 0;
+        while self.pending.len() - offset >= RESAMPLE_CHUNK_FRAMES {
+            let chunk = &self.pending[offset..offset + RESAMPLE_CHUNK_FRAMES];
+            let converted = self.resampler.process(&[chunk], None)?;
+            self.append(&converted[0], output);
+            offset += RESAMPLE_CHUNK_FRAMES;
+        }
+        self.pending.drain(..offset);
+        Ok(())
+    }
+
+    /// 残りの入力を変換し、出力長を揃える
+    fn finish(mut self, output: &mut Vec<f32>) -> Result<()> {
+        let expected = (self.input_frames as f64 * self.ratio).round() as u64;
+
+        if !self.pending.is_empty() {
+            let pending = std::mem::take(&mut self.pending);
+            let converted = self.resampler.process_partial(Some(&[pending]), None)?;
+            self.append(&converted[0], output);
+        }
+        while self.output_frames < expected {
+            let converted = self.resampler.process_partial::<Vec<f32>>(None, None)?;
+            if converted[0].is_empty() {
+                break;
+            }
+            self.append(&converted[0], output);
+        }
+
+        let excess = self.output_frames.saturating_sub(expected) as usize;
+        output.truncate(output.len() - excess);
+        Ok(())
     }
-}
 
-impl Drop for AudioProcessor {
-    fn drop(&mut self) {
-        // 一時ディレクトリは自動的にクリーンアップされます
+    fn append(&mut self, converted: &[f32], output: &mut Vec<f32>) {
+        output.extend_from_slice(converted);
+        self.output_frames += converted.len() as u64;
     }
 }
 
@@ -0,0 +1,278 @@
+use crate::config::{ApiKeyConfig, AuthConfig};
+use crate::models::ApiKeyUsage;
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// =============================================================================
+// API キー認証とレート制限
+// - `Authorization: Bearer <key>` を設定ファイルのキーと照合する
+// - キーごとに直近 1 分間のリクエスト数を数え、上限を超えたら拒否する
+// - 管理操作のルートは管理者キー（`admin = true`）だけに許可する
+// - 月間の音声時間（クォータ）はサーバー統計のキー別使用量で判定する
+//   （使用量はファイルに保存し、再起動しても数え直さない）
+// =============================================================================
+
+/// レート制限を数える期間
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// 管理者キー（`admin = true`）だけが使えるルート（メソッド, ルートのパターン）
+const ADMIN_ROUTES: &[(&str, &str)] = &[
+    ("POST", "/admin/models/{name}/download"),
+    ("GET", "/admin/models/{name}/download"),
+    ("GET", "/admin/downloads"),
+    ("POST", "/models/{name}/load"),
+    ("POST", "/models/{name}/unload"),
+    ("PUT", "/glossaries/{name}"),
+    ("DELETE", "/glossaries/{name}"),
+];
+
+/// 認証済みの呼び出し元
+/// - ミドルウェアがリクエストの拡張に入れ、ハンドラが使用量の記録と所有者の確認に使う
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiCaller {
+    /// キー名
+    pub name: String,
+    /// 管理者キーかどうか
+    pub admin: bool,
+}
+
+impl ApiCaller {
+    /// 履歴などを作成したキーのものに絞る場合のキー名
+    /// - 管理者キーは絞らない（None）
+    pub fn owner_scope(&self) -> Option<&str> {
+        (!self.admin).then_some(self.name.as_str())
+    }
+}
+
+/// 認証/制限で拒否した理由
+#[derive(Debug, Clone, PartialEq)]
+pub enum AuthRejection {
+    /// `Authorization` ヘッダーが無い/Bearer 形式でない
+    MissingKey,
+    /// 登録されていないキー
+    InvalidKey,
+    /// 管理者キーでない（管理操作のルート）
+    AdminRequired,
+    /// 1 分あたりのリクエスト数の上限を超えた
+    RateLimited { limit: u32, retry_after: Duration },
+    /// 今月の音声時間の上限を超えた
+    QuotaExceeded {
+        used_minutes: u64,
+        quota_minutes: u64,
+    },
+}
+
+impl AuthRejection {
+    pub fn message(&self) -> String {
+        match self {
+            AuthRejection::MissingKey => {
+                "API キーが必要です（Authorization: Bearer <key>）".to_string()
+            }
+            AuthRejection::InvalidKey => "API キーが正しくありません".to_string(),
+            AuthRejection::AdminRequired => "この操作には管理者の API キーが必要です".to_string(),
+            AuthRejection::RateLimited { limit, .. } => {
+                format!("リクエスト数の上限（{}回/分）を超えました", limit)
+            }
+            AuthRejection::QuotaExceeded {
+                used_minutes,
+                quota_minutes,
+            } => format!(
+                "今月の音声時間の上限を超えました（{}分 / {}分）",
+                used_minutes, quota_minutes
+            ),
+        }
+    }
+}
+
+/// API キーの照合とキーごとのレート制限
+pub struct ApiKeyAuth {
+    config: AuthConfig,
+    /// キー名 → 直近 1 分間に受け付けたリクエストの時刻
+    recent: Mutex<HashMap<String, VecDeque<Instant>>>,
+}
+
+impl ApiKeyAuth {
+    pub fn new(config: AuthConfig) -> Self {
+        Self {
+            config,
+            recent: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// キー無しで利用できるパスかどうか
+    pub fn is_public(&self, path: &str) -> bool {
+        self.config.public_paths.iter().any(|public| public == path)
+    }
+
+    /// 管理者キーだけが使えるルートかどうか
+    /// - `route` はルートのパターン（`/models/{name}/load` など）
+    pub fn requires_admin(&self, method: &str, route: &str) -> bool {
+        ADMIN_ROUTES
+            .iter()
+            .any(|(admin_method, admin_route)| *admin_method == method && *admin_route == route)
+    }
+
+    /// `Authorization` ヘッダーの値からキーを特定する
+    /// - 比較は一定時間で行い、キーの推測に使える応答時間の差を作らない
+    pub fn authenticate(
+        &self,
+        authorization: Option<&str>,
+    ) -> Result<&ApiKeyConfig, AuthRejection> {
+        let token = authorization
+            .and_then(bearer_token)
+            .ok_or(AuthRejection::MissingKey)?;
+
+        self.config
+            .keys
+            .iter()
+            .fold(None, |found, key| {
+                let matched = constant_time_eq(key.key.as_bytes(), token.as_bytes());
+                found.or(matched.then_some(key))
+            })
+            .ok_or(AuthRejection::InvalidKey)
+    }
+
+    /// キー名から設定を引く
+    pub fn key(&self, name: &str) -> Option<&ApiKeyConfig> {
+        self.config.keys.iter().find(|key| key.name == name)
+    }
+
+    /// レート制限を確認し、受け付けるならリクエストを数える
+    /// - 直近 1 分間（スライディングウィンドウ）の件数で判定する
+    pub fn check_rate(&self, key: &ApiKeyConfig, now: Instant) -> Result<(), AuthRejection> {
+        if key.requests_per_minute == 0 {
+            return Ok(());
+        }
+
+        let mut recent = self.recent.lock().unwrap();
+        let times = recent.entry(key.name.clone()).or_default();
+        while times
+            .front()
+            .is_some_and(|time| now.duration_since(*time) >= RATE_WINDOW)
+        {
+            times.pop_front();
+        }
+
+        if times.len() >= key.requests_per_minute as usize {
+            // 最も古いリクエストが期間外になれば受け付けられる
+            let retry_after = times
+                .front()
+                .map(|oldest| RATE_WINDOW.saturating_sub(now.duration_since(*oldest)))
+                .unwrap_or(RATE_WINDOW);
+            return Err(AuthRejection::RateLimited {
+                limit: key.requests_per_minute,
+                retry_after,
+            });
+        }
+        times.push_back(now);
+        Ok(())
+    }
+}
+
+/// 管理操作のルートなら管理者キーかどうかを確認する
+pub fn check_admin(key: &ApiKeyConfig, admin_only: bool) -> Result<(), AuthRejection> {
+    if admin_only && !key.admin {
+        return Err(AuthRejection::AdminRequired);
+    }
+    Ok(())
+}
+
+/// 今月の使用量（ミリ秒）がキーの上限に達していないか確認する
+/// - 受付時点に加え、1 リクエストで複数の音声を推論する場合は各音声の前にも判定する
+///   （`AppState::check_key_quota`）。推論中の 1 件が上限を少し超えることはある
+pub fn check_quota(key: &ApiKeyConfig, used_ms: u64) -> Result<(), AuthRejection> {
+    if key.monthly_audio_minutes == 0 || used_ms < key.monthly_audio_minutes * 60_000 {
+        return Ok(());
+    }
+    Err(AuthRejection::QuotaExceeded {
+        used_minutes: used_ms / 60_000,
+        quota_minutes: key.monthly_audio_minutes,
+    })
+}
+
+/// API キーごとの使用量の保存先（JSON ファイル）
+/// - 起動時に読み込んでサーバー統計に戻し、使用量が変わるたびに書き出す
+pub struct UsageFile {
+    path: PathBuf,
+    /// 書き出しの直列化（古い使用量で新しいものを上書きしない）
+    writing: Mutex<()>,
+}
+
+impl UsageFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            writing: Mutex::new(()),
+        }
+    }
+
+    /// 保存した使用量を読み込む（ファイルが無ければ空）
+    /// - 読めない場合は警告して空から数える
+    pub fn load(&self) -> BTreeMap<String, ApiKeyUsage> {
+        let bytes = match fs::read(&self.path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return BTreeMap::new(),
+            Err(e) => {
+                eprintln!(
+                    "API キーの使用量を読み込めません: {} ({})",
+                    self.path.display(),
+                    e
+                );
+                return BTreeMap::new();
+            }
+        };
+        serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+            eprintln!(
+                "API キーの使用量を読み込めません: {} ({})",
+                self.path.display(),
+                e
+            );
+            BTreeMap::new()
+        })
+    }
+
+    /// `snapshot` で取った使用量を書き出す
+    /// - 書き出し中の呼び出しは待たせ、待った後に取った最新の使用量を書く
+    /// - 書きかけのファイルを読まないよう、一時ファイルに書いてから置き換える
+    pub fn save(&self, snapshot: impl FnOnce() -> BTreeMap<String, ApiKeyUsage>) -> Result<()> {
+        let _writing = self.writing.lock().unwrap();
+        let usage = snapshot();
+
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("ディレクトリを作成できません: {}", dir.display()))?;
+        }
+        let temp_path = self.path.with_extension("json.tmp");
+        fs::write(&temp_path, serde_json::to_vec_pretty(&usage)?)
+            .and_then(|_| fs::rename(&temp_path, &self.path))
+            .with_context(|| {
+                format!(
+                    "API キーの使用量を保存できません: {}",
+                    self.path.display()
+                )
+            })
+    }
+}
+
+/// `Bearer <token>` からトークンを取り出す（スキーム名の大文字小文字は問わない）
+fn bearer_token(header: &str) -> Option<&str> {
+    let (scheme, token) = header.trim().split_once(' ')?;
+    let token = token.trim();
+    (scheme.eq_ignore_ascii_case("bearer") && !token.is_empty()).then_some(token)
+}
+
+/// 長さ以外の情報を比較時間から漏らさないバイト列比較
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
@@ -0,0 +1,299 @@
+use crate::models::ErrorResponse;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io::{Cursor, Read, Write};
+use std::path::{Component, Path};
+
+// =============================================================================
+// 一括文字起こし（`POST /transcribe/batch`）
+// - 複数の `file` フィールドや ZIP/tar アーカイブから音声を取り出す
+// - 結果はファイルごとの字幕/テキストと `manifest.json` をまとめた ZIP で返す
+// =============================================================================
+
+/// 1 回の一括処理で扱えるファイル数の上限
+pub const MAX_BATCH_FILES: usize = 500;
+/// 結果 ZIP に入れる一覧のファイル名
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// 文字起こしする 1 ファイル
+#[derive(Debug, Clone)]
+pub struct BatchInput {
+    /// アップロード時のファイル名、またはアーカイブ内のパス（`/` 区切り）
+    pub name: String,
+    pub data: Vec<u8>,
+}
+
+/// 文字起こししなかったファイル
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SkippedFile {
+    pub name: String,
+    pub reason: String,
+}
+
+/// アップロード/アーカイブから取り出した音声
+#[derive(Debug, Default)]
+pub struct BatchInputs {
+    pub files: Vec<BatchInput>,
+    pub skipped: Vec<SkippedFile>,
+    total_bytes: usize,
+}
+
+/// 取り出す際の制限
+#[derive(Debug, Clone)]
+pub struct BatchLimits {
+    /// アーカイブ内で音声として扱う拡張子（小文字）
+    pub supported_formats: Vec<String>,
+    /// 1 ファイルの上限（バイト）。超えるものは文字起こしせずに記録する
+    pub max_file_bytes: usize,
+    /// 展開後の合計の上限（バイト）。超えたら一括処理全体をエラーにする
+    pub max_total_bytes: usize,
+}
+
+impl BatchInputs {
+    /// アップロードされた 1 ファイルを追加する
+    /// - `.zip` / `.tar` は展開し、対応形式の拡張子を持つ音声だけを取り出す
+    /// - それ以外は音声としてそのまま追加する（形式は文字起こし時に検証）
+    pub fn add_upload(
+        &mut self,
+        filename: &str,
+        data: Vec<u8>,
+        limits: &BatchLimits,
+    ) -> Result<()> {
+        match archive_kind(filename, &data) {
+            Some(ArchiveKind::Zip) => self.add_zip(filename, data, limits),
+            Some(ArchiveKind::Tar) => self.add_tar(filename, data, limits),
+            None => self.add_file(filename.to_string(), data, limits),
+        }
+    }
+
+    fn add_zip(&mut self, archive_name: &str, data: Vec<u8>, limits: &BatchLimits) -> Result<()> {
+        let mut archive = zip::ZipArchive::new(Cursor::new(data))
+            .with_context(|| format!("ZIP を読み込めません: {}", archive_name))?;
+
+        for index in 0..archive.len() {
+            let entry = archive
+                .by_index(index)
+                .with_context(|| format!("ZIP を読み込めません: {}", archive_name))?;
+            if !entry.is_file() {
+                continue;
+            }
+            let Some(name) = entry.enclosed_name().and_then(|path| entry_name(&path)) else {
+                continue;
+            };
+            let size = entry.size();
+            self.add_entry(name, size, entry, limits)?;
+        }
+        Ok(())
+    }
+
+    fn add_tar(&mut self, archive_name: &str, data: Vec<u8>, limits: &BatchLimits) -> Result<()> {
+        let mut archive = tar::Archive::new(Cursor::new(data));
+        let entries = archive
+            .entries()
+            .with_context(|| format!("tar を読み込めません: {}", archive_name))?;
+
+        for entry in entries {
+            let entry = entry.with_context(|| format!("tar を読み込めません: {}", archive_name))?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let Some(name) = entry.path().ok().and_then(|path| entry_name(&path)) else {
+                continue;
+            };
+            let size = entry.size();
+            self.add_entry(name, size, entry, limits)?;
+        }
+        Ok(())
+    }
+
+    /// アーカイブ内の 1 エントリを追加（隠しファイル/対応外の拡張子は記録して飛ばす）
+    fn add_entry(
+        &mut self,
+        name: String,
+        size: u64,
+        reader: impl Read,
+        limits: &BatchLimits,
+    ) -> Result<()> {
+        if name
+            .split('/')
+            .any(|part| part.starts_with('.') || part == "__MACOSX")
+        {
+            return Ok(());
+        }
+        if !has_supported_extension(&name, &limits.supported_formats) {
+            self.skip(name, "対応していない形式です");
+            return Ok(());
+        }
+        if size > limits.max_file_bytes as u64 {
+            self.skip(name, "ファイルサイズが制限を超えています");
+            return Ok(());
+        }
+
+        // 申告サイズを信用せず、上限 + 1 バイトまでしか読まない
+        let mut data = Vec::new();
+        reader
+            .take(limits.max_file_bytes as u64 + 1)
+            .read_to_end(&mut data)
+            .with_context(|| format!("アーカイブから取り出せません: {}", name))?;
+        self.add_file(name, data, limits)
+    }
+
+    fn add_file(&mut self, name: String, data: Vec<u8>, limits: &BatchLimits) -> Result<()> {
+        if data.len() > limits.max_file_bytes {
+            self.skip(name, "ファイルサイズが制限を超えています");
+            return Ok(());
+        }
+        if self.files.len() >= MAX_BATCH_FILES {
+            return Err(anyhow::anyhow!(
+                "一度に文字起こしできるのは {} ファイルまでです",
+                MAX_BATCH_FILES
+            ));
+        }
+        self.total_bytes += data.len();
+        if self.total_bytes > limits.max_total_bytes {
+            return Err(anyhow::anyhow!(
+                "展開後の合計サイズが制限（{} バイト）を超えています",
+                limits.max_total_bytes
+            ));
+        }
+        self.files.push(BatchInput { name, data });
+        Ok(())
+    }
+
+    fn skip(&mut self, name: String, reason: &str) {
+        self.skipped.push(SkippedFile {
+            name,
+            reason: reason.to_string(),
+        });
+    }
+}
+
+enum ArchiveKind {
+    Zip,
+    Tar,
+}
+
+/// 拡張子（無ければ先頭のシグネチャ）でアーカイブを判定
+fn archive_kind(filename: &str, data: &[u8]) -> Option<ArchiveKind> {
+    let extension = Path::new(filename)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase());
+
+    match extension.as_deref() {
+        Some("zip") => Some(ArchiveKind::Zip),
+        Some("tar") => Some(ArchiveKind::Tar),
+        _ if data.starts_with(b"PK\x03\x04") => Some(ArchiveKind::Zip),
+        _ if data.get(257..262) == Some(b"ustar") => Some(ArchiveKind::Tar),
+        _ => None,
+    }
+}
+
+/// アーカイブ内のパスを `/` 区切りの相対パスにする（`..` や絶対パスは扱わない）
+fn entry_name(path: &Path) -> Option<String> {
+    let mut parts = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_str()?.to_string()),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    (!parts.is_empty()).then(|| parts.join("/"))
+}
+
+fn has_supported_extension(name: &str, supported_formats: &[String]) -> bool {
+    Path::new(name)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            supported_formats
+                .iter()
+                .any(|format| format.eq_ignore_ascii_case(ext))
+        })
+}
+
+/// 1 ファイルの処理結果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchFileResult {
+    /// アップロード時のファイル名、またはアーカイブ内のパス
+    pub name: String,
+    /// 結果 ZIP 内の出力ファイル
+    pub outputs: Vec<String>,
+    pub language: Option<String>,
+    pub duration_ms: Option<u64>,
+    pub processing_time_ms: Option<u64>,
+    pub model: Option<String>,
+    /// 履歴に保存した場合のエントリ ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history_id: Option<String>,
+    /// 失敗した場合のエラー
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorResponse>,
+}
+
+/// 結果 ZIP の `manifest.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchManifest {
+    /// 入力順
+    pub files: Vec<BatchFileResult>,
+    pub skipped: Vec<SkippedFile>,
+    pub succeeded: usize,
+    pub failed: usize,
+    /// 文字起こしできた音声の合計（ミリ秒）
+    pub total_duration_ms: u64,
+    /// 一括処理全体にかかった時間（ミリ秒）
+    pub processing_time_ms: u64,
+}
+
+/// 出力ファイル名の重複を避ける
+/// - 拡張子を除いたパスが既出なら `name-2`, `name-3`, ... とする
+/// - `manifest` は一覧用に予約する
+#[derive(Debug)]
+pub struct OutputNames {
+    used: HashSet<String>,
+}
+
+impl Default for OutputNames {
+    fn default() -> Self {
+        Self {
+            used: HashSet::from([MANIFEST_FILE.trim_end_matches(".json").to_string()]),
+        }
+    }
+}
+
+impl OutputNames {
+    /// 入力名から拡張子を除いた、重複しないパスを返す
+    pub fn reserve(&mut self, input_name: &str) -> String {
+        let file_start = input_name.rfind('/').map_or(0, |slash| slash + 1);
+        let stem = match input_name[file_start..].rfind('.') {
+            Some(dot) if dot > 0 => &input_name[..file_start + dot],
+            _ => input_name,
+        };
+
+        let mut candidate = stem.to_string();
+        let mut counter = 2;
+        while !self.used.insert(candidate.clone()) {
+            candidate = format!("{}-{}", stem, counter);
+            counter += 1;
+        }
+        candidate
+    }
+}
+
+/// `manifest.json` と出力ファイルをまとめた ZIP を作る
+pub fn build_archive(manifest: &BatchManifest, outputs: &[(String, String)]) -> Result<Vec<u8>> {
+    let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    writer.start_file(MANIFEST_FILE, options)?;
+    writer.write_all(&serde_json::to_vec_pretty(manifest)?)?;
+    for (name, body) in outputs {
+        writer.start_file(name.as_str(), options)?;
+        writer.write_all(body.as_bytes())?;
+    }
+
+    Ok(writer.finish()?.into_inner())
+}
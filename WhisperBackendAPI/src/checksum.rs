@@ -0,0 +1,34 @@
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+// =============================================================================
+// チェックサム（SHA-256）
+// - ダウンロードしたモデルファイルの検証と、Webhook 通知の署名（HMAC-SHA256）に使う
+// - ハッシュ/HMAC の計算は `sha2` / `hmac` クレートに任せる
+// =============================================================================
+
+/// ファイルの SHA-256（16 進小文字）
+/// - 大きなモデルファイルでもメモリに載せずに計算する
+pub fn sha256_file(path: &Path) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(to_hex(&hasher.finalize()))
+}
+
+/// HMAC-SHA256（RFC 2104）
+pub fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC は任意の長さの鍵を受け付ける");
+    mac.update(message);
+    mac.finalize().into_bytes().into()
+}
+
+/// SHA-256 の 16 進表記として妥当か（64 文字の 16 進数）
+pub fn is_sha256_hex(value: &str) -> bool {
+    value.len() == 64 && value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
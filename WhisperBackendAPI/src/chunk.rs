@@ -0,0 +1,112 @@
+use crate::config::ChunkingConfig;
+use crate::models::TranscriptionSegment;
+use crate::vad::quietest_point;
+
+// =============================================================================
+// 長い音声の分割推論
+// - 音声を目安の長さごとに、手前の最も静かな位置で区切る
+// - 各チャンクは前のチャンクと `overlap_seconds` だけ重ねて推論し、区切りの前後の文脈を補う
+// - 結果は元の時間軸に戻し、重なった部分の重複を除いてつなぎ合わせる
+// =============================================================================
+
+/// 重複とみなす文字列の最小の長さ（文字数）
+const MIN_OVERLAP_CHARS: usize = 2;
+
+/// 推論するチャンク（元の音声のサンプル位置、`end` は含まない）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AudioChunk {
+    /// 推論する範囲の先頭（前のチャンクとの重なりを含む）
+    pub start: usize,
+    pub end: usize,
+    /// 前のチャンクとの区切り。`start..boundary` は前のチャンクと重なっている
+    pub boundary: usize,
+}
+
+/// 音声をチャンクに分ける
+/// - 区切りは `boundary + chunk_seconds` から手前へ `search_seconds` の範囲で最も静かな位置
+/// - 残りが目安の長さ + 探索範囲に収まれば最後のチャンクにする（短い音声は 1 チャンク）
+pub fn plan_chunks(samples: &[f32], sample_rate: u32, config: &ChunkingConfig) -> Vec<AudioChunk> {
+    let seconds = |s: u32| s as usize * sample_rate as usize;
+    let chunk_len = seconds(config.chunk_seconds).max(1);
+    let overlap = seconds(config.overlap_seconds);
+    let search = seconds(config.search_seconds).min(chunk_len - 1);
+
+    let mut chunks = Vec::new();
+    let mut boundary: usize = 0;
+    loop {
+        let start = boundary.saturating_sub(overlap);
+        let target = boundary + chunk_len;
+        if target + search >= samples.len() {
+            chunks.push(AudioChunk {
+                start,
+                end: samples.len(),
+                boundary,
+            });
+            return chunks;
+        }
+
+        let end = quietest_point(samples, sample_rate, target - search, target).max(boundary + 1);
+        chunks.push(AudioChunk {
+            start,
+            end,
+            boundary,
+        });
+        boundary = end;
+    }
+}
+
+/// チャンク内の時刻を元の時間軸に戻す
+pub fn offset_segment(segment: &mut TranscriptionSegment, offset_ms: u64) {
+    segment.start_time_ms += offset_ms;
+    segment.end_time_ms += offset_ms;
+    for word in segment.words.iter_mut().flatten() {
+        word.start_time_ms += offset_ms;
+        word.end_time_ms += offset_ms;
+    }
+}
+
+/// 次のチャンクのセグメント（元の時間軸）をつなぎ合わせる
+/// - `boundary_ms` より前に終わるセグメントは前のチャンクと重なった部分なので捨てる
+///   （前のチャンクが失敗した場合はチャンクの先頭を渡し、重なった部分も残す）
+/// - 区切りをまたぐセグメントは、直前のセグメントの末尾と重複するテキストを除く
+pub fn stitch_segments(
+    merged: &mut Vec<TranscriptionSegment>,
+    segments: Vec<TranscriptionSegment>,
+    boundary_ms: u64,
+) {
+    for mut segment in segments {
+        if segment.end_time_ms <= boundary_ms {
+            continue;
+        }
+
+        if let Some(last) = merged.last() {
+            if segment.start_time_ms < last.end_time_ms {
+                let previous_end_ms = last.end_time_ms;
+                let text = segment.text.trim();
+                if last.text.trim().ends_with(text) {
+                    continue;
+                }
+                let overlap = overlap_len(last.text.trim(), text);
+                if overlap > 0 {
+                    let rest: String = text.chars().skip(overlap).collect();
+                    segment.text = rest.trim_start().to_string();
+                    segment.start_time_ms = previous_end_ms.min(segment.end_time_ms);
+                    if let Some(words) = segment.words.as_mut() {
+                        words.retain(|word| word.start_time_ms >= previous_end_ms);
+                    }
+                }
+            }
+        }
+        merged.push(segment);
+    }
+}
+
+/// `previous` の末尾と `next` の先頭が一致する長さ（文字数、`MIN_OVERLAP_CHARS` 未満は 0）
+fn overlap_len(previous: &str, next: &str) -> usize {
+    let next: Vec<char> = next.chars().collect();
+    let previous: Vec<char> = previous.chars().collect();
+    (MIN_OVERLAP_CHARS..=next.len().min(previous.len()))
+        .rev()
+        .find(|&len| previous[previous.len() - len..] == next[..len])
+        .unwrap_or(0)
+}
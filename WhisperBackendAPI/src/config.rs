@@ -1,3 +1,6 @@
+use crate::audio::ChannelMode;
+use crate::diarize::MAX_SPEAKERS;
+use crate::glossary::Glossary;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::fs;
@@ -5,17 +8,41 @@ use std::path::Path;
 
 // =============================================================================
 // 設定モデル
-// - サーバー/Whisper/音声処理/性能/パス/制限の各カテゴリで構成
+// - サーバー/Whisper/音声処理/VAD/分割推論/話者分離/後処理/性能/パス/履歴/Webhook/制限/用語集の各カテゴリで構成
 // - `Config::load_or_create_default` で既定ファイル生成にも対応
 // =============================================================================
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub server: ServerConfig,
+    /// API キー認証（未指定の場合は無効）
+    #[serde(default)]
+    pub auth: AuthConfig,
     pub whisper: WhisperConfig,
     pub audio: AudioConfig,
+    /// 無音区間の検出（未指定の場合は無効）
+    #[serde(default)]
+    pub vad: VadConfig,
+    /// 長い音声の分割推論（未指定の場合は無効）
+    #[serde(default)]
+    pub chunking: ChunkingConfig,
+    /// 話者分離（未指定の場合は無効）
+    #[serde(default)]
+    pub diarization: DiarizationConfig,
+    /// ハルシネーション/繰り返しの除去
+    #[serde(default)]
+    pub filter: FilterConfig,
     pub performance: PerformanceConfig,
     pub paths: PathsConfig,
+    /// 文字起こし履歴の保存（未指定の場合は無効）
+    #[serde(default)]
+    pub history: HistoryConfig,
+    /// ジョブ終了時の Webhook 通知
+    #[serde(default)]
+    pub webhook: WebhookConfig,
     pub limits: LimitsConfig,
+    /// 用語集（API で登録するものとは別に、読み取り専用で定義できる）
+    #[serde(default)]
+    pub glossaries: Vec<GlossaryConfig>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,12 +51,52 @@ pub struct ServerConfig {
     pub host: String,
     /// バインドするポート（例: 8080）
     pub port: u16,
-    /// 許可する CORS オリジン
+    /// 許可する CORS オリジン（`*` を含む場合はすべて許可）
     pub cors_origins: Vec<String>,
     /// リクエストの最大サイズ（バイト）
     pub max_request_size: usize,
 }
 
+/// API キー認証の設定
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuthConfig {
+    /// 有効にすると `Authorization: Bearer <key>` が必須になる
+    pub enabled: bool,
+    /// キー無しで利用できるパス（ヘルスチェック等）
+    pub public_paths: Vec<String>,
+    /// 発行した API キー
+    pub keys: Vec<ApiKeyConfig>,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            public_paths: vec!["/health".to_string()],
+            keys: Vec::new(),
+        }
+    }
+}
+
+/// API キーごとの設定
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiKeyConfig {
+    /// 統計/ログに出す名前（利用チーム名など）
+    pub name: String,
+    /// キーの値（`Authorization: Bearer` で送る文字列）
+    pub key: String,
+    /// 1 分あたりのリクエスト数の上限（0 は無制限）
+    #[serde(default)]
+    pub requests_per_minute: u32,
+    /// 1 か月あたりの音声時間の上限（分、0 は無制限）
+    #[serde(default)]
+    pub monthly_audio_minutes: u64,
+    /// 管理操作（モデルのダウンロード/読み込み/破棄、用語集の登録/削除）と、全キーの履歴/ジョブの参照を許可する
+    #[serde(default)]
+    pub admin: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WhisperConfig {
     /// Whisper モデルの実ファイルパス
@@ -40,6 +107,14 @@ pub struct WhisperConfig {
     pub language: String,
     /// GPU を有効にするか（whisper-rs の対応に依存）
     pub enable_gpu: bool,
+    /// 同時に読み込んでおけるモデルの合計サイズ（MB、0 は無制限）
+    /// - 超過する場合は最も長く使われていないモデルから破棄する
+    #[serde(default = "default_model_memory_budget_mb")]
+    pub model_memory_budget_mb: u64,
+    /// リクエストの `model` に読み込まれていないモデルを指定されたら、その場で読み込むか
+    /// - 読み込みで他のモデルが破棄されうるため、既定（false）では読み込み済みのモデルだけを使う
+    #[serde(default)]
+    pub load_models_on_request: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -52,6 +127,10 @@ pub struct AudioConfig {
     pub buffer_size: usize,
     /// 許可される拡張子（簡易判定）
     pub supported_formats: Vec<String>,
+    /// 多チャンネル音声の扱い（mix / left / right / separate / チャンネル番号）
+    /// - リクエストの `channel_mode` で上書きできる
+    #[serde(default)]
+    pub channel_mode: ChannelMode,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -60,9 +139,13 @@ pub struct PerformanceConfig {
     pub audio_threads: usize,
     /// Whisper 推論に割くスレッド数
     pub whisper_threads: usize,
-    /// API の同時処理上限（現状は統計のみ）
+    /// 同時に推論できるリクエスト数（全モデルの合計。Whisper エンジンプールのサイズ）
     pub max_concurrent_requests: usize,
-    /// リクエストのタイムアウト（秒）
+    /// 空きエンジンを待てるリクエスト数（超過分は 429 で拒否）
+    #[serde(default = "default_max_queued_requests")]
+    pub max_queued_requests: usize,
+    /// 同期リクエストのタイムアウト（秒、0 は無制限）
+    /// - 超過すると推論を中断して `TIMEOUT` を返す（非同期ジョブには適用しない）
     pub request_timeout_seconds: u64,
 }
 
@@ -76,14 +159,220 @@ pub struct PathsConfig {
     pub upload_dir: String,
 }
 
+/// 音声区間検出（VAD）の設定
+/// - フレームごとの音量（dBFS）で発話区間を求め、無音部分を推論から除く
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VadConfig {
+    /// 既定で VAD を使うかどうか（リクエストの `vad` で上書き可能）
+    pub enabled: bool,
+    /// 発話とみなすフレーム音量の下限（dBFS）
+    pub threshold_db: f32,
+    /// これより短い発話区間は雑音として捨てる（ミリ秒）
+    pub min_speech_ms: u32,
+    /// これより短い無音は区切りとみなさず前後の発話をつなげる（ミリ秒）
+    pub min_silence_ms: u32,
+    /// 発話区間の前後に残す余白（ミリ秒）
+    pub padding_ms: u32,
+}
+
+impl Default for VadConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold_db: -40.0,
+            min_speech_ms: 250,
+            min_silence_ms: 500,
+            padding_ms: 200,
+        }
+    }
+}
+
+/// 長い音声の分割推論の設定
+/// - 音声を無音付近で区切り、前後を少し重ねたチャンクごとに推論してからつなぎ合わせる
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChunkingConfig {
+    /// 既定で分割するかどうか（リクエストの `chunking` で上書き可能）
+    pub enabled: bool,
+    /// 1 チャンクの長さの目安（秒、30 以上）。これより短い音声は分割しない
+    pub chunk_seconds: u32,
+    /// 前のチャンクと重ねる長さ（秒）。重なった部分の重複は取り除く
+    pub overlap_seconds: u32,
+    /// 区切りの位置を探す範囲（秒）。目安の位置から手前へこの範囲で最も静かな所で区切る
+    pub search_seconds: u32,
+    /// 1 リクエストで同時に推論するチャンク数の上限
+    /// - エンジンプールに空きがある分だけ並列にする（空きが無ければ 1 つずつ）
+    pub max_parallel: usize,
+}
+
+impl Default for ChunkingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            chunk_seconds: 300,
+            overlap_seconds: 2,
+            search_seconds: 10,
+            max_parallel: 4,
+        }
+    }
+}
+
+/// 話者分離の設定
+/// - セグメントごとの音声の特徴（MFCC）を BIC でクラスタリングし、各セグメントに話者ラベルを付ける
+/// - tinydiarize モデル（ファイル名に `tdrz` を含むもの）では話者交代の検出も使う
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiarizationConfig {
+    /// 有効にすると話者を推定する（リクエストの `diarize` で上書きできる）
+    pub enabled: bool,
+    /// 推定する話者数の上限（1〜32）
+    pub max_speakers: usize,
+    /// 話者をまとめる判定の緩さ（BIC のペナルティ係数。大きいほどまとめやすく、話者数が少なくなる）
+    pub penalty: f32,
+    /// これより短いセグメント（ミリ秒）はクラスタリングに使わず、最も近い話者に割り当てる
+    pub min_segment_ms: u32,
+}
+
+impl Default for DiarizationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_speakers: 8,
+            penalty: 1.0,
+            min_segment_ms: 1000,
+        }
+    }
+}
+
+/// 文字起こし結果の後処理（ハルシネーション/繰り返しの除去）の設定
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FilterConfig {
+    /// 既定で後処理を行うかどうか（リクエストの `filter` で上書き可能）
+    pub enabled: bool,
+    /// 無音確率がこれを超え、かつ平均対数確率が `logprob_threshold` 未満のセグメントを除く
+    /// - 無音確率は `word_timestamps` の指定に関係なく、30 秒ウィンドウごとにエンコーダを再実行して求める
+    ///   （後処理が有効な間は処理時間が延びる）
+    pub no_speech_threshold: f32,
+    /// 無音判定に使う平均対数確率のしきい値
+    pub logprob_threshold: f32,
+    /// 同じ内容がこの回数を超えて続いたら繰り返しとみなす
+    pub max_repeats: usize,
+    /// セグメント全体がこれらの語句と一致したら除く（空白/句読点/大文字小文字は無視）
+    pub blocklist: Vec<String>,
+}
+
+impl Default for FilterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            no_speech_threshold: 0.6,
+            logprob_threshold: -1.0,
+            max_repeats: 3,
+            blocklist: vec![
+                "ご視聴ありがとうございました".to_string(),
+                "ご視聴いただきありがとうございました".to_string(),
+                "チャンネル登録よろしくお願いします".to_string(),
+                "チャンネル登録お願いします".to_string(),
+                "Thank you for watching".to_string(),
+                "Thanks for watching".to_string(),
+            ],
+        }
+    }
+}
+
+/// 文字起こし履歴の設定
+/// - 結果と元の音声を `paths.upload_dir` の下（`history/`）に保存し、後から取り出せるようにする
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoryConfig {
+    /// 有効にすると成功した文字起こしを保存する
+    pub enabled: bool,
+    /// 元の音声ファイルも保存するか
+    pub keep_audio: bool,
+    /// 保存期間（日、0 は無期限）
+    pub retention_days: u32,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            keep_audio: true,
+            retention_days: 30,
+        }
+    }
+}
+
+/// Webhook 通知の設定
+/// - ジョブの `callback_url` への送信が 2xx 以外/接続失敗のとき、待ち時間を倍にしながら再送する
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhookConfig {
+    /// 送信の最大回数（初回を含む）
+    pub max_attempts: u32,
+    /// 最初の再送までの待ち時間（ミリ秒）
+    pub initial_backoff_ms: u64,
+    /// 再送までの待ち時間の上限（ミリ秒）
+    pub max_backoff_ms: u64,
+    /// 1 回の送信の制限時間（秒）
+    pub timeout_seconds: u64,
+    /// 内部向けのアドレス（ループバック/プライベート/リンクローカル等）でも送信を許可する通知先
+    /// - ホスト名（例: `hooks.internal`）か、アドレス/CIDR（例: `10.0.0.5`、`10.0.0.0/8`）
+    /// - 既定は空（内部向けの `callback_url` はすべて 400）
+    pub allowed_hosts: Vec<String>,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff_ms: 1000,
+            max_backoff_ms: 60_000,
+            timeout_seconds: 10,
+            allowed_hosts: Vec::new(),
+        }
+    }
+}
+
+/// 設定ファイルで定義する用語集（`[[glossaries]]`）
+/// - 語句は whisper の初期プロンプトに使い、`correct` が true なら結果の表記も直す
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GlossaryConfig {
+    /// リクエストの `glossary` で指定する名前（英数字/-/_）
+    pub name: String,
+    /// 分野の用語/製品名/人名など
+    pub terms: Vec<String>,
+    /// 既定で語句に近い表記を語句の表記に直すか
+    #[serde(default)]
+    pub correct: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LimitsConfig {
     /// アップロード最大ファイルサイズ（MB）
     pub max_file_size_mb: usize,
     /// 音声の最大長（分）
     pub max_audio_duration_minutes: u32,
-    /// 一時ファイルの自動クリーンアップまでの時間（分）
+    /// 一時ファイルの自動クリーンアップまでの時間（分、0 は掃除しない）
+    /// - `paths.temp_dir` と `paths.upload_dir`（履歴を除く）のこれより古いファイルを定期的に削除する
     pub cleanup_temp_files_after_minutes: u32,
+    /// 終了した非同期ジョブの結果を保持する時間（分）
+    #[serde(default = "default_job_retention_minutes")]
+    pub job_retention_minutes: u32,
+}
+
+fn default_model_memory_budget_mb() -> u64 {
+    4096
+}
+
+fn default_max_queued_requests() -> usize {
+    100
+}
+
+fn default_job_retention_minutes() -> u32 {
+    60
 }
 
 impl Default for Config {
@@ -95,11 +384,14 @@ impl Default for Config {
                 cors_origins: vec!["*".to_string()],
                 max_request_size: 100 * 1024 * 1024, // 100MB
             },
+            auth: AuthConfig::default(),
             whisper: WhisperConfig {
                 model_path: "models/ggml-large-v3-turbo-q5_0.bin".to_string(),
                 default_model: "large-q5_0".to_string(),
                 language: "auto".to_string(),
                 enable_gpu: true,
+                model_memory_budget_mb: default_model_memory_budget_mb(),
+                load_models_on_request: false,
             },
             audio: AudioConfig {
                 sample_rate: 16000,
@@ -113,11 +405,17 @@ impl Default for Config {
                     "flac".to_string(),
                     "ogg".to_string(),
                 ],
+                channel_mode: ChannelMode::default(),
             },
+            vad: VadConfig::default(),
+            chunking: ChunkingConfig::default(),
+            diarization: DiarizationConfig::default(),
+            filter: FilterConfig::default(),
             performance: PerformanceConfig {
                 audio_threads: 10,
                 whisper_threads: 14,
                 max_concurrent_requests: 10,
+                max_queued_requests: default_max_queued_requests(),
                 request_timeout_seconds: 300, // 5 minutes
             },
             paths: PathsConfig {
@@ -125,11 +423,15 @@ impl Default for Config {
                 temp_dir: "temp".to_string(),
                 upload_dir: "uploads".to_string(),
             },
+            history: HistoryConfig::default(),
+            webhook: WebhookConfig::default(),
             limits: LimitsConfig {
                 max_file_size_mb: 50,
                 max_audio_duration_minutes: 180,
                 cleanup_temp_files_after_minutes: 60,
+                job_retention_minutes: default_job_retention_minutes(),
             },
+            glossaries: Vec::new(),
         }
     }
 }
@@ -222,6 +524,133 @@ impl Config {
             ));
         }
 
+        self.validate_auth()?;
+        self.validate_chunking()?;
+        self.validate_diarization()?;
+        self.validate_glossaries()?;
+        self.validate_webhook()?;
+
+        Ok(())
+    }
+
+    /// Webhook 通知の設定の検証
+    pub fn validate_webhook(&self) -> Result<()> {
+        let webhook = &self.webhook;
+        if webhook.max_attempts == 0 {
+            return Err(anyhow::anyhow!(
+                "webhook.max_attempts は1以上である必要があります"
+            ));
+        }
+        if webhook.timeout_seconds == 0 {
+            return Err(anyhow::anyhow!(
+                "webhook.timeout_seconds は1以上である必要があります"
+            ));
+        }
+        if webhook.initial_backoff_ms > webhook.max_backoff_ms {
+            return Err(anyhow::anyhow!(
+                "webhook.initial_backoff_ms は max_backoff_ms 以下にしてください"
+            ));
+        }
+        // CIDR は「アドレス/プレフィックス長」の形で、長さはアドレスのビット数以下
+        for entry in &webhook.allowed_hosts {
+            if let Some((network, prefix)) = entry.split_once('/') {
+                let bits = match network.parse::<std::net::IpAddr>() {
+                    Ok(std::net::IpAddr::V4(_)) => 32,
+                    Ok(std::net::IpAddr::V6(_)) => 128,
+                    Err(_) => 0,
+                };
+                if !prefix.parse::<u32>().is_ok_and(|p| bits > 0 && p <= bits) {
+                    return Err(anyhow::anyhow!(
+                        "webhook.allowed_hosts の CIDR が不正です: {}",
+                        entry
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 話者分離の設定の検証
+    pub fn validate_diarization(&self) -> Result<()> {
+        let diarization = &self.diarization;
+        if !(1..=MAX_SPEAKERS).contains(&diarization.max_speakers) {
+            return Err(anyhow::anyhow!(
+                "diarization.max_speakers は1〜{}で指定してください",
+                MAX_SPEAKERS
+            ));
+        }
+        if diarization.penalty.is_nan() || diarization.penalty <= 0.0 {
+            return Err(anyhow::anyhow!(
+                "diarization.penalty は0より大きい値を指定してください"
+            ));
+        }
+        Ok(())
+    }
+
+    /// 用語集の設定の検証
+    /// - 名前は英数字/-/_ で重複不可、語句は 1 つ以上
+    pub fn validate_glossaries(&self) -> Result<()> {
+        let mut names = std::collections::HashSet::new();
+        for glossary in &self.glossaries {
+            Glossary::new(&glossary.name, &glossary.terms, glossary.correct, true)
+                .map_err(|e| anyhow::anyhow!("用語集 {} の設定が不正です: {}", glossary.name, e))?;
+            if !names.insert(glossary.name.as_str()) {
+                return Err(anyhow::anyhow!(
+                    "用語集の名前が重複しています: {}",
+                    glossary.name
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// 分割推論の設定の検証
+    /// - whisper は 30 秒単位で推論するため、チャンクは 30 秒以上
+    /// - 重なり/区切りを探す範囲はチャンクより短くする
+    pub fn validate_chunking(&self) -> Result<()> {
+        let chunking = &self.chunking;
+        if chunking.chunk_seconds < 30 {
+            return Err(anyhow::anyhow!(
+                "chunking.chunk_seconds は30以上である必要があります"
+            ));
+        }
+        if chunking.overlap_seconds + chunking.search_seconds >= chunking.chunk_seconds {
+            return Err(anyhow::anyhow!(
+                "chunking.overlap_seconds と search_seconds の合計は chunk_seconds より短くしてください"
+            ));
+        }
+        Ok(())
+    }
+
+    /// API キー設定の検証
+    /// - 有効時はキーが 1 つ以上必要
+    /// - 名前/キーの空や重複は不可（使用量を名前で集計するため）
+    pub fn validate_auth(&self) -> Result<()> {
+        if self.auth.enabled && self.auth.keys.is_empty() {
+            return Err(anyhow::anyhow!(
+                "API キー認証を有効にする場合は [[auth.keys]] を1つ以上設定してください"
+            ));
+        }
+
+        let mut names = std::collections::HashSet::new();
+        let mut keys = std::collections::HashSet::new();
+        for key in &self.auth.keys {
+            if key.name.trim().is_empty() || key.key.trim().is_empty() {
+                return Err(anyhow::anyhow!("API キーの name と key は空にできません"));
+            }
+            if !names.insert(key.name.as_str()) {
+                return Err(anyhow::anyhow!(
+                    "API キーの名前が重複しています: {}",
+                    key.name
+                ));
+            }
+            if !keys.insert(key.key.as_str()) {
+                return Err(anyhow::anyhow!(
+                    "API キーの値が重複しています: {}",
+                    key.name
+                ));
+            }
+        }
         Ok(())
     }
 
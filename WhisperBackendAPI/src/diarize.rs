@@ -0,0 +1,500 @@
+use crate::config::DiarizationConfig;
+use crate::models::TranscriptionSegment;
+use std::ops::Range;
+
+// =============================================================================
+// 話者分離
+// - セグメントごとに発話フレームの MFCC を求め、話者ごとの特徴の分布（対角ガウス分布）とみなす
+// - 分布を BIC（ベイズ情報量規準）で比較し、凝集型クラスタリングで同じ話者のセグメントをまとめる
+//   - 2 つの分布を 1 つにまとめても BIC が悪化しない限り併合する（話者数を事前に知らなくてよい）
+// - tinydiarize モデル（tdrz）の場合は、話者交代の検出で区切られた範囲を 1 人の発話としてまとめる
+// - 短いセグメントは特徴が不安定なため、クラスタリング後に最も尤度の高い話者へ割り当てる
+// =============================================================================
+
+/// 推定する話者数の上限（設定の `max_speakers` とリクエストの `num_speakers`）
+pub const MAX_SPEAKERS: usize = 32;
+/// 分析フレームの長さ/間隔（ミリ秒）
+const FRAME_MS: usize = 25;
+const HOP_MS: usize = 10;
+/// メルフィルタバンクの帯域数と、使うケプストラム係数の数（c1〜c12、音量に依存する c0 は使わない）
+const MEL_BANDS: usize = 26;
+const CEPSTRA: usize = 12;
+/// メルフィルタバンクの下限/上限の周波数（Hz）
+const MIN_HZ: f32 = 60.0;
+const MAX_HZ: f32 = 7600.0;
+/// これより静かなフレーム（dBFS）は特徴量に使わない
+const SILENCE_DB: f32 = -45.0;
+/// 分散の下限（一定の値が続くフレームで対数が発散しないように）
+const VARIANCE_FLOOR: f64 = 1e-4;
+
+type Features = [f64; CEPSTRA];
+
+/// 話者ラベル（1 始まり、例: `SPEAKER_1`）
+pub fn speaker_label(index: usize) -> String {
+    format!("SPEAKER_{}", index + 1)
+}
+
+/// セグメントに話者ラベルを付け、推定した話者数を返す
+/// - `samples` はセグメントと同じ時間軸の音声
+/// - `num_speakers` 指定時はその人数になるまで併合する（発話が足りなければ少なくなる）
+/// - `speaker_turns` が true なら `speaker_turn`（tinydiarize）で区切られた範囲を 1 人の発話とする
+/// - 発話フレームが 1 つも無ければラベルを付けずに 0 を返す
+pub fn diarize(
+    samples: &[f32],
+    sample_rate: u32,
+    segments: &mut [TranscriptionSegment],
+    config: &DiarizationConfig,
+    num_speakers: Option<usize>,
+    speaker_turns: bool,
+) -> usize {
+    let mfcc = Mfcc::new(sample_rate);
+    let units: Vec<Unit> = unit_ranges(segments, speaker_turns)
+        .into_iter()
+        .map(|range| {
+            let mut frames = Vec::new();
+            for segment in &segments[range.clone()] {
+                let start = ms_to_samples(segment.start_time_ms, sample_rate).min(samples.len());
+                let end =
+                    ms_to_samples(segment.end_time_ms, sample_rate).clamp(start, samples.len());
+                mfcc.extend_frames(&samples[start..end], &mut frames);
+            }
+            Unit { range, frames }
+        })
+        .collect();
+
+    // 十分な長さのあるまとまりだけでクラスタリングする（無ければ発話のあるものすべて）
+    let min_frames = (config.min_segment_ms as usize / HOP_MS).max(1);
+    let mut clustered: Vec<usize> = (0..units.len())
+        .filter(|&i| units[i].frames.len() >= min_frames)
+        .collect();
+    if clustered.is_empty() {
+        clustered = (0..units.len())
+            .filter(|&i| !units[i].frames.is_empty())
+            .collect();
+    }
+    if clustered.is_empty() {
+        return 0;
+    }
+
+    let max_speakers = config.max_speakers.clamp(1, MAX_SPEAKERS);
+    let target = num_speakers.map(|n| n.clamp(1, MAX_SPEAKERS));
+    let clusters = cluster(
+        clustered
+            .iter()
+            .map(|&i| Gaussian::from_frames(&units[i].frames))
+            .collect(),
+        config.penalty as f64,
+        target,
+        max_speakers,
+    );
+
+    // まとまりごとの話者（クラスタリングしていないものは最も尤度の高い話者、発話が無ければ直前の話者）
+    let mut assigned: Vec<Option<usize>> = vec![None; units.len()];
+    let models: Vec<&Gaussian> = clusters.iter().map(|(model, _)| model).collect();
+    for (cluster_index, (_, members)) in clusters.iter().enumerate() {
+        for &member in members {
+            assigned[clustered[member]] = Some(cluster_index);
+        }
+    }
+    for (unit, slot) in units.iter().zip(assigned.iter_mut()) {
+        if slot.is_none() && !unit.frames.is_empty() {
+            *slot = most_likely(&models, &unit.frames);
+        }
+    }
+    let mut previous = assigned.iter().flatten().next().copied();
+    for slot in assigned.iter_mut() {
+        match slot {
+            Some(speaker) => previous = Some(*speaker),
+            None => *slot = previous,
+        }
+    }
+
+    // 最初に話した順にラベルを付け直す
+    let mut order: Vec<usize> = Vec::new();
+    for (unit, speaker) in units.iter().zip(assigned) {
+        let Some(speaker) = speaker else { continue };
+        let index = match order.iter().position(|&s| s == speaker) {
+            Some(index) => index,
+            None => {
+                order.push(speaker);
+                order.len() - 1
+            }
+        };
+        for segment in &mut segments[unit.range.clone()] {
+            segment.speaker = Some(speaker_label(index));
+        }
+    }
+    order.len()
+}
+
+/// 話者ごとにまとめた全文テキスト
+/// - 話者が替わるたびに改行し、行頭に `[SPEAKER_1] ` を付ける
+pub fn speaker_text(segments: &[TranscriptionSegment]) -> String {
+    let mut lines: Vec<(Option<&str>, String)> = Vec::new();
+    for segment in segments {
+        let speaker = segment.speaker.as_deref();
+        match lines.last_mut() {
+            Some((last, text)) if *last == speaker => text.push_str(&segment.text),
+            _ => lines.push((speaker, segment.text.clone())),
+        }
+    }
+
+    lines
+        .into_iter()
+        .map(|(speaker, text)| match speaker {
+            Some(speaker) => format!("[{}] {}", speaker, text.trim()),
+            None => text.trim().to_string(),
+        })
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// 1 人の発話とみなすセグメントのまとまり
+struct Unit {
+    range: Range<usize>,
+    frames: Vec<Features>,
+}
+
+/// セグメントをまとまりに分ける
+/// - 話者交代の検出を使う場合は、交代の印が付いたセグメントまでを 1 つにする
+/// - それ以外は 1 セグメントずつ
+fn unit_ranges(segments: &[TranscriptionSegment], speaker_turns: bool) -> Vec<Range<usize>> {
+    if !speaker_turns {
+        return (0..segments.len()).map(|i| i..i + 1).collect();
+    }
+
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    for (i, segment) in segments.iter().enumerate() {
+        if segment.speaker_turn || i + 1 == segments.len() {
+            ranges.push(start..i + 1);
+            start = i + 1;
+        }
+    }
+    ranges
+}
+
+fn ms_to_samples(ms: u64, sample_rate: u32) -> usize {
+    (ms * sample_rate as u64 / 1000) as usize
+}
+
+// -----------------------------------------------------------------------------
+// クラスタリング
+// -----------------------------------------------------------------------------
+
+/// 特徴量の対角ガウス分布（十分統計量で持ち、併合は足し合わせるだけ）
+#[derive(Debug, Clone)]
+struct Gaussian {
+    count: f64,
+    sum: Features,
+    sum_sq: Features,
+}
+
+impl Gaussian {
+    fn from_frames(frames: &[Features]) -> Self {
+        let mut gaussian = Self {
+            count: frames.len() as f64,
+            sum: [0.0; CEPSTRA],
+            sum_sq: [0.0; CEPSTRA],
+        };
+        for frame in frames {
+            for (d, &value) in frame.iter().enumerate() {
+                gaussian.sum[d] += value;
+                gaussian.sum_sq[d] += value * value;
+            }
+        }
+        gaussian
+    }
+
+    fn merged(&self, other: &Self) -> Self {
+        let mut merged = self.clone();
+        merged.count += other.count;
+        for d in 0..CEPSTRA {
+            merged.sum[d] += other.sum[d];
+            merged.sum_sq[d] += other.sum_sq[d];
+        }
+        merged
+    }
+
+    fn mean_variance(&self, d: usize) -> (f64, f64) {
+        let mean = self.sum[d] / self.count;
+        let variance = (self.sum_sq[d] / self.count - mean * mean).max(VARIANCE_FLOOR);
+        (mean, variance)
+    }
+
+    /// 共分散行列の行列式の対数
+    fn log_det(&self) -> f64 {
+        (0..CEPSTRA).map(|d| self.mean_variance(d).1.ln()).sum()
+    }
+
+    /// フレーム列の対数尤度（定数項を除く）
+    fn log_likelihood(&self, frames: &[Features]) -> f64 {
+        let params: Vec<(f64, f64)> = (0..CEPSTRA).map(|d| self.mean_variance(d)).collect();
+        frames
+            .iter()
+            .map(|frame| {
+                frame
+                    .iter()
+                    .zip(&params)
+                    .map(|(&x, &(mean, variance))| {
+                        -0.5 * (variance.ln() + (x - mean).powi(2) / variance)
+                    })
+                    .sum::<f64>()
+            })
+            .sum()
+    }
+}
+
+/// 2 つの分布を併合したときの BIC の増加量（負なら同じ話者とみなせる）
+/// - ΔBIC = N/2·log|Σ| − N1/2·log|Σ1| − N2/2·log|Σ2| − λ·P/2·log N（P は分布のパラメータ数）
+fn delta_bic(a: &Gaussian, b: &Gaussian, penalty: f64) -> f64 {
+    let merged = a.merged(b);
+    let parameters = (2 * CEPSTRA) as f64;
+    0.5 * (merged.count * merged.log_det() - a.count * a.log_det() - b.count * b.log_det())
+        - penalty * 0.5 * parameters * merged.count.ln()
+}
+
+/// 凝集型クラスタリング（戻り値は (分布, 入力の添字) のクラスタ列）
+/// - ΔBIC が最小の組を併合していく
+/// - `target` 指定時はその数になるまで、それ以外は ΔBIC が負の間と、`max_speakers` を超えている間
+fn cluster(
+    gaussians: Vec<Gaussian>,
+    penalty: f64,
+    target: Option<usize>,
+    max_speakers: usize,
+) -> Vec<(Gaussian, Vec<usize>)> {
+    let n = gaussians.len();
+    let mut clusters: Vec<Option<(Gaussian, Vec<usize>)>> = gaussians
+        .into_iter()
+        .enumerate()
+        .map(|(i, gaussian)| Some((gaussian, vec![i])))
+        .collect();
+    // scores[i][j]（i < j）: クラスタ i と j を併合したときの ΔBIC
+    let mut scores = vec![vec![f64::INFINITY; n]; n];
+    for i in 0..n {
+        for j in i + 1..n {
+            scores[i][j] = delta_bic(
+                &clusters[i].as_ref().unwrap().0,
+                &clusters[j].as_ref().unwrap().0,
+                penalty,
+            );
+        }
+    }
+
+    let mut remaining = n;
+    while remaining > 1 {
+        let mut best = (f64::INFINITY, 0, 0);
+        for (i, row) in scores.iter().enumerate() {
+            for (j, &score) in row.iter().enumerate().skip(i + 1) {
+                if score < best.0 {
+                    best = (score, i, j);
+                }
+            }
+        }
+        let (score, i, j) = best;
+        if !score.is_finite() {
+            break;
+        }
+        let done = match target {
+            Some(target) => remaining <= target,
+            None => score >= 0.0 && remaining <= max_speakers,
+        };
+        if done {
+            break;
+        }
+
+        let (absorbed, members) = clusters[j].take().unwrap();
+        let (gaussian, own_members) = clusters[i].as_mut().unwrap();
+        *gaussian = gaussian.merged(&absorbed);
+        own_members.extend(members);
+        remaining -= 1;
+
+        for k in 0..n {
+            scores[k.min(j)][k.max(j)] = f64::INFINITY;
+            if k == i {
+                continue;
+            }
+            if let Some((other, _)) = clusters[k].as_ref() {
+                let merged = &clusters[i].as_ref().unwrap().0;
+                scores[k.min(i)][k.max(i)] = delta_bic(merged, other, penalty);
+            }
+        }
+    }
+
+    clusters.into_iter().flatten().collect()
+}
+
+/// フレーム列の尤度が最も高い話者
+fn most_likely(models: &[&Gaussian], frames: &[Features]) -> Option<usize> {
+    models
+        .iter()
+        .map(|model| model.log_likelihood(frames))
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(index, _)| index)
+}
+
+// -----------------------------------------------------------------------------
+// MFCC
+// -----------------------------------------------------------------------------
+
+/// MFCC の抽出器（窓関数/フィルタバンク/DCT の係数を前計算して持つ）
+struct Mfcc {
+    frame_len: usize,
+    hop: usize,
+    fft_size: usize,
+    window: Vec<f32>,
+    /// FFT の回転因子（`fft_size / 2` 個の (sin, cos)）
+    twiddles: Vec<(f32, f32)>,
+    /// 帯域ごとの (FFT ビン, 重み)
+    filters: Vec<Vec<(usize, f32)>>,
+    /// dct[k][m]: c(k+1) に対する帯域 m の係数
+    dct: Vec<[f64; MEL_BANDS]>,
+}
+
+impl Mfcc {
+    fn new(sample_rate: u32) -> Self {
+        let rate = sample_rate as usize;
+        let frame_len = (FRAME_MS * rate / 1000).max(2);
+        let hop = (HOP_MS * rate / 1000).max(1);
+        let fft_size = frame_len.next_power_of_two();
+
+        // Hamming 窓
+        let window = (0..frame_len)
+            .map(|i| {
+                0.54 - 0.46 * (2.0 * std::f32::consts::PI * i as f32 / (frame_len - 1) as f32).cos()
+            })
+            .collect();
+
+        let twiddles = (0..fft_size / 2)
+            .map(|k| (-2.0 * std::f32::consts::PI * k as f32 / fft_size as f32).sin_cos())
+            .collect();
+
+        // 三角形のメルフィルタ
+        let to_mel = |hz: f32| 2595.0 * (1.0 + hz / 700.0).log10();
+        let to_hz = |mel: f32| 700.0 * (10f32.powf(mel / 2595.0) - 1.0);
+        let max_hz = MAX_HZ.min(sample_rate as f32 / 2.0);
+        let (low, high) = (to_mel(MIN_HZ), to_mel(max_hz));
+        let bins: Vec<usize> = (0..MEL_BANDS + 2)
+            .map(|i| {
+                let hz = to_hz(low + (high - low) * i as f32 / (MEL_BANDS + 1) as f32);
+                ((fft_size as f32 * hz / sample_rate as f32).round() as usize).min(fft_size / 2)
+            })
+            .collect();
+        let filters = (0..MEL_BANDS)
+            .map(|m| {
+                let (left, center, right) = (bins[m], bins[m + 1], bins[m + 2]);
+                (left..=right)
+                    .filter_map(|bin| {
+                        let weight = if bin <= center {
+                            (bin - left) as f32 / (center - left).max(1) as f32
+                        } else {
+                            (right - bin) as f32 / (right - center).max(1) as f32
+                        };
+                        (weight > 0.0).then_some((bin, weight))
+                    })
+                    .collect()
+            })
+            .collect();
+
+        // DCT-II
+        let dct = (1..=CEPSTRA)
+            .map(|k| {
+                let mut row = [0.0; MEL_BANDS];
+                for (m, value) in row.iter_mut().enumerate() {
+                    *value = (std::f64::consts::PI * k as f64 * (m as f64 + 0.5)
+                        / MEL_BANDS as f64)
+                        .cos();
+                }
+                row
+            })
+            .collect();
+
+        Self {
+            frame_len,
+            hop,
+            fft_size,
+            window,
+            twiddles,
+            filters,
+            dct,
+        }
+    }
+
+    /// 発話フレーム（`SILENCE_DB` 以上）の MFCC を `out` に追加する
+    fn extend_frames(&self, samples: &[f32], out: &mut Vec<Features>) {
+        let mut re = vec![0.0f32; self.fft_size];
+        let mut im = vec![0.0f32; self.fft_size];
+        let mut position = 0;
+        while position + self.frame_len <= samples.len() {
+            let frame = &samples[position..position + self.frame_len];
+            position += self.hop;
+
+            let mean_square = frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32;
+            if 10.0 * mean_square.max(f32::MIN_POSITIVE).log10() < SILENCE_DB {
+                continue;
+            }
+
+            // プリエンファシス → 窓掛け → パワースペクトル
+            re.fill(0.0);
+            im.fill(0.0);
+            for i in 0..self.frame_len {
+                let previous = if i == 0 { frame[0] } else { frame[i - 1] };
+                re[i] = (frame[i] - 0.97 * previous) * self.window[i];
+            }
+            fft(&mut re, &mut im, &self.twiddles);
+
+            let mut log_energies = [0.0f64; MEL_BANDS];
+            for (energy, filter) in log_energies.iter_mut().zip(&self.filters) {
+                let sum: f32 = filter
+                    .iter()
+                    .map(|&(bin, weight)| weight * (re[bin] * re[bin] + im[bin] * im[bin]))
+                    .sum();
+                *energy = (sum as f64).max(1e-10).ln();
+            }
+
+            let mut features = [0.0; CEPSTRA];
+            for (value, row) in features.iter_mut().zip(&self.dct) {
+                *value = row.iter().zip(&log_energies).map(|(c, e)| c * e).sum();
+            }
+            out.push(features);
+        }
+    }
+}
+
+/// 基数 2 の FFT（その場で変換。長さは 2 のべき乗、`twiddles` は長さの半分）
+fn fft(re: &mut [f32], im: &mut [f32], twiddles: &[(f32, f32)]) {
+    let n = re.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let stride = n / len;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (sin, cos) = twiddles[k * stride];
+                let (a, b) = (start + k, start + k + len / 2);
+                let t_re = re[b] * cos - im[b] * sin;
+                let t_im = re[b] * sin + im[b] * cos;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+            }
+        }
+        len <<= 1;
+    }
+}
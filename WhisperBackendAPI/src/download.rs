@@ -0,0 +1,455 @@
+use crate::checksum;
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::Utc;
+use reqwest::header::{HeaderMap, CONTENT_RANGE, ETAG, IF_RANGE, LAST_MODIFIED, RANGE};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+// =============================================================================
+// モデルのダウンロード
+// - カタログのモデルをサーバー側で `paths.models_dir` へダウンロードする
+// - 途中のデータは `.part` ファイルに保存し、再実行時は HTTP Range で続きから取得する
+//   （取得時の ETag/Last-Modified を `.part.etag` に残し、`If-Range` で同じ版のときだけ続きを受け取る）
+// - 完了後に SHA-256 を計算し、期待値と照合してから本来のファイル名へ移動する
+//   （期待値が無い場合は `allow_unverified` の指定時だけ検証せずに移動する）
+// =============================================================================
+
+/// 読み込みバッファのサイズ（1MB）
+const BUFFER_SIZE: usize = 1024 * 1024;
+
+/// ダウンロードの段階
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DownloadPhase {
+    Queued,
+    Downloading,
+    Verifying,
+    Completed,
+    Failed,
+}
+
+impl DownloadPhase {
+    /// 終了状態（完了/失敗）かどうか
+    pub fn is_finished(&self) -> bool {
+        matches!(self, DownloadPhase::Completed | DownloadPhase::Failed)
+    }
+}
+
+/// ダウンロードの状態（`/admin/models/{name}/download` のレスポンス）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadStatus {
+    pub model: String,
+    pub file_name: String,
+    pub phase: DownloadPhase,
+    pub downloaded_bytes: u64,
+    /// 全体サイズ（サーバーが返さない場合は None）
+    pub total_bytes: Option<u64>,
+    /// 進捗（0〜100、全体サイズが不明な場合は None）
+    pub progress: Option<u8>,
+    /// 途中から再開した場合の開始位置（バイト）
+    pub resumed_from: u64,
+    /// ダウンロードしたファイルの SHA-256
+    pub sha256: Option<String>,
+    /// 期待値と照合できたか（期待値が無い場合は false）
+    pub verified: bool,
+    pub error: Option<String>,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+}
+
+impl DownloadStatus {
+    pub fn new(model: &str, file_name: &str) -> Self {
+        Self {
+            model: model.to_string(),
+            file_name: file_name.to_string(),
+            phase: DownloadPhase::Queued,
+            downloaded_bytes: 0,
+            total_bytes: None,
+            progress: None,
+            resumed_from: 0,
+            sha256: None,
+            verified: false,
+            error: None,
+            started_at: Utc::now().to_rfc3339(),
+            finished_at: None,
+        }
+    }
+}
+
+/// ダウンロード中の進捗通知
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DownloadEvent {
+    /// 転送中（取得済みバイト数、全体サイズ、再開位置）
+    Progress {
+        downloaded: u64,
+        total: Option<u64>,
+        resumed_from: u64,
+    },
+    /// 転送完了後のチェックサム計算中
+    Verifying,
+}
+
+/// ダウンロード結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DownloadOutcome {
+    pub bytes: u64,
+    pub resumed_from: u64,
+    pub sha256: String,
+    pub verified: bool,
+}
+
+/// モデルごとのダウンロード状態
+/// - 同じモデルのダウンロードは同時に 1 つだけ
+pub struct DownloadManager {
+    downloads: Mutex<HashMap<String, DownloadStatus>>,
+}
+
+impl Default for DownloadManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DownloadManager {
+    pub fn new() -> Self {
+        Self {
+            downloads: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// ダウンロードを登録する
+    /// - 同じモデルが実行中なら登録せず、実行中の状態と false を返す
+    pub fn begin(&self, model: &str, file_name: &str) -> (DownloadStatus, bool) {
+        let mut downloads = self.downloads.lock().unwrap();
+        if let Some(current) = downloads.get(model) {
+            if !current.phase.is_finished() {
+                return (current.clone(), false);
+            }
+        }
+        let status = DownloadStatus::new(model, file_name);
+        downloads.insert(model.to_string(), status.clone());
+        (status, true)
+    }
+
+    /// 進捗通知を状態へ反映する
+    pub fn apply_event(&self, model: &str, event: DownloadEvent) {
+        self.update(model, |status| match event {
+            DownloadEvent::Progress {
+                downloaded,
+                total,
+                resumed_from,
+            } => {
+                status.phase = DownloadPhase::Downloading;
+                status.downloaded_bytes = downloaded;
+                status.total_bytes = total;
+                status.resumed_from = resumed_from;
+                status.progress = total
+                    .filter(|total| *total > 0)
+                    .map(|total| ((downloaded.min(total) * 100) / total) as u8);
+            }
+            DownloadEvent::Verifying => status.phase = DownloadPhase::Verifying,
+        });
+    }
+
+    /// 完了として記録する
+    pub fn complete(&self, model: &str, outcome: &DownloadOutcome) {
+        self.update(model, |status| {
+            status.phase = DownloadPhase::Completed;
+            status.downloaded_bytes = outcome.bytes;
+            status.total_bytes = Some(outcome.bytes);
+            status.progress = Some(100);
+            status.resumed_from = outcome.resumed_from;
+            status.sha256 = Some(outcome.sha256.clone());
+            status.verified = outcome.verified;
+            status.finished_at = Some(Utc::now().to_rfc3339());
+        });
+    }
+
+    /// 失敗として記録する
+    pub fn fail(&self, model: &str, error: String) {
+        self.update(model, |status| {
+            status.phase = DownloadPhase::Failed;
+            status.error = Some(error);
+            status.finished_at = Some(Utc::now().to_rfc3339());
+        });
+    }
+
+    pub fn get(&self, model: &str) -> Option<DownloadStatus> {
+        self.downloads.lock().unwrap().get(model).cloned()
+    }
+
+    /// 全ダウンロードの状態（モデル名順）
+    pub fn list(&self) -> Vec<DownloadStatus> {
+        let mut downloads: Vec<DownloadStatus> =
+            self.downloads.lock().unwrap().values().cloned().collect();
+        downloads.sort_by(|a, b| a.model.cmp(&b.model));
+        downloads
+    }
+
+    fn update(&self, model: &str, f: impl FnOnce(&mut DownloadStatus)) {
+        if let Some(status) = self.downloads.lock().unwrap().get_mut(model) {
+            f(status);
+        }
+    }
+}
+
+/// `download_to_file` の指定
+#[derive(Debug, Clone, Copy)]
+pub struct DownloadOptions<'a> {
+    /// 期待する SHA-256（16 進）。無ければ配布元が返すハッシュを使う
+    pub expected_sha256: Option<&'a str>,
+    /// 期待値が得られなくても検証せずに配置する
+    pub allow_unverified: bool,
+    /// `.part` を使わずに最初から取得する（`overwrite` 指定時）
+    pub restart: bool,
+    /// 接続のタイムアウト
+    pub connect_timeout: Duration,
+}
+
+/// ダウンロード途中のファイルのパス（GUI と同じく拡張子を `.part` にする）
+pub fn partial_path(dest: &Path) -> PathBuf {
+    dest.with_extension("part")
+}
+
+/// `.part` を取得したときの応答の ETag（無ければ Last-Modified）を保存するファイルのパス
+pub fn partial_validator_path(dest: &Path) -> PathBuf {
+    dest.with_extension("part.etag")
+}
+
+/// URL から `dest` へファイルをダウンロードする（ブロッキング）
+/// - `.part` ファイルがあれば Range リクエストで続きから取得する
+/// - 配布元のファイルが `.part` の取得時から変わっていれば（`If-Range` が一致しなければ）最初から取得する
+/// - 取得時の ETag/Last-Modified が残っていない `.part` と、`restart` 指定時の `.part` は使わない
+/// - `expected_sha256` が無い場合は、HuggingFace が返す `X-Linked-ETag`（LFS の SHA-256）を期待値に使う
+/// - 期待値が得られない場合は、`allow_unverified` でなければ転送を始めずにエラー
+/// - チェックサムが一致しない場合は `.part` を削除してエラー
+/// - 転送が途中で切れた場合は `.part` を残し、次回の再開に使う
+pub fn download_to_file(
+    url: &str,
+    dest: &Path,
+    options: DownloadOptions,
+    mut on_event: impl FnMut(DownloadEvent),
+) -> Result<DownloadOutcome> {
+    let connect_timeout = options.connect_timeout;
+    if let Some(parent) = dest.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("ディレクトリを作成できません: {}", parent.display()))?;
+        }
+    }
+
+    let part = partial_path(dest);
+    let validator_path = partial_validator_path(dest);
+    if options.restart {
+        remove_partial(dest);
+    }
+    // 版を確認できない `.part` から続きを取ると別の版のデータとつながってしまうため捨てる
+    let validator = fs::read_to_string(&validator_path)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    let existing = match &validator {
+        Some(_) => fs::metadata(&part).map(|m| m.len()).unwrap_or(0),
+        None => {
+            remove_partial(dest);
+            0
+        }
+    };
+
+    // 大きなモデルでも途中で打ち切られないよう、全体のタイムアウトは設けない
+    let client = reqwest::blocking::Client::builder()
+        .connect_timeout(connect_timeout)
+        .timeout(None::<Duration>)
+        .build()?;
+
+    let mut request = client.get(url);
+    if let Some(validator) = validator.as_deref().filter(|_| existing > 0) {
+        request = request
+            .header(RANGE, format!("bytes={}-", existing))
+            .header(IF_RANGE, validator);
+    }
+    let mut response = request
+        .send()
+        .with_context(|| format!("ダウンロードを開始できません: {}", url))?;
+    let status = response.status();
+
+    let expected = match options.expected_sha256 {
+        Some(value) => Some(value.trim().to_ascii_lowercase()),
+        None => linked_etag_sha256(response.headers())
+            .or_else(|| fetch_linked_sha256(url, connect_timeout)),
+    };
+    if expected.is_none() && !options.allow_unverified {
+        bail!(
+            "配布元からチェックサムを取得できないため、検証できないファイルは配置しません。\
+             sha256 を指定するか、allow_unverified を true にして再実行してください"
+        );
+    }
+
+    let (mut file, resumed_from, total) = if existing > 0 && status == StatusCode::PARTIAL_CONTENT {
+        let (start, total) = parse_content_range(&response)
+            .ok_or_else(|| anyhow!("Content-Range ヘッダーが不正です"))?;
+        if start != existing {
+            bail!(
+                "再開位置が一致しません（要求: {}, 応答: {}）",
+                existing,
+                start
+            );
+        }
+        if response_validator(response.headers()).is_some_and(|value| Some(value) != validator) {
+            remove_partial(dest);
+            bail!("配布元のファイルが変わったため途中のファイルを削除しました。再実行してください");
+        }
+        let file = OpenOptions::new().append(true).open(&part)?;
+        (Some(file), existing, total)
+    } else if existing > 0 && status == StatusCode::RANGE_NOT_SATISFIABLE {
+        // 要求位置がファイル末尾以降: 取得済みなら検証へ、そうでなければ最初からやり直す
+        let total = content_range_total(&response);
+        if total != Some(existing) {
+            remove_partial(dest);
+            bail!("途中のファイルが不正なため削除しました。再実行してください");
+        }
+        (None, existing, total)
+    } else if status.is_success() {
+        // 200 の場合は Range に対応していないか、配布元のファイルが変わったため最初から
+        let file = File::create(&part)
+            .with_context(|| format!("ファイルを作成できません: {}", part.display()))?;
+        // 次回の再開で同じ版か確かめられるよう、検証子を残す（無ければ再開しない）
+        match response_validator(response.headers()) {
+            Some(value) => fs::write(&validator_path, value)?,
+            None => {
+                let _ = fs::remove_file(&validator_path);
+            }
+        }
+        (Some(file), 0, response.content_length())
+    } else {
+        bail!("ダウンロードに失敗しました: HTTP {}", status);
+    };
+
+    let mut downloaded = resumed_from;
+    on_event(DownloadEvent::Progress {
+        downloaded,
+        total,
+        resumed_from,
+    });
+
+    if let Some(file) = file.as_mut() {
+        let mut buf = vec![0u8; BUFFER_SIZE];
+        loop {
+            let n = response
+                .read(&mut buf)
+                .context("ダウンロード中に接続が切断されました")?;
+            if n == 0 {
+                break;
+            }
+            file.write_all(&buf[..n])?;
+            downloaded += n as u64;
+            on_event(DownloadEvent::Progress {
+                downloaded,
+                total,
+                resumed_from,
+            });
+        }
+        file.flush()?;
+    }
+    drop(file);
+
+    if let Some(total) = total {
+        if downloaded != total {
+            bail!(
+                "ダウンロードが途中で終了しました（{}/{} バイト）。再実行すると続きから取得します",
+                downloaded,
+                total
+            );
+        }
+    }
+
+    on_event(DownloadEvent::Verifying);
+    let sha256 = checksum::sha256_file(&part)?;
+    let verified = match expected {
+        Some(expected) if expected == sha256 => true,
+        Some(expected) => {
+            remove_partial(dest);
+            bail!(
+                "チェックサムが一致しません（期待値: {}, 実際: {}）",
+                expected,
+                sha256
+            );
+        }
+        None => false,
+    };
+
+    fs::rename(&part, dest)
+        .with_context(|| format!("ファイルを移動できません: {}", dest.display()))?;
+    let _ = fs::remove_file(&validator_path);
+
+    Ok(DownloadOutcome {
+        bytes: downloaded,
+        resumed_from,
+        sha256,
+        verified,
+    })
+}
+
+/// `.part` と検証子のファイルを削除する
+fn remove_partial(dest: &Path) {
+    let _ = fs::remove_file(partial_path(dest));
+    let _ = fs::remove_file(partial_validator_path(dest));
+}
+
+/// `If-Range` に使う検証子（強い ETag、無ければ Last-Modified）
+/// - 弱い ETag（`W/`）は `If-Range` に使えないため扱わない
+fn response_validator(headers: &HeaderMap) -> Option<String> {
+    let etag = headers
+        .get(ETAG)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|value| !value.is_empty() && !value.starts_with("W/"));
+    etag.or_else(|| {
+        headers
+            .get(LAST_MODIFIED)
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+    })
+    .map(str::to_string)
+}
+
+/// `Content-Range: bytes start-end/total` から (start, total) を取得する
+fn parse_content_range(response: &reqwest::blocking::Response) -> Option<(u64, Option<u64>)> {
+    let value = response.headers().get(CONTENT_RANGE)?.to_str().ok()?;
+    let range = value.trim().strip_prefix("bytes ")?;
+    let (span, total) = range.split_once('/')?;
+    let (start, _) = span.split_once('-')?;
+    Some((start.trim().parse().ok()?, total.trim().parse().ok()))
+}
+
+/// `Content-Range: bytes */total` から全体サイズを取得する
+fn content_range_total(response: &reqwest::blocking::Response) -> Option<u64> {
+    let value = response.headers().get(CONTENT_RANGE)?.to_str().ok()?;
+    value.rsplit_once('/')?.1.trim().parse().ok()
+}
+
+/// HuggingFace の `X-Linked-ETag`（LFS オブジェクトの SHA-256）を取得する
+fn linked_etag_sha256(headers: &HeaderMap) -> Option<String> {
+    let value = headers.get("x-linked-etag")?.to_str().ok()?;
+    let value = value.trim().trim_start_matches("W/").trim_matches('"');
+    checksum::is_sha256_hex(value).then(|| value.to_ascii_lowercase())
+}
+
+/// リダイレクト前の応答から `X-Linked-ETag` を取得する
+/// - HuggingFace の `resolve` URL は CDN へリダイレクトし、ハッシュはリダイレクト応答にだけ付く
+fn fetch_linked_sha256(url: &str, connect_timeout: Duration) -> Option<String> {
+    let client = reqwest::blocking::Client::builder()
+        .connect_timeout(connect_timeout)
+        .timeout(connect_timeout)
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .ok()?;
+    let response = client.head(url).send().ok()?;
+    linked_etag_sha256(response.headers())
+}
@@ -0,0 +1,194 @@
+use crate::models::{
+    ResponseFormat, TranscribeResponse, TranscriptionSegment, VerboseSegment, VerboseTranscription,
+    VerboseWord,
+};
+
+// =============================================================================
+// 文字起こし結果のエクスポート
+// - `format` フィールドで指定された形式（text/srt/vtt/tsv/verbose_json）へ変換する
+// - ダウンロード用のファイル名と Content-Disposition もここで組み立てる
+// =============================================================================
+
+/// 全文テキスト
+pub fn to_text(response: &TranscribeResponse) -> String {
+    let mut text = response.text.trim().to_string();
+    text.push('\n');
+    text
+}
+
+/// SRT 字幕
+pub fn to_srt(segments: &[TranscriptionSegment]) -> String {
+    segments
+        .iter()
+        .enumerate()
+        .map(|(index, segment)| trimmed(segment).to_srt_format(index))
+        .collect()
+}
+
+/// WebVTT 字幕
+/// - 話者は声のタグ（`<v SPEAKER_1>`）で表す
+pub fn to_vtt(segments: &[TranscriptionSegment]) -> String {
+    let mut vtt = String::from("WEBVTT\n\n");
+    for segment in segments {
+        let mut cue = trimmed(&TranscriptionSegment {
+            speaker: None,
+            ..segment.clone()
+        });
+        if let Some(speaker) = segment.speaker.as_deref() {
+            cue.text = format!("<v {}>{}", speaker, cue.text);
+        }
+        vtt.push_str(&cue.to_vtt_format());
+    }
+    vtt
+}
+
+/// TSV（whisper.cpp の `-otsv` と同じく start/end はミリ秒）
+pub fn to_tsv(segments: &[TranscriptionSegment]) -> String {
+    let mut tsv = String::from("start\tend\ttext\n");
+    for segment in segments {
+        // タブ/改行は列区切りを壊すので空白に置換
+        let text: String = segment
+            .labelled_text()
+            .chars()
+            .map(|c| {
+                if matches!(c, '\t' | '\r' | '\n') {
+                    ' '
+                } else {
+                    c
+                }
+            })
+            .collect();
+        tsv.push_str(&format!(
+            "{}\t{}\t{}\n",
+            segment.start_time_ms, segment.end_time_ms, text
+        ));
+    }
+    tsv
+}
+
+/// 詳細 JSON（`verbose_json`）
+pub fn to_verbose(response: &TranscribeResponse, translated: bool) -> VerboseTranscription {
+    let segments = response
+        .segments
+        .as_deref()
+        .unwrap_or_default()
+        .iter()
+        .enumerate()
+        .map(|(id, segment)| VerboseSegment {
+            id,
+            start: ms_to_seconds(segment.start_time_ms),
+            end: ms_to_seconds(segment.end_time_ms),
+            text: segment.text.trim().to_string(),
+            avg_logprob: segment.avg_logprob,
+            no_speech_prob: segment.no_speech_prob,
+            channel: segment.channel,
+            speaker: segment.speaker.clone(),
+            words: segment.words.as_ref().map(|words| {
+                words
+                    .iter()
+                    .map(|word| VerboseWord {
+                        word: word.word.clone(),
+                        start: ms_to_seconds(word.start_time_ms),
+                        end: ms_to_seconds(word.end_time_ms),
+                        probability: word.probability,
+                    })
+                    .collect()
+            }),
+        })
+        .collect();
+
+    VerboseTranscription {
+        task: if translated {
+            "translate"
+        } else {
+            "transcribe"
+        }
+        .to_string(),
+        language: response.language.clone(),
+        duration: ms_to_seconds(response.duration_ms.unwrap_or(0)),
+        text: response.text.trim().to_string(),
+        processing_time_ms: response.processing_time_ms,
+        segments,
+        decoding: response.decoding.clone(),
+    }
+}
+
+/// 指定形式のレスポンス本文を生成
+/// - json は `TranscribeResponse` をそのまま JSON にする
+pub fn render(
+    response: &TranscribeResponse,
+    format: ResponseFormat,
+    translated: bool,
+) -> anyhow::Result<String> {
+    let segments = response.segments.as_deref().unwrap_or_default();
+
+    let body = match format {
+        ResponseFormat::Json => serde_json::to_string(response)?,
+        ResponseFormat::Text => to_text(response),
+        ResponseFormat::Srt => to_srt(segments),
+        ResponseFormat::Vtt => to_vtt(segments),
+        ResponseFormat::Tsv => to_tsv(segments),
+        ResponseFormat::VerboseJson => serde_json::to_string(&to_verbose(response, translated))?,
+    };
+
+    Ok(body)
+}
+
+/// アップロードされたファイル名から、ダウンロード用のファイル名を作る
+/// - 例: `meeting.mp3` + srt → `meeting.srt`
+pub fn download_filename(source_filename: &str, format: ResponseFormat) -> String {
+    let stem = std::path::Path::new(source_filename)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .filter(|s| !s.is_empty())
+        .unwrap_or("transcription");
+
+    format!("{}.{}", stem, format.extension())
+}
+
+/// `Content-Disposition` ヘッダ値
+/// - 日本語などの非 ASCII ファイル名は RFC 5987 の `filename*` で渡し、
+///   `filename` には ASCII の代替名を入れる
+pub fn content_disposition(filename: &str) -> String {
+    let fallback: String = filename
+        .chars()
+        .map(|c| {
+            if c.is_ascii() && !c.is_ascii_control() && c != '"' && c != '\\' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    format!(
+        "attachment; filename=\"{}\"; filename*=UTF-8''{}",
+        fallback,
+        percent_encode(filename)
+    )
+}
+
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::new();
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// 字幕用のセグメント（前後の空白を除き、チャンネル/話者があれば `[chN]` / `[SPEAKER_N]` を付ける）
+fn trimmed(segment: &TranscriptionSegment) -> TranscriptionSegment {
+    TranscriptionSegment::new(
+        segment.labelled_text(),
+        segment.start_time_ms,
+        segment.end_time_ms,
+    )
+}
+
+fn ms_to_seconds(ms: u64) -> f64 {
+    ms as f64 / 1000.0
+}
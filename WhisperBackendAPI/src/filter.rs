@@ -0,0 +1,138 @@
+use crate::config::FilterConfig;
+use crate::models::{FilterReason, FilteredSegment, TranscriptionSegment};
+
+// =============================================================================
+// ハルシネーション/繰り返しの除去
+// - whisper が無音や音楽区間で出しがちな定型句・繰り返しをセグメント単位で取り除く
+// - 取り除いた（書き換えた）セグメントは理由付きで返し、レスポンスで報告する
+// =============================================================================
+
+/// 繰り返しとみなす単位の最大文字数（長いテキストでの探索コストを抑える）
+const MAX_REPEAT_UNIT_CHARS: usize = 64;
+
+/// セグメント列を後処理する
+/// - 定型句（`blocklist`）と一致するセグメントを除く
+/// - 無音確率が高く、平均対数確率が低いセグメントを除く（値は後処理が有効なら推論時に求める）
+/// - 同じ内容が `max_repeats` 回を超えて続くセグメントは最初の 1 つだけ残す
+/// - セグメント内で同じ文字列が `max_repeats` 回を超えて続く箇所は 1 回にまとめる
+/// - 戻り値は残ったセグメントと、除いた/書き換えたセグメント（元のテキスト）
+pub fn filter_segments(
+    segments: Vec<TranscriptionSegment>,
+    config: &FilterConfig,
+) -> (Vec<TranscriptionSegment>, Vec<FilteredSegment>) {
+    let blocklist: Vec<String> = config
+        .blocklist
+        .iter()
+        .map(|phrase| normalize(phrase))
+        .filter(|phrase| !phrase.is_empty())
+        .collect();
+
+    let mut kept: Vec<TranscriptionSegment> = Vec::with_capacity(segments.len());
+    let mut removed = Vec::new();
+    // 直前から同じ内容が続いているセグメント
+    let mut run: Vec<TranscriptionSegment> = Vec::new();
+
+    for mut segment in segments {
+        let normalized = normalize(&segment.text);
+
+        if !normalized.is_empty() && blocklist.contains(&normalized) {
+            removed.push(FilteredSegment::new(&segment, FilterReason::Blocklist));
+            continue;
+        }
+
+        let no_speech = segment
+            .no_speech_prob
+            .is_some_and(|probability| probability > config.no_speech_threshold)
+            && segment
+                .avg_logprob
+                .is_some_and(|logprob| logprob < config.logprob_threshold);
+        if no_speech {
+            removed.push(FilteredSegment::new(&segment, FilterReason::NoSpeech));
+            continue;
+        }
+
+        if let Some(collapsed) = collapse_repetitions(&segment.text, config.max_repeats) {
+            removed.push(FilteredSegment::new(&segment, FilterReason::Repetition));
+            segment.text = collapsed;
+        }
+
+        if run
+            .first()
+            .is_some_and(|first| normalize(&first.text) != normalize(&segment.text))
+        {
+            flush_run(&mut run, &mut kept, &mut removed, config.max_repeats);
+        }
+        run.push(segment);
+    }
+    flush_run(&mut run, &mut kept, &mut removed, config.max_repeats);
+
+    (kept, removed)
+}
+
+/// 同じ内容が続いたセグメントを確定する（`max_repeats` を超えていれば最初の 1 つだけ残す）
+fn flush_run(
+    run: &mut Vec<TranscriptionSegment>,
+    kept: &mut Vec<TranscriptionSegment>,
+    removed: &mut Vec<FilteredSegment>,
+    max_repeats: usize,
+) {
+    let mut segments = run.drain(..);
+    if segments.len() > max_repeats.max(1) {
+        kept.extend(segments.next());
+        removed.extend(
+            segments.map(|segment| FilteredSegment::new(&segment, FilterReason::Repetition)),
+        );
+    } else {
+        kept.extend(segments);
+    }
+}
+
+/// テキスト内で同じ文字列が `max_repeats` 回を超えて連続する箇所を 1 回にまとめる
+/// - 変更が無ければ `None`
+/// - 単位は文字列なので、空白で区切らない言語の「ありがとうありがとう…」にも効く
+pub fn collapse_repetitions(text: &str, max_repeats: usize) -> Option<String> {
+    let limit = max_repeats.max(1);
+    let chars: Vec<char> = text.chars().collect();
+    let mut output: Vec<char> = Vec::with_capacity(chars.len());
+    let mut changed = false;
+    let mut i = 0;
+
+    while i < chars.len() {
+        // 位置 i から始まる最も長く続く繰り返しを探す
+        let mut best: Option<(usize, usize)> = None;
+        for unit in 1..=((chars.len() - i) / (limit + 1)).min(MAX_REPEAT_UNIT_CHARS) {
+            let pattern = &chars[i..i + unit];
+            let mut count = 1;
+            while i + (count + 1) * unit <= chars.len()
+                && &chars[i + count * unit..i + (count + 1) * unit] == pattern
+            {
+                count += 1;
+            }
+            if count > limit && best.is_none_or(|(u, c)| unit * count > u * c) {
+                best = Some((unit, count));
+            }
+        }
+
+        match best {
+            Some((unit, count)) => {
+                output.extend_from_slice(&chars[i..i + unit]);
+                i += unit * count;
+                changed = true;
+            }
+            None => {
+                output.push(chars[i]);
+                i += 1;
+            }
+        }
+    }
+
+    changed.then(|| output.into_iter().collect::<String>().trim().to_string())
+}
+
+/// 比較用にテキストを正規化（英数字/かな漢字以外を除き、小文字にする）
+fn normalize(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
@@ -0,0 +1,394 @@
+use crate::config::GlossaryConfig;
+use crate::models::{DecodingParameters, FilterReason, FilteredSegment, TranscriptionSegment};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+// =============================================================================
+// 用語集
+// - 分野の用語/製品名/人名のリストに名前を付けて登録し、文字起こしで `glossary=<名前>` で参照する
+//   - 設定ファイル（`[[glossaries]]`）のもの: 読み取り専用
+//   - API（`PUT /glossaries/{name}`）で登録したもの: `<root>/<name>.json` に保存
+// - 語句から whisper の初期プロンプトを組み立てる
+// - 任意で、セグメント中の語句に近い表記（1〜2 文字違い/大文字小文字違い）を語句の表記に直す
+// =============================================================================
+
+/// 用語集の名前の最大文字数
+pub const MAX_NAME_CHARS: usize = 64;
+/// 1 つの用語集の最大語句数
+pub const MAX_TERMS: usize = 500;
+/// 1 語句の最大文字数
+pub const MAX_TERM_CHARS: usize = 100;
+/// 表記を直す語句の最小文字数（短い語句は誤修正が多いため初期プロンプトにだけ使う）
+const MIN_CORRECTION_CHARS: usize = 4;
+/// 2 文字違いまで直す語句の最小文字数（これより短い語句は 1 文字違いまで）
+const MIN_TWO_EDITS_CHARS: usize = 8;
+
+/// 名前付きの用語集（`GET /glossaries/{name}`）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Glossary {
+    pub name: String,
+    /// 語句（前にあるものほど初期プロンプトで優先する）
+    pub terms: Vec<String>,
+    /// 既定で表記を直すか（リクエストの `glossary_correction` で上書きできる）
+    pub correct: bool,
+    /// 設定ファイルで定義したもの（API では変更/削除できない）
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+/// 用語集の登録内容（`PUT /glossaries/{name}` の本文）
+#[derive(Debug, Clone, Deserialize)]
+pub struct GlossaryUpload {
+    pub terms: Vec<String>,
+    #[serde(default)]
+    pub correct: bool,
+}
+
+/// 用語集の一覧（`GET /glossaries`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GlossaryListResponse {
+    pub glossaries: Vec<Glossary>,
+}
+
+impl Glossary {
+    /// 名前と語句を検証して作る
+    /// - 名前はファイル名に使うため英数字/`-`/`_` のみ
+    /// - 語句は前後の空白を除き、空のものと重複（大文字小文字を区別しない）は除く
+    pub fn new(
+        name: &str,
+        terms: &[String],
+        correct: bool,
+        read_only: bool,
+    ) -> Result<Self, String> {
+        validate_name(name)?;
+
+        let mut seen = HashSet::new();
+        let mut normalized = Vec::with_capacity(terms.len());
+        for term in terms.iter().map(|term| term.trim()) {
+            if term.is_empty() || !seen.insert(term.to_lowercase()) {
+                continue;
+            }
+            if term.chars().count() > MAX_TERM_CHARS || term.chars().any(char::is_control) {
+                return Err(format!(
+                    "語句は改行/制御文字を含まない {} 文字以内で指定してください: {}",
+                    MAX_TERM_CHARS, term
+                ));
+            }
+            normalized.push(term.to_string());
+        }
+        if normalized.is_empty() {
+            return Err("語句を 1 つ以上指定してください".to_string());
+        }
+        if normalized.len() > MAX_TERMS {
+            return Err(format!("語句は {} 個以内で指定してください", MAX_TERMS));
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            terms: normalized,
+            correct,
+            read_only,
+        })
+    }
+
+    /// 語句から初期プロンプトを組み立てる
+    /// - 語句を `, ` でつなぎ、リクエストの初期プロンプトがあれば後ろに続ける
+    /// - 全体が `DecodingParameters::MAX_PROMPT_CHARS` に収まるよう、入りきらない語句は後ろから省く
+    pub fn prompt(&self, user_prompt: Option<&str>) -> String {
+        let user_prompt = user_prompt
+            .map(str::trim)
+            .filter(|prompt| !prompt.is_empty());
+        let budget = DecodingParameters::MAX_PROMPT_CHARS
+            .saturating_sub(user_prompt.map_or(0, |prompt| prompt.chars().count() + 1));
+
+        let mut prompt = String::new();
+        let mut used = 0;
+        for term in &self.terms {
+            let separator = if prompt.is_empty() { "" } else { ", " };
+            let len = separator.len() + term.chars().count();
+            if used + len > budget {
+                break;
+            }
+            prompt.push_str(separator);
+            prompt.push_str(term);
+            used += len;
+        }
+
+        if let Some(user_prompt) = user_prompt {
+            if !prompt.is_empty() {
+                prompt.push(' ');
+            }
+            prompt.push_str(user_prompt);
+        }
+        prompt
+    }
+}
+
+/// 用語集の名前を検証
+pub fn validate_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name.chars().count() <= MAX_NAME_CHARS
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "用語集の名前は英数字/-/_ の {} 文字以内で指定してください: {}",
+            MAX_NAME_CHARS, name
+        ))
+    }
+}
+
+/// 用語集の保存先と、読み込んだ用語集（名前 → 用語集）
+pub struct GlossaryStore {
+    root: PathBuf,
+    glossaries: RwLock<BTreeMap<String, Glossary>>,
+}
+
+impl GlossaryStore {
+    /// `root` に保存した用語集と設定の用語集を読み込む
+    /// - 読めないファイルは警告して飛ばす。設定と同名のものは設定を優先する
+    /// - ディレクトリは最初の登録時に作る
+    pub fn open(root: impl Into<PathBuf>, configured: &[GlossaryConfig]) -> Self {
+        let root = root.into();
+        let mut glossaries = BTreeMap::new();
+
+        if let Ok(dir_entries) = fs::read_dir(&root) {
+            for dir_entry in dir_entries.flatten() {
+                let path = dir_entry.path();
+                if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                    continue;
+                }
+                match read_glossary(&path) {
+                    Ok(glossary) => {
+                        glossaries.insert(glossary.name.clone(), glossary);
+                    }
+                    Err(e) => eprintln!("用語集を読み込めません: {} - {}", path.display(), e),
+                }
+            }
+        }
+
+        for config in configured {
+            match Glossary::new(&config.name, &config.terms, config.correct, true) {
+                Ok(glossary) => {
+                    glossaries.insert(glossary.name.clone(), glossary);
+                }
+                Err(e) => eprintln!("設定の用語集を読み込めません: {}", e),
+            }
+        }
+
+        Self {
+            root,
+            glossaries: RwLock::new(glossaries),
+        }
+    }
+
+    /// 名前順の一覧
+    pub fn list(&self) -> Vec<Glossary> {
+        self.glossaries.read().unwrap().values().cloned().collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<Glossary> {
+        self.glossaries.read().unwrap().get(name).cloned()
+    }
+
+    /// 登録（同名のものは置き換える）し、ファイルに保存する
+    /// - 読み取り専用かどうかは呼び出し側で確認する
+    pub fn put(&self, glossary: Glossary) -> Result<()> {
+        fs::create_dir_all(&self.root)
+            .with_context(|| format!("ディレクトリを作成できません: {}", self.root.display()))?;
+
+        // 書きかけのファイルを読まないよう、一時ファイルに書いてから置き換える
+        let path = self.root.join(format!("{}.json", glossary.name));
+        let temp_path = self.root.join(format!(".{}.json.tmp", glossary.name));
+        fs::write(&temp_path, serde_json::to_vec_pretty(&glossary)?)
+            .and_then(|_| fs::rename(&temp_path, &path))
+            .with_context(|| format!("用語集を保存できません: {}", path.display()))?;
+
+        self.glossaries
+            .write()
+            .unwrap()
+            .insert(glossary.name.clone(), glossary);
+        Ok(())
+    }
+
+    /// 削除（見つからなければ false）
+    pub fn delete(&self, name: &str) -> Result<bool> {
+        let mut glossaries = self.glossaries.write().unwrap();
+        if glossaries.remove(name).is_none() {
+            return Ok(false);
+        }
+        match fs::remove_file(self.root.join(format!("{}.json", name))) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(true),
+            Err(e) => Err(e).context("用語集のファイルを削除できません"),
+        }
+    }
+}
+
+fn read_glossary(path: &std::path::Path) -> Result<Glossary> {
+    let glossary: Glossary = serde_json::from_slice(&fs::read(path)?)?;
+    // ファイル名と中身の名前が食い違うもの、手で編集されて不正になったものは読まない
+    let expected = path.file_stem().and_then(|stem| stem.to_str());
+    if expected != Some(glossary.name.as_str()) {
+        anyhow::bail!("ファイル名と用語集の名前が一致しません");
+    }
+    Glossary::new(&glossary.name, &glossary.terms, glossary.correct, false)
+        .map_err(anyhow::Error::msg)
+}
+
+// -----------------------------------------------------------------------------
+// 表記の修正
+// -----------------------------------------------------------------------------
+
+/// 表記を直す対象の語句
+struct CorrectionTerm {
+    chars: Vec<char>,
+    /// 小文字にした語句（比較用）
+    lower: Vec<char>,
+    /// 許容する編集距離
+    max_edits: usize,
+}
+
+impl CorrectionTerm {
+    /// 文字数の多い順に並べる（長い語句を優先して当てはめる）
+    fn from_terms(terms: &[String]) -> Vec<Self> {
+        let mut correction_terms: Vec<Self> = terms
+            .iter()
+            .map(|term| term.chars().collect::<Vec<char>>())
+            .filter(|chars| chars.len() >= MIN_CORRECTION_CHARS)
+            .map(|chars| Self {
+                lower: chars.iter().map(|&c| lowercase(c)).collect(),
+                max_edits: if chars.len() >= MIN_TWO_EDITS_CHARS {
+                    2
+                } else {
+                    1
+                },
+                chars,
+            })
+            .collect();
+        correction_terms.sort_by_key(|term| std::cmp::Reverse(term.chars.len()));
+        correction_terms
+    }
+
+    /// `start` から始まる語句に近い表記の（長さ, 編集距離）
+    /// - 長さは語句の文字数 ±1。編集距離が最も小さいものを選ぶ
+    /// - 英数字で始まる/終わる語句は単語の途中に当てはめない
+    fn match_at(&self, chars: &[char], lower: &[char], start: usize) -> Option<(usize, usize)> {
+        let len = self.chars.len();
+        [len, len - 1, len + 1]
+            .into_iter()
+            .filter(|&window| start + window <= chars.len())
+            .filter(|&window| self.fits(chars, start, window))
+            .filter(|&window| {
+                // 先頭か末尾の 1 文字が一致しないものは比較しない（探索コストを抑える）
+                lower[start] == self.lower[0] || lower[start + window - 1] == self.lower[len - 1]
+            })
+            .map(|window| {
+                (
+                    window,
+                    edit_distance(&lower[start..start + window], &self.lower),
+                )
+            })
+            .filter(|&(_, distance)| distance <= self.max_edits)
+            .min_by_key(|&(_, distance)| distance)
+    }
+
+    /// 当てはめる範囲の前後が語句と同じ種類の文字で区切られているか
+    /// - 語句が文字（漢字/かなを含む）で始まる/終わるなら、範囲も文字で始まる/終わる
+    /// - 語句が英数字で始まる/終わるなら、範囲の外側は英数字以外
+    fn fits(&self, chars: &[char], start: usize, window: usize) -> bool {
+        let end = start + window;
+        let (first, last) = (self.chars[0], self.chars[self.chars.len() - 1]);
+        if first.is_alphanumeric() != chars[start].is_alphanumeric()
+            || last.is_alphanumeric() != chars[end - 1].is_alphanumeric()
+        {
+            return false;
+        }
+        let outside_word = |c: Option<&char>| c.is_none_or(|c| !c.is_ascii_alphanumeric());
+        (!first.is_ascii_alphanumeric() || outside_word(start.checked_sub(1).map(|i| &chars[i])))
+            && (!last.is_ascii_alphanumeric() || outside_word(chars.get(end)))
+    }
+}
+
+/// セグメントのテキストを語句の表記に直す（単語タイムスタンプはそのまま）
+/// - 戻り値は書き換えたセグメント（元のテキスト）
+pub fn correct_segments(
+    segments: &mut [TranscriptionSegment],
+    terms: &[String],
+) -> Vec<FilteredSegment> {
+    let terms = CorrectionTerm::from_terms(terms);
+    let mut corrected = Vec::new();
+    if terms.is_empty() {
+        return corrected;
+    }
+    for segment in segments {
+        if let Some(text) = correct_with(&segment.text, &terms) {
+            corrected.push(FilteredSegment::new(segment, FilterReason::Glossary));
+            segment.text = text;
+        }
+    }
+    corrected
+}
+
+/// テキスト中の語句に近い表記を語句の表記に直す（直すものが無ければ None）
+fn correct_with(text: &str, terms: &[CorrectionTerm]) -> Option<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let lower: Vec<char> = chars.iter().map(|&c| lowercase(c)).collect();
+    // 最初に当てはまる語句（長い順）の (語句, 長さ, 編集距離)
+    let best_at = |start: usize| {
+        terms.iter().find_map(|term| {
+            term.match_at(&chars, &lower, start)
+                .map(|(window, distance)| (term, window, distance))
+        })
+    };
+
+    let mut corrected = String::with_capacity(text.len());
+    let mut changed = false;
+    let mut position = 0;
+    while position < chars.len() {
+        let matched = best_at(position).filter(|&(_, _, distance)| {
+            // 1 文字後ろからの方が近い場合は、そちらに当てはめる（「は田中太郎」の「は」を巻き込まない）
+            distance == 0
+                || best_at(position + 1)
+                    .is_none_or(|(_, _, next_distance)| next_distance >= distance)
+        });
+        match matched {
+            Some((term, window, _)) => {
+                changed |= chars[position..position + window] != term.chars[..];
+                corrected.extend(&term.chars);
+                position += window;
+            }
+            None => {
+                corrected.push(chars[position]);
+                position += 1;
+            }
+        }
+    }
+    changed.then_some(corrected)
+}
+
+fn lowercase(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// 編集距離（レーベンシュタイン距離、文字単位）
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, &ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
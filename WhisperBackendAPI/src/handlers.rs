@@ -1,44 +1,348 @@
-use crate::audio::{format_file_size, AudioProcessor};
-use crate::config::Config;
+use crate::audio::{format_file_size, AudioProcessor, ChannelMode};
+use crate::auth::{check_admin, check_quota, ApiCaller, ApiKeyAuth, AuthRejection, UsageFile};
+use crate::batch::{
+    build_archive, BatchFileResult, BatchInputs, BatchLimits, BatchManifest, OutputNames,
+};
+use crate::checksum;
+use crate::chunk::plan_chunks;
+use crate::config::{Config, WebhookConfig};
+use crate::diarize;
+use crate::download::{self, DownloadManager, DownloadOptions, DownloadPhase, DownloadStatus};
+use crate::export;
+use crate::glossary::{Glossary, GlossaryListResponse, GlossaryStore, GlossaryUpload};
+use crate::history::{HistoryListResponse, HistoryParameters, HistoryQuery, HistoryStore};
+use crate::janitor::Janitor;
+use crate::jobs::{JobInfo, JobOutcome, JobStore};
+use crate::metrics::{Metrics, MetricsGauges};
 use crate::models::*;
-use crate::whisper::{get_language_name, get_supported_languages, preprocess_audio, WhisperEngine};
+use crate::openai::{
+    self, OpenAiAudioRequest, OpenAiErrorBody, OpenAiErrorResponse, OpenAiResponseFormat,
+    TimestampGranularity,
+};
+use crate::registry::{model_size_mb, resolve_model, ModelRegistry};
+use crate::webhook::{self, WebhookTarget};
+use crate::whisper::{
+    get_language_name, get_supported_languages, preprocess_audio, InferenceHooks, PoolError,
+    PooledEngine, TranscribeOptions, WhisperEngine, WhisperEnginePool,
+    LANGUAGE_DETECTION_WINDOW_SECONDS,
+};
 use axum::{
-    extract::{Multipart, State},
-    http::StatusCode,
-    response::Json,
+    body::Bytes,
+    extract::{MatchedPath, Multipart, Path, Query, Request, State},
+    http::{header, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Json, Response,
+    },
+    Extension,
 };
+use futures_util::stream::{self, Stream, StreamExt};
+use std::convert::Infallible;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
-use std::time::Instant;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
 
 // =============================================================================
 // Application State
-// - ハンドラ間で共有する情報を集約（設定、Whisper エンジン、統計、起動時刻）
+// - ハンドラ間で共有する情報を集約（設定、Whisper エンジンプール、統計、起動時刻）
 // - `Arc<Mutex<..>>` を用いてスレッドセーフに共有
 // =============================================================================
 
 #[derive(Clone)]
 pub struct AppState {
     pub config: Arc<Config>,
-    pub whisper_engine: Arc<Mutex<Option<WhisperEngine>>>,
+    /// 読み込み済みモデル（モデル名 → エンジンプール）
+    pub models: Arc<ModelRegistry<WhisperEnginePool>>,
+    /// モデル読み込みの直列化（同じモデルの二重読み込みを防ぐ）
+    pub model_load_lock: Arc<tokio::sync::Mutex<()>>,
+    /// 全モデルのプールで共有する推論の許可（合計の同時推論数を `max_concurrent_requests` に抑える）
+    pub engine_permits: Arc<Semaphore>,
     pub stats: Arc<Mutex<ServerStats>>,
     pub start_time: Arc<Instant>,
+    pub jobs: Arc<JobStore>,
+    /// サーバー側のモデルダウンロード
+    pub downloads: Arc<DownloadManager>,
+    /// API キー認証とレート制限
+    pub auth: Arc<ApiKeyAuth>,
+    /// API キーごとの使用量の保存先（認証が有効な場合のみ）
+    pub key_usage: Option<Arc<UsageFile>>,
+    /// Prometheus メトリクス
+    pub metrics: Arc<Metrics>,
+    /// 文字起こし履歴（`history.enabled` の場合のみ）
+    pub history: Option<Arc<HistoryStore>>,
+    /// 用語集（設定のものと API で登録したもの）
+    pub glossaries: Arc<GlossaryStore>,
+    /// 一時ファイルの掃除（直近の結果は `/health` で返す）
+    pub janitor: Arc<Janitor>,
 }
 
 impl AppState {
     pub fn new(config: Config) -> Self {
+        let job_retention = Duration::from_secs(config.limits.job_retention_minutes as u64 * 60);
+        let memory_budget_mb = config.whisper.model_memory_budget_mb;
+        let auth = ApiKeyAuth::new(config.auth.clone());
+        // 月間クォータを再起動で数え直さないよう、キー別の使用量を保存から戻す
+        let key_usage = config.auth.enabled.then(|| {
+            Arc::new(UsageFile::new(
+                std::path::Path::new(&config.paths.upload_dir)
+                    .join("auth")
+                    .join("usage.json"),
+            ))
+        });
+        let stats = ServerStats {
+            api_keys: key_usage
+                .as_ref()
+                .map(|file| file.load())
+                .unwrap_or_default(),
+            ..Default::default()
+        };
+        // 履歴ストアを開けない場合は保存せずに起動する
+        let history = config
+            .history
+            .enabled
+            .then(|| {
+                let root = std::path::Path::new(&config.paths.upload_dir).join("history");
+                HistoryStore::open(root, &config.history)
+                    .map(Arc::new)
+                    .map_err(|e| eprintln!("履歴を無効にして起動します: {}", e))
+                    .ok()
+            })
+            .flatten();
+        let glossaries = GlossaryStore::open(
+            std::path::Path::new(&config.paths.upload_dir).join("glossaries"),
+            &config.glossaries,
+        );
+        let janitor = Janitor::new(&config, history.clone());
+        let engine_permits = Arc::new(Semaphore::new(
+            config.performance.max_concurrent_requests.max(1),
+        ));
         Self {
             config: Arc::new(config),
-            whisper_engine: Arc::new(Mutex::new(None)),
-            stats: Arc::new(Mutex::new(ServerStats::default())),
+            models: Arc::new(ModelRegistry::new(memory_budget_mb)),
+            model_load_lock: Arc::new(tokio::sync::Mutex::new(())),
+            engine_permits,
+            stats: Arc::new(Mutex::new(stats)),
             start_time: Arc::new(Instant::now()),
+            jobs: Arc::new(JobStore::new(job_retention)),
+            downloads: Arc::new(DownloadManager::new()),
+            auth: Arc::new(auth),
+            key_usage,
+            metrics: Arc::new(Metrics::new()),
+            history,
+            glossaries: Arc::new(glossaries),
+            janitor: Arc::new(janitor),
         }
     }
 
     pub fn with_whisper_engine(self, engine: WhisperEngine) -> Self {
         // 起動後に Whisper エンジンを差し込む（初期化に失敗してもサーバーは起動できる設計）
-        *self.whisper_engine.lock().unwrap() = Some(engine);
+        // - 同時処理数 `max_concurrent_requests` 分のエンジンでモデルを共有するプールを作る
+        // - 設定の既定モデルとして登録する
+        let pool = self.new_pool(engine);
+        let size_mb = model_size_mb(std::path::Path::new(&self.config.whisper.model_path));
+        if let Err(e) = self
+            .models
+            .insert(&self.config.whisper.default_model, pool, size_mb, true)
+        {
+            eprintln!("既定モデルを登録できませんでした: {}", e);
+        }
         self
     }
+
+    /// API キーの文字起こし成功と音声の長さを記録し、使用量を保存する
+    /// - キー無しのリクエストは何もしない
+    pub fn record_key_success(&self, caller: Option<&str>, audio_duration_ms: Option<u64>) {
+        self.update_key_usage(caller, |stats, key| {
+            stats.record_key_success(Some(key), audio_duration_ms)
+        });
+    }
+
+    /// 文字起こし以外で推論した音声の長さを API キーの使用量に加え、保存する
+    pub fn record_key_audio(&self, caller: Option<&str>, audio_duration_ms: u64) {
+        self.update_key_usage(caller, |stats, key| {
+            stats.record_key_audio(key, audio_duration_ms)
+        });
+    }
+
+    /// 呼び出し元のキーが今月の上限に達していないか、現在の使用量で確認する
+    /// - 受付後に使用量が増える処理（一括の各ファイルなど）で、推論の前に呼ぶ
+    /// - 認証が無効（呼び出し元が無い）なら何もしない
+    pub fn check_key_quota(&self, caller: Option<&str>) -> ApiResult<()> {
+        let Some(key) = caller.and_then(|name| self.auth.key(name)) else {
+            return Ok(());
+        };
+        let used_ms = self.stats.lock().unwrap().key_month_audio_ms(&key.name);
+        check_quota(key, used_ms)
+            .map_err(|rejection| ApiError::new(ApiErrorCode::QuotaExceeded, rejection.message()))
+    }
+
+    /// キー別の使用量を更新し、保存先があれば書き出す（失敗しても処理は続ける）
+    fn update_key_usage(&self, caller: Option<&str>, update: impl FnOnce(&mut ServerStats, &str)) {
+        let Some(key) = caller else {
+            return;
+        };
+        let Some(file) = self.key_usage.as_ref() else {
+            update(&mut self.stats.lock().unwrap(), key);
+            return;
+        };
+        let saved = file.save(|| {
+            let mut stats = self.stats.lock().unwrap();
+            update(&mut stats, key);
+            stats.api_keys.clone()
+        });
+        if let Err(e) = saved {
+            eprintln!("{:#}", e);
+        }
+    }
+
+    fn new_pool(&self, engine: WhisperEngine) -> WhisperEnginePool {
+        WhisperEnginePool::with_permits(
+            engine,
+            Arc::clone(&self.engine_permits),
+            self.config.performance.max_concurrent_requests,
+            self.config.performance.max_queued_requests,
+        )
+    }
+
+    /// 既定モデルのエンジンプールを取得（未初期化なら None）
+    pub fn engine_pool(&self) -> Option<Arc<WhisperEnginePool>> {
+        self.models.default_model()
+    }
+
+    /// 指定モデルのエンジンプールをモデル名とともに取得する
+    /// - `model` 未指定なら既定モデル
+    /// - 読み込まれていないモデルは `whisper.load_models_on_request` の場合だけここで読み込む
+    ///   （メモリ予算を超える分は LRU で破棄）。それ以外は 503 MODEL_NOT_LOADED
+    pub async fn model_pool(
+        &self,
+        model: Option<&str>,
+    ) -> ApiResult<(String, Arc<WhisperEnginePool>)> {
+        let Some(name) = model.map(str::trim).filter(|name| !name.is_empty()) else {
+            return self
+                .models
+                .default_name()
+                .and_then(|name| self.models.get(&name).map(|pool| (name, pool)))
+                .ok_or_else(|| {
+                    ApiError::new(
+                        ApiErrorCode::ModelNotLoaded,
+                        "Whisperエンジンが初期化されていません",
+                    )
+                });
+        };
+
+        let (name, _) = self.resolve_model(name)?;
+        if let Some(pool) = self.models.get(&name) {
+            return Ok((name, pool));
+        }
+        if !self.config.whisper.load_models_on_request {
+            return Err(ApiError::new(
+                ApiErrorCode::ModelNotLoaded,
+                format!("モデル {} は読み込まれていません", name),
+            )
+            .with_details(format!(
+                "POST /models/{}/load で読み込んでから指定してください",
+                name
+            )));
+        }
+        self.load_model(&name).await?;
+        let pool = self.models.get(&name).ok_or_else(|| {
+            ApiError::new(
+                ApiErrorCode::ModelNotLoaded,
+                format!("モデル {} は読み込み直後に破棄されました", name),
+            )
+        })?;
+        Ok((name, pool))
+    }
+
+    /// モデル名をレジストリ上の名前とファイルパスへ解決する
+    fn resolve_model(&self, name: &str) -> ApiResult<(String, std::path::PathBuf)> {
+        resolve_model(&self.config, &ModelCatalog::default(), name).ok_or_else(|| {
+            ApiError::new(
+                ApiErrorCode::ModelNotFound,
+                format!("モデルが見つかりません: {}", name),
+            )
+            .with_details(format!(
+                "{} にモデルファイルを配置してください（GET /models で一覧を確認できます）",
+                self.config.paths.models_dir
+            ))
+        })
+    }
+
+    /// モデルを読み込んでレジストリへ登録し、破棄したモデル名を返す
+    /// - 読み込み済みなら何もしない
+    pub async fn load_model(&self, name: &str) -> ApiResult<(String, Vec<String>)> {
+        let (name, path) = self.resolve_model(name)?;
+
+        let _guard = self.model_load_lock.lock().await;
+        if self.models.is_loaded(&name) {
+            return Ok((name, Vec::new()));
+        }
+
+        let size_mb = model_size_mb(&path);
+        let config = Arc::clone(&self.config);
+        let model_path = path.to_string_lossy().to_string();
+        let engine = tokio::task::spawn_blocking(move || WhisperEngine::new(&model_path, &config))
+            .await
+            .map_err(|e| {
+                ApiError::new(
+                    ApiErrorCode::InternalError,
+                    format!("処理スレッドエラー: {}", e),
+                )
+            })?
+            .map_err(|e| {
+                ApiError::new(
+                    ApiErrorCode::ProcessingFailed,
+                    format!("モデル {} の読み込みに失敗: {}", name, e),
+                )
+            })?;
+
+        let pool = self.new_pool(engine);
+        let make_default = name == self.config.whisper.default_model;
+        let evicted = self
+            .models
+            .insert(&name, pool, size_mb, make_default)
+            .map_err(|e| ApiError::new(ApiErrorCode::ServerOverloaded, e.to_string()))?;
+        if !evicted.is_empty() {
+            println!(
+                "メモリ予算に収めるためモデルを破棄しました: {}",
+                evicted.join(", ")
+            );
+        }
+        println!("モデルを読み込みました: {} ({}MB)", name, size_mb);
+
+        Ok((name, evicted))
+    }
+
+    /// 同じモデルの空いているエンジンを待たずに借りる（最大 `count` 個）
+    /// - 1 リクエストの分割推論を並列にするために使う。空きが無ければ空の Vec
+    pub fn borrow_idle_engines(&self, model_name: &str, count: usize) -> Vec<PooledEngine> {
+        let Some(pool) = self.models.get(model_name) else {
+            return Vec::new();
+        };
+        std::iter::from_fn(|| pool.try_acquire())
+            .take(count)
+            .collect()
+    }
+
+    /// エンジンを借り出し、使うモデル名とともに返す
+    /// - 空きが無ければ FIFO で待機し、待機数が上限を超える場合は 429 を返す
+    pub async fn acquire_engine(&self, model: Option<&str>) -> ApiResult<(String, PooledEngine)> {
+        let (name, pool) = self.model_pool(model).await?;
+
+        let engine = pool.acquire().await.map_err(|e| match e {
+            PoolError::QueueFull => ApiError::new(ApiErrorCode::ServerOverloaded, e.to_string())
+                .with_details(format!(
+                    "同時処理数: {}, 待機上限: {}",
+                    pool.size(),
+                    pool.max_queued()
+                )),
+            PoolError::Closed => ApiError::new(ApiErrorCode::InternalError, e.to_string()),
+        })?;
+        Ok((name, engine))
+    }
 }
 
 // =============================================================================
@@ -76,17 +380,34 @@ impl From<anyhow::Error> for ApiError {
     }
 }
 
-impl axum::response::IntoResponse for ApiError {
-    fn into_response(self) -> axum::response::Response {
-        let status_code = match self.code {
+impl ApiError {
+    /// エラーコードに対応する HTTP ステータス
+    pub fn status_code(&self) -> StatusCode {
+        match self.code {
             ApiErrorCode::InvalidInput => StatusCode::BAD_REQUEST,
             ApiErrorCode::FileTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
             ApiErrorCode::UnsupportedFormat => StatusCode::UNSUPPORTED_MEDIA_TYPE,
             ApiErrorCode::ProcessingFailed => StatusCode::INTERNAL_SERVER_ERROR,
             ApiErrorCode::ModelNotLoaded => StatusCode::SERVICE_UNAVAILABLE,
+            ApiErrorCode::ModelNotFound => StatusCode::NOT_FOUND,
             ApiErrorCode::ServerOverloaded => StatusCode::TOO_MANY_REQUESTS,
             ApiErrorCode::InternalError => StatusCode::INTERNAL_SERVER_ERROR,
-        };
+            ApiErrorCode::JobNotFound => StatusCode::NOT_FOUND,
+            ApiErrorCode::JobNotReady => StatusCode::CONFLICT,
+            ApiErrorCode::HistoryNotFound => StatusCode::NOT_FOUND,
+            ApiErrorCode::GlossaryNotFound => StatusCode::NOT_FOUND,
+            ApiErrorCode::Unauthorized => StatusCode::UNAUTHORIZED,
+            ApiErrorCode::Forbidden => StatusCode::FORBIDDEN,
+            ApiErrorCode::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+            ApiErrorCode::QuotaExceeded => StatusCode::TOO_MANY_REQUESTS,
+            ApiErrorCode::Timeout => StatusCode::GATEWAY_TIMEOUT,
+        }
+    }
+}
+
+impl axum::response::IntoResponse for ApiError {
+    fn into_response(self) -> axum::response::Response {
+        let status_code = self.status_code();
 
         let response = ErrorResponse {
             error: self.message,
@@ -98,6 +419,43 @@ impl axum::response::IntoResponse for ApiError {
     }
 }
 
+/// OpenAI 互換エンドポイント用のエラー
+/// - ステータスは `ApiError` と同じで、本文を OpenAI の `{"error": {...}}` 形式にする
+#[derive(Debug)]
+pub struct OpenAiError(pub ApiError);
+
+impl From<ApiError> for OpenAiError {
+    fn from(err: ApiError) -> Self {
+        OpenAiError(err)
+    }
+}
+
+impl axum::response::IntoResponse for OpenAiError {
+    fn into_response(self) -> axum::response::Response {
+        let status_code = self.0.status_code();
+        let error_type = if status_code.is_server_error() {
+            "server_error"
+        } else {
+            "invalid_request_error"
+        };
+        let message = match self.0.details {
+            Some(details) => format!("{} ({})", self.0.message, details),
+            None => self.0.message,
+        };
+
+        let response = OpenAiErrorResponse {
+            error: OpenAiErrorBody {
+                message,
+                error_type: error_type.to_string(),
+                param: None,
+                code: Some(self.0.code.as_str().to_lowercase()),
+            },
+        };
+
+        (status_code, Json(response)).into_response()
+    }
+}
+
 // =============================================================================
 // Request Handlers
 // =============================================================================
@@ -105,8 +463,11 @@ impl axum::response::IntoResponse for ApiError {
 /// 基本的な文字起こしエンドポイント
 pub async fn transcribe_basic(
     State(state): State<AppState>,
+    caller: Option<Extension<ApiCaller>>,
     mut multipart: Multipart,
-) -> ApiResult<Json<TranscribeResponse>> {
+) -> ApiResult<Response> {
+    let caller = caller.map(|Extension(caller)| caller.name);
+
     // 統計情報を更新
     // - 受信直後にリクエスト数/アクティブ数を更新
     {
@@ -116,46 +477,35 @@ pub async fn transcribe_basic(
 
     let start_time = Instant::now();
 
-    // ファイルフィールドを取得
-    // - フロントエンドは `file` という name で送る想定
-    // - 最初のフィールドだけを受け取り、追加フィールドは扱わない
-    let field = multipart
-        .next_field()
-        .await
-        .map_err(|e| {
-            ApiError::new(
-                ApiErrorCode::InvalidInput,
-                format!("マルチパートデータの解析に失敗: {}", e),
-            )
-        })?
-        .ok_or_else(|| {
-            ApiError::new(
-                ApiErrorCode::InvalidInput,
-                "ファイルフィールドが見つかりません",
-            )
-        })?;
-
-    let filename = field.file_name().unwrap_or("audio").to_string();
-
-    let file_data = field.bytes().await.map_err(|e| {
-        ApiError::new(
-            ApiErrorCode::InvalidInput,
-            format!("ファイルデータの読み込みに失敗: {}", e),
-        )
-    })?;
-
-    // 処理を実行
-    // - 共通処理 `process_transcription` へ委譲
-    let result = process_transcription(
-        state.clone(),
-        file_data.to_vec(),
-        filename,
+    // フォームを読み取る
+    // - フロントエンドは `file` という name で音声を送る想定
+    // - `format` に字幕形式などを指定した場合はセグメントも生成する
+    let (file_data, filename, mut request) = read_transcribe_form(
+        &mut multipart,
         TranscribeRequest {
-            language: None,
             translate_to_english: Some(false),
             include_timestamps: Some(false),
+            ..Default::default()
         },
+    )
+    .await?;
+    reject_callback(&request)?;
+    let format = request.format.unwrap_or_default();
+    if format.requires_segments() {
+        request.include_timestamps = Some(true);
+    }
+    let translated = request.translate_to_english.unwrap_or(false);
+
+    // 処理を実行
+    // - 共通処理 `process_transcription` へ委譲（`request_timeout_seconds` を適用）
+    let result = process_transcription_with_timeout(
+        state.clone(),
+        file_data,
+        filename.clone(),
+        request,
+        caller.clone(),
         start_time,
+        TranscriptionHooks::default(),
     )
     .await;
 
@@ -164,8 +514,12 @@ pub async fn transcribe_basic(
     // - 失敗: 失敗カウントを加算
     match &result {
         Ok(response) => {
-            let mut stats = state.stats.lock().unwrap();
-            stats.record_success(response.processing_time_ms, response.duration_ms);
+            state
+                .stats
+                .lock()
+                .unwrap()
+                .record_success(response.processing_time_ms, response.duration_ms);
+            state.record_key_success(caller.as_deref(), response.duration_ms);
         }
         Err(_) => {
             let mut stats = state.stats.lock().unwrap();
@@ -173,228 +527,1663 @@ pub async fn transcribe_basic(
         }
     }
 
-    result
+    let Json(response) = result?;
+    match format {
+        ResponseFormat::Json => Ok(Json(response).into_response()),
+        format => export_response(&response, format, &filename, translated),
+    }
+}
+
+/// タイムスタンプ付き文字起こしエンドポイント
+/// - セグメント（start/end/text）のみを返却します（全文テキストは返しません）
+/// - `format` に json 以外を指定した場合はその形式で返します
+pub async fn transcribe_with_timestamps(
+    State(state): State<AppState>,
+    caller: Option<Extension<ApiCaller>>,
+    mut multipart: Multipart,
+) -> ApiResult<Response> {
+    let caller = caller.map(|Extension(caller)| caller.name);
+
+    // 統計情報を更新
+    {
+        let mut stats = state.stats.lock().unwrap();
+        stats.record_request();
+    }
+
+    let start_time = Instant::now();
+    let (file_data, filename, mut request) = read_transcribe_form(
+        &mut multipart,
+        TranscribeRequest {
+            translate_to_english: Some(false),
+            include_timestamps: Some(true),
+            ..Default::default()
+        },
+    )
+    .await?;
+    reject_callback(&request)?;
+    // このエンドポイントは常にセグメントを返す
+    request.include_timestamps = Some(true);
+    let format = request.format.unwrap_or_default();
+    let translated = request.translate_to_english.unwrap_or(false);
+
+    // 処理を実行
+    let result = process_transcription_with_timeout(
+        state.clone(),
+        file_data,
+        filename.clone(),
+        request,
+        caller.clone(),
+        start_time,
+        TranscriptionHooks::default(),
+    )
+    .await;
+
+    // 統計情報を更新しつつ、セグメントのみ返却
+    match result {
+        Ok(axum::response::Json(resp)) => {
+            state
+                .stats
+                .lock()
+                .unwrap()
+                .record_success(resp.processing_time_ms, resp.duration_ms);
+            state.record_key_success(caller.as_deref(), resp.duration_ms);
+
+            match format {
+                ResponseFormat::Json => Ok(Json(resp.segments.unwrap_or_default()).into_response()),
+                format => export_response(&resp, format, &filename, translated),
+            }
+        }
+        Err(e) => {
+            let mut stats = state.stats.lock().unwrap();
+            stats.record_failure();
+            Err(e)
+        }
+    }
+}
+
+/// セグメントを逐次返す文字起こし（`POST /transcribe/stream`）
+/// - フォームは `/transcribe-with-timestamps` と同じ
+/// - `text/event-stream` で `progress` / `segment` を推論中に送り、最後に `done`（または `error`）を送る
+/// - 入力エラーはストリーム開始前に通常のエラーレスポンスで返す
+pub async fn transcribe_stream(
+    State(state): State<AppState>,
+    caller: Option<Extension<ApiCaller>>,
+    mut multipart: Multipart,
+) -> ApiResult<Sse<impl Stream<Item = Result<Event, Infallible>>>> {
+    let caller = caller.map(|Extension(caller)| caller.name);
+    let start_time = Instant::now();
+    let (file_data, filename, mut request) = read_transcribe_form(
+        &mut multipart,
+        TranscribeRequest {
+            translate_to_english: Some(false),
+            include_timestamps: Some(true),
+            ..Default::default()
+        },
+    )
+    .await?;
+    reject_callback(&request)?;
+    request.include_timestamps = Some(true);
+    resolve_decoding(&request.decoding)?;
+
+    {
+        let mut stats = state.stats.lock().unwrap();
+        stats.record_request();
+    }
+
+    // 推論スレッド → SSE への受け渡し
+    // - 受信側（クライアント）が切断したら推論を中断する
+    let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+    let hooks = {
+        let progress_sender = sender.clone();
+        let segment_sender = sender.clone();
+        TranscriptionHooks {
+            on_start: None,
+            inference: InferenceHooks {
+                on_progress: Some(Box::new(move |progress| {
+                    let _ = progress_sender.send(TranscribeStreamEvent::Progress(progress));
+                })),
+                on_segment: Some(Box::new(move |segment| {
+                    let _ = segment_sender.send(TranscribeStreamEvent::Segment(segment));
+                })),
+                ..Default::default()
+            },
+        }
+    };
+
+    tokio::spawn(async move {
+        // 切断を検知したら処理の Future を破棄する（推論は中断フラグで止まる）
+        let result = tokio::select! {
+            result = process_transcription_with_timeout(
+                state.clone(),
+                file_data,
+                filename,
+                request,
+                caller.clone(),
+                start_time,
+                hooks,
+            ) => result,
+            _ = sender.closed() => {
+                println!("クライアントが切断したため推論を中断しました");
+                state.stats.lock().unwrap().record_cancellation();
+                return;
+            }
+        };
+
+        let event = match result {
+            Ok(Json(response)) => {
+                state
+                    .stats
+                    .lock()
+                    .unwrap()
+                    .record_success(response.processing_time_ms, response.duration_ms);
+                state.record_key_success(caller.as_deref(), response.duration_ms);
+                TranscribeStreamEvent::Done(Box::new(response))
+            }
+            Err(e) => {
+                state.stats.lock().unwrap().record_failure();
+                TranscribeStreamEvent::Error(ErrorResponse {
+                    error: e.message,
+                    code: e.code.as_str().to_string(),
+                    details: e.details,
+                })
+            }
+        };
+        let _ = sender.send(event);
+    });
+
+    // 最後のイベント（done/error）を送ったらストリームを閉じる
+    let events = stream::unfold(Some(receiver), |receiver| async move {
+        let mut receiver = receiver?;
+        let event = receiver.recv().await?;
+        let sse_event = Event::default()
+            .event(event.name())
+            .data(event.data().unwrap_or_default());
+        let next = (!event.is_final()).then_some(receiver);
+        Some((Ok(sse_event), next))
+    });
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+/// 複数ファイル/アーカイブの一括文字起こし（`POST /transcribe/batch`）
+/// - `file` を複数指定するか、ZIP/tar アーカイブを送る（混在可）
+/// - `formats` に出力形式をカンマ区切りで指定（既定 `srt,vtt,txt`）。他のフォームは `/transcribe` と同じ
+/// - ファイルごとの出力と `manifest.json` をまとめた ZIP を返す
+/// - 失敗したファイルは manifest にエラーを記録し、残りの処理を続ける
+/// - 各ファイルの前に API キーの月間クォータを確認し、超えた後のファイルは `QUOTA_EXCEEDED` にする
+pub async fn transcribe_batch(
+    State(state): State<AppState>,
+    caller: Option<Extension<ApiCaller>>,
+    mut multipart: Multipart,
+) -> ApiResult<Response> {
+    let caller = caller.map(|Extension(caller)| caller.name);
+    let start_time = Instant::now();
+    let (uploads, formats, mut request) = read_batch_form(&mut multipart).await?;
+    reject_callback(&request)?;
+    // 字幕を出力するため常にセグメントを生成する
+    request.include_timestamps = Some(true);
+    resolve_decoding(&request.decoding)?;
+    let translated = request.translate_to_english.unwrap_or(false);
+
+    // アーカイブの展開はブロッキングスレッドで行う
+    // - 展開後の合計は `max_request_size` の 4 倍まで（圧縮爆弾対策）
+    let limits = BatchLimits {
+        supported_formats: state.config.audio.supported_formats.clone(),
+        max_file_bytes: state.config.max_file_size_bytes(),
+        max_total_bytes: state.config.server.max_request_size.saturating_mul(4),
+    };
+    let inputs = tokio::task::spawn_blocking(move || {
+        let mut inputs = BatchInputs::default();
+        for (filename, data) in uploads {
+            inputs.add_upload(&filename, data, &limits)?;
+        }
+        Ok::<_, anyhow::Error>(inputs)
+    })
+    .await
+    .map_err(|e| {
+        ApiError::new(
+            ApiErrorCode::InternalError,
+            format!("処理スレッドエラー: {}", e),
+        )
+    })?
+    .map_err(|e| ApiError::new(ApiErrorCode::InvalidInput, e.to_string()))?;
+
+    let BatchInputs { files, skipped, .. } = inputs;
+    if files.is_empty() {
+        return Err(ApiError::new(
+            ApiErrorCode::InvalidInput,
+            "文字起こしできる音声がありません",
+        )
+        .with_details(format!(
+            "対応形式: {}",
+            state.config.audio.supported_formats.join(", ")
+        )));
+    }
+
+    // エンジンプールの同時処理数まで並行して文字起こしする（結果は入力順）
+    // - 制限時間（`request_timeout_seconds`）はファイルごとに適用する
+    let concurrency = state.config.performance.max_concurrent_requests.max(1);
+    let results: Vec<_> = stream::iter(files)
+        .map(|input| {
+            let state = state.clone();
+            let request = request.clone();
+            let caller = caller.clone();
+            async move {
+                state.stats.lock().unwrap().record_request();
+                // 先に終わったファイルの使用量も含めて、ファイルごとにクォータを確認する
+                let result = match state.check_key_quota(caller.as_deref()) {
+                    Ok(()) => {
+                        process_transcription_with_timeout(
+                            state.clone(),
+                            input.data,
+                            input.name.clone(),
+                            request,
+                            caller.clone(),
+                            Instant::now(),
+                            TranscriptionHooks::default(),
+                        )
+                        .await
+                    }
+                    Err(e) => Err(e),
+                };
+                if let Ok(Json(response)) = &result {
+                    state.record_key_success(caller.as_deref(), response.duration_ms);
+                }
+                (input.name, result)
+            }
+        })
+        .buffered(concurrency)
+        .collect()
+        .await;
+
+    let mut names = OutputNames::default();
+    let mut outputs = Vec::new();
+    let mut manifest = BatchManifest {
+        files: Vec::with_capacity(results.len()),
+        skipped,
+        succeeded: 0,
+        failed: 0,
+        total_duration_ms: 0,
+        processing_time_ms: 0,
+    };
+    for (name, result) in results {
+        let file = match result {
+            Ok(Json(response)) => {
+                state
+                    .stats
+                    .lock()
+                    .unwrap()
+                    .record_success(response.processing_time_ms, response.duration_ms);
+
+                let stem = names.reserve(&name);
+                let mut file_outputs = Vec::with_capacity(formats.len());
+                for format in &formats {
+                    let output = format!("{}.{}", stem, format.extension());
+                    outputs.push((
+                        output.clone(),
+                        export::render(&response, *format, translated)?,
+                    ));
+                    file_outputs.push(output);
+                }
+
+                manifest.succeeded += 1;
+                manifest.total_duration_ms += response.duration_ms.unwrap_or(0);
+                BatchFileResult {
+                    name,
+                    outputs: file_outputs,
+                    language: response.language,
+                    duration_ms: response.duration_ms,
+                    processing_time_ms: Some(response.processing_time_ms),
+                    model: response.model,
+                    history_id: response.history_id,
+                    error: None,
+                }
+            }
+            Err(e) => {
+                state.stats.lock().unwrap().record_failure();
+                manifest.failed += 1;
+                BatchFileResult {
+                    name,
+                    outputs: Vec::new(),
+                    language: None,
+                    duration_ms: None,
+                    processing_time_ms: None,
+                    model: None,
+                    history_id: None,
+                    error: Some(ErrorResponse {
+                        error: e.message,
+                        code: e.code.as_str().to_string(),
+                        details: e.details,
+                    }),
+                }
+            }
+        };
+        manifest.files.push(file);
+    }
+    manifest.processing_time_ms = start_time.elapsed().as_millis() as u64;
+    println!(
+        "一括文字起こし完了: 成功 {}件 / 失敗 {}件 / スキップ {}件",
+        manifest.succeeded,
+        manifest.failed,
+        manifest.skipped.len()
+    );
+
+    let archive = tokio::task::spawn_blocking(move || build_archive(&manifest, &outputs))
+        .await
+        .map_err(|e| {
+            ApiError::new(
+                ApiErrorCode::InternalError,
+                format!("処理スレッドエラー: {}", e),
+            )
+        })??;
+
+    Ok((
+        [
+            (
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/zip"),
+            ),
+            (
+                header::CONTENT_DISPOSITION,
+                HeaderValue::from_static("attachment; filename=\"transcripts.zip\""),
+            ),
+        ],
+        archive,
+    )
+        .into_response())
+}
+
+/// 一括文字起こしのフォームを読み取る
+/// - file: 音声またはアーカイブ（複数可）
+/// - formats（format）: 出力形式のカンマ区切り（srt / vtt / txt / tsv / json / verbose_json）
+/// - その他は `/transcribe` と同じ
+async fn read_batch_form(
+    multipart: &mut Multipart,
+) -> ApiResult<(
+    Vec<(String, Vec<u8>)>,
+    Vec<ResponseFormat>,
+    TranscribeRequest,
+)> {
+    let mut uploads = Vec::new();
+    let mut formats = vec![
+        ResponseFormat::Srt,
+        ResponseFormat::Vtt,
+        ResponseFormat::Text,
+    ];
+    let mut request = TranscribeRequest {
+        translate_to_english: Some(false),
+        include_timestamps: Some(true),
+        ..Default::default()
+    };
+
+    while let Some(field) = multipart.next_field().await.map_err(|e| {
+        ApiError::new(
+            ApiErrorCode::InvalidInput,
+            format!("マルチパートデータの解析に失敗: {}", e),
+        )
+    })? {
+        let field_name = field.name().unwrap_or("").to_string();
+
+        if field_name == "file" {
+            let filename = field.file_name().unwrap_or("audio").to_string();
+            let data = field.bytes().await.map_err(|e| {
+                ApiError::new(
+                    ApiErrorCode::InvalidInput,
+                    format!("ファイルデータの読み込みに失敗: {}", e),
+                )
+            })?;
+            uploads.push((filename, data.to_vec()));
+            continue;
+        }
+
+        let value = field.text().await.map_err(|e| {
+            ApiError::new(
+                ApiErrorCode::InvalidInput,
+                format!("{} パラメータの読み込みに失敗: {}", field_name, e),
+            )
+        })?;
+        match field_name.as_str() {
+            "formats" | "format" => formats = parse_batch_formats(value.trim())?,
+            _ => apply_transcribe_field(&mut request, &field_name, value.trim())?,
+        }
+    }
+
+    if uploads.is_empty() {
+        return Err(ApiError::new(
+            ApiErrorCode::InvalidInput,
+            "ファイルが見つかりません",
+        ));
+    }
+
+    Ok((uploads, formats, request))
+}
+
+/// 一括文字起こしの出力形式（カンマ区切り。`txt` は `text` と同じ）
+/// - 出力ファイルの拡張子が重なる組み合わせ（json と verbose_json）は指定できない
+fn parse_batch_formats(value: &str) -> ApiResult<Vec<ResponseFormat>> {
+    let mut formats: Vec<ResponseFormat> = Vec::new();
+    for name in value
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+    {
+        let format = if name.eq_ignore_ascii_case("txt") {
+            ResponseFormat::Text
+        } else {
+            parse_response_format(name)?
+        };
+        if formats.contains(&format) {
+            continue;
+        }
+        if formats.iter().any(|f| f.extension() == format.extension()) {
+            return Err(ApiError::new(
+                ApiErrorCode::InvalidInput,
+                format!("出力ファイルの拡張子が重複する形式です: {}", name),
+            ));
+        }
+        formats.push(format);
+    }
+
+    if formats.is_empty() {
+        return Err(ApiError::new(
+            ApiErrorCode::InvalidInput,
+            "formats を 1 つ以上指定してください",
+        ));
+    }
+    Ok(formats)
+}
+
+/// OpenAI 互換: 文字起こし（`POST /v1/audio/transcriptions`）
+pub async fn openai_transcriptions(
+    State(state): State<AppState>,
+    caller: Option<Extension<ApiCaller>>,
+    mut multipart: Multipart,
+) -> Result<Response, OpenAiError> {
+    let caller = caller.map(|Extension(caller)| caller.name);
+    openai_audio(state, caller, &mut multipart, false).await
+}
+
+/// OpenAI 互換: 英語への翻訳（`POST /v1/audio/translations`）
+pub async fn openai_translations(
+    State(state): State<AppState>,
+    caller: Option<Extension<ApiCaller>>,
+    mut multipart: Multipart,
+) -> Result<Response, OpenAiError> {
+    let caller = caller.map(|Extension(caller)| caller.name);
+    openai_audio(state, caller, &mut multipart, true).await
+}
+
+/// OpenAI 互換エンドポイントの共通処理
+/// - フォームを `TranscribeRequest` へ写像し、タイムスタンプ付きで文字起こしする
+async fn openai_audio(
+    state: AppState,
+    caller: Option<String>,
+    multipart: &mut Multipart,
+    translate: bool,
+) -> Result<Response, OpenAiError> {
+    let (file_data, filename, openai_request) = read_openai_form(multipart).await?;
+
+    {
+        let mut stats = state.stats.lock().unwrap();
+        stats.record_request();
+    }
+
+    let request = TranscribeRequest {
+        language: openai_request.language.clone(),
+        model: openai_request.server_model().map(str::to_string),
+        translate_to_english: Some(translate),
+        include_timestamps: Some(true),
+        word_timestamps: Some(
+            openai_request
+                .timestamp_granularities
+                .contains(&TimestampGranularity::Word),
+        ),
+        format: None,
+        channel_mode: None,
+        vad: None,
+        filter: None,
+        chunking: None,
+        glossary: None,
+        glossary_correction: None,
+        diarize: None,
+        num_speakers: None,
+        callback_url: None,
+        callback_secret: None,
+        decoding: DecodingOptions {
+            temperature: openai_request.temperature,
+            initial_prompt: openai_request.prompt.clone(),
+            ..Default::default()
+        },
+    };
+
+    let result = process_transcription_with_timeout(
+        state.clone(),
+        file_data,
+        filename,
+        request,
+        caller.clone(),
+        Instant::now(),
+        TranscriptionHooks::default(),
+    )
+    .await;
+
+    match &result {
+        Ok(Json(response)) => {
+            state
+                .stats
+                .lock()
+                .unwrap()
+                .record_success(response.processing_time_ms, response.duration_ms);
+            state.record_key_success(caller.as_deref(), response.duration_ms);
+        }
+        Err(_) => {
+            let mut stats = state.stats.lock().unwrap();
+            stats.record_failure();
+        }
+    }
+
+    let Json(response) = result?;
+    let body = openai::render(&response, &openai_request, translate).map_err(ApiError::from)?;
+
+    Ok((
+        [(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static(openai_request.response_format.content_type()),
+        )],
+        body,
+    )
+        .into_response())
+}
+
+/// OpenAI 互換フォームを読み取る
+/// - file / model / language / prompt / response_format / temperature /
+///   timestamp_granularities[]
+async fn read_openai_form(
+    multipart: &mut Multipart,
+) -> ApiResult<(Vec<u8>, String, OpenAiAudioRequest)> {
+    let mut file_data = Vec::new();
+    let mut filename = String::new();
+    let mut request = OpenAiAudioRequest::default();
+
+    while let Some(field) = multipart.next_field().await.map_err(|e| {
+        ApiError::new(
+            ApiErrorCode::InvalidInput,
+            format!("マルチパートデータの解析に失敗: {}", e),
+        )
+    })? {
+        let field_name = field.name().unwrap_or("").to_string();
+
+        if field_name == "file" {
+            filename = field.file_name().unwrap_or("audio").to_string();
+            file_data = field
+                .bytes()
+                .await
+                .map_err(|e| {
+                    ApiError::new(
+                        ApiErrorCode::InvalidInput,
+                        format!("ファイルデータの読み込みに失敗: {}", e),
+                    )
+                })?
+                .to_vec();
+            continue;
+        }
+
+        let value = field.text().await.map_err(|e| {
+            ApiError::new(
+                ApiErrorCode::InvalidInput,
+                format!("{} パラメータの読み込みに失敗: {}", field_name, e),
+            )
+        })?;
+        let value = value.trim().to_string();
+
+        match field_name.as_str() {
+            "model" => request.model = Some(value),
+            "language" if !value.is_empty() => request.language = Some(value),
+            "prompt" if !value.is_empty() => request.prompt = Some(value),
+            "response_format" => {
+                request.response_format = OpenAiResponseFormat::parse(&value).ok_or_else(|| {
+                    let supported: Vec<&str> = OpenAiResponseFormat::ALL
+                        .iter()
+                        .map(|f| f.as_str())
+                        .collect();
+                    ApiError::new(
+                        ApiErrorCode::InvalidInput,
+                        format!("サポートされていない response_format: {}", value),
+                    )
+                    .with_details(format!("指定可能な形式: {}", supported.join(", ")))
+                })?;
+            }
+            "temperature" => {
+                let temperature = value
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|t| (0.0..=1.0).contains(t))
+                    .ok_or_else(|| {
+                        ApiError::new(
+                            ApiErrorCode::InvalidInput,
+                            format!("temperature は 0〜1 の数値で指定してください: {}", value),
+                        )
+                    })?;
+                request.temperature = Some(temperature);
+            }
+            "timestamp_granularities[]" | "timestamp_granularities" => {
+                let granularity = TimestampGranularity::parse(&value).ok_or_else(|| {
+                    ApiError::new(
+                        ApiErrorCode::InvalidInput,
+                        format!("サポートされていない timestamp_granularities: {}", value),
+                    )
+                })?;
+                request.timestamp_granularities.push(granularity);
+            }
+            _ => {} // 未知のフィールドは無視
+        }
+    }
+
+    if file_data.is_empty() {
+        return Err(ApiError::new(
+            ApiErrorCode::InvalidInput,
+            "ファイルが見つかりません",
+        ));
+    }
+
+    Ok((file_data, filename, request))
+}
+
+/// マルチパートフォームから音声ファイルと文字起こしパラメータを読み取る
+/// - file: 音声データ本体
+/// - language: 言語コード（例: ja, en, auto など）
+/// - model: 使用するモデル名（例: base。未指定なら既定モデル）
+/// - translate_to_english: true/false
+/// - include_timestamps: true/false
+/// - word_timestamps: true/false（単語タイムスタンプと信頼度）
+/// - format: json / text / srt / vtt / tsv / verbose_json
+/// - channel_mode: mix / left / right / separate / チャンネル番号
+/// - vad: true/false（無音区間を除いて推論）
+/// - filter: true/false（ハルシネーション/繰り返しの除去）
+/// - chunking: true/false（長い音声を分割して推論）
+/// - glossary: 用語集の名前、glossary_correction: true/false（表記の修正）
+/// - diarize: true/false（話者分離）、num_speakers: 話者数
+/// - callback_url / callback_secret: ジョブ終了時の通知先と署名の鍵（`POST /jobs` のみ。他では 400）
+/// - デコード: temperature, temperature_increment, beam_size, best_of,
+///   no_speech_threshold, logprob_threshold, initial_prompt（prompt）, suppress_blank
+/// - 未知のフィールドは無視し、指定の無い項目は `request` の値を使う
+async fn read_transcribe_form(
+    multipart: &mut Multipart,
+    mut request: TranscribeRequest,
+) -> ApiResult<(Vec<u8>, String, TranscribeRequest)> {
+    let mut file_data = Vec::new();
+    let mut filename = String::new();
+
+    while let Some(field) = multipart.next_field().await.map_err(|e| {
+        ApiError::new(
+            ApiErrorCode::InvalidInput,
+            format!("マルチパートデータの解析に失敗: {}", e),
+        )
+    })? {
+        let field_name = field.name().unwrap_or("").to_string();
+
+        if field_name == "file" {
+            filename = field.file_name().unwrap_or("audio").to_string();
+            file_data = field
+                .bytes()
+                .await
+                .map_err(|e| {
+                    ApiError::new(
+                        ApiErrorCode::InvalidInput,
+                        format!("ファイルデータの読み込みに失敗: {}", e),
+                    )
+                })?
+                .to_vec();
+            continue;
+        }
+
+        let value = field.text().await.map_err(|e| {
+            ApiError::new(
+                ApiErrorCode::InvalidInput,
+                format!("{} パラメータの読み込みに失敗: {}", field_name, e),
+            )
+        })?;
+        apply_transcribe_field(&mut request, &field_name, value.trim())?;
+    }
+
+    if file_data.is_empty() {
+        return Err(ApiError::new(
+            ApiErrorCode::InvalidInput,
+            "ファイルが見つかりません",
+        ));
+    }
+
+    Ok((file_data, filename, request))
+}
+
+/// 文字起こしパラメータのフォーム値を `request` に反映する（未知のフィールドは無視）
+fn apply_transcribe_field(
+    request: &mut TranscribeRequest,
+    field_name: &str,
+    value: &str,
+) -> ApiResult<()> {
+    let decoding = &mut request.decoding;
+
+    match field_name {
+        "language" => request.language = Some(value.to_string()),
+        "model" => request.model = Some(value.to_string()),
+        "translate_to_english" => {
+            request.translate_to_english = Some(value.parse().unwrap_or(false))
+        }
+        "include_timestamps" => request.include_timestamps = Some(value.parse().unwrap_or(false)),
+        "word_timestamps" => request.word_timestamps = Some(value.parse().unwrap_or(false)),
+        "format" => request.format = Some(parse_response_format(value)?),
+        "channel_mode" => request.channel_mode = Some(parse_channel_mode(value)?),
+        "vad" => request.vad = Some(value.parse().unwrap_or(false)),
+        "filter" => request.filter = Some(value.parse().unwrap_or(false)),
+        "chunking" => request.chunking = Some(value.parse().unwrap_or(false)),
+        "glossary" => request.glossary = Some(value.to_string()),
+        "glossary_correction" => request.glossary_correction = Some(value.parse().unwrap_or(false)),
+        "diarize" => request.diarize = Some(value.parse().unwrap_or(false)),
+        "num_speakers" => request.num_speakers = Some(parse_form_value(field_name, value)?),
+        "callback_url" => request.callback_url = Some(value.to_string()),
+        "callback_secret" => request.callback_secret = Some(value.to_string()),
+        "temperature" => decoding.temperature = Some(parse_form_value(field_name, value)?),
+        "temperature_increment" | "temperature_inc" => {
+            decoding.temperature_increment = Some(parse_form_value(field_name, value)?)
+        }
+        "beam_size" => decoding.beam_size = Some(parse_form_value(field_name, value)?),
+        "best_of" => decoding.best_of = Some(parse_form_value(field_name, value)?),
+        "no_speech_threshold" => {
+            decoding.no_speech_threshold = Some(parse_form_value(field_name, value)?)
+        }
+        "logprob_threshold" => {
+            decoding.logprob_threshold = Some(parse_form_value(field_name, value)?)
+        }
+        "initial_prompt" | "prompt" => decoding.initial_prompt = Some(value.to_string()),
+        "suppress_blank" => decoding.suppress_blank = Some(parse_form_value(field_name, value)?),
+        _ => {} // 未知のフィールドは無視
+    }
+    Ok(())
+}
+
+/// フォームの値を数値/真偽値として解釈
+fn parse_form_value<T: std::str::FromStr>(name: &str, value: &str) -> ApiResult<T> {
+    value.parse().map_err(|_| {
+        ApiError::new(
+            ApiErrorCode::InvalidInput,
+            format!("{} の値が不正です: {}", name, value),
+        )
+    })
+}
+
+/// デコードパラメータを検証
+fn resolve_decoding(options: &DecodingOptions) -> ApiResult<DecodingParameters> {
+    options
+        .resolve()
+        .map_err(|message| ApiError::new(ApiErrorCode::InvalidInput, message))
+}
+
+/// `format` の値を解釈
+fn parse_response_format(value: &str) -> ApiResult<ResponseFormat> {
+    ResponseFormat::parse(value).ok_or_else(|| {
+        let supported: Vec<&str> = ResponseFormat::ALL.iter().map(|f| f.as_str()).collect();
+        ApiError::new(
+            ApiErrorCode::InvalidInput,
+            format!("サポートされていないレスポンス形式: {}", value),
+        )
+        .with_details(format!("指定可能な形式: {}", supported.join(", ")))
+    })
+}
+
+/// json 以外の形式でレスポンスを組み立てる
+/// - Content-Type は形式に合わせ、Content-Disposition でダウンロード用のファイル名を付ける
+fn export_response(
+    response: &TranscribeResponse,
+    format: ResponseFormat,
+    source_filename: &str,
+    translated: bool,
+) -> ApiResult<Response> {
+    let body = export::render(response, format, translated)?;
+    let filename = export::download_filename(source_filename, format);
+    let disposition = HeaderValue::from_str(&export::content_disposition(&filename))
+        .map_err(|e| ApiError::new(ApiErrorCode::InternalError, e.to_string()))?;
+
+    Ok((
+        [
+            (
+                header::CONTENT_TYPE,
+                HeaderValue::from_static(format.content_type()),
+            ),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+/// 文字起こし処理へ差し込むフック
+/// - 非同期ジョブが状態/進捗を追跡したり、ストリーミング応答がセグメントを逐次送るために使う
+#[derive(Default)]
+pub struct TranscriptionHooks {
+    /// エンジンを確保し、処理を開始した時点で呼ばれる
+    pub on_start: Option<Box<dyn FnOnce() + Send>>,
+    /// 推論中のフック
+    pub inference: InferenceHooks,
+}
+
+/// 処理の Future が完了前に破棄されたら推論の中断フラグを立てるガード
+/// - クライアントの切断（ハンドラの破棄）、タイムアウト、ジョブのキャンセルで推論を止め、エンジンを早く返す
+struct AbortOnDrop {
+    flag: Arc<AtomicBool>,
+    armed: bool,
+}
+
+impl AbortOnDrop {
+    fn new(flag: Arc<AtomicBool>) -> Self {
+        Self { flag, armed: true }
+    }
+
+    /// 処理が完了したので中断しない
+    fn disarm(&mut self) {
+        self.armed = false;
+    }
+}
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        if self.armed {
+            self.flag.store(true, Ordering::SeqCst);
+        }
+    }
+}
+
+/// `performance.request_timeout_seconds` を適用して文字起こしする（同期リクエスト用）
+/// - 制限時間はエンジンの空き待ちを含む。0 は無制限
+/// - 時間内に終わらなければ処理を破棄して推論を中断し、`TIMEOUT` を返す
+/// - 非同期ジョブには適用しない（長い音声向けのため。中断は `DELETE /jobs/{id}`）
+pub async fn process_transcription_with_timeout(
+    state: AppState,
+    file_data: Vec<u8>,
+    filename: String,
+    request: TranscribeRequest,
+    caller: Option<String>,
+    start_time: Instant,
+    hooks: TranscriptionHooks,
+) -> ApiResult<Json<TranscribeResponse>> {
+    let timeout_seconds = state.config.performance.request_timeout_seconds;
+    let processing = process_transcription(
+        state, file_data, filename, request, caller, start_time, hooks,
+    );
+    if timeout_seconds == 0 {
+        return processing.await;
+    }
+
+    tokio::time::timeout(Duration::from_secs(timeout_seconds), processing)
+        .await
+        .unwrap_or_else(|_| {
+            println!(
+                "処理が制限時間（{}秒）を超えたため推論を中断しました",
+                timeout_seconds
+            );
+            Err(ApiError::new(
+                ApiErrorCode::Timeout,
+                format!("処理が制限時間（{}秒）を超えました", timeout_seconds),
+            )
+            .with_details("長い音声は非同期ジョブ（POST /jobs）を利用してください"))
+        })
+}
+
+/// 文字起こし処理の共通ロジック
+/// - この Future が完了前に破棄されると推論を中断する（`hooks.inference.abort` を立てる）
+/// - `caller` は呼び出し元の API キー名（履歴の所有者として記録する。認証が無効なら None）
+async fn process_transcription(
+    state: AppState,
+    file_data: Vec<u8>,
+    filename: String,
+    request: TranscribeRequest,
+    caller: Option<String>,
+    start_time: Instant,
+    hooks: TranscriptionHooks,
+) -> ApiResult<Json<TranscribeResponse>> {
+    let TranscriptionHooks {
+        on_start,
+        inference: mut inference_hooks,
+    } = hooks;
+    let abort = Arc::clone(inference_hooks.abort.get_or_insert_with(Default::default));
+    let mut abort_on_drop = AbortOnDrop::new(abort);
+    state.metrics.record_upload(file_data.len());
+
+    // ファイルサイズの検証
+    // - アップロードサイズが設定値を超えていないかチェック
+    let config = &state.config;
+    let max_size = config.max_file_size_bytes();
+    if file_data.len() > max_size {
+        return Err(ApiError::new(
+            ApiErrorCode::FileTooLarge,
+            format!(
+                "ファイルサイズが制限を超えています: {} > {}",
+                format_file_size(file_data.len() as u64),
+                format_file_size(max_size as u64)
+            ),
+        ));
+    }
+
+    // デコードパラメータの検証（既定値を補った実効値をレスポンスで返す）
+    let mut decoding = resolve_decoding(&request.decoding)?;
+    // 用語集の語句を初期プロンプトに加え、表記を直す場合は語句を渡す
+    let glossary_terms = match request.glossary.as_deref() {
+        Some(name) => {
+            let glossary = state
+                .glossaries
+                .get(name)
+                .ok_or_else(|| glossary_not_found(name))?;
+            decoding.initial_prompt = Some(glossary.prompt(decoding.initial_prompt.as_deref()));
+            request
+                .glossary_correction
+                .unwrap_or(glossary.correct)
+                .then_some(glossary.terms)
+        }
+        None => None,
+    };
+    if request
+        .num_speakers
+        .is_some_and(|n| !(1..=diarize::MAX_SPEAKERS).contains(&n))
+    {
+        return Err(ApiError::new(
+            ApiErrorCode::InvalidInput,
+            format!(
+                "num_speakers は1〜{}で指定してください",
+                diarize::MAX_SPEAKERS
+            ),
+        ));
+    }
+    // 単語タイムスタンプはセグメントの一部として返すため、セグメントも生成する
+    let word_timestamps = request.word_timestamps.unwrap_or(false);
+    let include_timestamps = request.include_timestamps.unwrap_or(false) || word_timestamps;
+    // 履歴にはセグメントを常に保存するため、返さない場合も結果に残す
+    // - 推論パラメータ（`include_timestamps`）は変えず、得られたセグメントを捨てないだけ
+    // - 音声はデコードと同じバッファを共有する（コピーしない）
+    let file_data = Bytes::from(file_data);
+    let history = state
+        .history
+        .clone()
+        .map(|store| (store, file_data.clone(), filename.clone()));
+    let mut options = TranscribeOptions {
+        language: request.language.clone(),
+        translate_to_english: request.translate_to_english.unwrap_or(false),
+        include_timestamps,
+        keep_segments: history.is_some(),
+        word_timestamps,
+        decoding: decoding.clone(),
+        vad: request
+            .vad
+            .unwrap_or(state.config.vad.enabled)
+            .then(|| state.config.vad.clone()),
+        filter: request
+            .filter
+            .unwrap_or(state.config.filter.enabled)
+            .then(|| state.config.filter.clone()),
+        chunking: request
+            .chunking
+            .unwrap_or(state.config.chunking.enabled)
+            .then(|| state.config.chunking.clone()),
+        glossary: glossary_terms,
+        diarization: request
+            .diarize
+            .unwrap_or(state.config.diarization.enabled)
+            .then(|| state.config.diarization.clone()),
+        num_speakers: request.num_speakers,
+    };
+    let channel_mode = request
+        .channel_mode
+        .unwrap_or(state.config.audio.channel_mode);
+    let parameters = HistoryParameters {
+        language: request.language.filter(|language| language != "auto"),
+        translate_to_english: options.translate_to_english,
+        word_timestamps,
+        channel_mode,
+        vad: options.vad.is_some(),
+        filter: options.filter.is_some(),
+    };
+
+    // エンジンを借り出す（アドミッション制御）
+    // - 同時処理数を超える場合はここで待機し、デコード等の重い処理も始めない
+    // - `model` 指定時は未読み込みならここで読み込む
+    // - 処理時間/実時間比は確保した時点から測り、空き待ちは別のヒストグラムに記録する
+    let queue_start = Instant::now();
+    let (model_name, engine) = state.acquire_engine(request.model.as_deref()).await?;
+    state
+        .metrics
+        .record_queue_wait(queue_start.elapsed().as_millis() as u64);
+    let processing_start = Instant::now();
+    if let Some(on_start) = on_start {
+        on_start();
+    }
+    // CPU集約的な処理をブロッキングスレッドで実行
+    // - デコード/リサンプリング/Whisper 推論などは重いので `spawn_blocking`
+    // - 借り出したエンジンはクロージャ終了時に Drop され、プールへ返却される
+    let config_clone = Arc::clone(&state.config);
+    let state_clone = state.clone();
+    let engine_model = model_name.clone();
+
+    let processing_result = tokio::task::spawn_blocking(move || {
+        // 音声データを読み込み、前処理まで行う
+        let (channels, duration_ms) =
+            load_audio_samples(&config_clone, file_data, &filename, channel_mode)?;
+
+        // 分割推論は、チャンクが 2 つ以上になる場合だけ、同じモデルのエンジンに空きがある分
+        // （チャンク数まで）並列にする（借りたエンジンは推論後に返す）
+        let helpers = match options.chunking.as_mut() {
+            Some(chunking) => {
+                let chunk_count = channels
+                    .iter()
+                    .map(|samples| {
+                        plan_chunks(samples, config_clone.audio.sample_rate, chunking).len()
+                    })
+                    .max()
+                    .unwrap_or(0);
+                let helpers = if chunk_count >= 2 {
+                    state_clone.borrow_idle_engines(
+                        &engine_model,
+                        chunking.max_parallel.min(chunk_count).saturating_sub(1),
+                    )
+                } else {
+                    Vec::new()
+                };
+                chunking.max_parallel = helpers.len() + 1;
+                helpers
+            }
+            None => Vec::new(),
+        };
+
+        // 文字起こし実行
+        // - include_timestamps=true の場合は詳細結果（セグメント/推定言語/処理時間）
+        // - それ以外は結合テキストのみ
+        // - separate はチャンネルごとに推論し、チャンネル番号付きで時刻順にまとめる
+        let mut result = if channel_mode == ChannelMode::Separate {
+            engine.transcribe_channels(&channels, &options, &mut inference_hooks)?
+        } else {
+            engine.transcribe_with_options(&channels[0], &options, &mut inference_hooks)?
+        };
+
+        drop(helpers);
+
+        if !include_timestamps {
+            result.processing_time_ms = start_time.elapsed().as_millis() as u64;
+        }
+
+        Ok::<_, anyhow::Error>((result, duration_ms))
+    })
+    .await
+    .map_err(|e| {
+        ApiError::new(
+            ApiErrorCode::InternalError,
+            format!("処理スレッドエラー: {}", e),
+        )
+    })?;
+    abort_on_drop.disarm();
+
+    if let Ok((_, duration_ms)) = &processing_result {
+        state.metrics.record_transcription(
+            processing_start.elapsed().as_millis() as u64,
+            Some(*duration_ms),
+        );
+    }
+
+    let (result, duration_ms) = processing_result
+        .map_err(|e| ApiError::new(ApiErrorCode::ProcessingFailed, e.to_string()))?;
+    let mut response = TranscribeResponse {
+        text: result.text,
+        language: result.language,
+        duration_ms: Some(duration_ms),
+        segments: Some(result.segments),
+        processing_time_ms: result.processing_time_ms,
+        decoding: Some(decoding),
+        language_detection: result.language_detection,
+        model: Some(model_name),
+        filtered: (!result.filtered.is_empty()).then_some(result.filtered),
+        corrections: (!result.corrections.is_empty()).then_some(result.corrections),
+        history_id: None,
+        failed_chunks: (!result.failed_chunks.is_empty()).then_some(result.failed_chunks),
+    };
+
+    // 履歴へ保存（失敗しても文字起こし結果は返す）
+    if let Some((store, audio, filename)) = history {
+        let entry = response.clone();
+        let saved = tokio::task::spawn_blocking(move || {
+            store.save(&filename, &audio, caller, parameters, entry)
+        })
+        .await;
+        match saved {
+            Ok(Ok(id)) => response.history_id = Some(id),
+            Ok(Err(e)) => eprintln!("{}", e),
+            Err(e) => eprintln!("履歴の保存スレッドエラー: {}", e),
+        }
+    }
+
+    if !include_timestamps {
+        response.segments = None;
+    }
+    Ok(Json(response))
+}
+
+/// アップロードされた音声を Whisper 入力用のサンプル列にする
+/// - 形式チェック → デコード/リサンプリング → 長さの検証 → 前処理
+/// - 戻り値はサンプル列と音声の長さ（ミリ秒）
+/// - サンプル列は通常 1 本、`channel_mode=separate` の場合はチャンネルごと
+fn load_audio_samples(
+    config: &Config,
+    file_data: impl AsRef<[u8]> + Send + Sync + 'static,
+    filename: &str,
+    channel_mode: ChannelMode,
+) -> anyhow::Result<(Vec<Vec<f32>>, u64)> {
+    // 音声プロセッサを作成
+    // - サポート形式/制限値の参照に利用
+    let mut audio_processor = AudioProcessor::new(config)?;
+    audio_processor.set_channel_mode(channel_mode);
+
+    // ファイル形式の検証
+    // - 設定で許可した拡張子のみ受け付ける（簡易チェック）
+    if !audio_processor.is_supported_format(filename) {
+        return Err(anyhow::anyhow!(
+            "サポートされていないファイル形式: {}",
+            filename
+        ));
+    }
+
+    // 音声データを処理
+    // - メモリ上のバイト列 → デコード → f32 サンプル列（ターゲット SR）
+    // - 一時ファイルは作らず、受け取ったバッファをそのままデコーダーへ渡す
+    let processed_audio = audio_processor
+        .process_audio_from_source(Box::new(std::io::Cursor::new(file_data)), filename)?;
+
+    // 音声の長さを検証
+    // - 設定の最大再生時間（分）を超えていないか
+    audio_processor.validate_audio_duration(&processed_audio.original_metadata)?;
+
+    // 音声データの前処理
+    // - 正規化などの軽微な前処理（チャンネルごと）
+    let mut channels = if channel_mode == ChannelMode::Separate {
+        processed_audio.channel_samples
+    } else {
+        vec![processed_audio.samples]
+    };
+    for samples in channels.iter_mut() {
+        preprocess_audio(samples);
+    }
+
+    Ok((channels, processed_audio.duration_ms))
+}
+
+/// `channel_mode` の値を解釈
+fn parse_channel_mode(value: &str) -> ApiResult<ChannelMode> {
+    ChannelMode::parse(value).ok_or_else(|| {
+        ApiError::new(
+            ApiErrorCode::InvalidInput,
+            format!("channel_mode の値が不正です: {}", value),
+        )
+        .with_details("指定可能な値: mix, left, right, separate, チャンネル番号（0 始まり）")
+    })
+}
+
+/// 言語検出エンドポイント（`POST /detect-language`）
+/// - 文字起こしは行わず、先頭 30 秒から言語ごとの確率を返す
+pub async fn detect_language(
+    State(state): State<AppState>,
+    caller: Option<Extension<ApiCaller>>,
+    mut multipart: Multipart,
+) -> ApiResult<Json<DetectLanguageResponse>> {
+    let start_time = Instant::now();
+    let caller = caller.map(|Extension(caller)| caller.name);
+    let (file_data, filename, request) =
+        read_transcribe_form(&mut multipart, TranscribeRequest::default()).await?;
+    reject_callback(&request)?;
+
+    let max_size = state.config.max_file_size_bytes();
+    if file_data.len() > max_size {
+        return Err(ApiError::new(
+            ApiErrorCode::FileTooLarge,
+            format!(
+                "ファイルサイズが制限を超えています: {} > {}",
+                format_file_size(file_data.len() as u64),
+                format_file_size(max_size as u64)
+            ),
+        ));
+    }
+
+    let (_, engine) = state.acquire_engine(request.model.as_deref()).await?;
+    let config = Arc::clone(&state.config);
+
+    let result = tokio::task::spawn_blocking(move || {
+        // 言語検出は 1 本の音声で行う（separate の場合は全チャンネルの平均）
+        let channel_mode = match request.channel_mode.unwrap_or(config.audio.channel_mode) {
+            ChannelMode::Separate => ChannelMode::Mix,
+            mode => mode,
+        };
+        let (channels, duration_ms) =
+            load_audio_samples(&config, file_data, &filename, channel_mode)?;
+        let detection = engine.detect_language(&channels[0])?;
+        Ok::<_, anyhow::Error>((detection, duration_ms))
+    })
+    .await
+    .map_err(|e| {
+        ApiError::new(
+            ApiErrorCode::InternalError,
+            format!("処理スレッドエラー: {}", e),
+        )
+    })?;
+
+    let (detection, duration_ms) =
+        result.map_err(|e| ApiError::new(ApiErrorCode::ProcessingFailed, e.to_string()))?;
+    // 推論した先頭ウィンドウの分だけ月間使用量に数える
+    state.record_key_audio(
+        caller.as_deref(),
+        duration_ms.min(LANGUAGE_DETECTION_WINDOW_SECONDS as u64 * 1000),
+    );
+
+    Ok(Json(DetectLanguageResponse {
+        detection,
+        duration_ms,
+        processing_time_ms: start_time.elapsed().as_millis() as u64,
+    }))
 }
 
-/// タイムスタンプ付き文字起こしエンドポイント
-/// - セグメント（start/end/text）のみを返却します（全文テキストは返しません）
-pub async fn transcribe_with_timestamps(
+/// 非同期ジョブの投入（`POST /jobs`）
+/// - フォームは `/transcribe-with-timestamps` と同じ（既定でセグメントを含める）
+/// - 受け付けた時点で 202 とジョブ情報を返し、処理はバックグラウンドで行う
+/// - `callback_url` を指定すると、終了時に結果を Webhook で通知する
+pub async fn create_job(
     State(state): State<AppState>,
+    caller: Option<Extension<ApiCaller>>,
     mut multipart: Multipart,
-) -> ApiResult<Json<Vec<TranscriptionSegment>>> {
-    // 統計情報を更新
+) -> ApiResult<(StatusCode, Json<JobInfo>)> {
+    let caller = caller.map(|Extension(caller)| caller.name);
+    let (file_data, filename, request) = read_transcribe_form(
+        &mut multipart,
+        TranscribeRequest {
+            translate_to_english: Some(false),
+            include_timestamps: Some(true),
+            ..Default::default()
+        },
+    )
+    .await?;
+    // 不正なパラメータは投入時点で 400 を返す
+    resolve_decoding(&request.decoding)?;
+    let callback = callback_target(&request, &state.config.webhook).await?;
+
     {
         let mut stats = state.stats.lock().unwrap();
         stats.record_request();
     }
 
-    let start_time = Instant::now();
-    let mut request = TranscribeRequest {
-        language: None,
-        translate_to_english: Some(false),
-        include_timestamps: Some(true),
+    let (job_id, cancel_flag) = state.jobs.create(&filename, caller.clone());
+    if let Some(target) = callback {
+        state.jobs.set_callback(&job_id, target);
+    }
+
+    let hooks = {
+        let jobs_start = Arc::clone(&state.jobs);
+        let jobs_progress = Arc::clone(&state.jobs);
+        let id_start = job_id.clone();
+        let id_progress = job_id.clone();
+        TranscriptionHooks {
+            on_start: Some(Box::new(move || jobs_start.mark_running(&id_start))),
+            inference: InferenceHooks {
+                on_progress: Some(Box::new(move |progress| {
+                    jobs_progress.set_progress(&id_progress, progress)
+                })),
+                // キャンセル（DELETE /jobs/{id}）で推論を中断する
+                abort: Some(cancel_flag),
+                ..Default::default()
+            },
+        }
     };
 
-    let mut file_data = Vec::new();
-    let mut filename = String::new();
+    let task = {
+        let state = state.clone();
+        let job_id = job_id.clone();
+        tokio::spawn(async move {
+            let start_time = Instant::now();
+            let result = process_transcription(
+                state.clone(),
+                file_data,
+                filename,
+                request,
+                caller.clone(),
+                start_time,
+                hooks,
+            )
+            .await;
+
+            match result {
+                Ok(Json(response)) => {
+                    state
+                        .stats
+                        .lock()
+                        .unwrap()
+                        .record_success(response.processing_time_ms, response.duration_ms);
+                    state.record_key_success(caller.as_deref(), response.duration_ms);
+                    state.jobs.complete(&job_id, response);
+                }
+                Err(e) => {
+                    state.stats.lock().unwrap().record_failure();
+                    state.jobs.fail(&job_id, e.code, e.message, e.details);
+                }
+            }
+            webhook::deliver(&state.jobs, &job_id, &state.config.webhook).await;
+        })
+    };
+    state.jobs.set_abort_handle(&job_id, task.abort_handle());
 
-    // マルチパートフィールドを処理
-    // - file: 音声データ本体
-    // - language: 言語コード（例: ja, en, auto など）
-    // - translate_to_english: true/false
-    while let Some(field) = multipart.next_field().await.map_err(|e| {
-        ApiError::new(
-            ApiErrorCode::InvalidInput,
-            format!("マルチパートデータの解析に失敗: {}", e),
+    let info = state
+        .jobs
+        .get(&job_id, None)
+        .ok_or_else(|| ApiError::new(ApiErrorCode::InternalError, "ジョブの登録に失敗しました"))?;
+
+    Ok((StatusCode::ACCEPTED, Json(info)))
+}
+
+/// ジョブの状態と進捗を取得（`GET /jobs/{id}`）
+/// - 認証が有効な場合、管理者以外のキーには自分が作成したものだけを返す（結果/キャンセルも同じ）
+pub async fn get_job(
+    State(state): State<AppState>,
+    caller: Option<Extension<ApiCaller>>,
+    Path(job_id): Path<String>,
+) -> ApiResult<Json<JobInfo>> {
+    state
+        .jobs
+        .get(&job_id, owner_scope(&caller))
+        .map(Json)
+        .ok_or_else(|| job_not_found(&job_id))
+}
+
+/// ジョブの結果を取得（`GET /jobs/{id}/result`）
+/// - 完了前/キャンセル済みの場合は 409、失敗時は処理時のエラーをそのまま返す
+pub async fn get_job_result(
+    State(state): State<AppState>,
+    caller: Option<Extension<ApiCaller>>,
+    Path(job_id): Path<String>,
+) -> ApiResult<Json<TranscribeResponse>> {
+    let outcome = state
+        .jobs
+        .outcome(&job_id, owner_scope(&caller))
+        .ok_or_else(|| job_not_found(&job_id))?;
+
+    match outcome {
+        JobOutcome::Completed(response) => Ok(Json(*response)),
+        JobOutcome::Pending(status) => Err(ApiError::new(
+            ApiErrorCode::JobNotReady,
+            "ジョブはまだ完了していません",
         )
-    })? {
-        let field_name = field.name().unwrap_or("").to_string();
+        .with_details(format!("status: {:?}", status).to_lowercase())),
+        JobOutcome::Failed { code, error } => Err(ApiError {
+            code,
+            message: error.error,
+            details: error.details,
+        }),
+        JobOutcome::Cancelled => Err(ApiError::new(
+            ApiErrorCode::JobNotReady,
+            "ジョブはキャンセルされました",
+        )),
+    }
+}
 
-        match field_name.as_str() {
-            "file" => {
-                filename = field.file_name().unwrap_or("audio").to_string();
-                file_data = field
-                    .bytes()
-                    .await
-                    .map_err(|e| {
-                        ApiError::new(
-                            ApiErrorCode::InvalidInput,
-                            format!("ファイルデータの読み込みに失敗: {}", e),
-                        )
-                    })?
-                    .to_vec();
-            }
-            "language" => {
-                let language = field.text().await.map_err(|e| {
-                    ApiError::new(
-                        ApiErrorCode::InvalidInput,
-                        format!("言語パラメータの読み込みに失敗: {}", e),
-                    )
-                })?;
-                request.language = Some(language);
-            }
-            "translate_to_english" => {
-                let translate = field.text().await.map_err(|e| {
-                    ApiError::new(
-                        ApiErrorCode::InvalidInput,
-                        format!("翻訳パラメータの読み込みに失敗: {}", e),
-                    )
-                })?;
-                request.translate_to_english = Some(translate.parse().unwrap_or(false));
-            }
-            _ => {} // 未知のフィールドは無視
-        }
+/// ジョブのキャンセル（`DELETE /jobs/{id}`）
+/// - 終了済みのジョブは変更せず、その状態を返す
+pub async fn cancel_job(
+    State(state): State<AppState>,
+    caller: Option<Extension<ApiCaller>>,
+    Path(job_id): Path<String>,
+) -> ApiResult<Json<JobInfo>> {
+    let (info, cancelled) = state
+        .jobs
+        .cancel(&job_id, owner_scope(&caller))
+        .ok_or_else(|| job_not_found(&job_id))?;
+
+    if cancelled {
+        state.stats.lock().unwrap().record_cancellation();
+        let jobs = Arc::clone(&state.jobs);
+        let config = state.config.webhook.clone();
+        tokio::spawn(async move { webhook::deliver(&jobs, &job_id, &config).await });
     }
 
-    if file_data.is_empty() {
+    Ok(Json(info))
+}
+
+/// `POST /jobs` 以外で通知先が指定されたら拒否する
+/// - 同期のエンドポイントでは通知しないため、黙って無視せず 400 を返す
+fn reject_callback(request: &TranscribeRequest) -> ApiResult<()> {
+    if request.callback_url.is_some() || request.callback_secret.is_some() {
         return Err(ApiError::new(
             ApiErrorCode::InvalidInput,
-            "ファイルが見つかりません",
+            "callback_url / callback_secret は POST /jobs でのみ指定できます",
         ));
     }
+    Ok(())
+}
 
-    // 処理を実行
-    let result =
-        process_transcription(state.clone(), file_data, filename, request, start_time).await;
+/// ジョブの通知先（`callback_url` / `callback_secret`）を検証
+async fn callback_target(
+    request: &TranscribeRequest,
+    config: &WebhookConfig,
+) -> ApiResult<Option<WebhookTarget>> {
+    match (&request.callback_url, &request.callback_secret) {
+        (Some(url), secret) => WebhookTarget::new(url, secret.as_deref(), config)
+            .await
+            .map(Some)
+            .map_err(|message| ApiError::new(ApiErrorCode::InvalidInput, message)),
+        (None, Some(_)) => Err(ApiError::new(
+            ApiErrorCode::InvalidInput,
+            "callback_secret は callback_url と一緒に指定してください",
+        )),
+        (None, None) => Ok(None),
+    }
+}
 
-    // 統計情報を更新しつつ、セグメントのみ返却
-    match result {
-        Ok(axum::response::Json(resp)) => {
-            let processing_time_ms = resp.processing_time_ms;
-            let duration_ms = resp.duration_ms;
-            let segments = resp.segments.unwrap_or_default();
+fn job_not_found(job_id: &str) -> ApiError {
+    ApiError::new(ApiErrorCode::JobNotFound, "ジョブが見つかりません")
+        .with_details(format!("id: {}", job_id))
+}
 
-            let mut stats = state.stats.lock().unwrap();
-            stats.record_success(processing_time_ms, duration_ms);
+/// `GET /history/{id}` のクエリ
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct HistoryFormatQuery {
+    /// json（既定）/ text / srt / vtt / tsv / verbose_json
+    pub format: Option<String>,
+}
 
-            Ok(Json(segments))
-        }
-        Err(e) => {
-            let mut stats = state.stats.lock().unwrap();
-            stats.record_failure();
-            Err(e)
-        }
+/// 文字起こし履歴の一覧/検索（`GET /history?q=...&limit=...&offset=...`）
+/// - 認証が有効な場合、管理者以外のキーには自分が保存したものだけを返す（取得/削除も同じ）
+pub async fn list_history(
+    State(state): State<AppState>,
+    caller: Option<Extension<ApiCaller>>,
+    Query(query): Query<HistoryQuery>,
+) -> ApiResult<Json<HistoryListResponse>> {
+    let owner = owner_scope(&caller);
+    Ok(Json(history_store(&state)?.list(&query, owner)))
+}
+
+/// 保存した文字起こしを取得（`GET /history/{id}`）
+/// - `format` に json 以外を指定すると、その形式でダウンロードできる
+pub async fn get_history(
+    State(state): State<AppState>,
+    caller: Option<Extension<ApiCaller>>,
+    Path(id): Path<String>,
+    Query(query): Query<HistoryFormatQuery>,
+) -> ApiResult<Response> {
+    let entry = history_store(&state)?
+        .get(&id, owner_scope(&caller))
+        .ok_or_else(|| history_not_found(&id))?;
+
+    let format = query
+        .format
+        .as_deref()
+        .map(parse_response_format)
+        .transpose()?
+        .unwrap_or_default();
+    match format {
+        ResponseFormat::Json => Ok(Json(entry).into_response()),
+        format => export_response(
+            &entry.result,
+            format,
+            &entry.filename,
+            entry.parameters.translate_to_english,
+        ),
     }
 }
 
-/// 文字起こし処理の共通ロジック
-async fn process_transcription(
-    state: AppState,
-    file_data: Vec<u8>,
-    filename: String,
-    request: TranscribeRequest,
-    start_time: Instant,
-) -> ApiResult<Json<TranscribeResponse>> {
-    // ファイルサイズの検証
-    // - アップロードサイズが設定値を超えていないかチェック
-    let config = &state.config;
-    let max_size = config.max_file_size_bytes();
-    if file_data.len() > max_size {
-        return Err(ApiError::new(
-            ApiErrorCode::FileTooLarge,
-            format!(
-                "ファイルサイズが制限を超えています: {} > {}",
-                format_file_size(file_data.len() as u64),
-                format_file_size(max_size as u64)
+/// 保存した元の音声を取得（`GET /history/{id}/audio`）
+pub async fn get_history_audio(
+    State(state): State<AppState>,
+    caller: Option<Extension<ApiCaller>>,
+    Path(id): Path<String>,
+) -> ApiResult<Response> {
+    let store = history_store(&state)?;
+    let (path, filename) = store.audio_path(&id, owner_scope(&caller)).ok_or_else(|| {
+        ApiError::new(
+            ApiErrorCode::HistoryNotFound,
+            "保存された音声が見つかりません",
+        )
+        .with_details(format!("id: {}", id))
+    })?;
+
+    let audio = tokio::fs::read(&path).await.map_err(|e| {
+        ApiError::new(
+            ApiErrorCode::InternalError,
+            format!("音声を読み込めません: {}", e),
+        )
+    })?;
+    let disposition = HeaderValue::from_str(&export::content_disposition(&filename))
+        .map_err(|e| ApiError::new(ApiErrorCode::InternalError, e.to_string()))?;
+
+    Ok((
+        [
+            (
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/octet-stream"),
             ),
-        ));
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        audio,
+    )
+        .into_response())
+}
+
+/// 保存した文字起こしを削除（`DELETE /history/{id}`）
+pub async fn delete_history(
+    State(state): State<AppState>,
+    caller: Option<Extension<ApiCaller>>,
+    Path(id): Path<String>,
+) -> ApiResult<StatusCode> {
+    let store = history_store(&state)?;
+    let owner = owner_scope(&caller).map(str::to_string);
+    let deleted = tokio::task::spawn_blocking({
+        let id = id.clone();
+        move || store.delete(&id, owner.as_deref())
+    })
+    .await
+    .map_err(|e| ApiError::new(ApiErrorCode::InternalError, e.to_string()))??;
+
+    if deleted {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(history_not_found(&id))
     }
+}
 
-    // CPU集約的な処理をブロッキングスレッドで実行
-    // - デコード/リサンプリング/Whisper 推論などは重いので `spawn_blocking`
-    let config_clone = Arc::clone(&state.config);
-    let whisper_engine = Arc::clone(&state.whisper_engine);
+/// 履歴/ジョブを呼び出し元のものに絞る場合のキー名
+/// - 認証が無効（呼び出し元が無い）/管理者キーの場合は絞らない
+fn owner_scope(caller: &Option<Extension<ApiCaller>>) -> Option<&str> {
+    caller
+        .as_ref()
+        .and_then(|Extension(caller)| caller.owner_scope())
+}
 
-    let processing_result = tokio::task::spawn_blocking(move || {
-        // 音声プロセッサを作成
-        // - 一時ディレクトリの準備や、サポート形式/制限値の参照に利用
-        let mut audio_processor = AudioProcessor::new(&config_clone)?;
-
-        // ファイル形式の検証
-        // - 設定で許可した拡張子のみ受け付ける（簡易チェック）
-        if !audio_processor.is_supported_format(&filename) {
-            return Err(anyhow::anyhow!(
-                "サポートされていないファイル形式: {}",
-                filename
-            ));
-        }
+fn history_store(state: &AppState) -> ApiResult<Arc<HistoryStore>> {
+    state.history.clone().ok_or_else(|| {
+        ApiError::new(ApiErrorCode::HistoryNotFound, "履歴の保存が無効です")
+            .with_details("config.toml の [history] enabled = true で有効になります")
+    })
+}
 
-        // 音声データを処理
-        // - バイト列 → 一時ファイル → デコード → f32 サンプル列（ターゲット SR）
-        let processed_audio = audio_processor.process_audio_from_bytes(&file_data, &filename)?;
+fn history_not_found(id: &str) -> ApiError {
+    ApiError::new(ApiErrorCode::HistoryNotFound, "履歴が見つかりません")
+        .with_details(format!("id: {}", id))
+}
 
-        // 音声の長さを検証
-        // - 設定の最大再生時間（分）を超えていないか
-        audio_processor.validate_audio_duration(&processed_audio.original_metadata)?;
+/// 用語集の一覧（`GET /glossaries`）
+pub async fn list_glossaries(State(state): State<AppState>) -> Json<GlossaryListResponse> {
+    Json(GlossaryListResponse {
+        glossaries: state.glossaries.list(),
+    })
+}
 
-        // 音声データの前処理
-        // - 正規化などの軽微な前処理
-        let mut audio_samples = processed_audio.samples;
-        preprocess_audio(&mut audio_samples);
+/// 用語集を取得（`GET /glossaries/{name}`）
+pub async fn get_glossary(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> ApiResult<Json<Glossary>> {
+    state
+        .glossaries
+        .get(&name)
+        .map(Json)
+        .ok_or_else(|| glossary_not_found(&name))
+}
 
-        // Whisperエンジンを取得
-        // - 起動時にロードできなかった場合は None → エラー
-        let engine_guard = whisper_engine.lock().unwrap();
-        let engine = engine_guard
-            .as_ref()
-            .ok_or_else(|| anyhow::anyhow!("Whisperエンジンが初期化されていません"))?;
+/// 用語集を登録/置き換え（`PUT /glossaries/{name}`）
+/// - 設定ファイルで定義したものは変更できない
+pub async fn put_glossary(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(upload): Json<GlossaryUpload>,
+) -> ApiResult<Json<Glossary>> {
+    check_glossary_writable(&state, &name)?;
+    let glossary = Glossary::new(&name, &upload.terms, upload.correct, false)
+        .map_err(|message| ApiError::new(ApiErrorCode::InvalidInput, message))?;
+
+    let store = Arc::clone(&state.glossaries);
+    tokio::task::spawn_blocking({
+        let glossary = glossary.clone();
+        move || store.put(glossary)
+    })
+    .await
+    .map_err(|e| ApiError::new(ApiErrorCode::InternalError, e.to_string()))??;
+    Ok(Json(glossary))
+}
 
-        // 文字起こし実行
-        // - include_timestamps=true の場合は詳細結果（セグメント/推定言語/処理時間）
-        // - それ以外は結合テキストのみ
-        let language = request.language.as_deref();
-        let translate_to_english = request.translate_to_english.unwrap_or(false);
-        let include_timestamps = request.include_timestamps.unwrap_or(false);
-
-        if include_timestamps {
-            let result = engine.transcribe_with_timestamps(
-                &audio_samples,
-                translate_to_english,
-                language,
-            )?;
-
-            Ok((
-                result.text,
-                Some(result.segments),
-                result.language,
-                processed_audio.duration_ms,
-                result.processing_time_ms,
-            ))
-        } else {
-            let text = engine.transcribe(&audio_samples)?;
-            let processing_time = start_time.elapsed().as_millis() as u64;
-
-            Ok((
-                text,
-                None,
-                language.map(|s| s.to_string()),
-                processed_audio.duration_ms,
-                processing_time,
-            ))
-        }
+/// 用語集を削除（`DELETE /glossaries/{name}`）
+pub async fn delete_glossary(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> ApiResult<StatusCode> {
+    check_glossary_writable(&state, &name)?;
+    let store = Arc::clone(&state.glossaries);
+    let deleted = tokio::task::spawn_blocking({
+        let name = name.clone();
+        move || store.delete(&name)
     })
     .await
-    .map_err(|e| {
-        ApiError::new(
-            ApiErrorCode::InternalError,
-            format!("処理スレッドエラー: {}", e),
-        )
-    })?;
+    .map_err(|e| ApiError::new(ApiErrorCode::InternalError, e.to_string()))??;
 
-    match processing_result {
-        Ok((text, segments, language, duration_ms, processing_time_ms)) => {
-            Ok(Json(TranscribeResponse {
-                text,
-                language,
-                duration_ms: Some(duration_ms),
-                segments,
-                processing_time_ms,
-            }))
-        }
-        Err(e) => Err(ApiError::new(ApiErrorCode::ProcessingFailed, e.to_string())),
+    if deleted {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(glossary_not_found(&name))
+    }
+}
+
+fn check_glossary_writable(state: &AppState, name: &str) -> ApiResult<()> {
+    match state.glossaries.get(name) {
+        Some(glossary) if glossary.read_only => Err(ApiError::new(
+            ApiErrorCode::InvalidInput,
+            "設定ファイルで定義した用語集は変更できません",
+        )
+        .with_details(format!("name: {}", name))),
+        _ => Ok(()),
     }
 }
 
+fn glossary_not_found(name: &str) -> ApiError {
+    ApiError::new(ApiErrorCode::GlossaryNotFound, "用語集が見つかりません")
+        .with_details(format!("name: {}", name))
+}
+
 /// 利用可能なモデル情報を取得
 pub async fn get_models(State(state): State<AppState>) -> ApiResult<Json<ModelsResponse>> {
     // 既知のモデル定義カタログ（ファイル名/サイズ/説明等）
@@ -403,7 +2192,7 @@ pub async fn get_models(State(state): State<AppState>) -> ApiResult<Json<ModelsR
 
     let mut models = Vec::new();
 
-    for (_key, model_def) in &catalog.models {
+    for (key, model_def) in &catalog.models {
         let file_path = models_dir.join(&model_def.file_name);
         let is_available = file_path.exists();
 
@@ -415,15 +2204,22 @@ pub async fn get_models(State(state): State<AppState>) -> ApiResult<Json<ModelsR
             model_def.size_mb
         };
 
+        // 既定モデルと同じファイルなら既定モデル名で読み込まれている
+        let is_loaded = resolve_model(&state.config, &catalog, key)
+            .is_some_and(|(name, _)| state.models.is_loaded(&name));
+
         models.push(ModelInfo {
+            id: key.clone(),
             name: model_def.name.clone(),
             file_path: file_path.to_string_lossy().to_string(),
             size_mb,
             description: model_def.description.clone(),
             language_support: model_def.language_support.clone(),
             is_available,
+            is_loaded,
         });
     }
+    models.sort_by(|a, b| a.size_mb.cmp(&b.size_mb).then_with(|| a.id.cmp(&b.id)));
 
     // 設定で選択されている既定モデル名
     let current_model = state.config.whisper.default_model.clone();
@@ -431,21 +2227,171 @@ pub async fn get_models(State(state): State<AppState>) -> ApiResult<Json<ModelsR
     Ok(Json(ModelsResponse {
         models,
         current_model,
+        loaded_models: state.models.loaded(),
+        memory_used_mb: state.models.total_size_mb(),
+        memory_budget_mb: state.models.memory_budget_mb(),
     }))
 }
 
+/// モデルを読み込む（`POST /models/{name}/load`）
+/// - 読み込み済みなら何もしない
+/// - メモリ予算を超える場合は、既定モデル以外を最も長く使われていない順に破棄する
+pub async fn load_model(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> ApiResult<Json<ModelLoadResponse>> {
+    let (name, evicted) = state.load_model(&name).await?;
+    Ok(Json(model_load_response(&state, name, evicted)))
+}
+
+/// モデルを破棄する（`POST /models/{name}/unload`）
+/// - 処理中のリクエストは完了までモデルを使い続け、その後メモリが解放される
+/// - 既定モデルを破棄すると、`model` を省略したリクエストは 503 になる
+pub async fn unload_model(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> ApiResult<Json<ModelLoadResponse>> {
+    // 読み込み後にファイルが削除された場合も破棄できるよう、読み込み済みの名前を優先する
+    let name = match name.trim() {
+        loaded if state.models.is_loaded(loaded) => loaded.to_string(),
+        other => state.resolve_model(other)?.0,
+    };
+    if !state.models.remove(&name) {
+        return Err(ApiError::new(
+            ApiErrorCode::ModelNotLoaded,
+            format!("モデル {} は読み込まれていません", name),
+        ));
+    }
+    println!("モデルを破棄しました: {}", name);
+
+    Ok(Json(model_load_response(&state, name, Vec::new())))
+}
+
+fn model_load_response(state: &AppState, name: String, evicted: Vec<String>) -> ModelLoadResponse {
+    ModelLoadResponse {
+        loaded: state.models.is_loaded(&name),
+        model: name,
+        evicted,
+        loaded_models: state.models.loaded(),
+        memory_used_mb: state.models.total_size_mb(),
+        memory_budget_mb: state.models.memory_budget_mb(),
+    }
+}
+
+/// カタログのモデルをサーバーへダウンロードする（`POST /admin/models/{name}/download`）
+/// - ダウンロードはバックグラウンドで行い、202 と現在の状態を返す（進捗は GET で確認）
+/// - 既に同じモデルをダウンロード中なら、その状態を返す
+/// - ファイルが既にある場合は `overwrite` を指定しない限りダウンロードしない
+pub async fn download_model(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    body: Option<Json<DownloadModelRequest>>,
+) -> ApiResult<(StatusCode, Json<DownloadStatus>)> {
+    let request = body.map(|Json(request)| request).unwrap_or_default();
+    let name = name.trim().to_string();
+
+    let catalog = ModelCatalog::default();
+    let definition = catalog.models.get(&name).ok_or_else(|| {
+        ApiError::new(
+            ApiErrorCode::ModelNotFound,
+            format!("モデル {} はカタログにありません", name),
+        )
+    })?;
+
+    let expected_sha256 = match request.sha256.as_deref().map(str::trim) {
+        Some(value) if !checksum::is_sha256_hex(value) => {
+            return Err(ApiError::new(
+                ApiErrorCode::InvalidInput,
+                "sha256 は 64 文字の 16 進数で指定してください",
+            ));
+        }
+        other => other.map(str::to_ascii_lowercase),
+    };
+
+    let dest = std::path::Path::new(&state.config.paths.models_dir).join(&definition.file_name);
+    if dest.is_file() && !request.overwrite {
+        let size = std::fs::metadata(&dest).map(|m| m.len()).unwrap_or(0);
+        let mut status = DownloadStatus::new(&name, &definition.file_name);
+        status.phase = DownloadPhase::Completed;
+        status.downloaded_bytes = size;
+        status.total_bytes = Some(size);
+        status.progress = Some(100);
+        status.finished_at = Some(status.started_at.clone());
+        return Ok((StatusCode::OK, Json(status)));
+    }
+
+    let (status, started) = state.downloads.begin(&name, &definition.file_name);
+    if !started {
+        return Ok((StatusCode::ACCEPTED, Json(status)));
+    }
+
+    let downloads = Arc::clone(&state.downloads);
+    let url = definition.download_url.clone();
+    // 接続のタイムアウト（request_timeout_seconds=0 は無制限の意味のため、その場合は 300 秒）
+    let connect_timeout =
+        Duration::from_secs(match state.config.performance.request_timeout_seconds {
+            0 => 300,
+            seconds => seconds,
+        });
+    let model = name.clone();
+    tokio::task::spawn_blocking(move || {
+        println!("モデルのダウンロードを開始します: {} ({})", model, url);
+        let result = download::download_to_file(
+            &url,
+            &dest,
+            DownloadOptions {
+                expected_sha256: expected_sha256.as_deref(),
+                allow_unverified: request.allow_unverified,
+                restart: request.overwrite,
+                connect_timeout,
+            },
+            |event| downloads.apply_event(&model, event),
+        );
+        match result {
+            Ok(outcome) => {
+                println!(
+                    "モデルのダウンロードが完了しました: {} (sha256: {}, 検証: {})",
+                    model, outcome.sha256, outcome.verified
+                );
+                downloads.complete(&model, &outcome);
+            }
+            Err(e) => {
+                eprintln!("モデルのダウンロードに失敗しました: {}: {:#}", model, e);
+                downloads.fail(&model, format!("{:#}", e));
+            }
+        }
+    });
+
+    Ok((StatusCode::ACCEPTED, Json(status)))
+}
+
+/// モデルのダウンロード状態を取得（`GET /admin/models/{name}/download`）
+pub async fn get_model_download(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> ApiResult<Json<DownloadStatus>> {
+    state.downloads.get(name.trim()).map(Json).ok_or_else(|| {
+        ApiError::new(
+            ApiErrorCode::ModelNotFound,
+            format!("モデル {} のダウンロードは見つかりません", name.trim()),
+        )
+    })
+}
+
+/// ダウンロード状態の一覧（`GET /admin/downloads`）
+pub async fn list_model_downloads(State(state): State<AppState>) -> Json<Vec<DownloadStatus>> {
+    Json(state.downloads.list())
+}
+
 /// ヘルスチェックエンドポイント
 pub async fn health_check(State(state): State<AppState>) -> Json<HealthResponse> {
     let uptime_seconds = state.start_time.elapsed().as_secs();
 
-    let model_loaded = {
-        let engine_guard = state.whisper_engine.lock().unwrap();
-        engine_guard.is_some()
-    };
+    let model_loaded = state.engine_pool().is_some();
 
-    // 設定から現在のモデル名を取得
+    // 既定モデル名（読み込まれていなければ None）
     let model_name = if model_loaded {
-        Some(state.config.whisper.default_model.clone())
+        state.models.default_name()
     } else {
         None
     };
@@ -462,6 +2408,7 @@ pub async fn health_check(State(state): State<AppState>) -> Json<HealthResponse>
         model_name,
         uptime_seconds,
         memory_usage_mb,
+        last_cleanup: state.janitor.last_report(),
     })
 }
 
@@ -469,9 +2416,37 @@ pub async fn health_check(State(state): State<AppState>) -> Json<HealthResponse>
 pub async fn get_stats(State(state): State<AppState>) -> Json<ServerStats> {
     let mut stats = state.stats.lock().unwrap().clone();
     stats.uptime_seconds = state.start_time.elapsed().as_secs();
+    stats.queued_requests = state.models.values().iter().map(|pool| pool.queued()).sum();
     Json(stats)
 }
 
+/// Prometheus メトリクス（`GET /metrics`）
+/// - リクエスト数/処理時間などの累計と、キュー長/読み込み済みモデル等の現在値をテキスト形式で返す
+pub async fn get_metrics(State(state): State<AppState>) -> Response {
+    let active_requests = state.stats.lock().unwrap().active_requests;
+    let models = state.models.entries();
+    let gauges = MetricsGauges {
+        active_requests,
+        queued_requests: models
+            .iter()
+            .map(|(name, pool)| (name.clone(), pool.queued()))
+            .collect(),
+        gpu_enabled: models
+            .iter()
+            .any(|(_, pool)| pool.get_model_info().enable_gpu),
+        uptime_seconds: state.start_time.elapsed().as_secs(),
+    };
+
+    (
+        [(
+            header::CONTENT_TYPE,
+            "text/plain; version=0.0.4; charset=utf-8",
+        )],
+        state.metrics.render(&gauges),
+    )
+        .into_response()
+}
+
 /// サポートされている言語のリストを取得
 pub async fn get_languages() -> Json<Vec<LanguageInfo>> {
     let languages = get_supported_languages()
@@ -487,14 +2462,12 @@ pub async fn get_languages() -> Json<Vec<LanguageInfo>> {
 
 /// GPU状態の確認
 pub async fn get_gpu_status(State(state): State<AppState>) -> ApiResult<Json<GpuStatusResponse>> {
-    let (gpu_enabled, model_info) = {
-        let engine_guard = state.whisper_engine.lock().unwrap();
-        if let Some(engine) = engine_guard.as_ref() {
-            let info = engine.get_model_info();
+    let (gpu_enabled, model_info) = match state.engine_pool() {
+        Some(pool) => {
+            let info = pool.get_model_info();
             (info.enable_gpu, Some(info))
-        } else {
-            (false, None)
         }
+        None => (false, None),
     };
 
     // 環境変数の確認
@@ -667,13 +2640,139 @@ pub fn get_memory_usage_mb() -> Option<u64> {
 
 /// CORS対応のための追加ヘッダー
 /// - OPTIONS への固定応答でプリフライトを許可
+/// - 許可するオリジンは `cors_layer` が設定に従って付ける
 pub async fn add_cors_headers() -> impl axum::response::IntoResponse {
     (
         [
-            ("Access-Control-Allow-Origin", "*"),
-            ("Access-Control-Allow-Methods", "GET, POST, OPTIONS"),
-            ("Access-Control-Allow-Headers", "Content-Type"),
+            ("Access-Control-Allow-Methods", "GET, POST, DELETE, OPTIONS"),
+            (
+                "Access-Control-Allow-Headers",
+                "Content-Type, Authorization",
+            ),
         ],
         StatusCode::OK,
     )
 }
+
+/// 設定の `cors_origins` から CORS レイヤーを作る
+/// - `*` を含む場合はすべてのオリジンを許可
+/// - ヘッダー値として不正なオリジンは警告して無視する（空なら他オリジンからの利用を許可しない）
+pub fn cors_layer(origins: &[String]) -> CorsLayer {
+    let layer = CorsLayer::new().allow_methods(Any).allow_headers(Any);
+    if origins.iter().any(|origin| origin.trim() == "*") {
+        return layer.allow_origin(Any);
+    }
+
+    let origins: Vec<HeaderValue> = origins
+        .iter()
+        .filter_map(|origin| {
+            HeaderValue::from_str(origin.trim().trim_end_matches('/'))
+                .map_err(|e| eprintln!("CORS オリジンを無視します: {} ({})", origin, e))
+                .ok()
+        })
+        .collect();
+    layer.allow_origin(AllowOrigin::list(origins))
+}
+
+/// リクエスト数をメトリクスに記録するミドルウェア
+/// - エンドポイントはルートのパターン（`/jobs/{id}` など）で集計し、ラベルの種類を増やさない
+/// - どのルートにも一致しないリクエストは `unmatched` とする
+pub async fn track_metrics(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let method = request.method().to_string();
+    let endpoint = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+
+    let response = next.run(request).await;
+    state
+        .metrics
+        .record_request(&method, &endpoint, response.status().as_u16());
+    response
+}
+
+/// API キー認証ミドルウェア
+/// - 認証が有効なら `Authorization: Bearer <key>` を確認し、キーごとのレート制限/月間クォータを適用する
+/// - 管理操作のルートは `admin = true` のキーだけに許可する（それ以外は 403）
+/// - OPTIONS（プリフライト）と `public_paths` は確認しない
+/// - 受け付けたリクエストには呼び出し元（`ApiCaller`）を付け、ハンドラが使用量を記録する
+pub async fn require_api_key(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let auth = Arc::clone(&state.auth);
+    if !auth.is_enabled()
+        || request.method() == Method::OPTIONS
+        || auth.is_public(request.uri().path())
+    {
+        return next.run(request).await;
+    }
+
+    let openai = request.uri().path().starts_with("/v1/");
+    let admin_only = request
+        .extensions()
+        .get::<MatchedPath>()
+        .is_some_and(|route| auth.requires_admin(request.method().as_str(), route.as_str()));
+    let authorization = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+
+    let checked = auth.authenticate(authorization).and_then(|key| {
+        let mut stats = state.stats.lock().unwrap();
+        let result = check_admin(key, admin_only)
+            .and_then(|()| check_quota(key, stats.key_month_audio_ms(&key.name)))
+            .and_then(|()| auth.check_rate(key, Instant::now()));
+        stats.record_key_request(&key.name, result.is_ok());
+        result.map(|()| ApiCaller {
+            name: key.name.clone(),
+            admin: key.admin,
+        })
+    });
+
+    match checked {
+        Ok(caller) => {
+            request.extensions_mut().insert(caller);
+            next.run(request).await
+        }
+        Err(rejection) => auth_rejection_response(rejection, openai),
+    }
+}
+
+/// 認証/制限で拒否したときのレスポンス
+/// - 401 には `WWW-Authenticate`、レート制限には `Retry-After` を付ける
+/// - OpenAI 互換エンドポイントでは OpenAI 形式の本文にする
+fn auth_rejection_response(rejection: AuthRejection, openai: bool) -> Response {
+    let code = match rejection {
+        AuthRejection::MissingKey | AuthRejection::InvalidKey => ApiErrorCode::Unauthorized,
+        AuthRejection::AdminRequired => ApiErrorCode::Forbidden,
+        AuthRejection::RateLimited { .. } => ApiErrorCode::RateLimited,
+        AuthRejection::QuotaExceeded { .. } => ApiErrorCode::QuotaExceeded,
+    };
+    let error = ApiError::new(code, rejection.message());
+    let mut response = if openai {
+        OpenAiError(error).into_response()
+    } else {
+        error.into_response()
+    };
+
+    let headers = response.headers_mut();
+    match rejection {
+        AuthRejection::MissingKey | AuthRejection::InvalidKey => {
+            headers.insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+        }
+        AuthRejection::RateLimited { retry_after, .. } => {
+            // 端数は切り上げ（0 秒にはしない）
+            let seconds = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+            headers.insert(header::RETRY_AFTER, HeaderValue::from(seconds.max(1)));
+        }
+        AuthRejection::AdminRequired | AuthRejection::QuotaExceeded { .. } => {}
+    }
+    response
+}
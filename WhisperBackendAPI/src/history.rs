@@ -0,0 +1,351 @@
+use crate::audio::ChannelMode;
+use crate::config::HistoryConfig;
+use crate::models::TranscribeResponse;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// =============================================================================
+// 文字起こし履歴
+// - 成功した文字起こしを 1 件 1 ディレクトリ（`<root>/<id>/`）で保存する
+//   - `entry.json`: 結果（セグメント/モデル/処理時間）と実行時のパラメータ
+//   - `audio.<拡張子>`: アップロードされた元の音声（`keep_audio` 時のみ）
+// - 起動時に読み込んでメモリ上に索引を持ち、一覧/検索はメモリ上で行う
+// - 保存期間を過ぎたものは保存時/起動時に削除する
+// - 保存したリクエストの API キーを記録し、一覧/取得/削除に `owner` を指定すると
+//   そのキーのエントリだけを扱う（他のキーのものは存在しないものとして扱う）
+// =============================================================================
+
+/// エントリのメタデータを保存するファイル名
+const ENTRY_FILE: &str = "entry.json";
+/// 一覧に含める本文の最大文字数
+const PREVIEW_CHARS: usize = 120;
+
+/// 文字起こし時のパラメータ（既定値を補った実効値）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryParameters {
+    /// 指定された言語（未指定/auto は None）
+    pub language: Option<String>,
+    pub translate_to_english: bool,
+    pub word_timestamps: bool,
+    pub channel_mode: ChannelMode,
+    pub vad: bool,
+    pub filter: bool,
+}
+
+/// 保存された文字起こし（`GET /history/{id}`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub id: String,
+    /// 保存時刻（RFC 3339）
+    pub created_at: String,
+    /// アップロード時のファイル名
+    pub filename: String,
+    /// 保存した音声のファイル名（エントリのディレクトリ内。保存しない設定なら None）
+    pub audio_file: Option<String>,
+    /// アップロードされた音声のサイズ（バイト）
+    pub audio_size_bytes: u64,
+    /// 保存したリクエストの API キー名（認証が無効なら None）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    pub parameters: HistoryParameters,
+    /// 文字起こし結果（セグメントは常に含む）
+    pub result: TranscribeResponse,
+}
+
+/// 一覧の 1 件（`GET /history`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistorySummary {
+    pub id: String,
+    pub created_at: String,
+    pub filename: String,
+    pub model: Option<String>,
+    pub language: Option<String>,
+    pub duration_ms: Option<u64>,
+    pub processing_time_ms: u64,
+    /// 本文の先頭部分
+    pub preview: String,
+    pub has_audio: bool,
+}
+
+impl From<&HistoryEntry> for HistorySummary {
+    fn from(entry: &HistoryEntry) -> Self {
+        let text = entry.result.text.trim();
+        let mut preview: String = text.chars().take(PREVIEW_CHARS).collect();
+        if preview.len() < text.len() {
+            preview.push('…');
+        }
+
+        Self {
+            id: entry.id.clone(),
+            created_at: entry.created_at.clone(),
+            filename: entry.filename.clone(),
+            model: entry.result.model.clone(),
+            language: entry.result.language.clone(),
+            duration_ms: entry.result.duration_ms,
+            processing_time_ms: entry.result.processing_time_ms,
+            preview,
+            has_audio: entry.audio_file.is_some(),
+        }
+    }
+}
+
+/// 一覧/検索の条件（`GET /history?q=...&limit=...&offset=...`）
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct HistoryQuery {
+    /// 本文に含まれる語句（空白区切りはすべて含むものに絞る。大文字小文字は無視）
+    pub q: Option<String>,
+    /// 返す件数（既定 50、最大 500）
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+/// 一覧のレスポンス
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryListResponse {
+    /// 条件に一致した件数（`limit`/`offset` 適用前）
+    pub total: usize,
+    /// 新しい順
+    pub entries: Vec<HistorySummary>,
+}
+
+/// 文字起こし履歴のストア
+/// - `Arc<HistoryStore>` として `AppState` で共有する
+pub struct HistoryStore {
+    root: PathBuf,
+    keep_audio: bool,
+    retention: Option<chrono::Duration>,
+    entries: Mutex<HashMap<String, HistoryEntry>>,
+}
+
+impl HistoryStore {
+    /// `root` のエントリを読み込んでストアを開く（無ければ作成）
+    /// - `entry.json` の無い/壊れたディレクトリは保存途中のものとして削除する
+    /// - 保存期間を過ぎたエントリも削除する
+    pub fn open(root: impl Into<PathBuf>, config: &HistoryConfig) -> Result<Self> {
+        let root = root.into();
+        fs::create_dir_all(&root)
+            .with_context(|| format!("履歴ディレクトリを作成できません: {}", root.display()))?;
+
+        let mut entries = HashMap::new();
+        for dir_entry in fs::read_dir(&root)? {
+            let path = dir_entry?.path();
+            if !path.is_dir() {
+                continue;
+            }
+            match read_entry(&path) {
+                Ok(entry) => {
+                    entries.insert(entry.id.clone(), entry);
+                }
+                Err(e) => {
+                    eprintln!(
+                        "履歴を読み込めないため削除します: {} ({})",
+                        path.display(),
+                        e
+                    );
+                    let _ = fs::remove_dir_all(&path);
+                }
+            }
+        }
+
+        let store = Self {
+            root,
+            keep_audio: config.keep_audio,
+            retention: (config.retention_days > 0)
+                .then(|| chrono::Duration::days(config.retention_days as i64)),
+            entries: Mutex::new(entries),
+        };
+        store.prune_expired(Utc::now());
+        Ok(store)
+    }
+
+    /// 文字起こし結果を保存し、エントリ ID を返す
+    /// - `owner` は保存したリクエストの API キー名
+    pub fn save(
+        &self,
+        filename: &str,
+        audio: &[u8],
+        owner: Option<String>,
+        parameters: HistoryParameters,
+        result: TranscribeResponse,
+    ) -> Result<String> {
+        self.prune_expired(Utc::now());
+
+        let id = uuid::Uuid::new_v4().to_string();
+        let dir = self.root.join(&id);
+        fs::create_dir_all(&dir)?;
+
+        let audio_file = self.keep_audio.then(|| audio_file_name(filename));
+        let entry = HistoryEntry {
+            id: id.clone(),
+            created_at: Utc::now().to_rfc3339(),
+            filename: filename.to_string(),
+            audio_file: audio_file.clone(),
+            audio_size_bytes: audio.len() as u64,
+            owner,
+            parameters,
+            result,
+        };
+
+        // 音声 → entry.json の順に書く（entry.json があれば保存が完了している）
+        let written = audio_file
+            .map_or(Ok(()), |name| fs::write(dir.join(name), audio))
+            .and_then(|_| {
+                let json = serde_json::to_vec_pretty(&entry).map_err(std::io::Error::from)?;
+                fs::write(dir.join(ENTRY_FILE), json)
+            });
+        if let Err(e) = written {
+            let _ = fs::remove_dir_all(&dir);
+            return Err(anyhow::anyhow!("履歴を保存できません: {}", e));
+        }
+
+        self.entries.lock().unwrap().insert(id.clone(), entry);
+        Ok(id)
+    }
+
+    /// 新しい順に一覧/検索する
+    /// - `owner` 指定時はそのキーが保存したエントリだけ
+    pub fn list(&self, query: &HistoryQuery, owner: Option<&str>) -> HistoryListResponse {
+        let terms: Vec<String> = query
+            .q
+            .as_deref()
+            .unwrap_or("")
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect();
+
+        let entries = self.entries.lock().unwrap();
+        let mut matched: Vec<&HistoryEntry> = entries
+            .values()
+            .filter(|entry| is_owned_by(entry, owner))
+            .filter(|entry| {
+                let text = entry.result.text.to_lowercase();
+                terms.iter().all(|term| text.contains(term.as_str()))
+            })
+            .collect();
+        matched.sort_by(|a, b| {
+            created_at(b)
+                .cmp(&created_at(a))
+                .then_with(|| a.id.cmp(&b.id))
+        });
+
+        let limit = query.limit.unwrap_or(50).min(500);
+        HistoryListResponse {
+            total: matched.len(),
+            entries: matched
+                .into_iter()
+                .skip(query.offset.unwrap_or(0))
+                .take(limit)
+                .map(HistorySummary::from)
+                .collect(),
+        }
+    }
+
+    pub fn get(&self, id: &str, owner: Option<&str>) -> Option<HistoryEntry> {
+        self.entries
+            .lock()
+            .unwrap()
+            .get(id)
+            .filter(|entry| is_owned_by(entry, owner))
+            .cloned()
+    }
+
+    /// 保存した音声のパスとアップロード時のファイル名
+    pub fn audio_path(&self, id: &str, owner: Option<&str>) -> Option<(PathBuf, String)> {
+        let entries = self.entries.lock().unwrap();
+        let entry = entries.get(id).filter(|entry| is_owned_by(entry, owner))?;
+        let audio_file = entry.audio_file.as_ref()?;
+        Some((self.root.join(id).join(audio_file), entry.filename.clone()))
+    }
+
+    /// エントリを削除（存在しなければ false）
+    pub fn delete(&self, id: &str, owner: Option<&str>) -> Result<bool> {
+        {
+            let mut entries = self.entries.lock().unwrap();
+            if !entries
+                .get(id)
+                .is_some_and(|entry| is_owned_by(entry, owner))
+            {
+                return Ok(false);
+            }
+            entries.remove(id);
+        }
+        remove_entry_dir(&self.root.join(id))?;
+        Ok(true)
+    }
+
+    /// 保存期間を過ぎたエントリを削除し、削除した件数を返す
+    pub fn prune_expired(&self, now: DateTime<Utc>) -> usize {
+        let Some(retention) = self.retention else {
+            return 0;
+        };
+
+        let expired: Vec<String> = {
+            let mut entries = self.entries.lock().unwrap();
+            let expired: Vec<String> = entries
+                .values()
+                .filter(|entry| created_at(entry).is_some_and(|t| now - t > retention))
+                .map(|entry| entry.id.clone())
+                .collect();
+            for id in &expired {
+                entries.remove(id);
+            }
+            expired
+        };
+
+        for id in &expired {
+            if let Err(e) = remove_entry_dir(&self.root.join(id)) {
+                eprintln!("期限切れの履歴を削除できません: {} ({})", id, e);
+            }
+        }
+        expired.len()
+    }
+}
+
+fn read_entry(dir: &Path) -> Result<HistoryEntry> {
+    let json = fs::read(dir.join(ENTRY_FILE))?;
+    let entry: HistoryEntry = serde_json::from_slice(&json)?;
+    // ディレクトリ名と ID が食い違うものは扱わない（パスは ID から組み立てるため）
+    if dir.file_name().and_then(|name| name.to_str()) != Some(entry.id.as_str()) {
+        return Err(anyhow::anyhow!("ID がディレクトリ名と一致しません"));
+    }
+    Ok(entry)
+}
+
+/// `owner` が指定されていれば、そのキーが保存したエントリかどうか
+fn is_owned_by(entry: &HistoryEntry, owner: Option<&str>) -> bool {
+    owner.is_none_or(|owner| entry.owner.as_deref() == Some(owner))
+}
+
+fn remove_entry_dir(dir: &Path) -> std::io::Result<()> {
+    match fs::remove_dir_all(dir) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+fn created_at(entry: &HistoryEntry) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(&entry.created_at)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// 保存する音声のファイル名（アップロード時の拡張子を英数字のみ引き継ぐ）
+fn audio_file_name(filename: &str) -> String {
+    let extension = Path::new(filename)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .filter(|ext| !ext.is_empty() && ext.len() <= 8)
+        .filter(|ext| ext.chars().all(|c| c.is_ascii_alphanumeric()))
+        .map(|ext| ext.to_ascii_lowercase());
+
+    match extension {
+        Some(extension) => format!("audio.{}", extension),
+        None => "audio".to_string(),
+    }
+}
@@ -0,0 +1,171 @@
+use crate::config::Config;
+use crate::history::HistoryStore;
+use crate::models::CleanupReport;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+// =============================================================================
+// 一時ファイルの掃除
+// - 異常終了したリクエストが残したファイルを `temp_dir` / `upload_dir` から定期的に削除する
+// - 更新から `limits.cleanup_temp_files_after_minutes` 分を過ぎたファイルが対象
+// - `upload_dir/history` は履歴の保存期間で管理するため、ファイル単位では消さない
+// - `upload_dir/glossaries` は API で登録した用語集なので消さない
+// - `upload_dir/auth` は API キーの使用量（月間クォータ）なので消さない
+// =============================================================================
+
+/// 掃除の間隔の下限/上限
+const MIN_INTERVAL: Duration = Duration::from_secs(60);
+const MAX_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// 一時ファイルの掃除役
+/// - `Arc<Janitor>` として `AppState` で共有し、直近の結果を `/health` で返す
+pub struct Janitor {
+    /// 掃除するディレクトリ
+    dirs: Vec<PathBuf>,
+    /// 掃除しないディレクトリ（配下を含む）
+    skip: Vec<PathBuf>,
+    /// これより古いファイルを削除する（None は掃除しない）
+    max_age: Option<Duration>,
+    history: Option<Arc<HistoryStore>>,
+    last: Mutex<Option<CleanupReport>>,
+}
+
+impl Janitor {
+    pub fn new(config: &Config, history: Option<Arc<HistoryStore>>) -> Self {
+        let mut dirs = vec![PathBuf::from(&config.paths.temp_dir)];
+        let upload_dir = PathBuf::from(&config.paths.upload_dir);
+        if !dirs.contains(&upload_dir) {
+            dirs.push(upload_dir.clone());
+        }
+
+        let minutes = config.limits.cleanup_temp_files_after_minutes;
+        Self {
+            dirs,
+            skip: vec![
+                upload_dir.join("history"),
+                upload_dir.join("glossaries"),
+                upload_dir.join("auth"),
+            ],
+            max_age: (minutes > 0).then(|| Duration::from_secs(minutes as u64 * 60)),
+            history,
+            last: Mutex::new(None),
+        }
+    }
+
+    /// 掃除の間隔（保存期間の半分を 1〜10 分に収める）。掃除しない設定なら None
+    pub fn interval(&self) -> Option<Duration> {
+        self.max_age
+            .map(|max_age| (max_age / 2).clamp(MIN_INTERVAL, MAX_INTERVAL))
+    }
+
+    /// 1 回掃除して結果を記録する
+    /// - `now` より `max_age` 以上前に更新されたファイルを削除し、空になったサブディレクトリも消す
+    /// - 保存期間を過ぎた履歴もここで削除する
+    pub fn sweep(&self, now: SystemTime) -> CleanupReport {
+        let mut report = CleanupReport::default();
+
+        if let Some(max_age) = self.max_age {
+            for dir in &self.dirs {
+                sweep_dir(dir, max_age, now, &self.skip, &mut report);
+            }
+        }
+        if let Some(history) = &self.history {
+            report.expired_history = history.prune_expired(now.into());
+        }
+
+        report.finished_at = chrono::DateTime::<chrono::Utc>::from(now).to_rfc3339();
+        *self.last.lock().unwrap() = Some(report.clone());
+        report
+    }
+
+    /// 直近の掃除の結果
+    pub fn last_report(&self) -> Option<CleanupReport> {
+        self.last.lock().unwrap().clone()
+    }
+}
+
+/// バックグラウンドで定期的に掃除する（起動直後にも 1 回行う）
+/// - 掃除しない設定（`cleanup_temp_files_after_minutes = 0`）で履歴も無効なら何もしない
+pub fn spawn(janitor: Arc<Janitor>) {
+    let interval = match (janitor.interval(), &janitor.history) {
+        (Some(interval), _) => interval,
+        (None, Some(_)) => MAX_INTERVAL,
+        (None, None) => return,
+    };
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            let janitor = Arc::clone(&janitor);
+            match tokio::task::spawn_blocking(move || janitor.sweep(SystemTime::now())).await {
+                Ok(report) if report.removed_files > 0 || report.failed > 0 => println!(
+                    "一時ファイルを掃除しました: {}件（{}バイト）削除、失敗 {}件",
+                    report.removed_files, report.removed_bytes, report.failed
+                ),
+                Ok(_) => {}
+                Err(e) => eprintln!("一時ファイルの掃除でエラー: {}", e),
+            }
+        }
+    });
+}
+
+/// `dir` 配下を再帰的に掃除する
+/// - シンボリックリンクはたどらず、リンク自体を古いファイルとして扱う
+fn sweep_dir(
+    dir: &Path,
+    max_age: Duration,
+    now: SystemTime,
+    skip: &[PathBuf],
+    report: &mut CleanupReport,
+) {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for dir_entry in read_dir.flatten() {
+        let path = dir_entry.path();
+        if skip.contains(&path) {
+            continue;
+        }
+        let Ok(metadata) = fs::symlink_metadata(&path) else {
+            continue;
+        };
+
+        if metadata.is_dir() {
+            sweep_dir(&path, max_age, now, skip, report);
+            // 空になったサブディレクトリも消す（作成直後のものは残す）
+            let is_empty = fs::read_dir(&path).is_ok_and(|mut entries| entries.next().is_none());
+            if is_empty && is_expired(&metadata, max_age, now) && fs::remove_dir(&path).is_err() {
+                report.failed += 1;
+            }
+            continue;
+        }
+
+        if !is_expired(&metadata, max_age, now) {
+            continue;
+        }
+        match fs::remove_file(&path) {
+            Ok(()) => {
+                report.removed_files += 1;
+                report.removed_bytes += metadata.len();
+            }
+            Err(e) => {
+                eprintln!("一時ファイルを削除できません: {} ({})", path.display(), e);
+                report.failed += 1;
+            }
+        }
+    }
+}
+
+/// 最終更新から `max_age` 以上経っているか（更新時刻が未来/取得不可なら対象外）
+fn is_expired(metadata: &fs::Metadata, max_age: Duration, now: SystemTime) -> bool {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|modified| now.duration_since(modified).ok())
+        .is_some_and(|age| age >= max_age)
+}
@@ -0,0 +1,314 @@
+use crate::models::{ApiErrorCode, ErrorResponse, TranscribeResponse};
+use crate::webhook::{CallbackStatus, WebhookTarget};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// =============================================================================
+// 非同期ジョブ管理
+// - 長時間の音声を HTTP リクエストから切り離して処理するためのジョブストア
+// - ジョブの状態/進捗/結果はメモリ上に保持し、完了後は保持期間を過ぎたら破棄する
+// - 作成したリクエストの API キーを記録し、取得/キャンセルに `owner` を指定すると
+//   そのキーのジョブだけを扱う（他のキーのものは存在しないものとして扱う）
+// =============================================================================
+
+/// ジョブの状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    /// 空きエンジン待ち
+    Queued,
+    /// 推論中
+    Running,
+    /// 完了（結果取得可能）
+    Completed,
+    /// 失敗
+    Failed,
+    /// キャンセル済み
+    Cancelled,
+}
+
+impl JobStatus {
+    /// 終了状態（これ以上変化しない）かどうか
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            JobStatus::Completed | JobStatus::Failed | JobStatus::Cancelled
+        )
+    }
+}
+
+/// `GET /jobs/{id}` で返すジョブ情報
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobInfo {
+    pub id: String,
+    pub status: JobStatus,
+    /// 推論の進捗（0〜100%）
+    pub progress: u8,
+    pub filename: String,
+    /// 受付時刻（RFC 3339）
+    pub created_at: String,
+    /// 推論開始時刻（RFC 3339）
+    pub started_at: Option<String>,
+    /// 終了時刻（RFC 3339）
+    pub finished_at: Option<String>,
+    /// 失敗時のエラー内容
+    pub error: Option<ErrorResponse>,
+    /// 終了時の Webhook 通知の配信状況（`callback_url` を指定した場合のみ）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub callback: Option<CallbackStatus>,
+}
+
+/// ジョブ結果の取得結果
+#[derive(Debug)]
+pub enum JobOutcome {
+    /// まだ終わっていない（現在の状態）
+    Pending(JobStatus),
+    Completed(Box<TranscribeResponse>),
+    Failed {
+        code: ApiErrorCode,
+        error: ErrorResponse,
+    },
+    Cancelled,
+}
+
+struct JobEntry {
+    info: JobInfo,
+    /// 作成したリクエストの API キー名（認証が無効なら None）
+    owner: Option<String>,
+    result: Option<TranscribeResponse>,
+    error_code: Option<ApiErrorCode>,
+    callback: Option<WebhookTarget>,
+    cancel_flag: Arc<AtomicBool>,
+    abort_handle: Option<tokio::task::AbortHandle>,
+    finished_at: Option<Instant>,
+}
+
+/// ジョブストア
+/// - `Arc<JobStore>` として `AppState` で共有する
+pub struct JobStore {
+    jobs: Mutex<HashMap<String, JobEntry>>,
+    retention: Duration,
+}
+
+impl JobStore {
+    /// 終了したジョブを `retention` の間保持するストアを作成
+    pub fn new(retention: Duration) -> Self {
+        Self {
+            jobs: Mutex::new(HashMap::new()),
+            retention,
+        }
+    }
+
+    /// 新しいジョブを登録（状態は Queued）
+    /// - `owner` は作成したリクエストの API キー名
+    /// - 戻り値はジョブ ID と、処理側がキャンセルを検知するためのフラグ
+    pub fn create(&self, filename: &str, owner: Option<String>) -> (String, Arc<AtomicBool>) {
+        self.prune_expired();
+
+        let id = uuid::Uuid::new_v4().to_string();
+        let cancel_flag = Arc::new(AtomicBool::new(false));
+        let entry = JobEntry {
+            info: JobInfo {
+                id: id.clone(),
+                status: JobStatus::Queued,
+                progress: 0,
+                filename: filename.to_string(),
+                created_at: now_rfc3339(),
+                started_at: None,
+                finished_at: None,
+                error: None,
+                callback: None,
+            },
+            owner,
+            result: None,
+            error_code: None,
+            callback: None,
+            cancel_flag: Arc::clone(&cancel_flag),
+            abort_handle: None,
+            finished_at: None,
+        };
+
+        self.jobs.lock().unwrap().insert(id.clone(), entry);
+        (id, cancel_flag)
+    }
+
+    /// 処理タスクの中断ハンドルを登録（キャンセル時に使用）
+    pub fn set_abort_handle(&self, id: &str, handle: tokio::task::AbortHandle) {
+        if let Some(entry) = self.jobs.lock().unwrap().get_mut(id) {
+            if !entry.info.status.is_finished() {
+                entry.abort_handle = Some(handle);
+            }
+        }
+    }
+
+    /// 終了時の通知先を登録
+    pub fn set_callback(&self, id: &str, target: WebhookTarget) {
+        if let Some(entry) = self.jobs.lock().unwrap().get_mut(id) {
+            entry.info.callback = Some(CallbackStatus::new(&target.url));
+            entry.callback = Some(target);
+        }
+    }
+
+    /// 登録した通知先
+    pub fn callback_target(&self, id: &str) -> Option<WebhookTarget> {
+        self.jobs.lock().unwrap().get(id)?.callback.clone()
+    }
+
+    /// 通知の配信状況を更新（ジョブの終了後に送るため、終了済みでも更新する）
+    pub fn update_callback(&self, id: &str, update: impl FnOnce(&mut CallbackStatus)) {
+        if let Some(status) = self
+            .jobs
+            .lock()
+            .unwrap()
+            .get_mut(id)
+            .and_then(|entry| entry.info.callback.as_mut())
+        {
+            update(status);
+        }
+    }
+
+    /// エンジンを確保して推論を開始した
+    pub fn mark_running(&self, id: &str) {
+        self.update_unfinished(id, |entry| {
+            entry.info.status = JobStatus::Running;
+            entry.info.started_at = Some(now_rfc3339());
+        });
+    }
+
+    /// 進捗を更新（0〜100 に丸める）
+    pub fn set_progress(&self, id: &str, progress: i32) {
+        let progress = progress.clamp(0, 100) as u8;
+        self.update_unfinished(id, |entry| {
+            entry.info.progress = entry.info.progress.max(progress);
+        });
+    }
+
+    /// 正常終了
+    pub fn complete(&self, id: &str, response: TranscribeResponse) {
+        self.update_unfinished(id, |entry| {
+            entry.info.status = JobStatus::Completed;
+            entry.info.progress = 100;
+            entry.result = Some(response);
+        });
+        self.mark_finished(id);
+    }
+
+    /// 異常終了
+    pub fn fail(&self, id: &str, code: ApiErrorCode, message: String, details: Option<String>) {
+        self.update_unfinished(id, |entry| {
+            entry.info.status = JobStatus::Failed;
+            entry.info.error = Some(ErrorResponse {
+                error: message,
+                code: code.as_str().to_string(),
+                details,
+            });
+            entry.error_code = Some(code);
+        });
+        self.mark_finished(id);
+    }
+
+    /// ジョブをキャンセル
+    /// - 戻り値の bool は今回の呼び出しでキャンセルされたかどうか
+    /// - 終了済みのジョブは変更せずそのまま返す
+    /// - 存在しない（`owner` 指定時は他のキーのジョブの）場合は None
+    pub fn cancel(&self, id: &str, owner: Option<&str>) -> Option<(JobInfo, bool)> {
+        {
+            let mut jobs = self.jobs.lock().unwrap();
+            let entry = jobs.get_mut(id).filter(|entry| is_owned_by(entry, owner))?;
+            if entry.info.status.is_finished() {
+                return Some((entry.info.clone(), false));
+            }
+
+            entry.cancel_flag.store(true, Ordering::SeqCst);
+            if let Some(handle) = entry.abort_handle.take() {
+                handle.abort();
+            }
+            entry.info.status = JobStatus::Cancelled;
+        }
+        self.mark_finished(id);
+        self.get(id, owner).map(|info| (info, true))
+    }
+
+    /// ジョブ情報を取得
+    /// - `owner` 指定時はそのキーが作成したジョブだけ
+    pub fn get(&self, id: &str, owner: Option<&str>) -> Option<JobInfo> {
+        self.jobs
+            .lock()
+            .unwrap()
+            .get(id)
+            .filter(|entry| is_owned_by(entry, owner))
+            .map(|entry| entry.info.clone())
+    }
+
+    /// ジョブ結果を取得
+    /// - `owner` 指定時はそのキーが作成したジョブだけ
+    pub fn outcome(&self, id: &str, owner: Option<&str>) -> Option<JobOutcome> {
+        let jobs = self.jobs.lock().unwrap();
+        let entry = jobs.get(id).filter(|entry| is_owned_by(entry, owner))?;
+
+        let outcome = match entry.info.status {
+            JobStatus::Completed => match &entry.result {
+                Some(result) => JobOutcome::Completed(Box::new(result.clone())),
+                None => JobOutcome::Pending(JobStatus::Completed),
+            },
+            JobStatus::Failed => JobOutcome::Failed {
+                code: entry
+                    .error_code
+                    .clone()
+                    .unwrap_or(ApiErrorCode::ProcessingFailed),
+                error: entry.info.error.clone().unwrap_or_else(|| ErrorResponse {
+                    error: "ジョブが失敗しました".to_string(),
+                    code: ApiErrorCode::ProcessingFailed.as_str().to_string(),
+                    details: None,
+                }),
+            },
+            JobStatus::Cancelled => JobOutcome::Cancelled,
+            status => JobOutcome::Pending(status),
+        };
+
+        Some(outcome)
+    }
+
+    /// 保持期間を過ぎた終了済みジョブを破棄
+    pub fn prune_expired(&self) {
+        let retention = self.retention;
+        self.jobs
+            .lock()
+            .unwrap()
+            .retain(|_, entry| match entry.finished_at {
+                Some(finished_at) => finished_at.elapsed() < retention,
+                None => true,
+            });
+    }
+
+    fn update_unfinished(&self, id: &str, update: impl FnOnce(&mut JobEntry)) {
+        if let Some(entry) = self.jobs.lock().unwrap().get_mut(id) {
+            // キャンセル後に完了通知が届いても状態は上書きしない
+            if !entry.info.status.is_finished() {
+                update(entry);
+            }
+        }
+    }
+
+    fn mark_finished(&self, id: &str) {
+        if let Some(entry) = self.jobs.lock().unwrap().get_mut(id) {
+            if entry.finished_at.is_none() {
+                entry.finished_at = Some(Instant::now());
+                entry.info.finished_at = Some(now_rfc3339());
+                entry.abort_handle = None;
+            }
+        }
+    }
+}
+
+/// `owner` が指定されていれば、そのキーが作成したジョブかどうか
+fn is_owned_by(entry: &JobEntry, owner: Option<&str>) -> bool {
+    owner.is_none_or(|owner| entry.owner.as_deref() == Some(owner))
+}
+
+fn now_rfc3339() -> String {
+    chrono::Utc::now().to_rfc3339()
+}
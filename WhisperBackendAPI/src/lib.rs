@@ -2,8 +2,25 @@
 // テストから各モジュールにアクセスできるようにするため
 
 pub mod audio;
+pub mod auth;
+pub mod batch;
+pub mod checksum;
+pub mod chunk;
 pub mod config;
+pub mod diarize;
+pub mod download;
+pub mod export;
+pub mod filter;
+pub mod glossary;
+pub mod history;
+pub mod janitor;
+pub mod jobs;
+pub mod metrics;
 pub mod models;
+pub mod openai;
+pub mod registry;
+pub mod vad;
+pub mod webhook;
 
 // whisper関連のモジュールは条件コンパイル
 #[cfg(feature = "whisper")]
@@ -28,7 +45,11 @@ pub mod whisper {
         pub text: String,
         pub segments: Vec<crate::models::TranscriptionSegment>,
         pub language: Option<String>,
+        pub language_detection: Option<crate::models::LanguageDetection>,
         pub processing_time_ms: u64,
+        pub filtered: Vec<crate::models::FilteredSegment>,
+        pub corrections: Vec<crate::models::FilteredSegment>,
+        pub failed_chunks: Vec<crate::models::FailedChunk>,
     }
 
     pub struct WhisperEngine;
@@ -50,6 +71,16 @@ pub mod whisper {
         }
     }
 
+    pub struct WhisperEnginePool;
+
+    impl WhisperEnginePool {
+        pub fn new(_model_path: &str, _config: &Config, _pool_size: usize) -> Result<Self> {
+            Err(anyhow::anyhow!(
+                "Whisper engine not available (feature disabled)"
+            ))
+        }
+    }
+
     /// サポートされている言語のリストを取得
     pub fn get_supported_languages() -> Vec<&'static str> {
         vec![
@@ -123,26 +154,32 @@ pub mod handlers {
     // whisper機能が無効の場合のモック実装
     use crate::config::Config;
     use crate::models::ServerStats;
+    use crate::registry::ModelRegistry;
     use std::sync::{Arc, Mutex};
     use std::time::Instant;
 
     #[derive(Clone)]
     pub struct AppState {
         pub config: Arc<Config>,
-        pub whisper_engine: Arc<Mutex<Option<crate::whisper::WhisperEngine>>>,
+        pub models: Arc<ModelRegistry<crate::whisper::WhisperEnginePool>>,
         pub stats: Arc<Mutex<ServerStats>>,
         pub start_time: Arc<Instant>,
     }
 
     impl AppState {
         pub fn new(config: Config) -> Self {
+            let memory_budget_mb = config.whisper.model_memory_budget_mb;
             Self {
                 config: Arc::new(config),
-                whisper_engine: Arc::new(Mutex::new(None)),
+                models: Arc::new(ModelRegistry::new(memory_budget_mb)),
                 stats: Arc::new(Mutex::new(ServerStats::default())),
                 start_time: Arc::new(Instant::now()),
             }
         }
+
+        pub fn engine_pool(&self) -> Option<Arc<crate::whisper::WhisperEnginePool>> {
+            self.models.default_model()
+        }
     }
 
     #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -152,7 +189,16 @@ pub mod handlers {
         UnsupportedFormat,
         ProcessingFailed,
         ModelNotLoaded,
+        ModelNotFound,
         ServerOverloaded,
+        JobNotFound,
+        JobNotReady,
+        HistoryNotFound,
+        GlossaryNotFound,
+        Unauthorized,
+        RateLimited,
+        QuotaExceeded,
+        Timeout,
         InternalError,
     }
 
@@ -164,7 +210,16 @@ pub mod handlers {
                 ApiErrorCode::UnsupportedFormat => "UNSUPPORTED_FORMAT",
                 ApiErrorCode::ProcessingFailed => "PROCESSING_FAILED",
                 ApiErrorCode::ModelNotLoaded => "MODEL_NOT_LOADED",
+                ApiErrorCode::ModelNotFound => "MODEL_NOT_FOUND",
                 ApiErrorCode::ServerOverloaded => "SERVER_OVERLOADED",
+                ApiErrorCode::JobNotFound => "JOB_NOT_FOUND",
+                ApiErrorCode::JobNotReady => "JOB_NOT_READY",
+                ApiErrorCode::HistoryNotFound => "HISTORY_NOT_FOUND",
+                ApiErrorCode::GlossaryNotFound => "GLOSSARY_NOT_FOUND",
+                ApiErrorCode::Unauthorized => "UNAUTHORIZED",
+                ApiErrorCode::RateLimited => "RATE_LIMITED",
+                ApiErrorCode::QuotaExceeded => "QUOTA_EXCEEDED",
+                ApiErrorCode::Timeout => "TIMEOUT",
                 ApiErrorCode::InternalError => "INTERNAL_ERROR",
             }
         }
@@ -6,22 +6,40 @@
 // - TCP リスナーをバインドしてサーバーを起動
 // =============================================================================
 mod audio;
+mod auth;
+mod batch;
+mod checksum;
+mod chunk;
 mod config;
+mod diarize;
+mod download;
+mod export;
+mod filter;
+mod glossary;
 mod handlers;
+mod history;
+mod janitor;
+mod jobs;
+mod metrics;
 mod models;
+mod openai;
+mod registry;
+mod vad;
+mod webhook;
 mod whisper;
 
 use crate::config::Config;
-use crate::handlers::{add_cors_headers, AppState};
+use crate::handlers::{add_cors_headers, cors_layer, require_api_key, track_metrics, AppState};
 use crate::whisper::WhisperEngine;
 use axum::extract::DefaultBodyLimit;
 use axum::{
+    middleware,
     routing::{get, options, post},
     Router,
 };
 use std::net::SocketAddr;
+use std::sync::Arc;
 use tower::ServiceBuilder;
-use tower_http::cors::{Any, CorsLayer};
 use tower_http::trace::TraceLayer;
 
 #[tokio::main]
@@ -64,47 +82,121 @@ async fn main() -> anyhow::Result<()> {
         }
     }
 
+    // 一時ファイルの定期的な掃除（異常終了したリクエストの残骸/期限切れの履歴）
+    janitor::spawn(Arc::clone(&app_state.janitor));
+
     // CORSレイヤーの設定
-    // - Any を指定してデモ/ローカル用途での疎通を優先
-    // - 本番では許可オリジンを明示し、許可ヘッダー/メソッドも最小化する
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
+    // - `server.cors_origins` のオリジンだけを許可（`*` はすべて許可）
+    let cors = cors_layer(&config.server.cors_origins);
+    if config.auth.enabled {
+        println!("API キー認証: 有効（{}件のキー）", config.auth.keys.len());
+    }
 
     // ルーターの構築
-    // - 文字起こし API（タイムスタンプ有/無）
-    // - モデル/言語/ヘルス/統計の情報系 API
+    // - 文字起こし API（タイムスタンプ有/無、OpenAI 互換）
+    // - モデル/言語/ヘルス/統計の情報系 API、モデルの読み込み/破棄
+    // - 非同期ジョブ API（投入/状態/結果/キャンセル）
     // - OPTIONS への CORS 応答（プリフライト）
-    // - ミドルウェア: HTTP トレース + CORS
+    // - ミドルウェア: HTTP トレース + メトリクス + CORS + API キー認証
     let app = Router::new()
         // 文字起こしエンドポイント
         .route("/transcribe", post(handlers::transcribe_basic))
+        .route("/transcribe/stream", post(handlers::transcribe_stream))
+        .route("/transcribe/batch", post(handlers::transcribe_batch))
         .route(
             "/transcribe-with-timestamps",
             post(handlers::transcribe_with_timestamps),
         )
+        // OpenAI 互換エンドポイント
+        .route(
+            "/v1/audio/transcriptions",
+            post(handlers::openai_transcriptions),
+        )
+        .route(
+            "/v1/audio/translations",
+            post(handlers::openai_translations),
+        )
+        // 言語検出エンドポイント
+        .route("/detect-language", post(handlers::detect_language))
         // 情報取得エンドポイント
         .route("/models", get(handlers::get_models))
+        .route("/models/{name}/load", post(handlers::load_model))
+        .route("/models/{name}/unload", post(handlers::unload_model))
+        .route(
+            "/admin/models/{name}/download",
+            post(handlers::download_model).get(handlers::get_model_download),
+        )
+        .route("/admin/downloads", get(handlers::list_model_downloads))
         .route("/languages", get(handlers::get_languages))
         .route("/health", get(handlers::health_check))
         .route("/stats", get(handlers::get_stats))
+        .route("/metrics", get(handlers::get_metrics))
         .route("/gpu-status", get(handlers::get_gpu_status))
+        // 非同期ジョブエンドポイント
+        .route("/jobs", post(handlers::create_job))
+        .route(
+            "/jobs/{id}",
+            get(handlers::get_job).delete(handlers::cancel_job),
+        )
+        .route("/jobs/{id}/result", get(handlers::get_job_result))
+        // 文字起こし履歴
+        .route("/history", get(handlers::list_history))
+        .route(
+            "/history/{id}",
+            get(handlers::get_history).delete(handlers::delete_history),
+        )
+        .route("/history/{id}/audio", get(handlers::get_history_audio))
+        // 用語集
+        .route("/glossaries", get(handlers::list_glossaries))
+        .route(
+            "/glossaries/{name}",
+            get(handlers::get_glossary)
+                .put(handlers::put_glossary)
+                .delete(handlers::delete_glossary),
+        )
         // CORS プリフライトリクエスト対応
         .route("/transcribe", options(add_cors_headers))
+        .route("/transcribe/stream", options(add_cors_headers))
+        .route("/transcribe/batch", options(add_cors_headers))
         .route("/transcribe-with-timestamps", options(add_cors_headers))
+        .route("/v1/audio/transcriptions", options(add_cors_headers))
+        .route("/v1/audio/translations", options(add_cors_headers))
+        .route("/detect-language", options(add_cors_headers))
         .route("/models", options(add_cors_headers))
+        .route("/models/{name}/load", options(add_cors_headers))
+        .route("/models/{name}/unload", options(add_cors_headers))
+        .route("/admin/models/{name}/download", options(add_cors_headers))
+        .route("/admin/downloads", options(add_cors_headers))
         .route("/languages", options(add_cors_headers))
         .route("/health", options(add_cors_headers))
         .route("/stats", options(add_cors_headers))
+        .route("/metrics", options(add_cors_headers))
         .route("/gpu-status", options(add_cors_headers))
+        .route("/jobs", options(add_cors_headers))
+        .route("/jobs/{id}", options(add_cors_headers))
+        .route("/jobs/{id}/result", options(add_cors_headers))
+        .route("/history", options(add_cors_headers))
+        .route("/history/{id}", options(add_cors_headers))
+        .route("/history/{id}/audio", options(add_cors_headers))
+        .route("/glossaries", options(add_cors_headers))
+        .route("/glossaries/{name}", options(add_cors_headers))
         // ミドルウェアの追加
         .layer(
             ServiceBuilder::new()
                 .layer(TraceLayer::new_for_http())
+                // 認証で拒否したリクエストも含めて数える
+                .layer(middleware::from_fn_with_state(
+                    app_state.clone(),
+                    track_metrics,
+                ))
                 .layer(cors)
                 // 本文サイズの上限（multipart 含む）を設定
-                .layer(DefaultBodyLimit::max(config.server.max_request_size)),
+                .layer(DefaultBodyLimit::max(config.server.max_request_size))
+                // CORS の内側で認証する（拒否したレスポンスにも CORS ヘッダーを付ける）
+                .layer(middleware::from_fn_with_state(
+                    app_state.clone(),
+                    require_api_key,
+                )),
         )
         // アプリケーション状態の共有
         // - ハンドラから Config や WhisperEngine、統計にアクセス可能
@@ -121,11 +213,33 @@ async fn main() -> anyhow::Result<()> {
     println!("API エンドポイント:");
     println!("  POST /transcribe - 基本的な文字起こし");
     println!("  POST /transcribe-with-timestamps - タイムスタンプ付き文字起こし");
+    println!("  POST /transcribe/stream - セグメントを逐次返す文字起こし（SSE）");
+    println!("  POST /transcribe/batch - 複数ファイル/ZIP/tar の一括文字起こし（結果は ZIP）");
+    println!("  POST /v1/audio/transcriptions - OpenAI 互換の文字起こし");
+    println!("  POST /v1/audio/translations - OpenAI 互換の英語翻訳");
+    println!("  POST /detect-language - 音声の言語検出");
     println!("  GET  /models - 利用可能なモデル一覧");
+    println!("  POST /models/{{name}}/load - モデルの読み込み");
+    println!("  POST /models/{{name}}/unload - モデルの破棄");
+    println!("  POST /admin/models/{{name}}/download - カタログのモデルをダウンロード");
+    println!("  GET  /admin/models/{{name}}/download - ダウンロードの進捗");
+    println!("  GET  /admin/downloads - ダウンロード状態の一覧");
     println!("  GET  /languages - サポートされている言語一覧");
     println!("  GET  /health - ヘルスチェック");
     println!("  GET  /stats - サーバー統計情報");
+    println!("  GET  /metrics - Prometheus メトリクス");
     println!("  GET  /gpu-status - GPU使用状態の詳細情報");
+    println!("  POST /jobs - 非同期文字起こしジョブの投入");
+    println!("  GET  /jobs/{{id}} - ジョブの状態/進捗");
+    println!("  GET  /jobs/{{id}}/result - ジョブの結果");
+    println!("  DELETE /jobs/{{id}} - ジョブのキャンセル");
+    println!("  GET  /history?q= - 文字起こし履歴の一覧/検索");
+    println!("  GET  /history/{{id}}?format= - 保存した文字起こし");
+    println!("  GET  /history/{{id}}/audio - 保存した元の音声");
+    println!("  DELETE /history/{{id}} - 履歴の削除");
+    println!("  GET  /glossaries - 用語集の一覧");
+    println!("  PUT  /glossaries/{{name}} - 用語集の登録/置き換え");
+    println!("  DELETE /glossaries/{{name}} - 用語集の削除");
     println!();
     println!("使用例:");
     println!("  curl -F \"file=@audio.wav\" http://{}/transcribe", addr);
@@ -0,0 +1,272 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+
+// =============================================================================
+// Prometheus メトリクス
+// - カウンタ/ヒストグラムはリクエストごとに記録し、`/metrics` でテキスト形式に出力する
+// - キュー長や読み込み済みモデルなどの現在値は出力時に集める（`MetricsGauges`）
+// =============================================================================
+
+/// 処理時間（秒）のバケット
+pub const PROCESSING_SECONDS_BUCKETS: &[f64] = &[
+    0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0, 600.0,
+];
+/// 音声の長さ（秒）のバケット
+pub const AUDIO_SECONDS_BUCKETS: &[f64] = &[
+    1.0, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0, 600.0, 1800.0, 3600.0,
+];
+/// エンジンの空き待ち時間（秒）のバケット
+pub const QUEUE_WAIT_SECONDS_BUCKETS: &[f64] = &[
+    0.01, 0.05, 0.1, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0,
+];
+/// 実時間比（処理時間 / 音声の長さ）のバケット
+pub const REAL_TIME_FACTOR_BUCKETS: &[f64] = &[0.05, 0.1, 0.25, 0.5, 0.75, 1.0, 1.5, 2.0, 5.0];
+
+/// 累積バケットのヒストグラム
+#[derive(Debug, Clone)]
+pub struct Histogram {
+    bounds: Vec<f64>,
+    /// バケットごとの件数（`bounds` の各上限以下、最後は +Inf）
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    pub fn new(bounds: &[f64]) -> Self {
+        Self {
+            bounds: bounds.to_vec(),
+            counts: vec![0; bounds.len() + 1],
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    pub fn observe(&mut self, value: f64) {
+        let index = self.bounds.partition_point(|bound| *bound < value);
+        self.counts[index] += 1;
+        self.sum += value;
+        self.count += 1;
+    }
+
+    fn render(&self, out: &mut String, name: &str, help: &str) {
+        write_header(out, name, help, "histogram");
+        let mut cumulative = 0;
+        for (bound, count) in self.bounds.iter().zip(&self.counts) {
+            cumulative += count;
+            let _ = writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, bound, cumulative);
+        }
+        let _ = writeln!(out, "{}_bucket{{le=\"+Inf\"}} {}", name, self.count);
+        let _ = writeln!(out, "{}_sum {}", name, self.sum);
+        let _ = writeln!(out, "{}_count {}", name, self.count);
+    }
+}
+
+/// 出力時に集める現在値
+#[derive(Debug, Clone, Default)]
+pub struct MetricsGauges {
+    /// 処理中のリクエスト数
+    pub active_requests: usize,
+    /// 読み込み済みモデルごとの空き待ちリクエスト数（モデル名, 待機数）
+    /// - 件数を読み込み済みモデル数として出力する
+    pub queued_requests: Vec<(String, usize)>,
+    /// 推論に GPU を使っているか（読み込み済みモデルのいずれか）
+    pub gpu_enabled: bool,
+    pub uptime_seconds: u64,
+}
+
+#[derive(Debug)]
+struct MetricsInner {
+    /// (メソッド, エンドポイント, ステータス) → 件数
+    requests: BTreeMap<(String, String, u16), u64>,
+    processing_seconds: Histogram,
+    queue_wait_seconds: Histogram,
+    audio_seconds: Histogram,
+    real_time_factor: Histogram,
+    upload_bytes: u64,
+}
+
+/// サーバー全体のメトリクス
+#[derive(Debug)]
+pub struct Metrics {
+    inner: Mutex<MetricsInner>,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self {
+            inner: Mutex::new(MetricsInner {
+                requests: BTreeMap::new(),
+                processing_seconds: Histogram::new(PROCESSING_SECONDS_BUCKETS),
+                queue_wait_seconds: Histogram::new(QUEUE_WAIT_SECONDS_BUCKETS),
+                audio_seconds: Histogram::new(AUDIO_SECONDS_BUCKETS),
+                real_time_factor: Histogram::new(REAL_TIME_FACTOR_BUCKETS),
+                upload_bytes: 0,
+            }),
+        }
+    }
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// HTTP リクエストを記録（エンドポイントはルートのパターン。例: `/jobs/{id}`）
+    pub fn record_request(&self, method: &str, endpoint: &str, status: u16) {
+        let mut inner = self.inner.lock().unwrap();
+        *inner
+            .requests
+            .entry((method.to_string(), endpoint.to_string(), status))
+            .or_default() += 1;
+    }
+
+    /// アップロードされた音声のバイト数を加算
+    pub fn record_upload(&self, bytes: usize) {
+        self.inner.lock().unwrap().upload_bytes += bytes as u64;
+    }
+
+    /// エンジンを確保するまで待った時間を記録（モデルの読み込みを含む）
+    pub fn record_queue_wait(&self, wait_ms: u64) {
+        self.inner
+            .lock()
+            .unwrap()
+            .queue_wait_seconds
+            .observe(wait_ms as f64 / 1000.0);
+    }
+
+    /// 成功した文字起こしの処理時間と音声の長さを記録
+    /// - 処理時間はエンジンを確保してからの時間（空き待ちは `record_queue_wait` で別に記録する）
+    /// - 実時間比は音声の長さが分かる場合のみ
+    pub fn record_transcription(&self, processing_ms: u64, audio_duration_ms: Option<u64>) {
+        let mut inner = self.inner.lock().unwrap();
+        let processing_seconds = processing_ms as f64 / 1000.0;
+        inner.processing_seconds.observe(processing_seconds);
+        if let Some(audio_ms) = audio_duration_ms.filter(|ms| *ms > 0) {
+            let audio_seconds = audio_ms as f64 / 1000.0;
+            inner.audio_seconds.observe(audio_seconds);
+            inner
+                .real_time_factor
+                .observe(processing_seconds / audio_seconds);
+        }
+    }
+
+    /// Prometheus のテキスト形式（0.0.4）で出力
+    pub fn render(&self, gauges: &MetricsGauges) -> String {
+        let inner = self.inner.lock().unwrap();
+        let mut out = String::new();
+
+        write_header(
+            &mut out,
+            "whisper_http_requests_total",
+            "HTTP リクエスト数（メソッド/エンドポイント/ステータス別）",
+            "counter",
+        );
+        for ((method, endpoint, status), count) in &inner.requests {
+            let _ = writeln!(
+                out,
+                "whisper_http_requests_total{{method=\"{}\",endpoint=\"{}\",status=\"{}\"}} {}",
+                escape_label(method),
+                escape_label(endpoint),
+                status,
+                count
+            );
+        }
+
+        inner.processing_seconds.render(
+            &mut out,
+            "whisper_transcription_processing_seconds",
+            "文字起こしの処理時間（秒、エンジンを確保してから）",
+        );
+        inner.queue_wait_seconds.render(
+            &mut out,
+            "whisper_engine_queue_wait_seconds",
+            "エンジンの空きを待った時間（秒）",
+        );
+        inner.audio_seconds.render(
+            &mut out,
+            "whisper_transcription_audio_seconds",
+            "文字起こしした音声の長さ（秒）",
+        );
+        inner.real_time_factor.render(
+            &mut out,
+            "whisper_transcription_real_time_factor",
+            "実時間比（処理時間 / 音声の長さ）",
+        );
+
+        write_header(
+            &mut out,
+            "whisper_upload_bytes_total",
+            "アップロードされた音声の合計バイト数",
+            "counter",
+        );
+        let _ = writeln!(out, "whisper_upload_bytes_total {}", inner.upload_bytes);
+
+        write_header(
+            &mut out,
+            "whisper_active_requests",
+            "処理中のリクエスト数",
+            "gauge",
+        );
+        let _ = writeln!(out, "whisper_active_requests {}", gauges.active_requests);
+
+        write_header(
+            &mut out,
+            "whisper_queued_requests",
+            "エンジンの空きを待っているリクエスト数（モデル別）",
+            "gauge",
+        );
+        for (model, queued) in &gauges.queued_requests {
+            let _ = writeln!(
+                out,
+                "whisper_queued_requests{{model=\"{}\"}} {}",
+                escape_label(model),
+                queued
+            );
+        }
+
+        write_header(
+            &mut out,
+            "whisper_models_loaded",
+            "読み込み済みのモデル数",
+            "gauge",
+        );
+        let _ = writeln!(
+            out,
+            "whisper_models_loaded {}",
+            gauges.queued_requests.len()
+        );
+
+        write_header(
+            &mut out,
+            "whisper_gpu_enabled",
+            "推論に GPU を使っているか（1: GPU, 0: CPU）",
+            "gauge",
+        );
+        let _ = writeln!(out, "whisper_gpu_enabled {}", u8::from(gauges.gpu_enabled));
+
+        write_header(
+            &mut out,
+            "whisper_uptime_seconds",
+            "サーバーの起動からの経過時間（秒）",
+            "gauge",
+        );
+        let _ = writeln!(out, "whisper_uptime_seconds {}", gauges.uptime_seconds);
+
+        out
+    }
+}
+
+fn write_header(out: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// ラベル値のエスケープ（`\`、`"`、改行）
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -1,12 +1,13 @@
+use crate::audio::ChannelMode;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 // =============================================================================
 // API Request/Response Models
 // - ハンドラとのデータ受け渡しに用いる型
 // =============================================================================
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TranscribeRequest {
     /// 言語コード（例: "ja", "en", "auto"）。未指定の場合は設定に従う
     pub language: Option<String>,
@@ -14,9 +15,280 @@ pub struct TranscribeRequest {
     pub translate_to_english: Option<bool>,
     /// セグメントのタイムスタンプを含めるかどうか
     pub include_timestamps: Option<bool>,
+    /// 使用するモデル名（例: "base"。未指定の場合は既定モデル）
+    #[serde(default)]
+    pub model: Option<String>,
+    /// 単語単位のタイムスタンプと信頼度を含めるかどうか（有効時はセグメントも返す）
+    #[serde(default)]
+    pub word_timestamps: Option<bool>,
+    /// レスポンス形式（未指定の場合は json）
+    #[serde(default)]
+    pub format: Option<ResponseFormat>,
+    /// 多チャンネル音声の扱い（未指定の場合は設定の `audio.channel_mode`）
+    #[serde(default)]
+    pub channel_mode: Option<ChannelMode>,
+    /// 無音区間を除いて推論するかどうか（未指定の場合は設定の `vad.enabled`）
+    #[serde(default)]
+    pub vad: Option<bool>,
+    /// ハルシネーション/繰り返しを除くかどうか（未指定の場合は設定の `filter.enabled`）
+    #[serde(default)]
+    pub filter: Option<bool>,
+    /// 長い音声を分割して推論するかどうか（未指定の場合は設定の `chunking.enabled`）
+    #[serde(default)]
+    pub chunking: Option<bool>,
+    /// 参照する用語集の名前（語句を初期プロンプトに加える）
+    #[serde(default)]
+    pub glossary: Option<String>,
+    /// 用語集の語句に近い表記を直すかどうか（未指定の場合は用語集の `correct`）
+    #[serde(default)]
+    pub glossary_correction: Option<bool>,
+    /// 話者を推定するかどうか（未指定の場合は設定の `diarization.enabled`）
+    #[serde(default)]
+    pub diarize: Option<bool>,
+    /// 話者数（分かっている場合。未指定なら推定する）
+    #[serde(default)]
+    pub num_speakers: Option<usize>,
+    /// 終了時に結果を POST する URL（`POST /jobs` のみ）
+    #[serde(default)]
+    pub callback_url: Option<String>,
+    /// 通知の署名に使う鍵（HMAC-SHA256）
+    #[serde(default, skip_serializing)]
+    pub callback_secret: Option<String>,
+    /// デコードパラメータ（未指定の項目は既定値）
+    #[serde(default, flatten)]
+    pub decoding: DecodingOptions,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// リクエストで指定できるデコードパラメータ
+/// - すべて任意。`resolve` で検証し、既定値を補った `DecodingParameters` にする
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DecodingOptions {
+    /// サンプリング温度（0〜1）
+    pub temperature: Option<f32>,
+    /// デコード失敗時に温度を上げて再試行する幅（0 で再試行しない）
+    pub temperature_increment: Option<f32>,
+    /// ビームサーチのビーム幅（指定時はビームサーチ）
+    pub beam_size: Option<i32>,
+    /// Greedy デコードの候補数
+    pub best_of: Option<i32>,
+    /// 無音と判定する確率のしきい値（0〜1）
+    pub no_speech_threshold: Option<f32>,
+    /// 平均対数確率がこれを下回ると再試行する（0 以下）
+    pub logprob_threshold: Option<f32>,
+    /// 初期プロンプト（用語や文脈のヒント）
+    pub initial_prompt: Option<String>,
+    /// 先頭の空白出力を抑制するか
+    pub suppress_blank: Option<bool>,
+}
+
+/// 実際に使われたデコードパラメータ（レスポンスで返す）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecodingParameters {
+    /// "greedy" または "beam_search"
+    pub strategy: String,
+    pub temperature: f32,
+    pub temperature_increment: f32,
+    pub beam_size: Option<i32>,
+    pub best_of: i32,
+    pub no_speech_threshold: f32,
+    pub logprob_threshold: f32,
+    pub initial_prompt: Option<String>,
+    pub suppress_blank: bool,
+}
+
+impl DecodingParameters {
+    /// whisper.cpp が同時に扱えるデコーダ数の上限
+    pub const MAX_DECODERS: i32 = 8;
+    /// 初期プロンプトの最大文字数
+    pub const MAX_PROMPT_CHARS: usize = 1000;
+}
+
+impl Default for DecodingParameters {
+    /// 従来どおりの Greedy（best_of=1）と whisper.cpp 既定のしきい値
+    fn default() -> Self {
+        Self {
+            strategy: "greedy".to_string(),
+            temperature: 0.0,
+            temperature_increment: 0.2,
+            beam_size: None,
+            best_of: 1,
+            no_speech_threshold: 0.6,
+            logprob_threshold: -1.0,
+            initial_prompt: None,
+            suppress_blank: true,
+        }
+    }
+}
+
+impl DecodingOptions {
+    /// 値を検証し、既定値を補ったパラメータを返す
+    /// - エラー時は利用者向けのメッセージを返す
+    pub fn resolve(&self) -> Result<DecodingParameters, String> {
+        let defaults = DecodingParameters::default();
+
+        let temperature = self.temperature.unwrap_or(defaults.temperature);
+        check_range("temperature", temperature, 0.0, 1.0)?;
+
+        let temperature_increment = self
+            .temperature_increment
+            .unwrap_or(defaults.temperature_increment);
+        check_range("temperature_increment", temperature_increment, 0.0, 1.0)?;
+
+        let max = DecodingParameters::MAX_DECODERS;
+        if let Some(beam_size) = self.beam_size {
+            if !(1..=max).contains(&beam_size) {
+                return Err(format!(
+                    "beam_size は 1〜{} で指定してください: {}",
+                    max, beam_size
+                ));
+            }
+        }
+
+        let best_of = self.best_of.unwrap_or(defaults.best_of);
+        if !(1..=max).contains(&best_of) {
+            return Err(format!(
+                "best_of は 1〜{} で指定してください: {}",
+                max, best_of
+            ));
+        }
+
+        let no_speech_threshold = self
+            .no_speech_threshold
+            .unwrap_or(defaults.no_speech_threshold);
+        check_range("no_speech_threshold", no_speech_threshold, 0.0, 1.0)?;
+
+        let logprob_threshold = self.logprob_threshold.unwrap_or(defaults.logprob_threshold);
+        check_range("logprob_threshold", logprob_threshold, -10.0, 0.0)?;
+
+        let initial_prompt = match self.initial_prompt.as_deref().map(str::trim) {
+            Some(prompt) if !prompt.is_empty() => {
+                if prompt.contains('\0') {
+                    return Err("initial_prompt に NUL 文字は使用できません".to_string());
+                }
+                if prompt.chars().count() > DecodingParameters::MAX_PROMPT_CHARS {
+                    return Err(format!(
+                        "initial_prompt は {} 文字以内で指定してください",
+                        DecodingParameters::MAX_PROMPT_CHARS
+                    ));
+                }
+                Some(prompt.to_string())
+            }
+            _ => None,
+        };
+
+        Ok(DecodingParameters {
+            strategy: if self.beam_size.is_some() {
+                "beam_search"
+            } else {
+                "greedy"
+            }
+            .to_string(),
+            temperature,
+            temperature_increment,
+            beam_size: self.beam_size,
+            best_of,
+            no_speech_threshold,
+            logprob_threshold,
+            initial_prompt,
+            suppress_blank: self.suppress_blank.unwrap_or(defaults.suppress_blank),
+        })
+    }
+}
+
+fn check_range(name: &str, value: f32, min: f32, max: f32) -> Result<(), String> {
+    if value.is_finite() && (min..=max).contains(&value) {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} は {}〜{} の範囲で指定してください: {}",
+            name, min, max, value
+        ))
+    }
+}
+
+/// 文字起こし結果のレスポンス形式
+/// - json: 従来の JSON（エンドポイントごとの形）
+/// - text: 全文テキストのみ
+/// - srt / vtt: 字幕ファイル
+/// - tsv: `start<TAB>end<TAB>text`（ミリ秒）
+/// - verbose_json: 言語/長さ/セグメント情報を含む詳細 JSON
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResponseFormat {
+    #[default]
+    Json,
+    Text,
+    Srt,
+    Vtt,
+    Tsv,
+    VerboseJson,
+}
+
+impl ResponseFormat {
+    /// 受け付ける形式名の一覧
+    pub const ALL: [ResponseFormat; 6] = [
+        ResponseFormat::Json,
+        ResponseFormat::Text,
+        ResponseFormat::Srt,
+        ResponseFormat::Vtt,
+        ResponseFormat::Tsv,
+        ResponseFormat::VerboseJson,
+    ];
+
+    /// 形式名を解釈（大文字小文字は区別しない）
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        Self::ALL
+            .into_iter()
+            .find(|format| format.as_str().eq_ignore_ascii_case(value))
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ResponseFormat::Json => "json",
+            ResponseFormat::Text => "text",
+            ResponseFormat::Srt => "srt",
+            ResponseFormat::Vtt => "vtt",
+            ResponseFormat::Tsv => "tsv",
+            ResponseFormat::VerboseJson => "verbose_json",
+        }
+    }
+
+    /// レスポンスの Content-Type
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ResponseFormat::Json | ResponseFormat::VerboseJson => "application/json",
+            ResponseFormat::Text => "text/plain; charset=utf-8",
+            ResponseFormat::Srt => "application/x-subrip; charset=utf-8",
+            ResponseFormat::Vtt => "text/vtt; charset=utf-8",
+            ResponseFormat::Tsv => "text/tab-separated-values; charset=utf-8",
+        }
+    }
+
+    /// ダウンロード時のファイル拡張子
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ResponseFormat::Json | ResponseFormat::VerboseJson => "json",
+            ResponseFormat::Text => "txt",
+            ResponseFormat::Srt => "srt",
+            ResponseFormat::Vtt => "vtt",
+            ResponseFormat::Tsv => "tsv",
+        }
+    }
+
+    /// セグメント（タイムスタンプ）が必要な形式かどうか
+    pub fn requires_segments(&self) -> bool {
+        matches!(
+            self,
+            ResponseFormat::Srt
+                | ResponseFormat::Vtt
+                | ResponseFormat::Tsv
+                | ResponseFormat::VerboseJson
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TranscribeResponse {
     /// 文字起こしテキスト（全体）
     pub text: String,
@@ -28,10 +300,177 @@ pub struct TranscribeResponse {
     pub segments: Option<Vec<TranscriptionSegment>>,
     /// サーバー側での処理時間（ミリ秒）
     pub processing_time_ms: u64,
+    /// 実際に使われたデコードパラメータ
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decoding: Option<DecodingParameters>,
+    /// 言語の自動検出結果（言語を指定しなかった場合のみ）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language_detection: Option<LanguageDetection>,
+    /// 文字起こしに使ったモデル名
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// 後処理で除いた/書き換えたセグメント（該当が無ければ省略）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filtered: Option<Vec<FilteredSegment>>,
+    /// 用語集で表記を直したセグメント（直す前のテキスト。該当が無ければ省略）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub corrections: Option<Vec<FilteredSegment>>,
+    /// 履歴に保存した場合のエントリ ID（`GET /history/{id}` で再取得できる）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history_id: Option<String>,
+    /// 分割推論で失敗したチャンク（該当が無ければ省略。その区間は結果に含まれない）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failed_chunks: Option<Vec<FailedChunk>>,
+}
+
+/// 言語検出の結果
+/// - 音声の先頭ウィンドウ（30 秒）から推定する
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LanguageDetection {
+    /// 最も確率の高い言語コード
+    pub language: String,
+    /// その言語の確率（0〜1）
+    pub probability: f32,
+    /// 確率の高い順の候補
+    pub probabilities: Vec<LanguageProbability>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LanguageProbability {
+    pub language: String,
+    pub probability: f32,
+}
+
+impl LanguageDetection {
+    /// 返却する候補数
+    pub const TOP_CANDIDATES: usize = 10;
+
+    /// (言語コード, 確率) の一覧から検出結果を作る
+    /// - 確率の高い順に並べ、上位 `TOP_CANDIDATES` 件を残す
+    pub fn from_probabilities(probabilities: Vec<(String, f32)>) -> Option<Self> {
+        let mut candidates: Vec<LanguageProbability> = probabilities
+            .into_iter()
+            .filter(|(_, probability)| probability.is_finite())
+            .map(|(language, probability)| LanguageProbability {
+                language,
+                probability,
+            })
+            .collect();
+        candidates.sort_by(|a, b| b.probability.total_cmp(&a.probability));
+        candidates.truncate(Self::TOP_CANDIDATES);
+
+        let best = candidates.first()?.clone();
+        Some(Self {
+            language: best.language,
+            probability: best.probability,
+            probabilities: candidates,
+        })
+    }
+}
+
+/// `POST /transcribe/stream` で送る SSE イベント
+/// - `progress`: 推論の進捗（`{"progress": 0〜100}`）
+/// - `segment`: whisper が確定したセグメント（テキストと開始/終了時刻）
+/// - `done`: 最終結果（`/transcribe-with-timestamps` と同じ内容と `processing_time_ms`）
+/// - `error`: エラー（通常のエラーレスポンスと同じ形）
+#[derive(Debug, Clone)]
+pub enum TranscribeStreamEvent {
+    Progress(i32),
+    Segment(TranscriptionSegment),
+    Done(Box<TranscribeResponse>),
+    Error(ErrorResponse),
+}
+
+impl TranscribeStreamEvent {
+    /// SSE の `event:` に入れるイベント名
+    pub fn name(&self) -> &'static str {
+        match self {
+            TranscribeStreamEvent::Progress(_) => "progress",
+            TranscribeStreamEvent::Segment(_) => "segment",
+            TranscribeStreamEvent::Done(_) => "done",
+            TranscribeStreamEvent::Error(_) => "error",
+        }
+    }
+
+    /// SSE の `data:` に入れる JSON
+    pub fn data(&self) -> serde_json::Result<String> {
+        match self {
+            TranscribeStreamEvent::Progress(progress) => {
+                serde_json::to_string(&serde_json::json!({ "progress": progress }))
+            }
+            TranscribeStreamEvent::Segment(segment) => serde_json::to_string(segment),
+            TranscribeStreamEvent::Done(response) => serde_json::to_string(response),
+            TranscribeStreamEvent::Error(error) => serde_json::to_string(error),
+        }
+    }
+
+    /// 最後のイベント（これ以降は送らない）かどうか
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            TranscribeStreamEvent::Done(_) | TranscribeStreamEvent::Error(_)
+        )
+    }
+}
+
+/// `POST /detect-language` のレスポンス
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectLanguageResponse {
+    #[serde(flatten)]
+    pub detection: LanguageDetection,
+    /// 入力音声の長さ（ミリ秒）
+    pub duration_ms: u64,
+    pub processing_time_ms: u64,
+}
+
+/// `format=verbose_json` のレスポンス
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerboseTranscription {
+    /// "transcribe" または "translate"
+    pub task: String,
+    pub language: Option<String>,
+    /// 入力音声の長さ（秒）
+    pub duration: f64,
+    pub text: String,
+    pub processing_time_ms: u64,
+    pub segments: Vec<VerboseSegment>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decoding: Option<DecodingParameters>,
+}
+
+/// `verbose_json` のセグメント（時刻は秒）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerboseSegment {
+    pub id: usize,
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avg_logprob: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub no_speech_prob: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub words: Option<Vec<VerboseWord>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<String>,
+}
+
+/// `verbose_json` の単語（時刻は秒）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerboseWord {
+    pub word: String,
+    pub start: f64,
+    pub end: f64,
+    pub probability: f32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelInfo {
+    /// リクエストの `model` に指定する名前（カタログのキー）
+    #[serde(default)]
+    pub id: String,
     /// 論理名（UI 表示向け）
     pub name: String,
     /// 実ファイルパス（存在チェックに使用）
@@ -44,12 +483,66 @@ pub struct ModelInfo {
     pub language_support: Vec<String>,
     /// サーバー上にダウンロード済みか
     pub is_available: bool,
+    /// メモリに読み込み済みか
+    #[serde(default)]
+    pub is_loaded: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ModelsResponse {
     pub models: Vec<ModelInfo>,
     pub current_model: String,
+    /// メモリに読み込み済みのモデル
+    #[serde(default)]
+    pub loaded_models: Vec<LoadedModelInfo>,
+    /// 読み込み済みモデルの合計サイズ（MB）
+    #[serde(default)]
+    pub memory_used_mb: u64,
+    /// モデルに使うメモリ予算（MB、0 は無制限）
+    #[serde(default)]
+    pub memory_budget_mb: u64,
+}
+
+/// メモリに読み込み済みのモデル
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadedModelInfo {
+    pub name: String,
+    /// モデルファイルのサイズ（MB、メモリ予算の計算に使う）
+    pub size_mb: u64,
+    /// リクエストで `model` を省略した場合に使われるモデルか
+    pub is_default: bool,
+    /// 読み込んだ時刻（RFC3339）
+    pub loaded_at: String,
+    /// 最後に使われた時刻（RFC3339）
+    pub last_used_at: String,
+}
+
+/// `POST /models/{name}/load` / `POST /models/{name}/unload` のレスポンス
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelLoadResponse {
+    pub model: String,
+    /// 操作後に読み込まれているか
+    pub loaded: bool,
+    /// メモリ予算に収めるため破棄したモデル
+    #[serde(default)]
+    pub evicted: Vec<String>,
+    pub loaded_models: Vec<LoadedModelInfo>,
+    pub memory_used_mb: u64,
+    pub memory_budget_mb: u64,
+}
+
+/// `POST /admin/models/{name}/download` のリクエスト（JSON、省略可）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DownloadModelRequest {
+    /// 期待する SHA-256（16 進）。省略時は配布元が返すハッシュがあれば照合する
+    #[serde(default)]
+    pub sha256: Option<String>,
+    /// 既にファイルがある場合も再ダウンロードする
+    #[serde(default)]
+    pub overwrite: bool,
+    /// 期待する SHA-256 が無く、配布元もハッシュを返さない場合でも検証せずに配置する
+    #[serde(default)]
+    pub allow_unverified: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -60,9 +553,27 @@ pub struct HealthResponse {
     pub model_name: Option<String>,
     pub uptime_seconds: u64,
     pub memory_usage_mb: Option<u64>,
+    /// 直近の一時ファイル掃除の結果（まだ実行していなければ省略）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_cleanup: Option<CleanupReport>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// 一時ファイル/アップロードの掃除 1 回分の結果
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CleanupReport {
+    /// 掃除を終えた時刻（RFC 3339）
+    pub finished_at: String,
+    /// 削除したファイル数
+    pub removed_files: usize,
+    /// 削除したファイルの合計サイズ（バイト）
+    pub removed_bytes: u64,
+    /// 削除に失敗したファイル/ディレクトリ数
+    pub failed: usize,
+    /// 保存期間を過ぎて削除した履歴の件数
+    pub expired_history: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ErrorResponse {
     pub error: String,
     pub code: String,
@@ -79,6 +590,24 @@ pub struct TranscriptionSegment {
     pub text: String,
     pub start_time_ms: u64,
     pub end_time_ms: u64,
+    /// 単語ごとのタイムスタンプ（`word_timestamps` 指定時のみ）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub words: Option<Vec<WordTiming>>,
+    /// セグメント内トークンの平均対数確率（`word_timestamps` 指定時、またはハルシネーション除去が有効な場合）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub avg_logprob: Option<f32>,
+    /// セグメントが無音である確率（`word_timestamps` 指定時、またはハルシネーション除去が有効な場合）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub no_speech_prob: Option<f32>,
+    /// 元のチャンネル番号（`channel_mode=separate` の場合のみ）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<usize>,
+    /// 話者ラベル（例: `SPEAKER_1`。話者分離の指定時のみ）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<String>,
+    /// 次のセグメントで話者が替わる（tinydiarize の検出結果。話者分離の内部でのみ使う）
+    #[serde(skip)]
+    pub speaker_turn: bool,
 }
 
 impl TranscriptionSegment {
@@ -87,7 +616,27 @@ impl TranscriptionSegment {
             text,
             start_time_ms,
             end_time_ms,
+            words: None,
+            avg_logprob: None,
+            no_speech_prob: None,
+            channel: None,
+            speaker: None,
+            speaker_turn: false,
+        }
+    }
+
+    /// チャンネル番号と話者を付けたテキスト（例: `[ch1] こんにちは`、`[SPEAKER_1] こんにちは`）
+    /// - どちらも無い場合はテキストのみ
+    pub fn labelled_text(&self) -> String {
+        let mut text = String::new();
+        if let Some(channel) = self.channel {
+            text.push_str(&format!("[ch{}] ", channel));
+        }
+        if let Some(speaker) = self.speaker.as_deref() {
+            text.push_str(&format!("[{}] ", speaker));
         }
+        text.push_str(self.text.trim());
+        text
     }
 
     pub fn duration_ms(&self) -> u64 {
@@ -143,6 +692,203 @@ impl TranscriptionSegment {
     }
 }
 
+/// チャンネルごとの文字起こし結果を 1 本の時系列にまとめる
+/// - 各セグメントにチャンネル番号（`channels` の添字）を付け、開始時刻順（同時刻はチャンネル順）に並べる
+pub fn merge_channel_segments(
+    channels: Vec<Vec<TranscriptionSegment>>,
+) -> Vec<TranscriptionSegment> {
+    let mut merged: Vec<TranscriptionSegment> = channels
+        .into_iter()
+        .enumerate()
+        .flat_map(|(channel, segments)| {
+            segments.into_iter().map(move |mut segment| {
+                segment.channel = Some(channel);
+                segment
+            })
+        })
+        .collect();
+    merged.sort_by_key(|segment| (segment.start_time_ms, segment.channel));
+    merged
+}
+
+/// 後処理でセグメントを除いた/書き換えた理由
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterReason {
+    /// 無音確率が高い（無音区間での誤認識）
+    NoSpeech,
+    /// 同じ内容の繰り返し（セグメント内の繰り返しは 1 回にまとめて残す）
+    Repetition,
+    /// 除外語句と一致
+    Blocklist,
+    /// 用語集の語句に合わせて表記を直した（テキストは直す前のもの。`corrections` にだけ現れる）
+    Glossary,
+}
+
+/// 後処理で除いた/書き換えたセグメント（元のテキストと時刻）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FilteredSegment {
+    pub text: String,
+    pub start_time_ms: u64,
+    pub end_time_ms: u64,
+    pub reason: FilterReason,
+    /// 元のチャンネル番号（`channel_mode=separate` の場合のみ）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<usize>,
+}
+
+impl FilteredSegment {
+    pub fn new(segment: &TranscriptionSegment, reason: FilterReason) -> Self {
+        Self {
+            text: segment.text.clone(),
+            start_time_ms: segment.start_time_ms,
+            end_time_ms: segment.end_time_ms,
+            reason,
+            channel: segment.channel,
+        }
+    }
+}
+
+/// 分割推論で文字起こしできなかった区間
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FailedChunk {
+    pub start_time_ms: u64,
+    pub end_time_ms: u64,
+    pub error: String,
+    /// 元のチャンネル番号（`channel_mode=separate` の場合のみ）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<usize>,
+}
+
+/// 単語単位のタイムスタンプ
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WordTiming {
+    pub word: String,
+    pub start_time_ms: u64,
+    pub end_time_ms: u64,
+    /// 単語を構成するトークンの平均確率（0〜1）
+    pub probability: f32,
+}
+
+/// whisper のトークン1つ分のタイミング（単語へまとめる前の中間表現）
+/// - トークン境界はマルチバイト文字の途中にもなり得るため、テキストはバイト列で持つ
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenTiming {
+    pub bytes: Vec<u8>,
+    pub start_time_ms: u64,
+    pub end_time_ms: u64,
+    pub probability: f32,
+}
+
+impl WordTiming {
+    /// トークン列を単語へまとめる
+    /// - UTF-8 として不完全なトークンは次のトークンと結合してから扱う
+    /// - 空白で始まるトークンを単語の先頭とする（BPE の区切り）
+    /// - 日本語/中国語/タイ語など空白で区切らない文字は1トークン（文字）ごとに単語とする
+    /// - 句読点のみのトークンは直前の単語へ含める
+    pub fn from_tokens(tokens: &[TokenTiming]) -> Vec<WordTiming> {
+        // 1. UTF-8 として完結する単位（piece）へまとめる
+        let mut pieces: Vec<(String, u64, u64, Vec<f32>)> = Vec::new();
+        let mut pending: Vec<u8> = Vec::new();
+        let mut pending_start = 0;
+        let mut pending_probabilities = Vec::new();
+
+        for token in tokens {
+            if pending.is_empty() {
+                pending_start = token.start_time_ms;
+            }
+            pending.extend_from_slice(&token.bytes);
+            pending_probabilities.push(token.probability);
+
+            if let Ok(text) = std::str::from_utf8(&pending) {
+                pieces.push((
+                    text.to_string(),
+                    pending_start,
+                    token.end_time_ms,
+                    std::mem::take(&mut pending_probabilities),
+                ));
+                pending.clear();
+            }
+        }
+        if !pending.is_empty() {
+            let end_time_ms = tokens
+                .last()
+                .map(|t| t.end_time_ms)
+                .unwrap_or(pending_start);
+            pieces.push((
+                String::from_utf8_lossy(&pending).into_owned(),
+                pending_start,
+                end_time_ms,
+                pending_probabilities,
+            ));
+        }
+
+        // 2. piece を単語へまとめる
+        let mut words: Vec<(String, u64, u64, Vec<f32>)> = Vec::new();
+        for (text, start_time_ms, end_time_ms, probabilities) in pieces {
+            let punctuation_only = text
+                .chars()
+                .all(|c| !c.is_alphanumeric() && !c.is_whitespace());
+            let starts_new_word = match words.last() {
+                None => true,
+                Some(_) if punctuation_only => false,
+                Some((previous, ..)) => {
+                    text.starts_with(char::is_whitespace)
+                        || text.chars().next().is_some_and(is_unspaced_script)
+                        || previous.chars().last().is_some_and(is_unspaced_script)
+                }
+            };
+
+            match words.last_mut() {
+                Some(word) if !starts_new_word => {
+                    word.0.push_str(&text);
+                    word.2 = end_time_ms;
+                    word.3.extend(probabilities);
+                }
+                _ => words.push((text, start_time_ms, end_time_ms, probabilities)),
+            }
+        }
+
+        words
+            .into_iter()
+            .filter_map(|(text, start_time_ms, end_time_ms, probabilities)| {
+                let word = text.trim();
+                if word.is_empty() {
+                    return None;
+                }
+                let probability = if probabilities.is_empty() {
+                    0.0
+                } else {
+                    probabilities.iter().sum::<f32>() / probabilities.len() as f32
+                };
+                Some(WordTiming {
+                    word: word.to_string(),
+                    start_time_ms,
+                    end_time_ms: end_time_ms.max(start_time_ms),
+                    probability,
+                })
+            })
+            .collect()
+    }
+}
+
+/// 単語を空白で区切らない文字体系（かな/漢字/タイ文字など）か
+fn is_unspaced_script(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x0E00..=0x0E7F // タイ文字
+            | 0x0E80..=0x0EFF // ラオ文字
+            | 0x1000..=0x109F // ミャンマー文字
+            | 0x1780..=0x17FF // クメール文字
+            | 0x3040..=0x30FF // ひらがな/カタカナ
+            | 0x3400..=0x4DBF // CJK 統合漢字拡張 A
+            | 0x4E00..=0x9FFF // CJK 統合漢字
+            | 0xF900..=0xFAFF // CJK 互換漢字
+            | 0xFF66..=0xFF9F // 半角カタカナ
+            | 0x20000..=0x2FFFF // CJK 統合漢字拡張 B 以降
+    )
+}
+
 // =============================================================================
 // Model Catalog
 // - UI のモデル選択やダウンロードリンク表示に使うメタ情報
@@ -280,13 +1026,67 @@ pub struct ServerStats {
     pub total_requests: u64,
     pub successful_transcriptions: u64,
     pub failed_transcriptions: u64,
+    /// キャンセルされたリクエスト/ジョブ数
+    #[serde(default)]
+    pub cancelled_transcriptions: u64,
     pub total_processing_time_ms: u64,
     pub total_audio_duration_ms: u64,
     pub average_processing_time_ms: f64,
     pub average_processing_time_one_minute_ms: f64,
     pub average_processing_time_one_minute_display: String,
     pub active_requests: usize,
+    /// 空きエンジンを待っているリクエスト数（`/stats` 取得時に更新）
+    #[serde(default)]
+    pub queued_requests: usize,
     pub uptime_seconds: u64,
+    /// API キーごとの使用量（キー名 → 使用量、認証が有効な場合のみ）
+    #[serde(default)]
+    pub api_keys: BTreeMap<String, ApiKeyUsage>,
+}
+
+/// API キーごとの使用量
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ApiKeyUsage {
+    /// 受け付けたリクエスト数
+    pub requests: u64,
+    /// レート制限/クォータ超過で拒否したリクエスト数
+    pub rejected_requests: u64,
+    pub successful_transcriptions: u64,
+    pub total_audio_duration_ms: u64,
+    /// 月間使用量の対象月（YYYY-MM、UTC）
+    pub month: String,
+    /// 対象月に文字起こしした音声の長さ
+    pub month_audio_duration_ms: u64,
+}
+
+impl ApiKeyUsage {
+    /// 指定月の音声時間（対象月が違えば 0）
+    pub fn month_audio_ms(&self, month: &str) -> u64 {
+        if self.month == month {
+            self.month_audio_duration_ms
+        } else {
+            0
+        }
+    }
+
+    /// 文字起こしした音声の長さを加算する（月が変わっていれば月間使用量を数え直す）
+    pub fn add_audio(&mut self, month: &str, audio_duration_ms: u64) {
+        if self.month != month {
+            self.month = month.to_string();
+            self.month_audio_duration_ms = 0;
+        }
+        self.total_audio_duration_ms = self
+            .total_audio_duration_ms
+            .saturating_add(audio_duration_ms);
+        self.month_audio_duration_ms = self
+            .month_audio_duration_ms
+            .saturating_add(audio_duration_ms);
+    }
+}
+
+/// 月間使用量を数える月（YYYY-MM、UTC）
+pub fn usage_month() -> String {
+    chrono::Utc::now().format("%Y-%m").to_string()
 }
 
 impl Default for ServerStats {
@@ -295,13 +1095,16 @@ impl Default for ServerStats {
             total_requests: 0,
             successful_transcriptions: 0,
             failed_transcriptions: 0,
+            cancelled_transcriptions: 0,
             total_processing_time_ms: 0,
             total_audio_duration_ms: 0,
             average_processing_time_ms: 0.0,
             average_processing_time_one_minute_ms: 0.0,
             average_processing_time_one_minute_display: "0.00 s/min".to_string(),
             active_requests: 0,
+            queued_requests: 0,
             uptime_seconds: 0,
+            api_keys: BTreeMap::new(),
         }
     }
 }
@@ -344,6 +1147,45 @@ impl ServerStats {
         self.active_requests = self.active_requests.saturating_sub(1);
     }
 
+    /// API キーのリクエストを記録（受け付けた/拒否した）
+    pub fn record_key_request(&mut self, key: &str, accepted: bool) {
+        let usage = self.api_keys.entry(key.to_string()).or_default();
+        if accepted {
+            usage.requests += 1;
+        } else {
+            usage.rejected_requests += 1;
+        }
+    }
+
+    /// API キーの文字起こし成功と音声の長さを記録（キー無しのリクエストは何もしない）
+    pub fn record_key_success(&mut self, key: Option<&str>, audio_duration_ms: Option<u64>) {
+        let Some(key) = key else {
+            return;
+        };
+        let usage = self.api_keys.entry(key.to_string()).or_default();
+        usage.successful_transcriptions += 1;
+        usage.add_audio(&usage_month(), audio_duration_ms.unwrap_or(0));
+    }
+
+    /// 言語検出など文字起こし以外で推論した音声の長さを API キーの使用量に加える
+    pub fn record_key_audio(&mut self, key: &str, audio_duration_ms: u64) {
+        let usage = self.api_keys.entry(key.to_string()).or_default();
+        usage.add_audio(&usage_month(), audio_duration_ms);
+    }
+
+    /// API キーの今月の音声時間（ミリ秒）
+    pub fn key_month_audio_ms(&self, key: &str) -> u64 {
+        self.api_keys
+            .get(key)
+            .map(|usage| usage.month_audio_ms(&usage_month()))
+            .unwrap_or(0)
+    }
+
+    pub fn record_cancellation(&mut self) {
+        self.cancelled_transcriptions += 1;
+        self.active_requests = self.active_requests.saturating_sub(1);
+    }
+
     pub fn success_rate(&self) -> f64 {
         if self.total_requests == 0 {
             0.0
@@ -373,7 +1215,17 @@ pub enum ApiErrorCode {
     UnsupportedFormat,
     ProcessingFailed,
     ModelNotLoaded,
+    ModelNotFound,
     ServerOverloaded,
+    JobNotFound,
+    JobNotReady,
+    HistoryNotFound,
+    GlossaryNotFound,
+    Unauthorized,
+    Forbidden,
+    RateLimited,
+    QuotaExceeded,
+    Timeout,
     InternalError,
 }
 
@@ -385,7 +1237,17 @@ impl ApiErrorCode {
             ApiErrorCode::UnsupportedFormat => "UNSUPPORTED_FORMAT",
             ApiErrorCode::ProcessingFailed => "PROCESSING_FAILED",
             ApiErrorCode::ModelNotLoaded => "MODEL_NOT_LOADED",
+            ApiErrorCode::ModelNotFound => "MODEL_NOT_FOUND",
             ApiErrorCode::ServerOverloaded => "SERVER_OVERLOADED",
+            ApiErrorCode::JobNotFound => "JOB_NOT_FOUND",
+            ApiErrorCode::JobNotReady => "JOB_NOT_READY",
+            ApiErrorCode::HistoryNotFound => "HISTORY_NOT_FOUND",
+            ApiErrorCode::GlossaryNotFound => "GLOSSARY_NOT_FOUND",
+            ApiErrorCode::Unauthorized => "UNAUTHORIZED",
+            ApiErrorCode::Forbidden => "FORBIDDEN",
+            ApiErrorCode::RateLimited => "RATE_LIMITED",
+            ApiErrorCode::QuotaExceeded => "QUOTA_EXCEEDED",
+            ApiErrorCode::Timeout => "TIMEOUT",
             ApiErrorCode::InternalError => "INTERNAL_ERROR",
         }
     }
@@ -0,0 +1,273 @@
+use crate::export;
+use crate::models::{TranscribeResponse, TranscriptionSegment};
+use serde::{Deserialize, Serialize};
+
+// =============================================================================
+// OpenAI 互換 API（/v1/audio/transcriptions, /v1/audio/translations）
+// - OpenAI Audio API と同じマルチパートのフィールド/レスポンス形を提供する
+// - 既存の SDK やツールがエンドポイントの URL を変えるだけで利用できるようにする
+// =============================================================================
+
+/// `response_format` の値
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OpenAiResponseFormat {
+    #[default]
+    Json,
+    Text,
+    Srt,
+    VerboseJson,
+    Vtt,
+}
+
+impl OpenAiResponseFormat {
+    pub const ALL: [OpenAiResponseFormat; 5] = [
+        OpenAiResponseFormat::Json,
+        OpenAiResponseFormat::Text,
+        OpenAiResponseFormat::Srt,
+        OpenAiResponseFormat::VerboseJson,
+        OpenAiResponseFormat::Vtt,
+    ];
+
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        Self::ALL
+            .into_iter()
+            .find(|format| format.as_str().eq_ignore_ascii_case(value))
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OpenAiResponseFormat::Json => "json",
+            OpenAiResponseFormat::Text => "text",
+            OpenAiResponseFormat::Srt => "srt",
+            OpenAiResponseFormat::VerboseJson => "verbose_json",
+            OpenAiResponseFormat::Vtt => "vtt",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            OpenAiResponseFormat::Json | OpenAiResponseFormat::VerboseJson => "application/json",
+            OpenAiResponseFormat::Text => "text/plain; charset=utf-8",
+            OpenAiResponseFormat::Srt => "application/x-subrip; charset=utf-8",
+            OpenAiResponseFormat::Vtt => "text/vtt; charset=utf-8",
+        }
+    }
+}
+
+/// `timestamp_granularities[]` の値
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimestampGranularity {
+    Segment,
+    Word,
+}
+
+impl TimestampGranularity {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "segment" => Some(TimestampGranularity::Segment),
+            "word" => Some(TimestampGranularity::Word),
+            _ => None,
+        }
+    }
+}
+
+/// OpenAI 互換エンドポイントのフォーム内容
+#[derive(Debug, Clone, Default)]
+pub struct OpenAiAudioRequest {
+    /// モデル名（`whisper-1` は既定モデル、それ以外はサーバー上のモデル名）
+    pub model: Option<String>,
+    /// 入力音声の言語（ISO-639-1）
+    pub language: Option<String>,
+    /// 直前の文脈/用語のヒント
+    pub prompt: Option<String>,
+    pub response_format: OpenAiResponseFormat,
+    /// サンプリング温度（0〜1）
+    pub temperature: Option<f32>,
+    pub timestamp_granularities: Vec<TimestampGranularity>,
+}
+
+impl OpenAiAudioRequest {
+    /// OpenAI のモデル名 `whisper-1` は既定モデルの別名として扱う
+    pub const DEFAULT_MODEL_ALIAS: &'static str = "whisper-1";
+
+    /// サーバー側で使うモデル名（既定モデルなら None）
+    pub fn server_model(&self) -> Option<&str> {
+        self.model
+            .as_deref()
+            .map(str::trim)
+            .filter(|model| !model.is_empty() && *model != Self::DEFAULT_MODEL_ALIAS)
+    }
+}
+
+/// `response_format=json` のレスポンス
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAiTranscription {
+    pub text: String,
+}
+
+/// `response_format=verbose_json` のレスポンス
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAiVerboseTranscription {
+    /// "transcribe" または "translate"
+    pub task: String,
+    /// 言語名（例: "japanese"）
+    pub language: String,
+    /// 入力音声の長さ（秒）
+    pub duration: f64,
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub segments: Option<Vec<OpenAiSegment>>,
+    /// `timestamp_granularities[]` に word を含む場合のみ
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub words: Option<Vec<OpenAiWord>>,
+}
+
+/// `verbose_json` のセグメント
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAiSegment {
+    pub id: usize,
+    /// セグメント開始位置（10ms 単位のフレーム）
+    pub seek: u64,
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+    pub tokens: Vec<i32>,
+    pub temperature: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avg_logprob: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub no_speech_prob: Option<f32>,
+}
+
+/// `verbose_json` の単語（時刻は秒）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAiWord {
+    pub word: String,
+    pub start: f64,
+    pub end: f64,
+}
+
+/// OpenAI 形式のエラーレスポンス
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAiErrorResponse {
+    pub error: OpenAiErrorBody,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAiErrorBody {
+    pub message: String,
+    #[serde(rename = "type")]
+    pub error_type: String,
+    pub param: Option<String>,
+    pub code: Option<String>,
+}
+
+/// 文字起こし結果を `response_format` に従ってレスポンス本文へ変換
+pub fn render(
+    response: &TranscribeResponse,
+    request: &OpenAiAudioRequest,
+    translated: bool,
+) -> anyhow::Result<String> {
+    let segments = response.segments.as_deref().unwrap_or_default();
+
+    let body = match request.response_format {
+        OpenAiResponseFormat::Json => serde_json::to_string(&OpenAiTranscription {
+            text: response.text.trim().to_string(),
+        })?,
+        OpenAiResponseFormat::Text => export::to_text(response),
+        OpenAiResponseFormat::Srt => export::to_srt(segments),
+        OpenAiResponseFormat::Vtt => export::to_vtt(segments),
+        OpenAiResponseFormat::VerboseJson => {
+            serde_json::to_string(&to_verbose(response, request, translated))?
+        }
+    };
+
+    Ok(body)
+}
+
+/// `verbose_json` 形式へ変換
+/// - OpenAI と同じく言語は英語名の小文字で返す
+pub fn to_verbose(
+    response: &TranscribeResponse,
+    request: &OpenAiAudioRequest,
+    translated: bool,
+) -> OpenAiVerboseTranscription {
+    let language = response
+        .language
+        .as_deref()
+        .or(request.language.as_deref())
+        .map(|code| crate::whisper::get_language_name(code).to_lowercase())
+        .unwrap_or_default();
+
+    // セグメントは timestamp_granularities 未指定時、または segment を含む場合に返す
+    let include_segments = request.timestamp_granularities.is_empty()
+        || request
+            .timestamp_granularities
+            .contains(&TimestampGranularity::Segment);
+
+    let temperature = response
+        .decoding
+        .as_ref()
+        .map(|decoding| decoding.temperature)
+        .unwrap_or(0.0);
+
+    let segments = include_segments.then(|| {
+        response
+            .segments
+            .as_deref()
+            .unwrap_or_default()
+            .iter()
+            .enumerate()
+            .map(|(id, segment)| to_openai_segment(id, segment, temperature))
+            .collect()
+    });
+
+    let include_words = request
+        .timestamp_granularities
+        .contains(&TimestampGranularity::Word);
+    let words = include_words.then(|| {
+        response
+            .segments
+            .as_deref()
+            .unwrap_or_default()
+            .iter()
+            .flat_map(|segment| segment.words.as_deref().unwrap_or_default())
+            .map(|word| OpenAiWord {
+                word: word.word.clone(),
+                start: word.start_time_ms as f64 / 1000.0,
+                end: word.end_time_ms as f64 / 1000.0,
+            })
+            .collect()
+    });
+
+    OpenAiVerboseTranscription {
+        task: if translated {
+            "translate"
+        } else {
+            "transcribe"
+        }
+        .to_string(),
+        language,
+        duration: response.duration_ms.unwrap_or(0) as f64 / 1000.0,
+        text: response.text.trim().to_string(),
+        segments,
+        words,
+    }
+}
+
+fn to_openai_segment(id: usize, segment: &TranscriptionSegment, temperature: f32) -> OpenAiSegment {
+    OpenAiSegment {
+        id,
+        seek: segment.start_time_ms / 10,
+        start: segment.start_time_ms as f64 / 1000.0,
+        end: segment.end_time_ms as f64 / 1000.0,
+        text: segment.text.trim().to_string(),
+        tokens: Vec::new(),
+        temperature,
+        avg_logprob: segment.avg_logprob,
+        no_speech_prob: segment.no_speech_prob,
+    }
+}
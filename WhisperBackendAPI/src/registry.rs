@@ -0,0 +1,293 @@
+use crate::config::Config;
+use crate::models::{LoadedModelInfo, ModelCatalog};
+use chrono::Utc;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+// =============================================================================
+// 読み込み済みモデルのレジストリ
+// - モデル名ごとにエンジンプールを保持し、再起動せずにモデルを追加/破棄できるようにする
+// - 合計サイズがメモリ予算を超える場合は、最も長く使われていないモデルから破棄する（LRU）
+// - 既定モデル（config の whisper.model_path）は自動では破棄せず、明示的な unload のみ
+// =============================================================================
+
+/// 読み込み済みモデルのレジストリ
+/// - `T` は通常 `WhisperEnginePool`（テストでは任意の型を使える）
+pub struct ModelRegistry<T> {
+    /// メモリ予算（MB、0 は無制限）
+    memory_budget_mb: u64,
+    inner: Mutex<RegistryInner<T>>,
+}
+
+struct RegistryInner<T> {
+    /// 使用順（末尾が最近使ったもの）
+    entries: Vec<LoadedModel<T>>,
+    default_model: Option<String>,
+}
+
+struct LoadedModel<T> {
+    name: String,
+    size_mb: u64,
+    value: Arc<T>,
+    info: LoadedModelInfo,
+}
+
+/// レジストリ操作のエラー
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegistryError {
+    /// 既定モデル以外を破棄してもメモリ予算に収まらない
+    ExceedsBudget {
+        name: String,
+        size_mb: u64,
+        available_mb: u64,
+    },
+}
+
+impl std::fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RegistryError::ExceedsBudget {
+                name,
+                size_mb,
+                available_mb,
+            } => write!(
+                f,
+                "モデル {} ({}MB) がメモリ予算に収まりません（空き: {}MB）",
+                name, size_mb, available_mb
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RegistryError {}
+
+impl<T> ModelRegistry<T> {
+    pub fn new(memory_budget_mb: u64) -> Self {
+        Self {
+            memory_budget_mb,
+            inner: Mutex::new(RegistryInner {
+                entries: Vec::new(),
+                default_model: None,
+            }),
+        }
+    }
+
+    /// モデルを取得し、最近使ったものとして記録する
+    pub fn get(&self, name: &str) -> Option<Arc<T>> {
+        let mut inner = self.inner.lock().unwrap();
+        let index = inner.entries.iter().position(|entry| entry.name == name)?;
+        let mut entry = inner.entries.remove(index);
+        entry.info.last_used_at = Utc::now().to_rfc3339();
+        let value = Arc::clone(&entry.value);
+        inner.entries.push(entry);
+        Some(value)
+    }
+
+    /// 既定モデルを取得（読み込まれていなければ None）
+    pub fn default_model(&self) -> Option<Arc<T>> {
+        let name = self.default_name()?;
+        self.get(&name)
+    }
+
+    /// 既定モデル名
+    pub fn default_name(&self) -> Option<String> {
+        self.inner.lock().unwrap().default_model.clone()
+    }
+
+    pub fn is_loaded(&self, name: &str) -> bool {
+        self.inner
+            .lock()
+            .unwrap()
+            .entries
+            .iter()
+            .any(|entry| entry.name == name)
+    }
+
+    /// モデルを登録する
+    /// - 同名のモデルは置き換える
+    /// - メモリ予算を超える場合は既定モデル以外を LRU 順に破棄し、破棄したモデル名を返す
+    /// - `make_default` が true なら既定モデルにする（既定モデルが未設定の場合も既定になる）
+    pub fn insert(
+        &self,
+        name: &str,
+        value: T,
+        size_mb: u64,
+        make_default: bool,
+    ) -> Result<Vec<String>, RegistryError> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.entries.retain(|entry| entry.name != name);
+
+        let is_default = make_default || inner.default_model.is_none();
+        let default_model = if is_default {
+            Some(name.to_string())
+        } else {
+            inner.default_model.clone()
+        };
+
+        let mut evicted = Vec::new();
+        if self.memory_budget_mb > 0 {
+            // 破棄できない（既定）モデルだけで予算を超えるなら、何も破棄せずにエラー
+            let pinned_mb: u64 = inner
+                .entries
+                .iter()
+                .filter(|entry| Some(&entry.name) == default_model.as_ref())
+                .map(|entry| entry.size_mb)
+                .sum();
+            if pinned_mb + size_mb > self.memory_budget_mb {
+                return Err(RegistryError::ExceedsBudget {
+                    name: name.to_string(),
+                    size_mb,
+                    available_mb: self.memory_budget_mb.saturating_sub(pinned_mb),
+                });
+            }
+
+            while inner.total_size_mb() + size_mb > self.memory_budget_mb {
+                let index = inner
+                    .entries
+                    .iter()
+                    .position(|entry| Some(&entry.name) != default_model.as_ref())
+                    .expect("既定モデル以外のモデルが存在するはずです");
+                evicted.push(inner.entries.remove(index).name);
+            }
+        }
+
+        let now = Utc::now().to_rfc3339();
+        inner.default_model = default_model;
+        inner.entries.push(LoadedModel {
+            name: name.to_string(),
+            size_mb,
+            value: Arc::new(value),
+            info: LoadedModelInfo {
+                name: name.to_string(),
+                size_mb,
+                is_default: false,
+                loaded_at: now.clone(),
+                last_used_at: now,
+            },
+        });
+
+        Ok(evicted)
+    }
+
+    /// モデルを破棄する（使用中のリクエストは完了まで参照を保持する）
+    /// - 既定モデルを破棄した場合、既定モデルは未設定になる
+    pub fn remove(&self, name: &str) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        let before = inner.entries.len();
+        inner.entries.retain(|entry| entry.name != name);
+        let removed = inner.entries.len() != before;
+        if removed && inner.default_model.as_deref() == Some(name) {
+            inner.default_model = None;
+        }
+        removed
+    }
+
+    /// 読み込み済みモデルの一覧（名前順）
+    pub fn loaded(&self) -> Vec<LoadedModelInfo> {
+        let inner = self.inner.lock().unwrap();
+        let mut models: Vec<LoadedModelInfo> = inner
+            .entries
+            .iter()
+            .map(|entry| LoadedModelInfo {
+                is_default: inner.default_model.as_deref() == Some(entry.name.as_str()),
+                ..entry.info.clone()
+            })
+            .collect();
+        models.sort_by(|a, b| a.name.cmp(&b.name));
+        models
+    }
+
+    /// 読み込み済みモデルの値（使用順）
+    pub fn values(&self) -> Vec<Arc<T>> {
+        self.inner
+            .lock()
+            .unwrap()
+            .entries
+            .iter()
+            .map(|entry| Arc::clone(&entry.value))
+            .collect()
+    }
+
+    /// 読み込み済みモデルの名前と値（使用順、使用時刻は更新しない）
+    pub fn entries(&self) -> Vec<(String, Arc<T>)> {
+        self.inner
+            .lock()
+            .unwrap()
+            .entries
+            .iter()
+            .map(|entry| (entry.name.clone(), Arc::clone(&entry.value)))
+            .collect()
+    }
+
+    /// 読み込み済みモデルの合計サイズ（MB）
+    pub fn total_size_mb(&self) -> u64 {
+        self.inner.lock().unwrap().total_size_mb()
+    }
+
+    pub fn memory_budget_mb(&self) -> u64 {
+        self.memory_budget_mb
+    }
+}
+
+impl<T> RegistryInner<T> {
+    fn total_size_mb(&self) -> u64 {
+        self.entries.iter().map(|entry| entry.size_mb).sum()
+    }
+}
+
+/// リクエストで指定されたモデル名を、レジストリ上の名前とモデルファイルのパスへ解決する
+/// - 既定モデル名（`whisper.default_model`）は `whisper.model_path`
+/// - カタログのキー（例: `base`）は `models_dir` 内のカタログのファイル名
+/// - それ以外は `models_dir/ggml-{name}.bin` が存在すれば利用する
+/// - 既定モデルと同じファイルを指す場合は既定モデル名へまとめる（二重読み込みを防ぐ）
+/// - パス区切りなどを含む不正な名前、ファイルが見つからない場合は None
+pub fn resolve_model(
+    config: &Config,
+    catalog: &ModelCatalog,
+    name: &str,
+) -> Option<(String, PathBuf)> {
+    let name = name.trim();
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        return None;
+    }
+
+    let default_path = PathBuf::from(&config.whisper.model_path);
+    if name == config.whisper.default_model {
+        return Some((name.to_string(), default_path));
+    }
+
+    let models_dir = Path::new(&config.paths.models_dir);
+    let path = match catalog.models.get(name) {
+        Some(definition) => models_dir.join(&definition.file_name),
+        None => models_dir.join(format!("ggml-{}.bin", name)),
+    };
+    if !path.is_file() {
+        return None;
+    }
+
+    if same_file(&path, &default_path) {
+        return Some((config.whisper.default_model.clone(), default_path));
+    }
+
+    Some((name.to_string(), path))
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// モデルファイルのサイズ（MB、切り上げ）
+/// - 読み込み後のメモリ使用量の目安としてメモリ予算の計算に使う
+pub fn model_size_mb(path: &Path) -> u64 {
+    std::fs::metadata(path)
+        .map(|metadata| metadata.len().div_ceil(1024 * 1024))
+        .unwrap_or(0)
+}
@@ -0,0 +1,201 @@
+use crate::config::VadConfig;
+use crate::models::TranscriptionSegment;
+
+// =============================================================================
+// 音声区間検出（VAD）
+// - フレームごとの RMS 音量（dBFS）で発話区間を求める
+// - 発話区間だけを詰めた音声で推論し、タイムスタンプを元の時間軸へ戻す
+// =============================================================================
+
+/// 音量を測るフレームの長さ（ミリ秒）
+const FRAME_MS: usize = 30;
+
+/// 発話区間（元の音声のサンプル位置、`end` は含まない）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpeechRegion {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl SpeechRegion {
+    fn len(&self) -> usize {
+        self.end - self.start
+    }
+}
+
+/// 発話区間を検出
+/// - 音量が `threshold_db` を超えるフレームを発話とする
+/// - `min_silence_ms` 未満の無音でつながる区間は 1 つにまとめ、`min_speech_ms` 未満の区間は捨てる
+/// - 残った区間の前後に `padding_ms` の余白を付ける（重なった区間は結合）
+pub fn detect_speech(samples: &[f32], sample_rate: u32, config: &VadConfig) -> Vec<SpeechRegion> {
+    let ms_to_samples = |ms: u32| ms as usize * sample_rate as usize / 1000;
+    let frame_len = (FRAME_MS * sample_rate as usize / 1000).max(1);
+
+    // 発話フレームが連続する区間
+    let mut regions: Vec<SpeechRegion> = Vec::new();
+    for (index, frame) in samples.chunks(frame_len).enumerate() {
+        if frame_db(frame) < config.threshold_db {
+            continue;
+        }
+        let start = index * frame_len;
+        let end = start + frame.len();
+        match regions.last_mut() {
+            Some(last) if last.end == start => last.end = end,
+            _ => regions.push(SpeechRegion { start, end }),
+        }
+    }
+
+    // 短い無音を挟む区間をつなげる
+    let min_silence = ms_to_samples(config.min_silence_ms);
+    let mut merged: Vec<SpeechRegion> = Vec::with_capacity(regions.len());
+    for region in regions {
+        match merged.last_mut() {
+            Some(last) if region.start - last.end < min_silence => last.end = region.end,
+            _ => merged.push(region),
+        }
+    }
+
+    // 短すぎる区間を捨て、余白を付ける
+    let min_speech = ms_to_samples(config.min_speech_ms);
+    let padding = ms_to_samples(config.padding_ms);
+    let mut padded: Vec<SpeechRegion> = Vec::with_capacity(merged.len());
+    for region in merged
+        .into_iter()
+        .filter(|region| region.len() >= min_speech)
+    {
+        let region = SpeechRegion {
+            start: region.start.saturating_sub(padding),
+            end: (region.end + padding).min(samples.len()),
+        };
+        match padded.last_mut() {
+            Some(last) if region.start <= last.end => last.end = region.end,
+            _ => padded.push(region),
+        }
+    }
+    padded
+}
+
+/// `start..end` の範囲で最も音量の小さいフレームの中央（サンプル位置）
+/// - 同じ音量のフレームが複数あれば後ろのものを選ぶ（長い無音では `end` 寄り）
+/// - 範囲が空なら `start`
+pub fn quietest_point(samples: &[f32], sample_rate: u32, start: usize, end: usize) -> usize {
+    let frame_len = (FRAME_MS * sample_rate as usize / 1000).max(1);
+    let end = end.min(samples.len());
+    let mut quietest = (f32::INFINITY, start);
+
+    let mut position = start;
+    while position < end {
+        let frame_end = (position + frame_len).min(end);
+        let db = frame_db(&samples[position..frame_end]);
+        if db <= quietest.0 {
+            quietest = (db, position + (frame_end - position) / 2);
+        }
+        position = frame_end;
+    }
+    quietest.1
+}
+
+/// フレームの RMS 音量（dBFS）
+fn frame_db(frame: &[f32]) -> f32 {
+    if frame.is_empty() {
+        return f32::NEG_INFINITY;
+    }
+    let mean_square = frame.iter().map(|sample| sample * sample).sum::<f32>() / frame.len() as f32;
+    10.0 * mean_square.max(f32::MIN_POSITIVE).log10()
+}
+
+/// 発話区間だけを詰めた音声と元の時間軸との対応
+#[derive(Debug, Clone)]
+pub struct SpeechTimeline {
+    sample_rate: u32,
+    /// (詰めた音声での開始位置, 元の区間)
+    regions: Vec<(usize, SpeechRegion)>,
+}
+
+impl SpeechTimeline {
+    pub fn new(regions: Vec<SpeechRegion>, sample_rate: u32) -> Self {
+        let mut offset = 0;
+        let regions = regions
+            .into_iter()
+            .map(|region| {
+                let entry = (offset, region);
+                offset += region.len();
+                entry
+            })
+            .collect();
+        Self {
+            sample_rate,
+            regions,
+        }
+    }
+
+    /// 発話区間があるかどうか
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+
+    /// 発話区間の合計の長さ（ミリ秒）
+    pub fn speech_ms(&self) -> u64 {
+        let samples: usize = self.regions.iter().map(|(_, region)| region.len()).sum();
+        self.samples_to_ms(samples)
+    }
+
+    /// 発話区間だけをつなげた音声
+    pub fn compact(&self, samples: &[f32]) -> Vec<f32> {
+        let mut compacted = Vec::with_capacity(self.regions.iter().map(|(_, r)| r.len()).sum());
+        for (_, region) in &self.regions {
+            compacted.extend_from_slice(&samples[region.start..region.end.min(samples.len())]);
+        }
+        compacted
+    }
+
+    /// 詰めた音声での開始時刻を元の時刻に戻す
+    /// - 区間の境目ちょうどの時刻は後ろの区間の先頭として扱う
+    pub fn start_ms(&self, compact_ms: u64) -> u64 {
+        let position = self.ms_to_samples(compact_ms);
+        let index = self
+            .regions
+            .partition_point(|(offset, _)| *offset <= position)
+            .saturating_sub(1);
+        self.to_original_ms(index, position)
+    }
+
+    /// 詰めた音声での終了時刻を元の時刻に戻す
+    /// - 区間の境目ちょうどの時刻は前の区間の末尾として扱う
+    pub fn end_ms(&self, compact_ms: u64) -> u64 {
+        let position = self.ms_to_samples(compact_ms);
+        let index = self
+            .regions
+            .partition_point(|(offset, region)| offset + region.len() < position)
+            .min(self.regions.len().saturating_sub(1));
+        self.to_original_ms(index, position)
+    }
+
+    /// セグメント（と単語）の時刻を元の時間軸に戻す
+    pub fn remap_segment(&self, segment: &mut TranscriptionSegment) {
+        segment.start_time_ms = self.start_ms(segment.start_time_ms);
+        segment.end_time_ms = self.end_ms(segment.end_time_ms).max(segment.start_time_ms);
+        for word in segment.words.iter_mut().flatten() {
+            word.start_time_ms = self.start_ms(word.start_time_ms);
+            word.end_time_ms = self.end_ms(word.end_time_ms).max(word.start_time_ms);
+        }
+    }
+
+    fn to_original_ms(&self, index: usize, position: usize) -> u64 {
+        match self.regions.get(index) {
+            Some((offset, region)) => {
+                let within = position.saturating_sub(*offset).min(region.len());
+                self.samples_to_ms(region.start + within)
+            }
+            None => self.samples_to_ms(position),
+        }
+    }
+
+    fn ms_to_samples(&self, ms: u64) -> usize {
+        (ms * self.sample_rate as u64 / 1000) as usize
+    }
+
+    fn samples_to_ms(&self, samples: usize) -> u64 {
+        samples as u64 * 1000 / self.sample_rate as u64
+    }
+}
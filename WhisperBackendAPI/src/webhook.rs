@@ -0,0 +1,363 @@
+use crate::checksum::{hmac_sha256, to_hex};
+use crate::config::WebhookConfig;
+use crate::jobs::{JobInfo, JobOutcome, JobStore};
+use crate::models::TranscribeResponse;
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+// =============================================================================
+// Webhook 通知
+// - 非同期ジョブが終了したら、投入時の `callback_url` へ結果（失敗時はエラー）を POST する
+// - `callback_secret` があれば「送信時刻.本文」の HMAC-SHA256 を `X-Webhook-Signature` に付ける
+//   （送信時刻は `X-Webhook-Timestamp`、再送のたびに付け直す）
+// - 2xx 以外の応答や接続失敗は、待ち時間を倍にしながら `webhook.max_attempts` 回まで送り直す
+// - 配信状況はジョブ情報の `callback` に記録する（`GET /jobs/{id}` で確認できる）
+// - 内部向けのアドレスへは送らない（投入時と送信時に名前解決して確認する）
+// =============================================================================
+
+/// 署名（`sha256=<16進>`、`callback_secret` を指定した場合のみ）
+pub const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+/// 送信時刻（Unix 秒）。署名の対象に含まれる
+pub const TIMESTAMP_HEADER: &str = "X-Webhook-Timestamp";
+/// イベント名（`job.completed` / `job.failed` / `job.cancelled`）
+pub const EVENT_HEADER: &str = "X-Webhook-Event";
+
+/// 通知先
+/// - 署名の鍵を含むため、ジョブ情報には URL と配信状況だけを載せる
+#[derive(Clone)]
+pub struct WebhookTarget {
+    pub url: String,
+    secret: Option<String>,
+}
+
+impl WebhookTarget {
+    /// 通知先を作成（http/https の URL のみ。空の鍵は指定なしとみなす）
+    /// - ホストを名前解決し、内部向けのアドレスは `webhook.allowed_hosts` で許可したものだけ受け付ける
+    pub async fn new(
+        url: &str,
+        secret: Option<&str>,
+        config: &WebhookConfig,
+    ) -> Result<Self, String> {
+        let parsed =
+            reqwest::Url::parse(url).map_err(|e| format!("callback_url が不正です: {}", e))?;
+        if !matches!(parsed.scheme(), "http" | "https") || parsed.host_str().is_none() {
+            return Err(format!(
+                "callback_url は http/https の URL を指定してください: {}",
+                url
+            ));
+        }
+        resolve_destination(&parsed, config).await?;
+        Ok(Self {
+            url: parsed.to_string(),
+            secret: secret.filter(|s| !s.is_empty()).map(str::to_string),
+        })
+    }
+}
+
+/// 通知先のホストを名前解決し、送信してよいアドレスを返す（SSRF 対策）
+/// - ループバック/リンクローカル（169.254.169.254 等）/プライベート/未指定/マルチキャスト等の
+///   アドレスが 1 つでも含まれれば拒否する
+/// - `allowed_hosts` にホスト名か、アドレス（`10.0.0.0/8` のような CIDR も可）があれば許可する
+pub async fn resolve_destination(
+    url: &reqwest::Url,
+    config: &WebhookConfig,
+) -> Result<Vec<SocketAddr>, String> {
+    let host = url
+        .host_str()
+        .ok_or_else(|| format!("callback_url にホストがありません: {}", url))?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let port = url.port_or_known_default().unwrap_or(80);
+
+    let addrs: Vec<SocketAddr> = match host.parse::<IpAddr>() {
+        Ok(ip) => vec![SocketAddr::new(ip, port)],
+        Err(_) => tokio::net::lookup_host((host, port))
+            .await
+            .map_err(|e| format!("callback_url のホストを名前解決できません: {}: {}", host, e))?
+            .collect(),
+    };
+    if addrs.is_empty() {
+        return Err(format!(
+            "callback_url のホストを名前解決できません: {}",
+            host
+        ));
+    }
+
+    let host_allowed = config
+        .allowed_hosts
+        .iter()
+        .any(|entry| entry.eq_ignore_ascii_case(host));
+    for addr in &addrs {
+        let ip = addr.ip();
+        let allowed = host_allowed
+            || config
+                .allowed_hosts
+                .iter()
+                .any(|entry| network_contains(entry, ip));
+        if is_internal(ip) && !allowed {
+            return Err(format!(
+                "callback_url に内部向けのアドレスは指定できません: {} ({})",
+                host, ip
+            ));
+        }
+    }
+    Ok(addrs)
+}
+
+/// 外部へ公開されていないアドレスか
+fn is_internal(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || a == 0 // 0.0.0.0/8
+                || (a == 100 && b & 0xc0 == 64) // 100.64.0.0/10（キャリアグレード NAT）
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(v4) => is_internal(IpAddr::V4(v4)),
+            None => {
+                ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    || ip.is_unique_local()
+                    || ip.is_unicast_link_local()
+            }
+        },
+    }
+}
+
+/// `allowed_hosts` の項目（アドレスか CIDR）が `ip` を含むか
+fn network_contains(entry: &str, ip: IpAddr) -> bool {
+    let (network, prefix) = match entry.split_once('/') {
+        Some((network, prefix)) => match prefix.parse::<u32>() {
+            Ok(prefix) => (network, Some(prefix)),
+            Err(_) => return false,
+        },
+        None => (entry, None),
+    };
+    let (network, ip, bits) = match (network.parse::<IpAddr>(), ip) {
+        (Ok(IpAddr::V4(network)), IpAddr::V4(ip)) => (
+            u128::from(u32::from(network)),
+            u128::from(u32::from(ip)),
+            32,
+        ),
+        (Ok(IpAddr::V6(network)), IpAddr::V6(ip)) => (u128::from(network), u128::from(ip), 128),
+        _ => return false,
+    };
+    let prefix = prefix.unwrap_or(bits);
+    if prefix > bits {
+        return false;
+    }
+    let shift = bits - prefix;
+    network.checked_shr(shift).unwrap_or(0) == ip.checked_shr(shift).unwrap_or(0)
+}
+
+/// 配信の状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryState {
+    /// ジョブの終了待ち/送信中
+    Pending,
+    /// 2xx の応答を受け取った
+    Delivered,
+    /// 最大回数まで送っても届かなかった
+    Failed,
+}
+
+/// 配信状況（`GET /jobs/{id}` の `callback`）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CallbackStatus {
+    pub url: String,
+    pub state: DeliveryState,
+    /// 送信した回数
+    pub attempts: u32,
+    /// 最後の応答の HTTP ステータス
+    pub last_status: Option<u16>,
+    /// 最後の送信のエラー（接続失敗/タイムアウト等）
+    pub last_error: Option<String>,
+    /// 届いた時刻（RFC 3339）
+    pub delivered_at: Option<String>,
+}
+
+impl CallbackStatus {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            state: DeliveryState::Pending,
+            attempts: 0,
+            last_status: None,
+            last_error: None,
+            delivered_at: None,
+        }
+    }
+}
+
+/// 通知の本文
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookPayload {
+    /// `job.completed` / `job.failed` / `job.cancelled`
+    pub event: String,
+    /// ジョブ情報（失敗時は `job.error` にエラー内容）
+    pub job: JobInfo,
+    /// 完了時の結果（`GET /jobs/{id}/result` と同じ）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<TranscribeResponse>,
+}
+
+impl WebhookPayload {
+    /// 終了したジョブの通知内容（終了前/存在しない場合は None）
+    pub fn for_job(jobs: &JobStore, job_id: &str) -> Option<Self> {
+        let mut job = jobs.get(job_id, None)?;
+        let (event, result) = match jobs.outcome(job_id, None)? {
+            JobOutcome::Completed(response) => ("job.completed", Some(*response)),
+            JobOutcome::Failed { .. } => ("job.failed", None),
+            JobOutcome::Cancelled => ("job.cancelled", None),
+            JobOutcome::Pending(_) => return None,
+        };
+        // 配信状況は送信中に変わるため本文には含めない
+        job.callback = None;
+        Some(Self {
+            event: event.to_string(),
+            job,
+            result,
+        })
+    }
+}
+
+/// 署名ヘッダーの値（`{timestamp}.{本文}` の HMAC-SHA256 を `sha256=<16進>` の形にしたもの）
+/// - 受け取る側は次の順で確認する
+///   1. `X-Webhook-Timestamp` と現在時刻の差が許容範囲（例: 5 分）を超えていれば拒否する（再送攻撃対策）
+///   2. 同じ鍵で `{X-Webhook-Timestamp}.{受け取った本文のバイト列}` の HMAC-SHA256 を計算する
+///   3. `X-Webhook-Signature` と定数時間で比較する
+pub fn signature(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut message = format!("{}.", timestamp).into_bytes();
+    message.extend_from_slice(body);
+    format!(
+        "sha256={}",
+        to_hex(&hmac_sha256(secret.as_bytes(), &message))
+    )
+}
+
+/// `failed_attempts` 回目の送信に失敗した後、次に送るまでの待ち時間
+/// - `initial_backoff_ms` から倍々で増やし、`max_backoff_ms` で頭打ちにする
+pub fn backoff(config: &WebhookConfig, failed_attempts: u32) -> Duration {
+    let factor = 1u64
+        .checked_shl(failed_attempts.saturating_sub(1))
+        .unwrap_or(u64::MAX);
+    Duration::from_millis(
+        config
+            .initial_backoff_ms
+            .saturating_mul(factor)
+            .min(config.max_backoff_ms),
+    )
+}
+
+/// 終了したジョブを通知先へ送る（届くか最大回数に達するまで再送する）
+/// - 通知先の無いジョブ/終了していないジョブは何もしない
+pub async fn deliver(jobs: &JobStore, job_id: &str, config: &WebhookConfig) {
+    let (Some(target), Some(payload)) = (
+        jobs.callback_target(job_id),
+        WebhookPayload::for_job(jobs, job_id),
+    ) else {
+        return;
+    };
+
+    let sent = match (
+        serde_json::to_vec(&payload),
+        build_client(&target, config).await,
+    ) {
+        (Ok(body), Ok(client)) => {
+            send_with_retry(jobs, job_id, &client, &target, &payload.event, body, config).await
+        }
+        (Err(e), _) => Err(format!("通知内容を作成できません: {}", e)),
+        (_, Err(e)) => Err(e),
+    };
+
+    match sent {
+        Ok(()) => println!("Webhook を送信しました: {} ({})", target.url, payload.event),
+        Err(e) => {
+            jobs.update_callback(job_id, |status| {
+                status.state = DeliveryState::Failed;
+                status.last_error.get_or_insert(e.clone());
+            });
+            eprintln!("Webhook を送信できませんでした: {} - {}", target.url, e);
+        }
+    }
+}
+
+/// 通知先へ送る HTTP クライアント
+/// - 投入後に DNS の応答が変わっても内部へ送らないよう、送信時に名前解決し直して確認したアドレスに固定する
+async fn build_client(
+    target: &WebhookTarget,
+    config: &WebhookConfig,
+) -> Result<reqwest::Client, String> {
+    let url = reqwest::Url::parse(&target.url).map_err(|e| e.to_string())?;
+    let addrs = resolve_destination(&url, config).await?;
+
+    // リダイレクト先へ本文と署名を送らないよう、リダイレクトは追わない
+    let mut builder = reqwest::Client::builder()
+        .timeout(Duration::from_secs(config.timeout_seconds))
+        .redirect(reqwest::redirect::Policy::none());
+    if let Some(domain) = url.domain() {
+        builder = builder.resolve_to_addrs(domain, &addrs);
+    }
+    builder
+        .build()
+        .map_err(|e| format!("HTTP クライアントを作成できません: {}", e))
+}
+
+async fn send_with_retry(
+    jobs: &JobStore,
+    job_id: &str,
+    client: &reqwest::Client,
+    target: &WebhookTarget,
+    event: &str,
+    body: Vec<u8>,
+    config: &WebhookConfig,
+) -> Result<(), String> {
+    for attempt in 1..=config.max_attempts {
+        let timestamp = chrono::Utc::now().timestamp();
+        let mut request = client
+            .post(&target.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, event)
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .body(body.clone());
+        if let Some(secret) = target.secret.as_deref() {
+            request = request.header(SIGNATURE_HEADER, signature(secret, timestamp, &body));
+        }
+
+        let result = request.send().await;
+        let delivered = matches!(&result, Ok(response) if response.status().is_success());
+        jobs.update_callback(job_id, |status| {
+            status.attempts = attempt;
+            match &result {
+                Ok(response) => {
+                    status.last_status = Some(response.status().as_u16());
+                    status.last_error = None;
+                }
+                Err(e) => status.last_error = Some(e.to_string()),
+            }
+            if delivered {
+                status.state = DeliveryState::Delivered;
+                status.delivered_at = Some(chrono::Utc::now().to_rfc3339());
+            }
+        });
+        if delivered {
+            return Ok(());
+        }
+
+        if attempt < config.max_attempts {
+            tokio::time::sleep(backoff(config, attempt)).await;
+        }
+    }
+
+    Err(format!(
+        "{}回送信しても届きませんでした",
+        config.max_attempts
+    ))
+}
@@ -1,9 +1,32 @@
-use crate::config::Config;
-use crate::models::TranscriptionSegment;
+use crate::chunk::{offset_segment, plan_chunks, stitch_segments, AudioChunk};
+use crate::config::{ChunkingConfig, Config, DiarizationConfig, FilterConfig, VadConfig};
+use crate::diarize::{diarize, speaker_text};
+use crate::filter::filter_segments;
+use crate::glossary::correct_segments;
+use crate::models::{
+    merge_channel_segments, DecodingParameters, FailedChunk, FilteredSegment, LanguageDetection,
+    TokenTiming, TranscriptionSegment, WordTiming,
+};
+use crate::vad::{detect_speech, SpeechTimeline};
 use anyhow::Result;
+use std::collections::HashMap;
+use std::ffi::{c_void, CStr};
+use std::os::raw::c_int;
 use std::path::Path;
-use std::sync::Arc;
-use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use whisper_rs::{
+    whisper_rs_sys, FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters,
+    WhisperState, WhisperSysContext, WhisperSysState,
+};
+
+/// Whisper の入力サンプルレート（Hz）
+const WHISPER_SAMPLE_RATE: usize = 16_000;
+/// 言語検出に使う先頭ウィンドウの長さ（秒）
+pub const LANGUAGE_DETECTION_WINDOW_SECONDS: usize = 30;
+/// whisper が一度にエンコードするウィンドウの長さ（ミリ秒）
+const WHISPER_WINDOW_MS: u64 = 30_000;
 
 /// Whisperエンジンのラッパー（スレッドセーフ）
 /// - whisper-rs の `WhisperContext` を `Arc` で共有
@@ -13,6 +36,8 @@ pub struct WhisperEngine {
     language: Option<String>,
     whisper_threads: i32,
     enable_gpu: bool,
+    /// tinydiarize モデル（話者交代を検出できる）かどうか
+    tinydiarize: bool,
 }
 
 /// Whisper処理の結果
@@ -21,7 +46,66 @@ pub struct TranscriptionResult {
     pub text: String,
     pub segments: Vec<TranscriptionSegment>,
     pub language: Option<String>,
+    /// 言語の自動検出結果（言語未指定時のみ）
+    pub language_detection: Option<LanguageDetection>,
     pub processing_time_ms: u64,
+    /// 後処理で除いた/書き換えたセグメント
+    pub filtered: Vec<FilteredSegment>,
+    /// 用語集で表記を直したセグメント（直す前のテキスト）
+    pub corrections: Vec<FilteredSegment>,
+    /// 分割推論で失敗したチャンク（その区間は `segments` に含まれない）
+    pub failed_chunks: Vec<FailedChunk>,
+}
+
+/// 1回の文字起こしのオプション
+#[derive(Debug, Clone, Default)]
+pub struct TranscribeOptions {
+    /// 言語コード（未指定ならエンジン既定）
+    pub language: Option<String>,
+    /// 英語へ翻訳するかどうか
+    pub translate_to_english: bool,
+    /// セグメントのタイムスタンプを取得するかどうか
+    pub include_timestamps: bool,
+    /// `include_timestamps` に関係なくセグメントを結果に残すかどうか（履歴の保存用）
+    /// - 推論パラメータは変えず、推論で得たセグメントを捨てないだけ
+    pub keep_segments: bool,
+    /// 単語単位のタイムスタンプ/信頼度を取得するかどうか（`include_timestamps` が前提）
+    pub word_timestamps: bool,
+    /// デコードパラメータ（検証済み）
+    pub decoding: DecodingParameters,
+    /// 指定時は発話区間だけを推論し、タイムスタンプを元の時間軸へ戻す
+    pub vad: Option<VadConfig>,
+    /// 指定時はハルシネーション/繰り返しを除く
+    pub filter: Option<FilterConfig>,
+    /// 指定時は長い音声をチャンクに分けて推論する
+    pub chunking: Option<ChunkingConfig>,
+    /// 指定時は用語集の語句に近い表記を語句の表記に直す
+    pub glossary: Option<Vec<String>>,
+    /// 指定時は各セグメントに話者ラベルを付ける
+    pub diarization: Option<DiarizationConfig>,
+    /// 話者数（話者分離の指定時のみ。未指定なら推定する）
+    pub num_speakers: Option<usize>,
+}
+
+/// 推論中に呼び出されるフック
+/// - ジョブの進捗更新など、`state.full` の実行中に外部へ状況を伝えるために使う
+#[derive(Default)]
+pub struct InferenceHooks {
+    /// 推論の進捗（0〜100%）
+    pub on_progress: Option<Box<dyn FnMut(i32) + Send>>,
+    /// whisper がセグメントを確定するたびに呼ばれる（テキストと開始/終了時刻のみ）
+    pub on_segment: Option<Box<dyn FnMut(TranscriptionSegment) + Send>>,
+    /// true になったら推論を打ち切る（タイムアウト/クライアントの切断/ジョブのキャンセル）
+    pub abort: Option<Arc<AtomicBool>>,
+}
+
+impl InferenceHooks {
+    /// 中断が要求されているか
+    fn is_aborted(&self) -> bool {
+        self.abort
+            .as_ref()
+            .is_some_and(|abort| abort.load(Ordering::SeqCst))
+    }
 }
 
 impl WhisperEngine {
@@ -140,6 +224,10 @@ impl WhisperEngine {
             language,
             whisper_threads: config.performance.whisper_threads as i32,
             enable_gpu: gpu_actually_enabled,
+            // tinydiarize のモデルはファイル名に `tdrz` を含む（例: ggml-small.en-tdrz.bin）
+            tinydiarize: Path::new(model_path)
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().contains("tdrz")),
         })
     }
 
@@ -148,7 +236,11 @@ impl WhisperEngine {
     pub fn transcribe(&self, audio_data: &[f32]) -> Result<String> {
         let start_time = std::time::Instant::now();
 
-        let result = self.transcribe_internal(audio_data, None, false, false)?;
+        let result = self.transcribe_internal(
+            audio_data,
+            &TranscribeOptions::default(),
+            &mut InferenceHooks::default(),
+        )?;
 
         let processing_time = start_time.elapsed().as_millis() as u64;
         println!("文字起こし完了: {}ms", processing_time);
@@ -163,10 +255,27 @@ impl WhisperEngine {
         audio_data: &[f32],
         translate_to_english: bool,
         language: Option<&str>,
+    ) -> Result<TranscriptionResult> {
+        let options = TranscribeOptions {
+            language: language.map(|s| s.to_string()),
+            translate_to_english,
+            include_timestamps: true,
+            ..Default::default()
+        };
+        self.transcribe_with_options(audio_data, &options, &mut InferenceHooks::default())
+    }
+
+    /// オプション/フック付きの文字起こし
+    /// - デコードパラメータを反映し、推論中の進捗を `hooks` へ通知する
+    pub fn transcribe_with_options(
+        &self,
+        audio_data: &[f32],
+        options: &TranscribeOptions,
+        hooks: &mut InferenceHooks,
     ) -> Result<TranscriptionResult> {
         let start_time = std::time::Instant::now();
 
-        let result = self.transcribe_internal(audio_data, language, translate_to_english, true)?;
+        let result = self.transcribe_internal(audio_data, options, hooks)?;
 
         let processing_time_ms = start_time.elapsed().as_millis() as u64;
 
@@ -180,34 +289,572 @@ impl WhisperEngine {
             text: result.text,
             segments: result.segments,
             language: result.language,
+            language_detection: result.language_detection,
             processing_time_ms,
+            filtered: result.filtered,
+            corrections: result.corrections,
+            failed_chunks: result.failed_chunks,
         })
     }
 
+    /// チャンネルごとに文字起こしし、結果を 1 本の時系列にまとめる（`channel_mode=separate`）
+    /// - 各セグメントにチャンネル番号を付け、開始時刻順に並べる
+    /// - 全文テキストは `[chN] テキスト` を時刻順に改行で連結したもの
+    /// - 進捗はチャンネル数で按分して 0〜100% に換算する
+    /// - 言語/言語検出結果は最初のチャンネルのもの
+    pub fn transcribe_channels(
+        &self,
+        channels: &[Vec<f32>],
+        options: &TranscribeOptions,
+        hooks: &mut InferenceHooks,
+    ) -> Result<TranscriptionResult> {
+        let start_time = std::time::Instant::now();
+        // 時刻順にまとめるため、セグメントは常に取得する
+        let channel_options = TranscribeOptions {
+            include_timestamps: true,
+            ..options.clone()
+        };
+
+        // フックは全チャンネルで共有する
+        let on_progress = Arc::new(Mutex::new(hooks.on_progress.take()));
+        let on_segment = Arc::new(Mutex::new(hooks.on_segment.take()));
+        let channel_count = channels.len().max(1) as i32;
+
+        let mut language = None;
+        let mut language_detection = None;
+        let mut channel_segments = Vec::with_capacity(channels.len());
+        let mut filtered = Vec::new();
+        let mut corrections = Vec::new();
+        let mut failed_chunks = Vec::new();
+
+        for (channel, samples) in channels.iter().enumerate() {
+            let mut channel_hooks = InferenceHooks {
+                abort: hooks.abort.clone(),
+                ..Default::default()
+            };
+            if on_progress.lock().unwrap().is_some() {
+                let on_progress = Arc::clone(&on_progress);
+                channel_hooks.on_progress = Some(Box::new(move |progress| {
+                    if let Some(hook) = on_progress.lock().unwrap().as_mut() {
+                        hook((channel as i32 * 100 + progress) / channel_count);
+                    }
+                }));
+            }
+            if on_segment.lock().unwrap().is_some() {
+                let on_segment = Arc::clone(&on_segment);
+                channel_hooks.on_segment = Some(Box::new(move |mut segment| {
+                    segment.channel = Some(channel);
+                    if let Some(hook) = on_segment.lock().unwrap().as_mut() {
+                        hook(segment);
+                    }
+                }));
+            }
+
+            let result = self.transcribe_internal(samples, &channel_options, &mut channel_hooks)?;
+            if channel == 0 {
+                language = result.language;
+                language_detection = result.language_detection;
+            }
+            channel_segments.push(result.segments);
+            filtered.extend(result.filtered.into_iter().map(|mut segment| {
+                segment.channel = Some(channel);
+                segment
+            }));
+            corrections.extend(result.corrections.into_iter().map(|mut segment| {
+                segment.channel = Some(channel);
+                segment
+            }));
+            failed_chunks.extend(result.failed_chunks.into_iter().map(|mut chunk| {
+                chunk.channel = Some(channel);
+                chunk
+            }));
+        }
+
+        hooks.on_progress = on_progress.lock().unwrap().take();
+        hooks.on_segment = on_segment.lock().unwrap().take();
+
+        let segments = merge_channel_segments(channel_segments);
+        let text = if segments.is_empty() {
+            "(音声を認識できませんでした)".to_string()
+        } else {
+            segments
+                .iter()
+                .map(TranscriptionSegment::labelled_text)
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+
+        let processing_time_ms = start_time.elapsed().as_millis() as u64;
+        println!(
+            "チャンネル別文字起こし完了: {}ms, {}チャンネル, {}セグメント",
+            processing_time_ms,
+            channels.len(),
+            segments.len()
+        );
+
+        Ok(TranscriptionResult {
+            text,
+            segments,
+            language,
+            language_detection,
+            processing_time_ms,
+            filtered,
+            corrections,
+            failed_chunks,
+        })
+    }
+
+    /// 音声の言語を検出
+    /// - 先頭ウィンドウ（30 秒）の言語ごとの確率を返す
+    pub fn detect_language(&self, audio_data: &[f32]) -> Result<LanguageDetection> {
+        if audio_data.is_empty() {
+            return Err(anyhow::anyhow!("音声データが空です"));
+        }
+
+        let mut state = self
+            .context
+            .create_state()
+            .map_err(|e| anyhow::anyhow!("Whisper状態の作成に失敗: {}", e))?;
+
+        self.detect_language_in_state(&mut state, audio_data)
+    }
+
+    /// 作成済みの state で言語を検出
+    fn detect_language_in_state(
+        &self,
+        state: &mut WhisperState,
+        audio_data: &[f32],
+    ) -> Result<LanguageDetection> {
+        let threads = self.whisper_threads.max(1) as usize;
+        let window_len = audio_data
+            .len()
+            .min(WHISPER_SAMPLE_RATE * LANGUAGE_DETECTION_WINDOW_SECONDS);
+
+        state
+            .pcm_to_mel(&audio_data[..window_len], threads)
+            .map_err(|e| anyhow::anyhow!("メルスペクトログラムの計算に失敗: {}", e))?;
+        let probabilities = state
+            .lang_detect(0, threads)
+            .map_err(|e| anyhow::anyhow!("言語検出に失敗: {}", e))?;
+
+        let probabilities = probabilities
+            .into_iter()
+            .enumerate()
+            .filter_map(|(id, probability)| {
+                whisper_rs::get_lang_str(id as i32).map(|code| (code.to_string(), probability))
+            })
+            .collect();
+
+        LanguageDetection::from_probabilities(probabilities)
+            .ok_or_else(|| anyhow::anyhow!("言語検出の結果が空です"))
+    }
+
     /// 内部的な文字起こし処理
-    /// - whisper-rs の `state.full` を用いる標準フロー
-    /// - language 指定（上書き）/翻訳モード/タイムスタンプ出力を切り替え
+    /// - VAD 指定時は発話区間だけを推論する
+    /// - 分割推論の指定時は長い音声をチャンクに分けて推論する
+    /// - 後処理の指定時はハルシネーション/繰り返しを除き、全文テキストを組み直す
+    /// - セグメントは `include_timestamps` または `keep_segments` 指定時のみ返す
     fn transcribe_internal(
         &self,
         audio_data: &[f32],
-        language_override: Option<&str>,
-        translate_to_english: bool,
-        include_timestamps: bool,
+        options: &TranscribeOptions,
+        hooks: &mut InferenceHooks,
+    ) -> Result<TranscriptionResult> {
+        let mut result = match options.vad.as_ref() {
+            Some(vad) => self.transcribe_speech(audio_data, vad, options, hooks)?,
+            None => self.transcribe_long(audio_data, options, hooks)?,
+        };
+
+        if let Some(filter) = options.filter.as_ref() {
+            let (segments, filtered) =
+                filter_segments(std::mem::take(&mut result.segments), filter);
+            if !filtered.is_empty() {
+                println!(
+                    "後処理: {}件のセグメントを除外/修正しました",
+                    filtered.len()
+                );
+                result.text = joined_text(&segments);
+            }
+            result.segments = segments;
+            result.filtered = filtered;
+        }
+
+        if let Some(terms) = options.glossary.as_ref() {
+            let corrected = correct_segments(&mut result.segments, terms);
+            if !corrected.is_empty() {
+                println!(
+                    "用語集: {}件のセグメントの表記を修正しました",
+                    corrected.len()
+                );
+                result.text = joined_text(&result.segments);
+                result.corrections = corrected;
+            }
+        }
+
+        if let Some(diarization) = options.diarization.as_ref() {
+            let speakers = diarize(
+                audio_data,
+                WHISPER_SAMPLE_RATE as u32,
+                &mut result.segments,
+                diarization,
+                options.num_speakers,
+                self.tinydiarize,
+            );
+            println!("話者分離: {}人の話者を推定しました", speakers);
+            if speakers > 0 {
+                result.text = speaker_text(&result.segments);
+            }
+        }
+
+        if !options.include_timestamps && !options.keep_segments {
+            result.segments.clear();
+        }
+        Ok(result)
+    }
+
+    /// 発話区間だけをつなげた音声で文字起こしする（VAD）
+    /// - セグメント/単語の時刻は元の音声の時間軸に戻す
+    /// - 発話区間が無ければ推論せずに空の結果を返す
+    fn transcribe_speech(
+        &self,
+        audio_data: &[f32],
+        vad: &VadConfig,
+        options: &TranscribeOptions,
+        hooks: &mut InferenceHooks,
     ) -> Result<TranscriptionResult> {
         // 音声データの検証
         if audio_data.is_empty() {
             return Err(anyhow::anyhow!("音声データが空です"));
         }
 
+        let regions = detect_speech(audio_data, WHISPER_SAMPLE_RATE as u32, vad);
+        let timeline = Arc::new(SpeechTimeline::new(regions, WHISPER_SAMPLE_RATE as u32));
+        let total_ms = (audio_data.len() * 1000 / WHISPER_SAMPLE_RATE) as u64;
+        println!(
+            "VAD: 発話区間 {:.1}秒 / {:.1}秒",
+            timeline.speech_ms() as f64 / 1000.0,
+            total_ms as f64 / 1000.0
+        );
+
+        if timeline.is_empty() {
+            return Ok(TranscriptionResult {
+                text: "(音声を認識できませんでした)".to_string(),
+                segments: Vec::new(),
+                language: options
+                    .language
+                    .clone()
+                    .or_else(|| self.language.clone())
+                    .filter(|language| !language.is_empty() && language != "auto"),
+                language_detection: None,
+                processing_time_ms: 0,
+                filtered: Vec::new(),
+                corrections: Vec::new(),
+                failed_chunks: Vec::new(),
+            });
+        }
+
+        // 逐次通知されるセグメントも元の時間軸で渡す
+        if let Some(mut on_segment) = hooks.on_segment.take() {
+            let timeline = Arc::clone(&timeline);
+            hooks.on_segment = Some(Box::new(move |mut segment| {
+                timeline.remap_segment(&mut segment);
+                on_segment(segment);
+            }));
+        }
+
+        let speech = timeline.compact(audio_data);
+        let mut result = self.transcribe_long(&speech, options, hooks)?;
+        for segment in result.segments.iter_mut() {
+            timeline.remap_segment(segment);
+        }
+        for chunk in result.failed_chunks.iter_mut() {
+            chunk.start_time_ms = timeline.start_ms(chunk.start_time_ms);
+            chunk.end_time_ms = timeline.end_ms(chunk.end_time_ms);
+        }
+        Ok(result)
+    }
+
+    /// 分割推論の指定があり、音声が 1 チャンクに収まらなければ分けて推論する
+    fn transcribe_long(
+        &self,
+        audio_data: &[f32],
+        options: &TranscribeOptions,
+        hooks: &mut InferenceHooks,
+    ) -> Result<TranscriptionResult> {
+        let chunks = match options.chunking.as_ref() {
+            Some(chunking) => plan_chunks(audio_data, WHISPER_SAMPLE_RATE as u32, chunking),
+            None => Vec::new(),
+        };
+        if chunks.len() <= 1 {
+            return self.transcribe_samples(audio_data, options, hooks);
+        }
+        self.transcribe_chunks(audio_data, &chunks, options, hooks)
+    }
+
+    /// チャンクごとに推論し、元の時間軸でつなぎ合わせる
+    /// - `chunking.max_parallel` 個までのスレッドで並行して推論する（コンテキストは共有、state はチャンクごと）
+    /// - セグメントを逐次通知する場合（SSE）は順序を保つため 1 つずつ推論する
+    /// - 言語未指定の場合は先頭で 1 度だけ検出し、全チャンクをその言語で推論する
+    /// - 失敗したチャンクは `failed_chunks` に記録し、残りの結果を返す（すべて失敗/中断した場合はエラー）
+    fn transcribe_chunks(
+        &self,
+        audio_data: &[f32],
+        chunks: &[AudioChunk],
+        options: &TranscribeOptions,
+        hooks: &mut InferenceHooks,
+    ) -> Result<TranscriptionResult> {
+        let to_ms = |samples: usize| (samples * 1000 / WHISPER_SAMPLE_RATE) as u64;
+        let requested_language = options
+            .language
+            .as_deref()
+            .or(self.language.as_deref())
+            .filter(|language| !language.is_empty() && *language != "auto")
+            .map(str::to_string);
+        let language_detection = match requested_language {
+            Some(_) => None,
+            None => match self.detect_language(audio_data) {
+                Ok(detection) => Some(detection),
+                Err(e) => {
+                    eprintln!("言語検出に失敗しました（チャンクごとに自動検出）: {}", e);
+                    None
+                }
+            },
+        };
+        let chunk_options = TranscribeOptions {
+            language: requested_language
+                .clone()
+                .or_else(|| language_detection.as_ref().map(|d| d.language.clone())),
+            ..options.clone()
+        };
+
+        let workers = match hooks.on_segment {
+            Some(_) => 1,
+            None => options
+                .chunking
+                .as_ref()
+                .map_or(1, |chunking| chunking.max_parallel)
+                .clamp(1, chunks.len()),
+        };
+        println!(
+            "分割推論: {}チャンク（{:.1}秒ごと）, 並列数 {}",
+            chunks.len(),
+            to_ms(chunks[0].end) as f64 / 1000.0,
+            workers
+        );
+
+        // フックは全チャンクで共有する（進捗はチャンクごとの進捗の平均）
+        let on_progress = Arc::new(Mutex::new(hooks.on_progress.take()));
+        let on_segment = Arc::new(Mutex::new(hooks.on_segment.take()));
+        let progress = Arc::new(Mutex::new(vec![0; chunks.len()]));
+        let abort = hooks.abort.clone();
+        let next = AtomicUsize::new(0);
+        let results: Mutex<Vec<Option<Result<TranscriptionResult>>>> =
+            Mutex::new((0..chunks.len()).map(|_| None).collect());
+
+        std::thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::SeqCst);
+                    let mut chunk_hooks = InferenceHooks {
+                        abort: abort.clone(),
+                        ..Default::default()
+                    };
+                    if index >= chunks.len() || chunk_hooks.is_aborted() {
+                        break;
+                    }
+                    let chunk = chunks[index];
+                    if on_progress.lock().unwrap().is_some() {
+                        let on_progress = Arc::clone(&on_progress);
+                        let progress = Arc::clone(&progress);
+                        chunk_hooks.on_progress = Some(Box::new(move |value| {
+                            let overall = {
+                                let mut progress = progress.lock().unwrap();
+                                progress[index] = value;
+                                progress.iter().sum::<i32>() / progress.len() as i32
+                            };
+                            if let Some(hook) = on_progress.lock().unwrap().as_mut() {
+                                hook(overall);
+                            }
+                        }));
+                    }
+                    if on_segment.lock().unwrap().is_some() {
+                        let on_segment = Arc::clone(&on_segment);
+                        let offset_ms = to_ms(chunk.start);
+                        chunk_hooks.on_segment = Some(Box::new(move |mut segment| {
+                            offset_segment(&mut segment, offset_ms);
+                            if let Some(hook) = on_segment.lock().unwrap().as_mut() {
+                                hook(segment);
+                            }
+                        }));
+                    }
+
+                    let result = self.transcribe_samples(
+                        &audio_data[chunk.start..chunk.end],
+                        &chunk_options,
+                        &mut chunk_hooks,
+                    );
+                    results.lock().unwrap()[index] = Some(result);
+                });
+            }
+        });
+
+        hooks.on_progress = on_progress.lock().unwrap().take();
+        hooks.on_segment = on_segment.lock().unwrap().take();
+        if hooks.is_aborted() {
+            return Err(anyhow::anyhow!("推論を中断しました"));
+        }
+
+        // 入力順につなぎ合わせる
+        let mut segments = Vec::new();
+        let mut failed_chunks = Vec::new();
+        let mut first_error = None;
+        let mut language = None;
+        let mut previous_succeeded = false;
+        for (chunk, result) in chunks.iter().zip(results.into_inner().unwrap()) {
+            match result.unwrap_or_else(|| Err(anyhow::anyhow!("推論を中断しました"))) {
+                Ok(result) => {
+                    let offset_ms = to_ms(chunk.start);
+                    let mut chunk_segments = result.segments;
+                    for segment in chunk_segments.iter_mut() {
+                        offset_segment(segment, offset_ms);
+                    }
+                    let boundary = if previous_succeeded {
+                        chunk.boundary
+                    } else {
+                        chunk.start
+                    };
+                    stitch_segments(&mut segments, chunk_segments, to_ms(boundary));
+                    language = language.or(result.language);
+                    previous_succeeded = true;
+                }
+                Err(e) => {
+                    eprintln!(
+                        "チャンク {:.1}〜{:.1}秒の推論に失敗しました: {}",
+                        to_ms(chunk.boundary) as f64 / 1000.0,
+                        to_ms(chunk.end) as f64 / 1000.0,
+                        e
+                    );
+                    failed_chunks.push(FailedChunk {
+                        start_time_ms: to_ms(chunk.boundary),
+                        end_time_ms: to_ms(chunk.end),
+                        error: e.to_string(),
+                        channel: None,
+                    });
+                    first_error.get_or_insert(e);
+                    previous_succeeded = false;
+                }
+            }
+        }
+        if failed_chunks.len() == chunks.len() {
+            return Err(first_error.unwrap_or_else(|| anyhow::anyhow!("推論に失敗しました")));
+        }
+
+        Ok(TranscriptionResult {
+            text: joined_text(&segments),
+            segments,
+            language: requested_language.or(chunk_options.language).or(language),
+            language_detection,
+            processing_time_ms: 0, // 呼び出し元で設定
+            filtered: Vec::new(),
+            corrections: Vec::new(),
+            failed_chunks,
+        })
+    }
+
+    /// サンプル列をそのまま文字起こしする
+    /// - whisper-rs の `state.full` を用いる標準フロー
+    /// - language 指定（上書き）/翻訳モード/タイムスタンプ出力/デコードパラメータを切り替え
+    /// - 後処理のためセグメントは常に取得する
+    fn transcribe_samples(
+        &self,
+        audio_data: &[f32],
+        options: &TranscribeOptions,
+        hooks: &mut InferenceHooks,
+    ) -> Result<TranscriptionResult> {
+        // 音声データの検証
+        if audio_data.is_empty() {
+            return Err(anyhow::anyhow!("音声データが空です"));
+        }
+        // デコード中などに中断された場合は推論を始めない
+        if hooks.is_aborted() {
+            return Err(anyhow::anyhow!("推論を中断しました"));
+        }
+
         // Whisperの状態を作成（各リクエストごとに新しい状態）
         let mut state = self
             .context
             .create_state()
             .map_err(|e| anyhow::anyhow!("Whisper状態の作成に失敗: {}", e))?;
 
+        // 言語の自動検出
+        // - 呼び出し時の指定もエンジン既定も無い（auto）場合は先頭ウィンドウで検出し、
+        //   検出した言語でデコードする
+        // - 検出に失敗した場合は whisper.cpp 内部の自動検出に任せる
+        let requested_language = options
+            .language
+            .as_deref()
+            .or(self.language.as_deref())
+            .filter(|language| !language.is_empty() && *language != "auto");
+        let language_detection = match requested_language {
+            Some(_) => None,
+            None => match self.detect_language_in_state(&mut state, audio_data) {
+                Ok(detection) => Some(detection),
+                Err(e) => {
+                    eprintln!("言語検出に失敗しました（自動検出で続行）: {}", e);
+                    None
+                }
+            },
+        };
+        let detected_options;
+        let options = match &language_detection {
+            Some(detection) => {
+                detected_options = TranscribeOptions {
+                    language: Some(detection.language.clone()),
+                    ..options.clone()
+                };
+                &detected_options
+            }
+            None => options,
+        };
+
         // パラメータを設定
-        // - 言語/スレッド数/翻訳/タイムスタンプ等
-        let params = self.make_params(language_override, translate_to_english, include_timestamps);
+        // - 言語/スレッド数/翻訳/タイムスタンプ/デコード等
+        let mut params = self.make_params(options);
+
+        // 進捗コールバック
+        // - `hooks` は `state.full` の完了まで借用されるため、ポインタは推論中ずっと有効
+        if let Some(on_progress) = hooks.on_progress.as_mut() {
+            unsafe {
+                params.set_progress_callback(Some(progress_trampoline));
+                params.set_progress_callback_user_data(
+                    on_progress as *mut Box<dyn FnMut(i32) + Send> as *mut c_void,
+                );
+            }
+        }
+
+        // 新しいセグメントのコールバック
+        // - 進捗と同様に `hooks` を `state.full` の完了まで借用し、ポインタを渡す
+        if let Some(on_segment) = hooks.on_segment.as_mut() {
+            unsafe {
+                params.set_new_segment_callback(Some(segment_trampoline));
+                params.set_new_segment_callback_user_data(
+                    on_segment as *mut Box<dyn FnMut(TranscriptionSegment) + Send> as *mut c_void,
+                );
+            }
+        }
+
+        // 中断コールバック
+        // - エンコーダ/デコーダの計算の合間に whisper.cpp から呼ばれ、true を返すと `state.full` が失敗で戻る
+        // - 各ウィンドウのエンコード前にも確認し、中断済みならエンコードせずに打ち切る
+        // - フラグは `hooks` が保持しており、`state.full` の完了まで有効
+        if let Some(abort) = hooks.abort.as_ref() {
+            unsafe {
+                params.set_abort_callback(Some(abort_trampoline));
+                params.set_abort_callback_user_data(Arc::as_ptr(abort) as *mut c_void);
+                params.set_start_encoder_callback(Some(encoder_begin_trampoline));
+                params.set_start_encoder_callback_user_data(Arc::as_ptr(abort) as *mut c_void);
+            }
+        }
 
         // 文字起こし実行
         if self.enable_gpu {
@@ -217,9 +864,17 @@ impl WhisperEngine {
         }
 
         let transcribe_start = std::time::Instant::now();
-        state
-            .full(params, audio_data)
-            .map_err(|e| anyhow::anyhow!("文字起こしに失敗: {}", e))?;
+        state.full(params, audio_data).map_err(|e| {
+            if hooks.is_aborted() {
+                anyhow::anyhow!("推論を中断しました")
+            } else {
+                anyhow::anyhow!("文字起こしに失敗: {}", e)
+            }
+        })?;
+        // エンコード前に打ち切られた場合、`state.full` は途中までの結果で成功を返す
+        if hooks.is_aborted() {
+            return Err(anyhow::anyhow!("推論を中断しました"));
+        }
 
         let transcribe_duration = transcribe_start.elapsed();
         println!(
@@ -234,7 +889,6 @@ impl WhisperEngine {
             .full_n_segments()
             .map_err(|e| anyhow::anyhow!("セグメント数の取得に失敗: {}", e))?;
 
-        let mut text_parts = Vec::new();
         let mut segments = Vec::new();
 
         for i in 0..segment_count {
@@ -242,62 +896,168 @@ impl WhisperEngine {
                 .full_get_segment_text(i)
                 .map_err(|e| anyhow::anyhow!("セグメント{}のテキスト取得に失敗: {}", i, e))?;
 
-            let segment_text = segment_text.trim().to_string();
-            text_parts.push(segment_text.clone());
+            let start_time = state
+                .full_get_segment_t0(i)
+                .map_err(|e| anyhow::anyhow!("セグメント{}の開始時間取得に失敗: {}", i, e))?;
+
+            let end_time = state
+                .full_get_segment_t1(i)
+                .map_err(|e| anyhow::anyhow!("セグメント{}の終了時間取得に失敗: {}", i, e))?;
 
-            if include_timestamps {
-                let start_time = state
-                    .full_get_segment_t0(i)
-                    .map_err(|e| anyhow::anyhow!("セグメント{}の開始時間取得に失敗: {}", i, e))?;
+            let mut segment = TranscriptionSegment::new(
+                segment_text.trim().to_string(),
+                start_time as u64 * 10, // centisecondsをミリ秒に変換
+                end_time as u64 * 10,
+            );
+
+            if self.uses_tinydiarize(options) {
+                segment.speaker_turn = state.full_get_segment_speaker_turn_next(i);
+            }
+
+            // 平均対数確率は後処理の無音判定でも使うため、後処理が有効なら単語が不要でも求める
+            if options.word_timestamps || options.filter.is_some() {
+                let (words, avg_logprob) = self.segment_words(&state, i)?;
+                segment.words = options.word_timestamps.then_some(words);
+                segment.avg_logprob = avg_logprob;
+            }
 
-                let end_time = state
-                    .full_get_segment_t1(i)
-                    .map_err(|e| anyhow::anyhow!("セグメント{}の終了時間取得に失敗: {}", i, e))?;
+            segments.push(segment);
+        }
 
-                segments.push(TranscriptionSegment {
-                    text: segment_text,
-                    start_time_ms: start_time as u64 * 10, // centisecondsをミリ秒に変換
-                    end_time_ms: end_time as u64 * 10,
+        // 無音確率（単語タイムスタンプ指定時、または値を使う後処理が有効な場合）
+        // - whisper.cpp は値を公開しないため、セグメントを含む 30 秒ウィンドウごとに
+        //   SOT 直後の no-speech トークンの確率を求める（ウィンドウ単位でキャッシュ）
+        // - ウィンドウごとにエンコーダを再実行して重いため、どちらも無い場合は省く
+        // - セグメント結果の取得後に行う（エンコーダ/デコーダの再実行は結果を変更しない）
+        if options.word_timestamps || options.filter.is_some() {
+            let mut window_probabilities: HashMap<u64, Option<f32>> = HashMap::new();
+            for segment in segments.iter_mut() {
+                let window = segment.start_time_ms / WHISPER_WINDOW_MS;
+                let probability = *window_probabilities.entry(window).or_insert_with(|| {
+                    self.no_speech_probability(&mut state, window * WHISPER_WINDOW_MS)
+                        .map_err(|e| eprintln!("無音確率の算出に失敗しました: {}", e))
+                        .ok()
                 });
+                segment.no_speech_prob = probability;
             }
         }
 
         // 全体のテキストを結合
-        let full_text = text_parts.join("").trim().to_string();
-
-        let final_text = if full_text.is_empty() {
-            "(音声を認識できませんでした)".to_string()
-        } else {
-            full_text
+        let final_text = joined_text(&segments);
+
+        // 言語
+        // - 明示指定（またはエンジン既定）があればそれを返す
+        // - auto の場合は検出結果。事前検出に失敗した場合は推論時に whisper が選んだ言語
+        let language = match (requested_language, &language_detection) {
+            (Some(language), _) => Some(language.to_string()),
+            (None, Some(detection)) => Some(detection.language.clone()),
+            (None, None) => state
+                .full_lang_id_from_state()
+                .ok()
+                .and_then(whisper_rs::get_lang_str)
+                .map(|code| code.to_string()),
         };
 
-        // 言語検出結果を取得（可能であれば）
-        // - 明示指定が優先。無ければエンジン既定（Config）を返す
-        let detected_language = language_override
-            .map(|lang| lang.to_string())
-            .or_else(|| self.language.clone());
-
         Ok(TranscriptionResult {
             text: final_text,
             segments,
-            language: detected_language,
+            language,
+            language_detection,
             processing_time_ms: 0, // 呼び出し元で設定
+            filtered: Vec::new(),
+            corrections: Vec::new(),
+            failed_chunks: Vec::new(),
         })
     }
 
+    /// セグメント内のトークンから単語タイムスタンプと平均対数確率を求める
+    /// - SOT/EOT/タイムスタンプなどの特殊トークンは除外する
+    fn segment_words(
+        &self,
+        state: &WhisperState,
+        segment: c_int,
+    ) -> Result<(Vec<WordTiming>, Option<f32>)> {
+        let token_count = state
+            .full_n_tokens(segment)
+            .map_err(|e| anyhow::anyhow!("セグメント{}のトークン数取得に失敗: {}", segment, e))?;
+        let token_eot = self.context.token_eot();
+
+        let mut tokens = Vec::new();
+        let mut logprob_sum = 0.0;
+        for j in 0..token_count {
+            let data = state
+                .full_get_token_data(segment, j)
+                .map_err(|e| anyhow::anyhow!("セグメント{}のトークン取得に失敗: {}", segment, e))?;
+            if data.id >= token_eot {
+                continue;
+            }
+
+            let bytes = self
+                .context
+                .token_to_cstr(data.id)
+                .map_err(|e| anyhow::anyhow!("トークン{}の変換に失敗: {}", data.id, e))?
+                .to_bytes()
+                .to_vec();
+
+            logprob_sum += data.plog;
+            tokens.push(TokenTiming {
+                bytes,
+                start_time_ms: data.t0.max(0) as u64 * 10,
+                end_time_ms: data.t1.max(0) as u64 * 10,
+                probability: data.p,
+            });
+        }
+
+        let avg_logprob = (!tokens.is_empty()).then(|| logprob_sum / tokens.len() as f32);
+        Ok((WordTiming::from_tokens(&tokens), avg_logprob))
+    }
+
+    /// `offset_ms` から始まるウィンドウが無音である確率
+    /// - `state.full` で計算済みのメルスペクトログラムを再エンコードし、
+    ///   SOT トークン直後の分布における no-speech トークンの確率を返す
+    fn no_speech_probability(&self, state: &mut WhisperState, offset_ms: u64) -> Result<f32> {
+        let threads = self.whisper_threads.max(1) as usize;
+        // メルスペクトログラムのフレームは 10ms 単位
+        state
+            .encode((offset_ms / 10) as usize, threads)
+            .map_err(|e| anyhow::anyhow!("エンコードに失敗: {}", e))?;
+        state
+            .decode(&[self.context.token_sot()], 0, threads)
+            .map_err(|e| anyhow::anyhow!("デコードに失敗: {}", e))?;
+        let logits = state
+            .get_logits()
+            .map_err(|e| anyhow::anyhow!("ロジットの取得に失敗: {}", e))?;
+
+        let no_speech = self.context.token_nosp() as usize;
+        let max = logits.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+        let sum: f32 = logits.iter().map(|logit| (logit - max).exp()).sum();
+        let probability = logits
+            .get(no_speech)
+            .map(|logit| (logit - max).exp() / sum)
+            .ok_or_else(|| anyhow::anyhow!("no-speech トークンが語彙にありません"))?;
+
+        Ok(probability)
+    }
+
     /// Whisperパラメータを作成
-    /// - Greedy デコード（best_of=1）
+    /// - 既定は Greedy デコード（best_of=1）、beam_size 指定時はビームサーチ
     /// - 進捗ログ等はサーバーコンソールを汚さないよう無効化
-    fn make_params<'a>(
-        &'a self,
-        language_override: Option<&'a str>,
-        translate_to_english: bool,
-        include_timestamps: bool,
-    ) -> FullParams<'a, 'static> {
-        let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+    fn make_params<'a>(&'a self, options: &'a TranscribeOptions) -> FullParams<'a, 'static> {
+        let decoding = &options.decoding;
+        let strategy = match decoding.beam_size {
+            Some(beam_size) => SamplingStrategy::BeamSearch {
+                beam_size,
+                patience: -1.0,
+            },
+            None => SamplingStrategy::Greedy {
+                best_of: decoding.best_of,
+            },
+        };
+        let mut params = FullParams::new(strategy);
+        let include_timestamps = options.include_timestamps;
 
         // 言語設定（優先順位: 呼び出し時の指定 > エンジン既定）
-        if let Some(language) = language_override.or(self.language.as_deref()) {
+        if let Some(language) = options.language.as_deref().or(self.language.as_deref()) {
             params.set_language(Some(language));
         }
 
@@ -310,16 +1070,41 @@ impl WhisperEngine {
         params.set_print_timestamps(include_timestamps);
 
         // 翻訳モード
-        params.set_translate(translate_to_english);
+        params.set_translate(options.translate_to_english);
+
+        // デコードパラメータ（温度フォールバック/しきい値/初期プロンプト）
+        params.set_temperature(decoding.temperature);
+        params.set_temperature_inc(decoding.temperature_increment);
+        params.set_no_speech_thold(decoding.no_speech_threshold);
+        params.set_logprob_thold(decoding.logprob_threshold);
+        params.set_suppress_blank(decoding.suppress_blank);
+        if let Some(prompt) = decoding.initial_prompt.as_deref() {
+            params.set_initial_prompt(prompt);
+        }
 
         // タイムスタンプの設定
         if include_timestamps {
             params.set_no_timestamps(false);
         }
 
+        // トークン単位のタイムスタンプ（単語タイムスタンプ用）
+        if options.word_timestamps {
+            params.set_token_timestamps(true);
+        }
+
+        // 話者交代の検出（tinydiarize モデルで話者分離を指定した場合）
+        if self.uses_tinydiarize(options) {
+            params.set_tdrz_enable(true);
+        }
+
         params
     }
 
+    /// 話者分離に tinydiarize の話者交代の検出を使うか
+    fn uses_tinydiarize(&self, options: &TranscribeOptions) -> bool {
+        self.tinydiarize && options.diarization.is_some()
+    }
+
     /// モデル情報を取得
     pub fn get_model_info(&self) -> ModelInfo {
         ModelInfo {
@@ -336,6 +1121,63 @@ impl WhisperEngine {
     }
 }
 
+/// whisper.cpp から呼ばれる進捗コールバック
+/// - `user_data` は `InferenceHooks::on_progress` を指す
+unsafe extern "C" fn progress_trampoline(
+    _ctx: *mut WhisperSysContext,
+    _state: *mut WhisperSysState,
+    progress: c_int,
+    user_data: *mut c_void,
+) {
+    let on_progress = &mut *(user_data as *mut Box<dyn FnMut(i32) + Send>);
+    on_progress(progress);
+}
+
+/// whisper.cpp から呼ばれる新しいセグメントのコールバック
+/// - `user_data` は `InferenceHooks::on_segment` を指す
+/// - 確定した末尾 `n_new` 個のセグメントを state から読み出して渡す
+unsafe extern "C" fn segment_trampoline(
+    _ctx: *mut WhisperSysContext,
+    state: *mut WhisperSysState,
+    n_new: c_int,
+    user_data: *mut c_void,
+) {
+    let on_segment = &mut *(user_data as *mut Box<dyn FnMut(TranscriptionSegment) + Send>);
+    let segment_count = whisper_rs_sys::whisper_full_n_segments_from_state(state);
+    for i in (segment_count - n_new).max(0)..segment_count {
+        let text = whisper_rs_sys::whisper_full_get_segment_text_from_state(state, i);
+        if text.is_null() {
+            continue;
+        }
+        let text = CStr::from_ptr(text).to_string_lossy();
+        let start_time = whisper_rs_sys::whisper_full_get_segment_t0_from_state(state, i);
+        let end_time = whisper_rs_sys::whisper_full_get_segment_t1_from_state(state, i);
+        on_segment(TranscriptionSegment::new(
+            text.trim().to_string(),
+            start_time.max(0) as u64 * 10, // centisecondsをミリ秒に変換
+            end_time.max(0) as u64 * 10,
+        ));
+    }
+}
+
+/// whisper.cpp から呼ばれる中断コールバック
+/// - `user_data` は `InferenceHooks::abort` のフラグを指す
+unsafe extern "C" fn abort_trampoline(user_data: *mut c_void) -> bool {
+    let abort = &*(user_data as *const AtomicBool);
+    abort.load(Ordering::SeqCst)
+}
+
+/// whisper.cpp から各ウィンドウのエンコード前に呼ばれるコールバック
+/// - `user_data` は `InferenceHooks::abort` のフラグを指す
+/// - false を返すとエンコードせずに推論を打ち切る
+unsafe extern "C" fn encoder_begin_trampoline(
+    _ctx: *mut WhisperSysContext,
+    _state: *mut WhisperSysState,
+    user_data: *mut c_void,
+) -> bool {
+    !abort_trampoline(user_data)
+}
+
 // Implement Debug without requiring inner WhisperContext to be Debug
 impl std::fmt::Debug for WhisperEngine {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -355,6 +1197,7 @@ impl Clone for WhisperEngine {
             language: self.language.clone(),
             whisper_threads: self.whisper_threads,
             enable_gpu: self.enable_gpu,
+            tinydiarize: self.tinydiarize,
         }
     }
 }
@@ -369,45 +1212,191 @@ pub struct ModelInfo {
 }
 
 /// Whisperエンジンプール（複数のリクエストを同時処理するため）
-/// - 現状のコードでは未使用だが、将来的なスループット向上に備えた構造
+/// - モデル（`WhisperContext`）は1度だけ読み込み、各エンジンで共有する
+///   （推論ごとに独立した `state` を作るため、同一コンテキストの並行利用が可能）
+/// - 同時推論数はセマフォの許可数で上限を設け、超過分はキューで待機させる
+///   （複数のプールでセマフォを共有すると、全モデル合計の同時推論数を制限できる）
+/// - セマフォは FIFO のため、待機中のリクエストは到着順に処理される
 pub struct WhisperEnginePool {
-    engines: Vec<WhisperEngine>,
-    current_index: std::sync::atomic::AtomicUsize,
+    idle: Mutex<Vec<WhisperEngine>>,
+    permits: Arc<Semaphore>,
+    pool_size: usize,
+    max_queued: usize,
+    queued: AtomicUsize,
+    model_info: ModelInfo,
 }
 
 impl WhisperEnginePool {
     /// 新しいエンジンプールを作成
+    /// - モデルを1度だけ読み込み、`pool_size` 個のエンジンで共有する
     pub fn new(model_path: &str, config: &Config, pool_size: usize) -> Result<Self> {
-        let mut engines = Vec::with_capacity(pool_size);
+        let engine = WhisperEngine::new(model_path, config)?;
+        Ok(Self::from_engine(
+            engine,
+            pool_size,
+            config.performance.max_queued_requests,
+        ))
+    }
 
-        for i in 0..pool_size {
-            let engine = WhisperEngine::new(model_path, config)
-                .map_err(|e| anyhow::anyhow!("エンジン{}の作成に失敗: {}", i, e))?;
-            engines.push(engine);
-        }
+    /// 読み込み済みのエンジンからプールを作成
+    /// - `pool_size` は1以上に丸める
+    pub fn from_engine(engine: WhisperEngine, pool_size: usize, max_queued: usize) -> Self {
+        let pool_size = pool_size.max(1);
+        Self::with_permits(
+            engine,
+            Arc::new(Semaphore::new(pool_size)),
+            pool_size,
+            max_queued,
+        )
+    }
+
+    /// 他のプールと共有するセマフォでプールを作成
+    /// - `permits` の許可数は `pool_size` 以下にする（許可を得ればアイドルのエンジンが必ずある）
+    pub fn with_permits(
+        engine: WhisperEngine,
+        permits: Arc<Semaphore>,
+        pool_size: usize,
+        max_queued: usize,
+    ) -> Self {
+        let pool_size = pool_size.max(1);
+        let model_info = engine.get_model_info();
+        let engines = vec![engine; pool_size];
 
         println!(
-            "Whisperエンジンプールを作成しました: {}個のエンジン",
-            pool_size
+            "Whisperエンジンプールを作成しました: {}個のエンジン（待機上限: {}）",
+            pool_size, max_queued
         );
 
-        Ok(Self {
-            engines,
-            current_index: std::sync::atomic::AtomicUsize::new(0),
-        })
+        Self {
+            idle: Mutex::new(engines),
+            permits,
+            pool_size,
+            max_queued,
+            queued: AtomicUsize::new(0),
+            model_info,
+        }
+    }
+
+    /// エンジンを借り出す（空きが無ければ待機キューに並ぶ）
+    /// - 待機数が上限に達している場合は `PoolError::QueueFull`
+    /// - 返されたガードを Drop するとエンジンはプールへ返却される
+    pub async fn acquire(self: &Arc<Self>) -> std::result::Result<PooledEngine, PoolError> {
+        // 空きがあれば待たずに取得
+        if let Ok(permit) = Arc::clone(&self.permits).try_acquire_owned() {
+            return Ok(self.checkout(permit));
+        }
+
+        // 待機キューへ（上限を超える場合は受け付けない）
+        let queued = self.queued.fetch_add(1, Ordering::SeqCst);
+        if queued >= self.max_queued {
+            self.queued.fetch_sub(1, Ordering::SeqCst);
+            return Err(PoolError::QueueFull);
+        }
+
+        // 待機中にリクエストがキャンセル（Future が Drop）されてもカウントを戻す
+        let _queued_guard = QueuedGuard(&self.queued);
+        let permit = Arc::clone(&self.permits)
+            .acquire_owned()
+            .await
+            .map_err(|_| PoolError::Closed)?;
+
+        Ok(self.checkout(permit))
+    }
+
+    /// 空いているエンジンがあれば待たずに借り出す（待機キューには並ばない）
+    /// - 1 リクエストの分割推論を並列にするために使う
+    pub fn try_acquire(self: &Arc<Self>) -> Option<PooledEngine> {
+        let permit = Arc::clone(&self.permits).try_acquire_owned().ok()?;
+        Some(self.checkout(permit))
     }
 
-    /// 利用可能なエンジンを取得（ラウンドロビン方式）
-    pub fn get_engine(&self) -> &WhisperEngine {
-        let index = self
-            .current_index
-            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-        &self.engines[index % self.engines.len()]
+    fn checkout(self: &Arc<Self>, permit: OwnedSemaphorePermit) -> PooledEngine {
+        let engine = self
+            .idle
+            .lock()
+            .unwrap()
+            .pop()
+            .expect("セマフォの許可数とアイドルエンジン数が一致していません");
+
+        PooledEngine {
+            engine: Some(engine),
+            pool: Arc::clone(self),
+            _permit: permit,
+        }
     }
 
     /// プール内のエンジン数を取得
     pub fn size(&self) -> usize {
-        self.engines.len()
+        self.pool_size
+    }
+
+    /// 空きエンジンを待っているリクエスト数
+    pub fn queued(&self) -> usize {
+        self.queued.load(Ordering::SeqCst)
+    }
+
+    /// 待機キューの上限
+    pub fn max_queued(&self) -> usize {
+        self.max_queued
+    }
+
+    /// 共有しているモデルの情報
+    pub fn get_model_info(&self) -> ModelInfo {
+        self.model_info.clone()
+    }
+}
+
+/// プールから借り出したエンジン
+/// - `Deref` で `WhisperEngine` として利用でき、Drop 時にプールへ返却される
+pub struct PooledEngine {
+    engine: Option<WhisperEngine>,
+    pool: Arc<WhisperEnginePool>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl std::ops::Deref for PooledEngine {
+    type Target = WhisperEngine;
+
+    fn deref(&self) -> &WhisperEngine {
+        self.engine.as_ref().expect("返却済みのエンジンです")
+    }
+}
+
+impl Drop for PooledEngine {
+    fn drop(&mut self) {
+        // 許可（_permit）より先にエンジンをアイドルへ戻すため、フィールド Drop 前に返却
+        if let Some(engine) = self.engine.take() {
+            self.pool.idle.lock().unwrap().push(engine);
+        }
+    }
+}
+
+/// エンジン取得時のエラー
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PoolError {
+    /// 待機キューが上限に達している
+    QueueFull,
+    /// プールが閉じられている
+    Closed,
+}
+
+impl std::fmt::Display for PoolError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PoolError::QueueFull => write!(f, "処理待ちのリクエストが上限に達しています"),
+            PoolError::Closed => write!(f, "Whisperエンジンプールが閉じられています"),
+        }
+    }
+}
+
+impl std::error::Error for PoolError {}
+
+/// 待機カウントを Drop で確実に減らすためのガード
+struct QueuedGuard<'a>(&'a AtomicUsize);
+
+impl Drop for QueuedGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
     }
 }
 
@@ -415,6 +1404,21 @@ impl WhisperEnginePool {
 // Utility Functions
 // =============================================================================
 
+/// セグメントのテキストを結合して全文にする（空なら認識できなかった旨の文言）
+fn joined_text(segments: &[TranscriptionSegment]) -> String {
+    let text = segments
+        .iter()
+        .map(|segment| segment.text.as_str())
+        .collect::<String>()
+        .trim()
+        .to_string();
+    if text.is_empty() {
+        "(音声を認識できませんでした)".to_string()
+    } else {
+        text
+    }
+}
+
 /// 音声データの前処理（ノイズ除去等）
 /// - まずは振幅の基本正規化のみ。
 /// - 追加のフィルタ処理は必要に応じて拡張可能。
@@ -0,0 +1,380 @@
+use std::io::Cursor;
+use tempfile::TempDir;
+use WhisperBackendAPI::{audio::*, config::Config};
+
+#[cfg(test)]
+mod audio_format_tests {
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 16000;
+    const FRAMES: usize = 8000;
+
+    /// テスト信号（440Hz、振幅 0.5 のサイン波）
+    fn test_signal() -> Vec<f64> {
+        (0..FRAMES)
+            .map(|i| {
+                (2.0 * std::f64::consts::PI * 440.0 * i as f64 / SAMPLE_RATE as f64).sin() * 0.5
+            })
+            .collect()
+    }
+
+    /// テスト用設定を作成（リサンプリングが起きないよう 16kHz）
+    fn create_test_config(temp_dir: &TempDir) -> Config {
+        let mut config = Config::default();
+        config.paths.temp_dir = temp_dir.path().to_string_lossy().to_string();
+        config.audio.sample_rate = SAMPLE_RATE;
+        config
+    }
+
+    /// デコード結果がテスト信号と一致するか確認
+    fn decode_and_compare(data: &[u8], filename: &str, tolerance: f32) -> ProcessedAudio {
+        let temp_dir = TempDir::new().unwrap();
+        let config = create_test_config(&temp_dir);
+        let mut processor = AudioProcessor::new(&config).unwrap();
+
+        let processed = processor
+            .process_audio_from_source(Box::new(Cursor::new(data.to_vec())), filename)
+            .unwrap_or_else(|e| panic!("{}: {}", filename, e));
+
+        assert_eq!(processed.samples.len(), FRAMES, "{}", filename);
+        for (actual, expected) in processed.samples.iter().zip(test_signal()) {
+            assert!(
+                (actual - expected as f32).abs() < tolerance,
+                "{}: {} != {}",
+                filename,
+                actual,
+                expected
+            );
+        }
+        processed
+    }
+
+    /// 各チャンネルに同じ信号を入れた WAV を生成
+    /// - `format_tag` 1 = 整数 PCM、3 = IEEE 浮動小数点
+    fn create_wav(format_tag: u16, bits: u16, channels: u16) -> Vec<u8> {
+        let mut data = Vec::new();
+        for value in test_signal() {
+            for _ in 0..channels {
+                match (format_tag, bits) {
+                    (1, 8) => data.push(((value * 127.0).round() as i16 + 128) as u8),
+                    (1, 16) => {
+                        data.extend_from_slice(&((value * 32767.0).round() as i16).to_le_bytes())
+                    }
+                    (1, 24) => data.extend_from_slice(
+                        &((value * 8_388_607.0).round() as i32).to_le_bytes()[..3],
+                    ),
+                    (1, 32) => data.extend_from_slice(
+                        &((value * 2_147_483_647.0).round() as i32).to_le_bytes(),
+                    ),
+                    (3, 32) => data.extend_from_slice(&(value as f32).to_le_bytes()),
+                    (3, 64) => data.extend_from_slice(&value.to_le_bytes()),
+                    _ => unreachable!(),
+                }
+            }
+        }
+
+        let block_align = channels * bits / 8;
+        let mut wav = Vec::new();
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + data.len() as u32).to_le_bytes());
+        wav.extend_from_slice(b"WAVE");
+        wav.extend_from_slice(b"fmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        wav.extend_from_slice(&format_tag.to_le_bytes());
+        wav.extend_from_slice(&channels.to_le_bytes());
+        wav.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+        wav.extend_from_slice(&(SAMPLE_RATE * block_align as u32).to_le_bytes());
+        wav.extend_from_slice(&block_align.to_le_bytes());
+        wav.extend_from_slice(&bits.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        wav.extend_from_slice(&data);
+        wav
+    }
+
+    /// 8bit 符号付き PCM の AIFF を生成
+    fn create_aiff_s8() -> Vec<u8> {
+        let data: Vec<u8> = test_signal()
+            .iter()
+            .map(|value| (value * 127.0).round() as i8 as u8)
+            .collect();
+
+        // 16000Hz を 80bit 拡張精度浮動小数点で表す（16000 = 1.953125 * 2^13）
+        let mut rate = Vec::new();
+        rate.extend_from_slice(&(16383u16 + 13).to_be_bytes());
+        rate.extend_from_slice(&((SAMPLE_RATE as u64) << (63 - 13)).to_be_bytes());
+
+        let mut comm = Vec::new();
+        comm.extend_from_slice(&1u16.to_be_bytes());
+        comm.extend_from_slice(&(FRAMES as u32).to_be_bytes());
+        comm.extend_from_slice(&8u16.to_be_bytes());
+        comm.extend_from_slice(&rate);
+
+        let mut body = Vec::new();
+        body.extend_from_slice(b"AIFF");
+        body.extend_from_slice(b"COMM");
+        body.extend_from_slice(&(comm.len() as u32).to_be_bytes());
+        body.extend_from_slice(&comm);
+        body.extend_from_slice(b"SSND");
+        body.extend_from_slice(&(8 + data.len() as u32).to_be_bytes());
+        body.extend_from_slice(&0u32.to_be_bytes());
+        body.extend_from_slice(&0u32.to_be_bytes());
+        body.extend_from_slice(&data);
+
+        let mut aiff = Vec::new();
+        aiff.extend_from_slice(b"FORM");
+        aiff.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        aiff.extend_from_slice(&body);
+        aiff
+    }
+
+    fn crc8(data: &[u8]) -> u8 {
+        let mut crc = 0u8;
+        for byte in data {
+            crc ^= byte;
+            for _ in 0..8 {
+                crc = if crc & 0x80 != 0 {
+                    (crc << 1) ^ 0x07
+                } else {
+                    crc << 1
+                };
+            }
+        }
+        crc
+    }
+
+    fn crc16(data: &[u8]) -> u16 {
+        let mut crc = 0u16;
+        for byte in data {
+            crc ^= (*byte as u16) << 8;
+            for _ in 0..8 {
+                crc = if crc & 0x8000 != 0 {
+                    (crc << 1) ^ 0x8005
+                } else {
+                    crc << 1
+                };
+            }
+        }
+        crc
+    }
+
+    /// 左に テスト信号、右に 符号を反転した信号を入れた 16bit ステレオ WAV を生成
+    fn create_split_stereo_wav() -> Vec<u8> {
+        let mut wav = create_wav(1, 16, 2);
+        let data_start = wav.len() - FRAMES * 4;
+        for frame in wav[data_start..].chunks_mut(4) {
+            let left = i16::from_le_bytes([frame[0], frame[1]]);
+            frame[2..].copy_from_slice(&(-left).to_le_bytes());
+        }
+        wav
+    }
+
+    /// 指定したチャンネルモードでデコード
+    fn decode_with_mode(data: &[u8], mode: ChannelMode) -> anyhow::Result<ProcessedAudio> {
+        let temp_dir = TempDir::new().unwrap();
+        let config = create_test_config(&temp_dir);
+        let mut processor = AudioProcessor::new(&config).unwrap();
+        processor.set_channel_mode(mode);
+        processor.process_audio_from_source(Box::new(Cursor::new(data.to_vec())), "split.wav")
+    }
+
+    fn assert_signal(samples: &[f32], sign: f32) {
+        assert_eq!(samples.len(), FRAMES);
+        for (actual, expected) in samples.iter().zip(test_signal()) {
+            assert!((actual - sign * expected as f32).abs() < 0.001);
+        }
+    }
+
+    /// FLAC のフレーム（モノラル 16bit、verbatim サブフレーム）を生成
+    fn flac_frame(number: u8, samples: &[i16]) -> Vec<u8> {
+        let mut frame = vec![0xFF, 0xF8, 0x70, 0x08, number];
+        frame.extend_from_slice(&(samples.len() as u16 - 1).to_be_bytes());
+        frame.push(crc8(&frame));
+        frame.push(0x02);
+        for sample in samples {
+            frame.extend_from_slice(&sample.to_be_bytes());
+        }
+        let crc = crc16(&frame);
+        frame.extend_from_slice(&crc.to_be_bytes());
+        frame
+    }
+
+    /// FLAC の STREAMINFO を含む Matroska の CodecPrivate
+    fn flac_codec_private(block_len: u16) -> Vec<u8> {
+        let mut info = Vec::new();
+        info.extend_from_slice(&block_len.to_be_bytes());
+        info.extend_from_slice(&block_len.to_be_bytes());
+        info.extend_from_slice(&[0; 6]);
+        let packed = ((SAMPLE_RATE as u64) << 44) | (15u64 << 36) | FRAMES as u64;
+        info.extend_from_slice(&packed.to_be_bytes());
+        info.extend_from_slice(&[0; 16]);
+
+        let mut private = b"fLaC".to_vec();
+        private.push(0x80);
+        private.extend_from_slice(&(info.len() as u32).to_be_bytes()[1..]);
+        private.extend_from_slice(&info);
+        private
+    }
+
+    /// EBML 要素（サイズは 8 バイトの可変長整数で表す）
+    fn ebml(id: u32, data: &[u8]) -> Vec<u8> {
+        let mut element: Vec<u8> = id
+            .to_be_bytes()
+            .into_iter()
+            .skip_while(|b| *b == 0)
+            .collect();
+        element.push(0x01);
+        element.extend_from_slice(&(data.len() as u64).to_be_bytes()[1..]);
+        element.extend_from_slice(data);
+        element
+    }
+
+    fn ebml_uint(id: u32, value: u64) -> Vec<u8> {
+        ebml(id, &value.to_be_bytes())
+    }
+
+    /// 映像・Opus（デコーダーなし）・FLAC の 3 トラックを持つ Matroska を生成
+    /// - 音声として使えるのは 3 番目の FLAC トラックだけ
+    fn create_multi_track_mkv() -> Vec<u8> {
+        let block_len = 800usize;
+
+        let video = [
+            ebml_uint(0xD7, 1),
+            ebml_uint(0x73C5, 1),
+            ebml(0x86, b"V_VP8"),
+        ]
+        .concat();
+        let opus = [
+            ebml_uint(0xD7, 2),
+            ebml_uint(0x73C5, 2),
+            ebml(0x86, b"A_OPUS"),
+            ebml(
+                0xE1,
+                &[ebml(0xB5, &48000f64.to_be_bytes()), ebml_uint(0x9F, 2)].concat(),
+            ),
+        ]
+        .concat();
+        let flac = [
+            ebml_uint(0xD7, 3),
+            ebml_uint(0x73C5, 3),
+            ebml(0x86, b"A_FLAC"),
+            ebml(0x63A2, &flac_codec_private(block_len as u16)),
+            ebml(
+                0xE1,
+                &[
+                    ebml(0xB5, &(SAMPLE_RATE as f64).to_be_bytes()),
+                    ebml_uint(0x9F, 1),
+                    ebml_uint(0x6264, 16),
+                ]
+                .concat(),
+            ),
+        ]
+        .concat();
+        let tracks = ebml(
+            0x1654AE6B,
+            &[ebml(0xAE, &video), ebml(0xAE, &opus), ebml(0xAE, &flac)].concat(),
+        );
+        let info = ebml(0x1549A966, &ebml_uint(0x2AD7B1, 1_000_000));
+
+        let samples: Vec<i16> = test_signal()
+            .iter()
+            .map(|value| (value * 32767.0).round() as i16)
+            .collect();
+        let mut cluster = ebml_uint(0xE7, 0);
+        // 他トラックのパケットは読み飛ばされる
+        cluster.extend(ebml(0xA3, &[0x82, 0x00, 0x00, 0x80, 0xFC, 0xFF, 0xFE]));
+        for (number, chunk) in samples.chunks(block_len).enumerate() {
+            let mut block = vec![0x83, 0x00, 0x00, 0x80];
+            block.extend(flac_frame(number as u8, chunk));
+            cluster.extend(ebml(0xA3, &block));
+        }
+
+        let segment = [info, tracks, ebml(0x1F43B675, &cluster)].concat();
+        [
+            ebml(0x1A45DFA3, &ebml(0x4282, b"matroska")),
+            ebml(0x18538067, &segment),
+        ]
+        .concat()
+    }
+
+    /// WAV の各ビット深度（8/16/24/32bit 整数、32/64bit 浮動小数点）をデコードできる
+    #[test]
+    fn test_decode_wav_bit_depths() {
+        let cases = [
+            (1, 8, 0.02),
+            (1, 16, 0.001),
+            (1, 24, 0.001),
+            (1, 32, 0.001),
+            (3, 32, 0.001),
+            (3, 64, 0.001),
+        ];
+
+        for (format_tag, bits, tolerance) in cases {
+            let wav = create_wav(format_tag, bits, 1);
+            let filename = format!("test_{}_{}.wav", format_tag, bits);
+            let processed = decode_and_compare(&wav, &filename, tolerance);
+            assert_eq!(processed.original_metadata.sample_rate, SAMPLE_RATE);
+        }
+    }
+
+    /// 多チャンネルはどのサンプル形式でもモノラルにまとめられる
+    #[test]
+    fn test_decode_stereo_24bit() {
+        let wav = create_wav(1, 24, 2);
+        let processed = decode_and_compare(&wav, "stereo.wav", 0.001);
+        assert_eq!(processed.original_metadata.channels, 2);
+    }
+
+    /// 8bit 符号付き PCM（AIFF）をデコードできる
+    #[test]
+    fn test_decode_aiff_s8() {
+        let aiff = create_aiff_s8();
+        decode_and_compare(&aiff, "test.aiff", 0.02);
+    }
+
+    /// 複数トラックの Matroska ではデコードできる音声トラックを選ぶ
+    #[test]
+    fn test_decode_multi_track_container() {
+        let mkv = create_multi_track_mkv();
+        let processed = decode_and_compare(&mkv, "multi.mkv", 0.001);
+        assert_eq!(processed.original_metadata.sample_rate, SAMPLE_RATE);
+        assert_eq!(processed.original_metadata.format, "mkv");
+    }
+    /// チャンネルモードで左右・番号指定のチャンネルを取り出せる
+    #[test]
+    fn test_channel_mode_select() {
+        let wav = create_split_stereo_wav();
+
+        let mixed = decode_with_mode(&wav, ChannelMode::Mix).unwrap();
+        assert!(mixed.samples.iter().all(|s| s.abs() < 0.001));
+
+        let left = decode_with_mode(&wav, ChannelMode::Left).unwrap();
+        assert_signal(&left.samples, 1.0);
+
+        let right = decode_with_mode(&wav, ChannelMode::Right).unwrap();
+        assert_signal(&right.samples, -1.0);
+
+        let index = decode_with_mode(&wav, ChannelMode::Index(1)).unwrap();
+        assert_signal(&index.samples, -1.0);
+    }
+
+    /// 存在しないチャンネル番号はエラーになる
+    #[test]
+    fn test_channel_mode_index_out_of_range() {
+        let wav = create_split_stereo_wav();
+        let error = decode_with_mode(&wav, ChannelMode::Index(2)).unwrap_err();
+        assert!(error.to_string().contains("チャンネル 2 は存在しません"));
+    }
+
+    /// separate ではチャンネルごとのサンプルを返す
+    #[test]
+    fn test_channel_mode_separate() {
+        let wav = create_split_stereo_wav();
+        let processed = decode_with_mode(&wav, ChannelMode::Separate).unwrap();
+
+        assert!(processed.samples.is_empty());
+        assert_eq!(processed.channel_samples.len(), 2);
+        assert_signal(&processed.channel_samples[0], 1.0);
+        assert_signal(&processed.channel_samples[1], -1.0);
+    }
+}
@@ -1,5 +1,5 @@
 use std::fs;
-use std::io::Write;
+use std::io::{Cursor, Write};
 use tempfile::TempDir;
 use WhisperBackendAPI::{audio::*, config::Config};
 
@@ -156,6 +156,7 @@ mod audio_tests {
 
         let processed = ProcessedAudio {
             samples: samples.clone(),
+            channel_samples: Vec::new(),
             sample_rate: 16000,
             duration_ms: 250,
             original_metadata: metadata,
@@ -261,31 +262,6 @@ mod audio_tests {
             .contains("音声ファイルが長すぎます"));
     }
 
-    /// create_temp_file_from_bytesのテスト
-    #[test]
-    fn test_create_temp_file_from_bytes() {
-        let temp_dir = TempDir::new().unwrap();
-        let config = create_test_config(&temp_dir);
-        let processor = AudioProcessor::new(&config).unwrap();
-
-        let test_data = b"test audio data";
-        let filename = "test.wav";
-
-        let temp_file = processor
-            .create_temp_file_from_bytes(test_data, filename)
-            .unwrap();
-
-        // ファイルが作成されることを確認
-        assert!(temp_file.path().exists());
-
-        // ファイル内容が正しいことを確認
-        let content = fs::read(temp_file.path()).unwrap();
-        assert_eq!(content, test_data);
-
-        // 拡張子が適切であることを確認
-        assert!(temp_file.path().to_string_lossy().ends_with(".wav"));
-    }
-
     /// probe_metadataのテスト（実際のWAVファイル）
     #[test]
     fn test_probe_metadata_wav() {
@@ -366,9 +342,9 @@ mod audio_tests {
             .contains("音声ファイルが見つかりません"));
     }
 
-    /// process_audio_from_bytes - 統合テスト
+    /// process_audio_from_source - 統合テスト（メモリ上のバイト列）
     #[test]
-    fn test_process_audio_from_bytes() {
+    fn test_process_audio_from_source() {
         let temp_dir = TempDir::new().unwrap();
         let config = create_test_config(&temp_dir);
         let mut processor = AudioProcessor::new(&config).unwrap();
@@ -378,7 +354,7 @@ mod audio_tests {
         let filename = "test.wav";
 
         let processed = processor
-            .process_audio_from_bytes(&wav_data, filename)
+            .process_audio_from_source(Box::new(Cursor::new(wav_data.clone())), filename)
             .unwrap();
 
         assert!(!processed.samples.is_empty());
@@ -431,6 +407,76 @@ mod audio_tests {
         // 約8000サンプル
     }
 
+    /// バイト列からの処理では一時ディレクトリにファイルを作らない
+    #[test]
+    fn test_process_audio_from_source_without_temp_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = create_test_config(&temp_dir);
+        let mut processor = AudioProcessor::new(&config).unwrap();
+
+        let wav_data = create_test_wav_data(16000, 1.0);
+        let processed = processor
+            .process_audio_from_source(Box::new(Cursor::new(wav_data.clone())), "test.wav")
+            .unwrap();
+
+        assert_eq!(processed.samples.len(), 16000);
+        assert_eq!(
+            processed.original_metadata.file_size_bytes,
+            wav_data.len() as u64
+        );
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 0);
+    }
+
+    /// チャンク単位のリサンプリングで長さと波形（周波数）が保たれる
+    #[test]
+    fn test_chunked_resampling_long_input() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = create_test_config(&temp_dir);
+        let mut processor = AudioProcessor::new(&config).unwrap();
+
+        // チャンク境界を多数またぐ長さ（30秒）
+        let wav_data = create_test_wav_data(44100, 30.0);
+        let processed = processor
+            .process_audio_from_source(Box::new(Cursor::new(wav_data.clone())), "long.wav")
+            .unwrap();
+
+        assert_eq!(processed.samples.len(), 30 * 16000);
+        assert_eq!(processed.duration_ms, 30_000);
+
+        // 440Hz のサイン波とほぼ一致する（チャンク境界で位相がずれない）
+        let max_error = processed
+            .samples
+            .iter()
+            .enumerate()
+            .skip(1000)
+            .take(processed.samples.len() - 2000)
+            .map(|(i, sample)| {
+                let t = i as f32 / 16000.0;
+                let expected = (2.0 * std::f32::consts::PI * 440.0 * t).sin() * 16383.0 / 32767.0;
+                (sample - expected).abs()
+            })
+            .fold(0.0f32, f32::max);
+        assert!(max_error < 0.1, "max_error = {}", max_error);
+    }
+
+    /// 最大再生時間を超える音声はエラーになる
+    #[test]
+    fn test_process_audio_too_long() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = create_test_config(&temp_dir);
+        config.limits.max_audio_duration_minutes = 0;
+        let mut processor = AudioProcessor::new(&config).unwrap();
+
+        let wav_data = create_test_wav_data(16000, 1.0);
+        let result = processor
+            .process_audio_from_source(Box::new(Cursor::new(wav_data.clone())), "test.wav");
+
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("音声ファイルが長すぎます"));
+    }
+
     /// 空のファイルに対するエラーハンドリング
     #[test]
     fn test_empty_file_error() {
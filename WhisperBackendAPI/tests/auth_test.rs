@@ -0,0 +1,500 @@
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+    middleware,
+    routing::{get, post},
+    Router,
+};
+use std::time::{Duration, Instant};
+use tower::ServiceExt;
+use WhisperBackendAPI::{
+    auth::{check_admin, check_quota, ApiKeyAuth, AuthRejection, UsageFile},
+    config::{ApiKeyConfig, AuthConfig, Config},
+    handlers::{cors_layer, get_languages, get_stats, health_check, require_api_key, AppState},
+    models::{ApiErrorCode, ApiKeyUsage, ServerStats},
+};
+
+#[cfg(test)]
+mod auth_tests {
+    use super::*;
+
+    fn key(name: &str, requests_per_minute: u32, monthly_audio_minutes: u64) -> ApiKeyConfig {
+        ApiKeyConfig {
+            name: name.to_string(),
+            key: format!("{}-secret", name),
+            requests_per_minute,
+            monthly_audio_minutes,
+            admin: false,
+        }
+    }
+
+    fn auth_config(keys: Vec<ApiKeyConfig>) -> AuthConfig {
+        AuthConfig {
+            enabled: true,
+            keys,
+            ..Default::default()
+        }
+    }
+
+    /// 認証ミドルウェアを通したテスト用ルーター
+    fn router(keys: Vec<ApiKeyConfig>) -> Router {
+        let mut config = Config::default();
+        config.auth = auth_config(keys);
+        let state = AppState::new(config);
+        Router::new()
+            .route("/health", get(health_check))
+            .route("/languages", get(get_languages))
+            .route("/stats", get(get_stats))
+            .route("/models/{name}/load", post(|| async { "loaded" }))
+            .layer(middleware::from_fn_with_state(
+                state.clone(),
+                require_api_key,
+            ))
+            .with_state(state)
+    }
+
+    fn request(method: Method, uri: &str, bearer: Option<&str>) -> Request<Body> {
+        let mut builder = Request::builder().method(method).uri(uri);
+        if let Some(token) = bearer {
+            builder = builder.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        builder.body(Body::empty()).unwrap()
+    }
+
+    /// 既定では無効、`[auth]` の無い設定ファイルも読み込める
+    #[test]
+    fn test_auth_config_default() {
+        let config = Config::default();
+        assert!(!config.auth.enabled);
+        assert_eq!(config.auth.public_paths, vec!["/health"]);
+
+        let mut value = toml::Value::try_from(&config).unwrap();
+        value.as_table_mut().unwrap().remove("auth");
+        let loaded: Config = toml::from_str(&toml::to_string(&value).unwrap()).unwrap();
+        assert_eq!(loaded.auth, AuthConfig::default());
+    }
+
+    /// `[[auth.keys]]` を読み込み、省略した上限は無制限（0）になる
+    #[test]
+    fn test_auth_config_keys_from_toml() {
+        let auth: AuthConfig = toml::from_str(
+            r#"
+            enabled = true
+
+            [[keys]]
+            name = "team-a"
+            key = "secret-a"
+            requests_per_minute = 30
+            monthly_audio_minutes = 600
+
+            [[keys]]
+            name = "team-b"
+            key = "secret-b"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(auth.public_paths, vec!["/health"]);
+        assert_eq!(auth.keys.len(), 2);
+        assert_eq!(auth.keys[0].requests_per_minute, 30);
+        assert_eq!(auth.keys[0].monthly_audio_minutes, 600);
+        assert_eq!(auth.keys[1].requests_per_minute, 0);
+        assert_eq!(auth.keys[1].monthly_audio_minutes, 0);
+    }
+
+    /// 有効なのにキーが無い、名前/キーが重複する設定はエラー
+    #[test]
+    fn test_validate_auth() {
+        let mut config = Config::default();
+        assert!(config.validate_auth().is_ok());
+
+        config.auth = auth_config(Vec::new());
+        assert!(config.validate_auth().is_err());
+
+        config.auth = auth_config(vec![key("team-a", 0, 0), key("team-b", 0, 0)]);
+        assert!(config.validate_auth().is_ok());
+
+        let mut duplicate = key("team-b", 0, 0);
+        duplicate.name = "team-a".to_string();
+        config.auth = auth_config(vec![key("team-a", 0, 0), duplicate]);
+        assert!(config.validate_auth().is_err());
+
+        let mut duplicate = key("team-b", 0, 0);
+        duplicate.key = "team-a-secret".to_string();
+        config.auth = auth_config(vec![key("team-a", 0, 0), duplicate]);
+        assert!(config.validate_auth().is_err());
+    }
+
+    /// Bearer トークンからキーを特定する
+    #[test]
+    fn test_authenticate() {
+        let auth = ApiKeyAuth::new(auth_config(vec![key("team-a", 0, 0), key("team-b", 0, 0)]));
+
+        assert_eq!(
+            auth.authenticate(Some("Bearer team-b-secret"))
+                .unwrap()
+                .name,
+            "team-b"
+        );
+        assert_eq!(
+            auth.authenticate(Some("bearer  team-a-secret "))
+                .unwrap()
+                .name,
+            "team-a"
+        );
+        assert_eq!(auth.authenticate(None), Err(AuthRejection::MissingKey));
+        assert_eq!(
+            auth.authenticate(Some("Basic team-a-secret")),
+            Err(AuthRejection::MissingKey)
+        );
+        assert_eq!(
+            auth.authenticate(Some("Bearer team-a")),
+            Err(AuthRejection::InvalidKey)
+        );
+    }
+
+    /// 直近 1 分間の件数で制限し、期間を過ぎれば再び受け付ける
+    #[test]
+    fn test_check_rate() {
+        let team_a = key("team-a", 2, 0);
+        let team_b = key("team-b", 2, 0);
+        let auth = ApiKeyAuth::new(auth_config(vec![team_a.clone(), team_b.clone()]));
+        let start = Instant::now();
+
+        assert!(auth.check_rate(&team_a, start).is_ok());
+        assert!(auth
+            .check_rate(&team_a, start + Duration::from_secs(20))
+            .is_ok());
+        match auth.check_rate(&team_a, start + Duration::from_secs(30)) {
+            Err(AuthRejection::RateLimited { limit, retry_after }) => {
+                assert_eq!(limit, 2);
+                assert_eq!(retry_after, Duration::from_secs(30));
+            }
+            other => panic!("レート制限されるはず: {:?}", other),
+        }
+        // キーごとに数える
+        assert!(auth
+            .check_rate(&team_b, start + Duration::from_secs(30))
+            .is_ok());
+        // 最初のリクエストから 1 分経てば受け付ける
+        assert!(auth
+            .check_rate(&team_a, start + Duration::from_secs(60))
+            .is_ok());
+
+        // 0 は無制限
+        let unlimited = key("team-c", 0, 0);
+        for _ in 0..100 {
+            assert!(auth.check_rate(&unlimited, start).is_ok());
+        }
+    }
+
+    /// 管理操作のルートは管理者キーだけに許可する
+    #[test]
+    fn test_check_admin() {
+        let auth = ApiKeyAuth::new(auth_config(vec![key("team-a", 0, 0)]));
+        assert!(auth.requires_admin("POST", "/models/{name}/load"));
+        assert!(auth.requires_admin("POST", "/admin/models/{name}/download"));
+        assert!(auth.requires_admin("PUT", "/glossaries/{name}"));
+        assert!(!auth.requires_admin("DELETE", "/history/{id}"));
+        assert!(!auth.requires_admin("GET", "/glossaries/{name}"));
+        assert!(!auth.requires_admin("GET", "/history/{id}"));
+        assert!(!auth.requires_admin("POST", "/transcribe"));
+
+        let mut admin = key("ops", 0, 0);
+        admin.admin = true;
+        assert!(check_admin(&admin, true).is_ok());
+        assert!(check_admin(&key("team-a", 0, 0), false).is_ok());
+        assert_eq!(
+            check_admin(&key("team-a", 0, 0), true),
+            Err(AuthRejection::AdminRequired)
+        );
+    }
+
+    /// 今月の使用量が上限に達したら拒否する
+    #[test]
+    fn test_check_quota() {
+        let limited = key("team-a", 0, 10);
+        assert!(check_quota(&limited, 9 * 60_000 + 59_999).is_ok());
+        assert_eq!(
+            check_quota(&limited, 10 * 60_000),
+            Err(AuthRejection::QuotaExceeded {
+                used_minutes: 10,
+                quota_minutes: 10
+            })
+        );
+        assert!(check_quota(&key("team-b", 0, 0), u64::MAX).is_ok());
+    }
+
+    /// 月が変わると月間使用量を数え直す（累計は残す）
+    #[test]
+    fn test_api_key_usage_month() {
+        let mut usage = ApiKeyUsage::default();
+        usage.add_audio("2026-09", 60_000);
+        usage.add_audio("2026-09", 30_000);
+        assert_eq!(usage.month_audio_ms("2026-09"), 90_000);
+        assert_eq!(usage.month_audio_ms("2026-10"), 0);
+
+        usage.add_audio("2026-10", 10_000);
+        assert_eq!(usage.month_audio_ms("2026-10"), 10_000);
+        assert_eq!(usage.total_audio_duration_ms, 100_000);
+    }
+
+    /// キー別の使用量を統計に記録する（キー無しのリクエストは記録しない）
+    #[test]
+    fn test_server_stats_key_usage() {
+        let mut stats = ServerStats::default();
+        stats.record_key_request("team-a", true);
+        stats.record_key_request("team-a", false);
+        stats.record_key_success(Some("team-a"), Some(120_000));
+        stats.record_key_success(None, Some(60_000));
+
+        let usage = &stats.api_keys["team-a"];
+        assert_eq!(usage.requests, 1);
+        assert_eq!(usage.rejected_requests, 1);
+        assert_eq!(usage.successful_transcriptions, 1);
+        assert_eq!(stats.key_month_audio_ms("team-a"), 120_000);
+        assert_eq!(stats.api_keys.len(), 1);
+    }
+
+    /// 使用量をファイルに書き出し、読み戻せる（無い/壊れたファイルは空）
+    #[test]
+    fn test_usage_file_round_trip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = UsageFile::new(dir.path().join("auth").join("usage.json"));
+        assert!(file.load().is_empty());
+
+        let mut stats = ServerStats::default();
+        stats.record_key_success(Some("team-a"), Some(120_000));
+        stats.record_key_audio("team-a", 30_000);
+        file.save(|| stats.api_keys.clone()).unwrap();
+
+        let loaded = file.load();
+        assert_eq!(loaded, stats.api_keys);
+        assert_eq!(loaded["team-a"].successful_transcriptions, 1);
+
+        std::fs::write(dir.path().join("auth").join("usage.json"), b"{broken").unwrap();
+        assert!(file.load().is_empty());
+    }
+
+    /// 再起動しても月間使用量は数え直さず、クォータを適用し続ける
+    #[test]
+    fn test_key_usage_survives_restart() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.paths.upload_dir = dir.path().to_string_lossy().to_string();
+        config.auth = auth_config(vec![key("team-a", 0, 1)]);
+
+        let state = AppState::new(config.clone());
+        state.record_key_success(Some("team-a"), Some(45_000));
+        state.record_key_audio(Some("team-a"), 15_000);
+        state.record_key_success(None, Some(60_000));
+        drop(state);
+
+        let restarted = AppState::new(config);
+        let stats = restarted.stats.lock().unwrap();
+        assert_eq!(stats.key_month_audio_ms("team-a"), 60_000);
+        assert_eq!(stats.api_keys["team-a"].successful_transcriptions, 1);
+        assert_eq!(
+            check_quota(&key("team-a", 0, 1), stats.key_month_audio_ms("team-a")),
+            Err(AuthRejection::QuotaExceeded {
+                used_minutes: 1,
+                quota_minutes: 1
+            })
+        );
+    }
+
+    /// 受付後に増えた使用量でもクォータを確認できる（一括の各ファイルの前に使う）
+    #[test]
+    fn test_check_key_quota_after_admission() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.paths.upload_dir = dir.path().to_string_lossy().to_string();
+        config.auth = auth_config(vec![key("team-a", 0, 1), key("team-b", 0, 0)]);
+        let state = AppState::new(config);
+
+        assert!(state.check_key_quota(Some("team-a")).is_ok());
+        state.record_key_success(Some("team-a"), Some(60_000));
+        let error = state.check_key_quota(Some("team-a")).unwrap_err();
+        assert!(matches!(error.code, ApiErrorCode::QuotaExceeded));
+        assert_eq!(error.status_code(), StatusCode::TOO_MANY_REQUESTS);
+
+        state.record_key_success(Some("team-b"), Some(600_000));
+        assert!(state.check_key_quota(Some("team-b")).is_ok());
+        assert!(state.check_key_quota(None).is_ok());
+    }
+
+    /// キーの無い/誤ったリクエストは 401、公開パスと OPTIONS は通す
+    #[tokio::test]
+    async fn test_middleware_requires_key() {
+        let app = router(vec![key("team-a", 0, 0)]);
+
+        let response = app
+            .clone()
+            .oneshot(request(Method::GET, "/languages", None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(response.headers()[header::WWW_AUTHENTICATE], "Bearer");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["code"], "UNAUTHORIZED");
+
+        let response = app
+            .clone()
+            .oneshot(request(Method::GET, "/languages", Some("wrong")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = app
+            .clone()
+            .oneshot(request(Method::GET, "/languages", Some("team-a-secret")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app
+            .clone()
+            .oneshot(request(Method::GET, "/health", None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app
+            .oneshot(request(Method::OPTIONS, "/languages", None))
+            .await
+            .unwrap();
+        assert_ne!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    /// 管理者でないキーの管理操作は 403、管理者キーは通す
+    #[tokio::test]
+    async fn test_middleware_admin_routes() {
+        let mut admin = key("ops", 0, 0);
+        admin.admin = true;
+        let app = router(vec![key("team-a", 0, 0), admin]);
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                Method::POST,
+                "/models/base/load",
+                Some("team-a-secret"),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["code"], "FORBIDDEN");
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                Method::POST,
+                "/models/base/load",
+                Some("ops-secret"),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // 管理操作以外は管理者でなくても使える
+        let response = app
+            .oneshot(request(Method::GET, "/languages", Some("team-a-secret")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    /// レート制限を超えると 429 と Retry-After を返し、統計に記録する
+    #[tokio::test]
+    async fn test_middleware_rate_limit() {
+        let app = router(vec![key("team-a", 1, 0)]);
+
+        let response = app
+            .clone()
+            .oneshot(request(Method::GET, "/languages", Some("team-a-secret")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app
+            .clone()
+            .oneshot(request(Method::GET, "/languages", Some("team-a-secret")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        let retry_after: u64 = response.headers()[header::RETRY_AFTER]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!((1..=60).contains(&retry_after));
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["code"], "RATE_LIMITED");
+
+        // 受け付けたリクエストはキー別に /stats へ出る
+        let response = router(vec![key("team-a", 0, 0)])
+            .oneshot(request(Method::GET, "/stats", Some("team-a-secret")))
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let stats: ServerStats = serde_json::from_slice(&body).unwrap();
+        assert_eq!(stats.api_keys["team-a"].requests, 1);
+    }
+
+    /// `cors_origins` に含まれるオリジンだけを許可する
+    #[tokio::test]
+    async fn test_cors_layer_origins() {
+        let app = Router::new()
+            .route("/languages", get(get_languages))
+            .layer(cors_layer(&["https://app.example.com/".to_string()]));
+
+        let preflight = |origin: &str| {
+            Request::builder()
+                .method(Method::OPTIONS)
+                .uri("/languages")
+                .header(header::ORIGIN, origin)
+                .header(header::ACCESS_CONTROL_REQUEST_METHOD, "GET")
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(preflight("https://app.example.com"))
+            .await
+            .unwrap();
+        assert_eq!(
+            response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://app.example.com"
+        );
+
+        let response = app
+            .oneshot(preflight("https://evil.example.com"))
+            .await
+            .unwrap();
+        assert!(response
+            .headers()
+            .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .is_none());
+
+        let response = Router::new()
+            .route("/languages", get(get_languages))
+            .layer(cors_layer(&["*".to_string()]))
+            .oneshot(preflight("https://any.example.com"))
+            .await
+            .unwrap();
+        assert_eq!(response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+    }
+}
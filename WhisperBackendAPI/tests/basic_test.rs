@@ -137,6 +137,9 @@ mod basic_tests {
         assert_eq!(ApiErrorCode::ProcessingFailed.as_str(), "PROCESSING_FAILED");
         assert_eq!(ApiErrorCode::ModelNotLoaded.as_str(), "MODEL_NOT_LOADED");
         assert_eq!(ApiErrorCode::ServerOverloaded.as_str(), "SERVER_OVERLOADED");
+        assert_eq!(ApiErrorCode::JobNotFound.as_str(), "JOB_NOT_FOUND");
+        assert_eq!(ApiErrorCode::JobNotReady.as_str(), "JOB_NOT_READY");
+        assert_eq!(ApiErrorCode::ModelNotFound.as_str(), "MODEL_NOT_FOUND");
         assert_eq!(ApiErrorCode::InternalError.as_str(), "INTERNAL_ERROR");
     }
 
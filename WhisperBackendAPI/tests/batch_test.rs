@@ -0,0 +1,300 @@
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, Request, StatusCode},
+    routing::post,
+    Extension, Router,
+};
+use std::io::{Cursor, Read, Write};
+use tower::ServiceExt;
+use WhisperBackendAPI::{
+    auth::ApiCaller,
+    batch::{build_archive, BatchInputs, BatchLimits, BatchManifest, OutputNames},
+    config::{ApiKeyConfig, Config},
+    handlers::{transcribe_batch, AppState},
+};
+
+#[cfg(test)]
+mod batch_tests {
+    use super::*;
+
+    fn limits() -> BatchLimits {
+        BatchLimits {
+            supported_formats: vec!["wav".to_string(), "mp3".to_string()],
+            max_file_bytes: 100,
+            max_total_bytes: 1000,
+        }
+    }
+
+    fn zip_of(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, data) in entries {
+            writer
+                .start_file(*name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn tar_of(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (name, data) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, name, *data).unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    fn names(inputs: &BatchInputs) -> Vec<&str> {
+        inputs.files.iter().map(|file| file.name.as_str()).collect()
+    }
+
+    /// ZIP から対応形式の音声だけを取り出し、対応外は記録、隠しファイルは無視する
+    #[test]
+    fn test_add_zip() {
+        let archive = zip_of(&[
+            ("a.wav", b"aaa"),
+            ("interviews/b.MP3", b"bb"),
+            ("notes.txt", b"memo"),
+            ("__MACOSX/._a.wav", b"x"),
+            (".DS_Store", b"x"),
+        ]);
+
+        let mut inputs = BatchInputs::default();
+        inputs.add_upload("set.zip", archive, &limits()).unwrap();
+
+        assert_eq!(names(&inputs), vec!["a.wav", "interviews/b.MP3"]);
+        assert_eq!(inputs.files[0].data, b"aaa");
+        assert_eq!(inputs.skipped.len(), 1);
+        assert_eq!(inputs.skipped[0].name, "notes.txt");
+    }
+
+    /// tar も展開できる（拡張子が無くてもシグネチャで判定）
+    #[test]
+    fn test_add_tar() {
+        let archive = tar_of(&[("day1/a.wav", b"a"), ("day1/b.wav", b"b")]);
+
+        let mut inputs = BatchInputs::default();
+        inputs.add_upload("upload", archive, &limits()).unwrap();
+        assert_eq!(names(&inputs), vec!["day1/a.wav", "day1/b.wav"]);
+    }
+
+    /// アーカイブ以外はそのまま音声として扱う
+    #[test]
+    fn test_add_plain_files() {
+        let mut inputs = BatchInputs::default();
+        inputs
+            .add_upload("a.wav", b"RIFF".to_vec(), &limits())
+            .unwrap();
+        inputs
+            .add_upload("b.m4a", b"....".to_vec(), &limits())
+            .unwrap();
+        assert_eq!(names(&inputs), vec!["a.wav", "b.m4a"]);
+        assert!(inputs.skipped.is_empty());
+    }
+
+    /// 1 ファイルの上限を超えるものは記録して飛ばし、合計の上限を超えたらエラー
+    #[test]
+    fn test_size_limits() {
+        let large = vec![0u8; 101];
+        let archive = zip_of(&[("large.wav", &large), ("small.wav", b"s")]);
+
+        let mut inputs = BatchInputs::default();
+        inputs.add_upload("set.zip", archive, &limits()).unwrap();
+        assert_eq!(names(&inputs), vec!["small.wav"]);
+        assert_eq!(inputs.skipped[0].name, "large.wav");
+
+        let mut inputs = BatchInputs::default();
+        let result = (0..11)
+            .try_for_each(|i| inputs.add_upload(&format!("{}.wav", i), vec![0u8; 100], &limits()));
+        assert!(result.is_err());
+    }
+
+    /// 出力名は拡張子を除いたパスで、重複や manifest とは重ならない
+    #[test]
+    fn test_output_names() {
+        let mut names = OutputNames::default();
+        assert_eq!(names.reserve("a.wav"), "a");
+        assert_eq!(names.reserve("a.mp3"), "a-2");
+        assert_eq!(names.reserve("dir.v2/a.wav"), "dir.v2/a");
+        assert_eq!(names.reserve("noext"), "noext");
+        assert_eq!(names.reserve("manifest.wav"), "manifest-2");
+    }
+
+    /// 結果 ZIP には manifest.json と出力ファイルが入る
+    #[test]
+    fn test_build_archive() {
+        let manifest = BatchManifest {
+            files: Vec::new(),
+            skipped: Vec::new(),
+            succeeded: 0,
+            failed: 0,
+            total_duration_ms: 0,
+            processing_time_ms: 5,
+        };
+        let outputs = vec![("a.srt".to_string(), "字幕".to_string())];
+
+        let archive = build_archive(&manifest, &outputs).unwrap();
+        let mut zip = zip::ZipArchive::new(Cursor::new(archive)).unwrap();
+        assert_eq!(zip.len(), 2);
+
+        let mut text = String::new();
+        zip.by_name("a.srt")
+            .unwrap()
+            .read_to_string(&mut text)
+            .unwrap();
+        assert_eq!(text, "字幕");
+
+        let manifest: serde_json::Value =
+            serde_json::from_reader(zip.by_name("manifest.json").unwrap()).unwrap();
+        assert_eq!(manifest["processing_time_ms"], 5);
+    }
+
+    fn multipart(fields: &[(&str, Option<&str>, &[u8])]) -> (String, Vec<u8>) {
+        let boundary = "batch-test-boundary";
+        let mut body = Vec::new();
+        for (name, filename, data) in fields {
+            body.extend_from_slice(format!("--{}\r\n", boundary).as_bytes());
+            match filename {
+                Some(filename) => body.extend_from_slice(
+                    format!(
+                        "Content-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\n\r\n",
+                        name, filename
+                    )
+                    .as_bytes(),
+                ),
+                None => body.extend_from_slice(
+                    format!("Content-Disposition: form-data; name=\"{}\"\r\n\r\n", name).as_bytes(),
+                ),
+            }
+            body.extend_from_slice(data);
+            body.extend_from_slice(b"\r\n");
+        }
+        body.extend_from_slice(format!("--{}--\r\n", boundary).as_bytes());
+        (format!("multipart/form-data; boundary={}", boundary), body)
+    }
+
+    async fn post_batch(fields: &[(&str, Option<&str>, &[u8])]) -> axum::response::Response {
+        post_batch_to(
+            Router::new()
+                .route("/transcribe/batch", post(transcribe_batch))
+                .with_state(AppState::new(Config::default())),
+            fields,
+        )
+        .await
+    }
+
+    async fn post_batch_to(
+        app: Router,
+        fields: &[(&str, Option<&str>, &[u8])],
+    ) -> axum::response::Response {
+        let (content_type, body) = multipart(fields);
+        app.oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/transcribe/batch")
+                .header(header::CONTENT_TYPE, content_type)
+                .body(Body::from(body))
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+    }
+
+    /// モデル未読み込みでも ZIP を返し、各ファイルの失敗を manifest に記録する
+    #[tokio::test]
+    async fn test_batch_endpoint_records_failures() {
+        let archive = zip_of(&[("a.wav", b"a"), ("readme.md", b"r")]);
+        let response = post_batch(&[
+            ("file", Some("set.zip"), &archive),
+            ("file", Some("b.wav"), b"b"),
+            ("formats", None, b"srt,txt"),
+        ])
+        .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/zip");
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let mut zip = zip::ZipArchive::new(Cursor::new(body.to_vec())).unwrap();
+        let manifest: serde_json::Value =
+            serde_json::from_reader(zip.by_name("manifest.json").unwrap()).unwrap();
+
+        assert_eq!(manifest["failed"], 2);
+        assert_eq!(manifest["succeeded"], 0);
+        assert_eq!(manifest["files"][0]["name"], "a.wav");
+        assert_eq!(manifest["files"][1]["name"], "b.wav");
+        assert_eq!(manifest["files"][0]["error"]["code"], "MODEL_NOT_LOADED");
+        assert_eq!(manifest["skipped"][0]["name"], "readme.md");
+    }
+
+    /// 各ファイルの前にクォータを確認し、上限に達したキーのファイルは QUOTA_EXCEEDED にする
+    #[tokio::test]
+    async fn test_batch_endpoint_checks_quota_per_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.paths.upload_dir = dir.path().to_string_lossy().to_string();
+        config.auth.enabled = true;
+        config.auth.keys = ["team-a", "team-b"]
+            .into_iter()
+            .map(|name| ApiKeyConfig {
+                name: name.to_string(),
+                key: format!("{}-secret", name),
+                requests_per_minute: 0,
+                monthly_audio_minutes: 1,
+                admin: false,
+            })
+            .collect();
+        let state = AppState::new(config);
+        // 受付後に別のリクエストで上限に達した
+        state.record_key_success(Some("team-a"), Some(60_000));
+
+        let manifest = |response: axum::response::Response| async move {
+            let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            let mut zip = zip::ZipArchive::new(Cursor::new(body.to_vec())).unwrap();
+            serde_json::from_reader::<_, serde_json::Value>(zip.by_name("manifest.json").unwrap())
+                .unwrap()
+        };
+        let fields: &[(&str, Option<&str>, &[u8])] =
+            &[("file", Some("a.wav"), b"a"), ("file", Some("b.wav"), b"b")];
+        let app = |name: &str| {
+            Router::new()
+                .route("/transcribe/batch", post(transcribe_batch))
+                .with_state(state.clone())
+                .layer(Extension(ApiCaller {
+                    name: name.to_string(),
+                    admin: false,
+                }))
+        };
+
+        let exceeded = manifest(post_batch_to(app("team-a"), fields).await).await;
+        assert_eq!(exceeded["failed"], 2);
+        for file in exceeded["files"].as_array().unwrap() {
+            assert_eq!(file["error"]["code"], "QUOTA_EXCEEDED");
+        }
+
+        // 上限に達していないキーはそのまま推論へ進む（モデルが無いので失敗する）
+        let within = manifest(post_batch_to(app("team-b"), fields).await).await;
+        assert_eq!(within["files"][0]["error"]["code"], "MODEL_NOT_LOADED");
+    }
+
+    /// 重複する拡張子の形式や、音声の無いアーカイブは 400
+    #[tokio::test]
+    async fn test_batch_endpoint_invalid_input() {
+        let response = post_batch(&[
+            ("file", Some("a.wav"), b"a"),
+            ("formats", None, b"json,verbose_json"),
+        ])
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let archive = zip_of(&[("readme.md", b"r")]);
+        let response = post_batch(&[("file", Some("docs.zip"), &archive)]).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = post_batch(&[("formats", None, b"srt")]).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
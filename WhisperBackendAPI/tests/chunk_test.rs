@@ -0,0 +1,156 @@
+use WhisperBackendAPI::{
+    chunk::{offset_segment, plan_chunks, stitch_segments, AudioChunk},
+    config::ChunkingConfig,
+    models::{TranscriptionSegment, WordTiming},
+    vad::quietest_point,
+};
+
+#[cfg(test)]
+mod chunk_tests {
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 16000;
+
+    fn seconds(s: f32) -> usize {
+        (s * SAMPLE_RATE as f32) as usize
+    }
+
+    /// 全体に 440Hz のサイン波が鳴り、指定した区間（秒）だけ無音の音声
+    fn tone_with_gaps(total_s: f32, gaps: &[(f32, f32)]) -> Vec<f32> {
+        let mut audio: Vec<f32> = (0..seconds(total_s))
+            .map(|i| {
+                (2.0 * std::f32::consts::PI * 440.0 * i as f32 / SAMPLE_RATE as f32).sin() * 0.3
+            })
+            .collect();
+        for &(start, end) in gaps {
+            audio[seconds(start)..seconds(end)].fill(0.0);
+        }
+        audio
+    }
+
+    fn config() -> ChunkingConfig {
+        ChunkingConfig {
+            enabled: true,
+            chunk_seconds: 60,
+            overlap_seconds: 2,
+            search_seconds: 10,
+            max_parallel: 2,
+        }
+    }
+
+    fn segment(text: &str, start_ms: u64, end_ms: u64) -> TranscriptionSegment {
+        TranscriptionSegment::new(text.to_string(), start_ms, end_ms)
+    }
+
+    /// 指定範囲で最も静かなフレームの位置を返す
+    #[test]
+    fn test_quietest_point() {
+        let audio = tone_with_gaps(10.0, &[(6.0, 6.5)]);
+        let point = quietest_point(&audio, SAMPLE_RATE, seconds(4.0), seconds(8.0));
+        assert!((seconds(6.0)..seconds(6.5)).contains(&point));
+
+        // 範囲が空なら先頭
+        assert_eq!(quietest_point(&audio, SAMPLE_RATE, 100, 100), 100);
+    }
+
+    /// 目安の長さ以下の音声は分割しない
+    #[test]
+    fn test_plan_short_audio() {
+        let audio = tone_with_gaps(65.0, &[]);
+        assert_eq!(
+            plan_chunks(&audio, SAMPLE_RATE, &config()),
+            vec![AudioChunk {
+                start: 0,
+                end: audio.len(),
+                boundary: 0,
+            }]
+        );
+    }
+
+    /// 目安の手前の無音で区切り、次のチャンクは重なりの分だけ前から始める
+    #[test]
+    fn test_plan_splits_at_silence() {
+        let audio = tone_with_gaps(150.0, &[(55.0, 55.5), (108.0, 108.5)]);
+        let chunks = plan_chunks(&audio, SAMPLE_RATE, &config());
+
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[0].start, 0);
+        assert!((seconds(55.0)..seconds(55.5)).contains(&chunks[0].end));
+        assert_eq!(chunks[1].boundary, chunks[0].end);
+        assert_eq!(chunks[1].start, chunks[0].end - seconds(2.0));
+        assert!((seconds(108.0)..seconds(108.5)).contains(&chunks[1].end));
+        assert_eq!(chunks[2].end, audio.len());
+
+        // 1 チャンクの長さは目安 + 重なりを超えない
+        for chunk in &chunks {
+            assert!(chunk.end - chunk.start <= seconds(62.0));
+        }
+    }
+
+    /// チャンク内の時刻（単語を含む）を元の時間軸に戻す
+    #[test]
+    fn test_offset_segment() {
+        let mut s = segment("こんにちは", 500, 1500);
+        s.words = Some(vec![WordTiming {
+            word: "こんにちは".to_string(),
+            start_time_ms: 500,
+            end_time_ms: 1500,
+            probability: 0.9,
+        }]);
+        offset_segment(&mut s, 60_000);
+
+        assert_eq!((s.start_time_ms, s.end_time_ms), (60_500, 61_500));
+        let word = &s.words.unwrap()[0];
+        assert_eq!((word.start_time_ms, word.end_time_ms), (60_500, 61_500));
+    }
+
+    /// 重なった部分だけのセグメントは捨て、区切りをまたぐセグメントは重複したテキストを除く
+    #[test]
+    fn test_stitch_removes_overlap() {
+        let mut merged = vec![
+            segment("最初の文です。", 0, 50_000),
+            segment("次の議題に移ります", 52_000, 60_000),
+        ];
+        let next = vec![
+            segment("最初の文です。", 58_000, 59_500),
+            segment("議題に移ります。予算について", 59_000, 63_000),
+            segment("説明します。", 63_000, 66_000),
+        ];
+        stitch_segments(&mut merged, next, 60_000);
+
+        let texts: Vec<&str> = merged.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            vec![
+                "最初の文です。",
+                "次の議題に移ります",
+                "。予算について",
+                "説明します。"
+            ]
+        );
+        assert_eq!(merged[2].start_time_ms, 60_000);
+    }
+
+    /// 前のセグメントの末尾に含まれる内容は捨て、重ならないものはそのまま残す
+    #[test]
+    fn test_stitch_drops_repeated_segment() {
+        let mut merged = vec![segment("Thanks everyone for joining.", 50_000, 60_000)];
+        let next = vec![
+            segment("for joining.", 59_000, 60_500),
+            segment("Let's start.", 61_000, 62_000),
+        ];
+        stitch_segments(&mut merged, next, 60_000);
+
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[1].text, "Let's start.");
+    }
+
+    /// 前のチャンクが失敗した場合（区切り = チャンクの先頭）は重なった部分も残す
+    #[test]
+    fn test_stitch_after_failed_chunk() {
+        let mut merged = Vec::new();
+        let next = vec![segment("途中から", 58_000, 59_000)];
+        stitch_segments(&mut merged, next, 58_000);
+        assert_eq!(merged.len(), 1);
+    }
+}
@@ -36,12 +36,14 @@ mod config_tests {
         assert_eq!(config.performance.audio_threads, 10);
         assert_eq!(config.performance.whisper_threads, 14);
         assert_eq!(config.performance.max_concurrent_requests, 10);
+        assert_eq!(config.performance.max_queued_requests, 100);
         assert_eq!(config.performance.request_timeout_seconds, 300);
 
         // 制限設定
         assert_eq!(config.limits.max_file_size_mb, 50);
         assert_eq!(config.limits.max_audio_duration_minutes, 180);
         assert_eq!(config.limits.cleanup_temp_files_after_minutes, 60);
+        assert_eq!(config.limits.job_retention_minutes, 60);
     }
 
     /// 設定ファイルの読み書きテスト
@@ -247,6 +249,96 @@ mod config_tests {
             .contains("最大ファイルサイズは1MB以上である必要があります"));
     }
 
+    /// バリデーションテスト - 分割推論の設定
+    #[test]
+    fn test_config_validate_chunking() {
+        let mut config = Config::default();
+        assert!(config.validate_chunking().is_ok());
+
+        config.chunking.chunk_seconds = 20;
+        assert!(config
+            .validate_chunking()
+            .unwrap_err()
+            .to_string()
+            .contains("chunk_seconds は30以上"));
+
+        config.chunking.chunk_seconds = 30;
+        config.chunking.overlap_seconds = 10;
+        config.chunking.search_seconds = 20;
+        assert!(config.validate_chunking().is_err());
+    }
+
+    /// バリデーションテスト - 用語集（TOML の [[glossaries]]）
+    #[test]
+    fn test_config_validate_glossaries() {
+        let glossary: GlossaryConfig =
+            toml::from_str("name = \"medical\"\nterms = [\"心電図\", \"CT\"]").unwrap();
+        assert!(!glossary.correct);
+
+        // 保存して読み直しても残る
+        let mut config = Config::default();
+        config.glossaries.push(glossary);
+        let mut config: Config = toml::from_str(&toml::to_string(&config).unwrap()).unwrap();
+        assert_eq!(config.glossaries[0].terms, vec!["心電図", "CT"]);
+        assert!(config.validate_glossaries().is_ok());
+
+        config.glossaries.push(config.glossaries[0].clone());
+        assert!(config
+            .validate_glossaries()
+            .unwrap_err()
+            .to_string()
+            .contains("重複"));
+
+        config.glossaries[1].name = "bad name".to_string();
+        assert!(config.validate_glossaries().is_err());
+    }
+
+    /// バリデーションテスト - 話者分離
+    #[test]
+    fn test_config_validate_diarization() {
+        let mut config = Config::default();
+        assert!(!config.diarization.enabled);
+        assert!(config.validate_diarization().is_ok());
+
+        config.diarization.max_speakers = 0;
+        assert!(config.validate_diarization().is_err());
+        config.diarization.max_speakers = 33;
+        assert!(config.validate_diarization().is_err());
+
+        config.diarization.max_speakers = 4;
+        config.diarization.penalty = 0.0;
+        assert!(config.validate_diarization().is_err());
+        config.diarization.penalty = f32::NAN;
+        assert!(config.validate_diarization().is_err());
+    }
+
+    /// バリデーションテスト - Webhook 通知
+    #[test]
+    fn test_config_validate_webhook() {
+        let mut config = Config::default();
+        assert_eq!(config.webhook.max_attempts, 5);
+        assert!(config.validate_webhook().is_ok());
+
+        config.webhook.max_attempts = 0;
+        assert!(config.validate_webhook().is_err());
+
+        config.webhook.max_attempts = 3;
+        config.webhook.initial_backoff_ms = 120_000;
+        assert!(config.validate_webhook().is_err());
+
+        config.webhook.initial_backoff_ms = 1000;
+        config.webhook.allowed_hosts = vec![
+            "hooks.internal".to_string(),
+            "10.0.0.0/8".to_string(),
+            "fd00::/8".to_string(),
+        ];
+        assert!(config.validate_webhook().is_ok());
+        config.webhook.allowed_hosts = vec!["10.0.0.0/33".to_string()];
+        assert!(config.validate_webhook().is_err());
+        config.webhook.allowed_hosts = vec!["internal/8".to_string()];
+        assert!(config.validate_webhook().is_err());
+    }
+
     /// ヘルパーメソッドのテスト
     #[test]
     fn test_config_helper_methods() {
@@ -316,4 +408,41 @@ mod config_tests {
             deserialized_config.audio.sample_rate
         );
     }
+
+    /// 追加された設定項目が無い既存の設定ファイルも読み込めることを確認
+    #[test]
+    fn test_config_load_legacy_file_uses_defaults() {
+        let mut value = toml::Value::try_from(Config::default()).unwrap();
+        value
+            .get_mut("performance")
+            .and_then(|v| v.as_table_mut())
+            .unwrap()
+            .remove("max_queued_requests");
+        value
+            .get_mut("limits")
+            .and_then(|v| v.as_table_mut())
+            .unwrap()
+            .remove("job_retention_minutes");
+        value
+            .get_mut("whisper")
+            .and_then(|v| v.as_table_mut())
+            .unwrap()
+            .remove("model_memory_budget_mb");
+        value
+            .get_mut("whisper")
+            .and_then(|v| v.as_table_mut())
+            .unwrap()
+            .remove("load_models_on_request");
+
+        let toml_string = toml::to_string(&value).unwrap();
+        assert!(!toml_string.contains("max_queued_requests"));
+        assert!(!toml_string.contains("job_retention_minutes"));
+        assert!(!toml_string.contains("model_memory_budget_mb"));
+
+        let config: Config = toml::from_str(&toml_string).unwrap();
+        assert_eq!(config.performance.max_queued_requests, 100);
+        assert_eq!(config.limits.job_retention_minutes, 60);
+        assert_eq!(config.whisper.model_memory_budget_mb, 4096);
+        assert!(!config.whisper.load_models_on_request);
+    }
 }
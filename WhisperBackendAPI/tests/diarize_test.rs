@@ -0,0 +1,215 @@
+use WhisperBackendAPI::{
+    config::DiarizationConfig,
+    diarize::{diarize, speaker_text},
+    export::{to_srt, to_verbose, to_vtt},
+    models::{TranscribeResponse, TranscriptionSegment},
+};
+
+#[cfg(test)]
+mod diarize_tests {
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 16000;
+
+    /// 話者の声の代わり（基本周波数と強調する帯域が話者ごとに違う調波音）
+    struct Voice {
+        pitch_hz: f32,
+        formant_hz: f32,
+    }
+
+    const LOW_VOICE: Voice = Voice {
+        pitch_hz: 110.0,
+        formant_hz: 700.0,
+    };
+    const HIGH_VOICE: Voice = Voice {
+        pitch_hz: 220.0,
+        formant_hz: 2200.0,
+    };
+
+    /// 声を `seconds` 秒鳴らす（`seed` で音程の揺れと雑音を変える）
+    fn speak(voice: &Voice, seconds: f32, seed: u32) -> Vec<f32> {
+        let mut noise = seed.wrapping_mul(2_654_435_761).max(1);
+        let pitch = voice.pitch_hz * (1.0 + (seed % 5) as f32 * 0.01);
+        (0..(seconds * SAMPLE_RATE as f32) as usize)
+            .map(|i| {
+                let t = i as f32 / SAMPLE_RATE as f32;
+                let syllable = 0.6 + 0.4 * (2.0 * std::f32::consts::PI * 4.0 * t).sin();
+                let mut sample = 0.0;
+                for k in 1..=30 {
+                    let hz = pitch * k as f32;
+                    let gain = 1.0 / (1.0 + ((hz - voice.formant_hz) / 300.0).powi(2));
+                    sample += gain * (2.0 * std::f32::consts::PI * hz * t).sin() / k as f32;
+                }
+                noise ^= noise << 13;
+                noise ^= noise >> 17;
+                noise ^= noise << 5;
+                let white = noise as f32 / u32::MAX as f32 - 0.5;
+                0.2 * syllable * sample + 0.01 * white
+            })
+            .collect()
+    }
+
+    /// 声を順に並べた音声と、それぞれに対応するセグメント
+    fn conversation(turns: &[(&Voice, f32)]) -> (Vec<f32>, Vec<TranscriptionSegment>) {
+        let mut audio = Vec::new();
+        let mut segments = Vec::new();
+        for (index, (voice, seconds)) in turns.iter().enumerate() {
+            let start_ms = audio.len() as u64 * 1000 / SAMPLE_RATE as u64;
+            audio.extend(speak(voice, *seconds, index as u32 + 1));
+            let end_ms = audio.len() as u64 * 1000 / SAMPLE_RATE as u64;
+            segments.push(TranscriptionSegment::new(
+                format!(" 発言{}", index + 1),
+                start_ms,
+                end_ms,
+            ));
+        }
+        (audio, segments)
+    }
+
+    fn speakers(segments: &[TranscriptionSegment]) -> Vec<&str> {
+        segments
+            .iter()
+            .map(|segment| segment.speaker.as_deref().unwrap_or("-"))
+            .collect()
+    }
+
+    /// 声の違う 2 人の発言を、最初に話した順の話者ラベルで分ける
+    #[test]
+    fn test_two_speakers() {
+        let (audio, mut segments) = conversation(&[
+            (&LOW_VOICE, 3.0),
+            (&HIGH_VOICE, 3.0),
+            (&LOW_VOICE, 2.0),
+            (&HIGH_VOICE, 3.0),
+            (&LOW_VOICE, 3.0),
+        ]);
+        let count = diarize(
+            &audio,
+            SAMPLE_RATE,
+            &mut segments,
+            &DiarizationConfig::default(),
+            None,
+            false,
+        );
+
+        assert_eq!(count, 2);
+        assert_eq!(
+            speakers(&segments),
+            vec![
+                "SPEAKER_1",
+                "SPEAKER_2",
+                "SPEAKER_1",
+                "SPEAKER_2",
+                "SPEAKER_1"
+            ]
+        );
+    }
+
+    /// 1 人だけの音声は 1 人にまとめる。短いセグメントは近い話者に割り当てる
+    #[test]
+    fn test_single_speaker() {
+        let (audio, mut segments) = conversation(&[
+            (&LOW_VOICE, 3.0),
+            (&LOW_VOICE, 2.5),
+            (&LOW_VOICE, 0.5),
+            (&LOW_VOICE, 3.0),
+        ]);
+        let count = diarize(
+            &audio,
+            SAMPLE_RATE,
+            &mut segments,
+            &DiarizationConfig::default(),
+            None,
+            false,
+        );
+
+        assert_eq!(count, 1);
+        assert!(speakers(&segments).iter().all(|&s| s == "SPEAKER_1"));
+    }
+
+    /// 話者数を指定するとその人数になるまでまとめる
+    #[test]
+    fn test_num_speakers() {
+        let (audio, mut segments) =
+            conversation(&[(&LOW_VOICE, 3.0), (&HIGH_VOICE, 3.0), (&LOW_VOICE, 3.0)]);
+        let count = diarize(
+            &audio,
+            SAMPLE_RATE,
+            &mut segments,
+            &DiarizationConfig::default(),
+            Some(1),
+            false,
+        );
+        assert_eq!(count, 1);
+    }
+
+    /// 話者交代の検出（tinydiarize）を使う場合は、交代までのセグメントを同じ話者にする
+    #[test]
+    fn test_speaker_turns() {
+        let (audio, mut segments) =
+            conversation(&[(&LOW_VOICE, 2.0), (&LOW_VOICE, 0.3), (&HIGH_VOICE, 3.0)]);
+        segments[1].speaker_turn = true;
+        diarize(
+            &audio,
+            SAMPLE_RATE,
+            &mut segments,
+            &DiarizationConfig::default(),
+            None,
+            true,
+        );
+        assert_eq!(
+            speakers(&segments),
+            vec!["SPEAKER_1", "SPEAKER_1", "SPEAKER_2"]
+        );
+    }
+
+    /// 無音だけならラベルを付けない
+    #[test]
+    fn test_silence() {
+        let mut segments = vec![TranscriptionSegment::new("".to_string(), 0, 1000)];
+        let count = diarize(
+            &vec![0.0; SAMPLE_RATE as usize],
+            SAMPLE_RATE,
+            &mut segments,
+            &DiarizationConfig::default(),
+            None,
+            false,
+        );
+        assert_eq!(count, 0);
+        assert!(segments[0].speaker.is_none());
+    }
+
+    fn labelled(text: &str, start_ms: u64, end_ms: u64, speaker: &str) -> TranscriptionSegment {
+        let mut segment = TranscriptionSegment::new(text.to_string(), start_ms, end_ms);
+        segment.speaker = Some(speaker.to_string());
+        segment
+    }
+
+    /// 全文テキストは話者が替わるたびに改行し、字幕/JSON には話者を含める
+    #[test]
+    fn test_outputs_carry_speaker() {
+        let segments = vec![
+            labelled(" では始めます。", 0, 1000, "SPEAKER_1"),
+            labelled(" 議題は予算です。", 1000, 2000, "SPEAKER_1"),
+            labelled(" 質問があります。", 2000, 3000, "SPEAKER_2"),
+        ];
+
+        assert_eq!(
+            speaker_text(&segments),
+            "[SPEAKER_1] では始めます。 議題は予算です。\n[SPEAKER_2] 質問があります。"
+        );
+        assert!(to_srt(&segments).contains("[SPEAKER_2] 質問があります。"));
+        assert!(to_vtt(&segments).contains("<v SPEAKER_2>質問があります。"));
+
+        let response = TranscribeResponse {
+            text: speaker_text(&segments),
+            segments: Some(segments),
+            ..serde_json::from_str(r#"{"text": "", "processing_time_ms": 0}"#).unwrap()
+        };
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["segments"][2]["speaker"], "SPEAKER_2");
+        assert!(json["segments"][2].get("speaker_turn").is_none());
+        let verbose = serde_json::to_value(to_verbose(&response, false)).unwrap();
+        assert_eq!(verbose["segments"][0]["speaker"], "SPEAKER_1");
+    }
+}
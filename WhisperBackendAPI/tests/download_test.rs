@@ -0,0 +1,368 @@
+use sha2::{Digest, Sha256};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use WhisperBackendAPI::{
+    checksum,
+    download::{self, DownloadEvent, DownloadManager, DownloadOptions, DownloadPhase},
+};
+
+#[cfg(test)]
+mod download_tests {
+    use super::*;
+
+    /// テスト用の HTTP スタブの設定
+    #[derive(Clone, Default)]
+    struct StubOptions {
+        /// Range ヘッダーに対応する
+        support_range: bool,
+        /// `X-Linked-ETag` として返すハッシュ
+        linked_etag: Option<String>,
+        /// `ETag` として返す値（`If-Range` が一致しなければ Range を無視して全体を返す）
+        etag: Option<String>,
+    }
+
+    /// ローカルの HTTP スタブを起動し、URL と受信した Range ヘッダーの記録を返す
+    /// - GET は `body` を返し、Range 指定があれば 206 で続きだけ返す
+    /// - HEAD はヘッダーのみ返す
+    fn start_stub(body: Vec<u8>, options: StubOptions) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let ranges = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&ranges);
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = match stream {
+                    Ok(stream) => stream,
+                    Err(_) => break,
+                };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut range_start = None;
+                let mut if_range = None;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    let lower = line.to_ascii_lowercase();
+                    if let Some(value) = lower.strip_prefix("range:") {
+                        recorded.lock().unwrap().push(value.trim().to_string());
+                        range_start = value
+                            .trim()
+                            .trim_start_matches("bytes=")
+                            .trim_end_matches('-')
+                            .parse::<usize>()
+                            .ok();
+                    }
+                    if let Some(value) = lower.strip_prefix("if-range:") {
+                        if_range = Some(value.trim().to_string());
+                    }
+                }
+                let quoted_etag = options.etag.as_ref().map(|etag| format!("\"{}\"", etag));
+                if if_range.is_some() && if_range != quoted_etag {
+                    range_start = None;
+                }
+
+                let mut etag = options
+                    .linked_etag
+                    .as_ref()
+                    .map(|etag| format!("X-Linked-ETag: \"{}\"\r\n", etag))
+                    .unwrap_or_default();
+                if let Some(quoted) = &quoted_etag {
+                    etag.push_str(&format!("ETag: {}\r\n", quoted));
+                }
+                let (status, payload, extra) = match range_start {
+                    Some(start) if options.support_range && start >= body.len() => (
+                        "416 Range Not Satisfiable",
+                        Vec::new(),
+                        format!("Content-Range: bytes */{}\r\n", body.len()),
+                    ),
+                    Some(start) if options.support_range => (
+                        "206 Partial Content",
+                        body[start..].to_vec(),
+                        format!(
+                            "Content-Range: bytes {}-{}/{}\r\n",
+                            start,
+                            body.len() - 1,
+                            body.len()
+                        ),
+                    ),
+                    _ => ("200 OK", body.clone(), String::new()),
+                };
+                let header = format!(
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\n{}{}Connection: close\r\n\r\n",
+                    status,
+                    payload.len(),
+                    extra,
+                    etag
+                );
+                let _ = stream.write_all(header.as_bytes());
+                if request_line.starts_with("GET") {
+                    let _ = stream.write_all(&payload);
+                }
+            }
+        });
+
+        (format!("http://{}/ggml-test.bin", addr), ranges)
+    }
+
+    fn temp_dest(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "whisper_download_test_{}_{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join("ggml-test.bin")
+    }
+
+    fn sample_body() -> Vec<u8> {
+        (0..3 * 1024 * 1024 + 123)
+            .map(|i| (i % 251) as u8)
+            .collect()
+    }
+
+    fn options(expected_sha256: Option<&str>, allow_unverified: bool) -> DownloadOptions<'_> {
+        DownloadOptions {
+            expected_sha256,
+            allow_unverified,
+            restart: false,
+            connect_timeout: Duration::from_secs(5),
+        }
+    }
+
+    /// 前回の取得で残った `.part` と検証子（ETag）を置く
+    fn write_partial(dest: &std::path::Path, data: &[u8], etag: Option<&str>) {
+        std::fs::write(download::partial_path(dest), data).unwrap();
+        if let Some(etag) = etag {
+            std::fs::write(
+                download::partial_validator_path(dest),
+                format!("\"{}\"", etag),
+            )
+            .unwrap();
+        }
+    }
+
+    fn sha256_of(data: &[u8]) -> String {
+        checksum::to_hex(&Sha256::digest(data))
+    }
+
+    /// ファイルの SHA-256 を既知のテストベクトルと照合する
+    #[test]
+    fn test_sha256_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("abc.bin");
+        std::fs::write(&path, b"abc").unwrap();
+        assert_eq!(
+            checksum::sha256_file(&path).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+
+        // 読み込みバッファより大きなファイル
+        let body = sample_body();
+        std::fs::write(&path, &body).unwrap();
+        assert_eq!(checksum::sha256_file(&path).unwrap(), sha256_of(&body));
+
+        assert!(checksum::is_sha256_hex(&sha256_of(b"abc")));
+        assert!(!checksum::is_sha256_hex("abc"));
+    }
+
+    /// 期待値を指定したダウンロードは検証済みになり、進捗が単調に増える
+    #[test]
+    fn test_download_with_checksum() {
+        let body = sample_body();
+        let (url, _) = start_stub(body.clone(), StubOptions::default());
+        let dest = temp_dest("full");
+
+        let mut progress = Vec::new();
+        let outcome = download::download_to_file(
+            &url,
+            &dest,
+            options(Some(&sha256_of(&body)), false),
+            |event| {
+                if let DownloadEvent::Progress { downloaded, .. } = event {
+                    progress.push(downloaded);
+                }
+            },
+        )
+        .unwrap();
+
+        assert!(outcome.verified);
+        assert_eq!(outcome.bytes, body.len() as u64);
+        assert_eq!(outcome.resumed_from, 0);
+        assert_eq!(std::fs::read(&dest).unwrap(), body);
+        assert!(!download::partial_path(&dest).exists());
+        assert!(progress.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(*progress.last().unwrap(), body.len() as u64);
+    }
+
+    /// `.part` があれば Range で続きから取得し、配布元のハッシュ（X-Linked-ETag）で検証する
+    #[test]
+    fn test_download_resume() {
+        let body = sample_body();
+        let (url, ranges) = start_stub(
+            body.clone(),
+            StubOptions {
+                support_range: true,
+                linked_etag: Some(sha256_of(&body)),
+                etag: Some("v1".to_string()),
+            },
+        );
+        let dest = temp_dest("resume");
+        write_partial(&dest, &body[..1000], Some("v1"));
+
+        let outcome =
+            download::download_to_file(&url, &dest, options(None, false), |_| {}).unwrap();
+
+        assert_eq!(ranges.lock().unwrap().as_slice(), ["bytes=1000-"]);
+        assert_eq!(outcome.resumed_from, 1000);
+        assert!(outcome.verified);
+        assert_eq!(std::fs::read(&dest).unwrap(), body);
+        assert!(!download::partial_validator_path(&dest).exists());
+    }
+
+    /// 配布元のファイルが変わっていれば（ETag が違えば）`.part` を使わず最初から取得する
+    #[test]
+    fn test_download_discards_stale_partial() {
+        let body = sample_body();
+        let (url, _) = start_stub(
+            body.clone(),
+            StubOptions {
+                support_range: true,
+                linked_etag: Some(sha256_of(&body)),
+                etag: Some("v2".to_string()),
+            },
+        );
+        let dest = temp_dest("stale");
+        write_partial(&dest, b"data of another version", Some("v1"));
+
+        let outcome =
+            download::download_to_file(&url, &dest, options(None, false), |_| {}).unwrap();
+
+        assert_eq!(outcome.resumed_from, 0);
+        assert!(outcome.verified);
+        assert_eq!(std::fs::read(&dest).unwrap(), body);
+    }
+
+    /// 検証子の無い `.part` と、`restart`（overwrite）指定時の `.part` からは再開しない
+    #[test]
+    fn test_download_does_not_resume_unknown_partial() {
+        let body = sample_body();
+        let (url, ranges) = start_stub(
+            body.clone(),
+            StubOptions {
+                support_range: true,
+                linked_etag: Some(sha256_of(&body)),
+                etag: Some("v1".to_string()),
+            },
+        );
+        let dest = temp_dest("unknown_partial");
+
+        write_partial(&dest, &body[..1000], None);
+        let outcome =
+            download::download_to_file(&url, &dest, options(None, false), |_| {}).unwrap();
+        assert_eq!(outcome.resumed_from, 0);
+        assert_eq!(std::fs::read(&dest).unwrap(), body);
+
+        write_partial(&dest, &body[..1000], Some("v1"));
+        let restart = DownloadOptions {
+            restart: true,
+            ..options(None, false)
+        };
+        let outcome = download::download_to_file(&url, &dest, restart, |_| {}).unwrap();
+        assert_eq!(outcome.resumed_from, 0);
+        assert_eq!(std::fs::read(&dest).unwrap(), body);
+
+        assert!(ranges.lock().unwrap().is_empty());
+    }
+
+    /// Range に対応しないサーバーでは最初から取得し直す（検証なしの配置は明示した場合のみ）
+    #[test]
+    fn test_download_restart_without_range_support() {
+        let body = sample_body();
+        let (url, _) = start_stub(body.clone(), StubOptions::default());
+        let dest = temp_dest("restart");
+        std::fs::write(download::partial_path(&dest), b"stale data").unwrap();
+
+        let outcome = download::download_to_file(&url, &dest, options(None, true), |_| {}).unwrap();
+
+        assert_eq!(outcome.resumed_from, 0);
+        assert!(!outcome.verified);
+        assert_eq!(outcome.sha256, sha256_of(&body));
+        assert_eq!(std::fs::read(&dest).unwrap(), body);
+    }
+
+    /// チェックサムが一致しなければ失敗し、途中のファイルも残さない
+    #[test]
+    fn test_download_checksum_mismatch() {
+        let body = sample_body();
+        let (url, _) = start_stub(body, StubOptions::default());
+        let dest = temp_dest("mismatch");
+
+        let result = download::download_to_file(
+            &url,
+            &dest,
+            options(Some(&sha256_of(b"other")), false),
+            |_| {},
+        );
+
+        assert!(result.is_err());
+        assert!(!dest.exists());
+        assert!(!download::partial_path(&dest).exists());
+    }
+
+    /// 期待するハッシュが得られなければ、明示しない限り転送せずに失敗する
+    #[test]
+    fn test_download_refuses_unverified() {
+        let body = sample_body();
+        let (url, _) = start_stub(body, StubOptions::default());
+        let dest = temp_dest("unverified");
+
+        let mut progress = 0;
+        let result = download::download_to_file(&url, &dest, options(None, false), |_| {
+            progress += 1;
+        });
+
+        assert!(result.is_err());
+        assert_eq!(progress, 0);
+        assert!(!dest.exists());
+        assert!(!download::partial_path(&dest).exists());
+    }
+
+    /// 同じモデルのダウンロードは同時に 1 つだけで、終了後は再登録できる
+    #[test]
+    fn test_download_manager() {
+        let manager = DownloadManager::new();
+        assert!(manager.begin("base", "ggml-base.bin").1);
+        let (running, started) = manager.begin("base", "ggml-base.bin");
+        assert!(!started);
+        assert_eq!(running.phase, DownloadPhase::Queued);
+
+        manager.apply_event(
+            "base",
+            DownloadEvent::Progress {
+                downloaded: 50,
+                total: Some(200),
+                resumed_from: 0,
+            },
+        );
+        let status = manager.get("base").unwrap();
+        assert_eq!(status.phase, DownloadPhase::Downloading);
+        assert_eq!(status.progress, Some(25));
+
+        manager.fail("base", "接続エラー".to_string());
+        let status = manager.get("base").unwrap();
+        assert_eq!(status.phase, DownloadPhase::Failed);
+        assert!(status.finished_at.is_some());
+
+        assert!(manager.begin("base", "ggml-base.bin").1);
+        assert_eq!(manager.list().len(), 1);
+    }
+}
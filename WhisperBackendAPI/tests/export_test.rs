@@ -0,0 +1,149 @@
+use WhisperBackendAPI::{
+    export,
+    models::{ResponseFormat, TranscribeResponse, TranscriptionSegment},
+};
+
+#[cfg(test)]
+mod export_tests {
+    use super::*;
+
+    fn sample_response() -> TranscribeResponse {
+        TranscribeResponse {
+            text: " こんにちは 世界".to_string(),
+            language: Some("ja".to_string()),
+            duration_ms: Some(4500),
+            segments: Some(vec![
+                TranscriptionSegment::new(" こんにちは".to_string(), 0, 1500),
+                TranscriptionSegment::new(" 世界\tです".to_string(), 1500, 3723456),
+            ]),
+            processing_time_ms: 321,
+            decoding: None,
+            language_detection: None,
+            model: None,
+            filtered: None,
+            corrections: None,
+            history_id: None,
+            failed_chunks: None,
+        }
+    }
+
+    /// 形式名の解釈
+    #[test]
+    fn test_response_format_parse() {
+        assert_eq!(ResponseFormat::parse("srt"), Some(ResponseFormat::Srt));
+        assert_eq!(ResponseFormat::parse(" VTT "), Some(ResponseFormat::Vtt));
+        assert_eq!(
+            ResponseFormat::parse("verbose_json"),
+            Some(ResponseFormat::VerboseJson)
+        );
+        assert_eq!(ResponseFormat::parse("docx"), None);
+        assert_eq!(ResponseFormat::default(), ResponseFormat::Json);
+    }
+
+    /// Content-Type と拡張子
+    #[test]
+    fn test_response_format_metadata() {
+        assert_eq!(ResponseFormat::Json.content_type(), "application/json");
+        assert_eq!(
+            ResponseFormat::Vtt.content_type(),
+            "text/vtt; charset=utf-8"
+        );
+        assert_eq!(ResponseFormat::Text.extension(), "txt");
+        assert_eq!(ResponseFormat::VerboseJson.extension(), "json");
+        assert!(ResponseFormat::Srt.requires_segments());
+        assert!(!ResponseFormat::Text.requires_segments());
+    }
+
+    #[test]
+    fn test_render_text() {
+        let body = export::render(&sample_response(), ResponseFormat::Text, false).unwrap();
+        assert_eq!(body, "こんにちは 世界\n");
+    }
+
+    #[test]
+    fn test_render_srt() {
+        let body = export::render(&sample_response(), ResponseFormat::Srt, false).unwrap();
+        assert_eq!(
+            body,
+            "1\n00:00:00,000 --> 00:00:01,500\nこんにちは\n\n\
+             2\n00:00:01,500 --> 01:02:03,456\n世界\tです\n\n"
+        );
+    }
+
+    #[test]
+    fn test_render_vtt() {
+        let body = export::render(&sample_response(), ResponseFormat::Vtt, false).unwrap();
+        assert!(body.starts_with("WEBVTT\n\n"));
+        assert!(body.contains("00:00:00.000 --> 00:00:01.500\nこんにちは\n\n"));
+        assert!(body.contains("00:00:01.500 --> 01:02:03.456\n"));
+    }
+
+    /// TSV ではテキスト中のタブを空白に置換する
+    #[test]
+    fn test_render_tsv() {
+        let body = export::render(&sample_response(), ResponseFormat::Tsv, false).unwrap();
+        let lines: Vec<&str> = body.lines().collect();
+        assert_eq!(lines[0], "start\tend\ttext");
+        assert_eq!(lines[1], "0\t1500\tこんにちは");
+        assert_eq!(lines[2], "1500\t3723456\t世界 です");
+    }
+
+    #[test]
+    fn test_render_verbose_json() {
+        let body = export::render(&sample_response(), ResponseFormat::VerboseJson, true).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&body).unwrap();
+
+        assert_eq!(value["task"], "translate");
+        assert_eq!(value["language"], "ja");
+        assert_eq!(value["duration"], 4.5);
+        assert_eq!(value["processing_time_ms"], 321);
+        assert_eq!(value["segments"][1]["id"], 1);
+        assert_eq!(value["segments"][1]["start"], 1.5);
+        assert_eq!(value["segments"][0]["text"], "こんにちは");
+    }
+
+    /// セグメントが無い場合は空の字幕になる
+    #[test]
+    fn test_render_without_segments() {
+        let mut response = sample_response();
+        response.segments = None;
+
+        assert_eq!(
+            export::render(&response, ResponseFormat::Srt, false).unwrap(),
+            ""
+        );
+        assert_eq!(
+            export::render(&response, ResponseFormat::Vtt, false).unwrap(),
+            "WEBVTT\n\n"
+        );
+    }
+
+    #[test]
+    fn test_download_filename() {
+        assert_eq!(
+            export::download_filename("meeting.mp3", ResponseFormat::Srt),
+            "meeting.srt"
+        );
+        assert_eq!(
+            export::download_filename("会議.wav", ResponseFormat::Text),
+            "会議.txt"
+        );
+        assert_eq!(
+            export::download_filename("", ResponseFormat::Vtt),
+            "transcription.vtt"
+        );
+    }
+
+    /// 非 ASCII のファイル名は filename* で渡す
+    #[test]
+    fn test_content_disposition() {
+        assert_eq!(
+            export::content_disposition("meeting.srt"),
+            "attachment; filename=\"meeting.srt\"; filename*=UTF-8''meeting.srt"
+        );
+        assert_eq!(
+            export::content_disposition("会議.srt"),
+            "attachment; filename=\"__.srt\"; filename*=UTF-8''%E4%BC%9A%E8%AD%B0.srt"
+        );
+    }
+}
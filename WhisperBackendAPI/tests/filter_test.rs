@@ -0,0 +1,140 @@
+use WhisperBackendAPI::{
+    config::{Config, FilterConfig},
+    filter::{collapse_repetitions, filter_segments},
+    models::{FilterReason, TranscriptionSegment},
+};
+
+#[cfg(test)]
+mod filter_tests {
+    use super::*;
+
+    fn segment(text: &str, start: u64, end: u64) -> TranscriptionSegment {
+        TranscriptionSegment::new(text.to_string(), start, end)
+    }
+
+    fn texts(segments: &[TranscriptionSegment]) -> Vec<&str> {
+        segments
+            .iter()
+            .map(|segment| segment.text.as_str())
+            .collect()
+    }
+
+    /// 既定で有効、`[filter]` の無い設定ファイルも読み込める
+    #[test]
+    fn test_filter_config_default() {
+        let config = Config::default();
+        assert!(config.filter.enabled);
+        assert!(config
+            .filter
+            .blocklist
+            .iter()
+            .any(|phrase| phrase == "ご視聴ありがとうございました"));
+
+        let mut value = toml::Value::try_from(&config).unwrap();
+        value.as_table_mut().unwrap().remove("filter");
+        let loaded: Config = toml::from_str(&toml::to_string(&value).unwrap()).unwrap();
+        assert_eq!(loaded.filter, FilterConfig::default());
+    }
+
+    /// 定型句は句読点や空白の違いを無視して除く
+    #[test]
+    fn test_filter_blocklist() {
+        let segments = vec![
+            segment("本日の議題です。", 0, 2000),
+            segment(" ご視聴ありがとうございました！", 2000, 4000),
+        ];
+        let (kept, removed) = filter_segments(segments, &FilterConfig::default());
+
+        assert_eq!(texts(&kept), vec!["本日の議題です。"]);
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].reason, FilterReason::Blocklist);
+        assert_eq!(removed[0].start_time_ms, 2000);
+        assert_eq!(removed[0].end_time_ms, 4000);
+    }
+
+    /// 無音確率が高く平均対数確率が低いセグメントだけを除く
+    #[test]
+    fn test_filter_no_speech() {
+        let mut silent = segment("えー", 0, 1000);
+        silent.no_speech_prob = Some(0.9);
+        silent.avg_logprob = Some(-1.5);
+        // 無音確率は高いが、確信度も高い
+        let mut confident = segment("はい", 1000, 2000);
+        confident.no_speech_prob = Some(0.9);
+        confident.avg_logprob = Some(-0.2);
+        // 値の無いセグメントは対象外
+        let plain = segment("続けます", 2000, 3000);
+
+        let (kept, removed) =
+            filter_segments(vec![silent, confident, plain], &FilterConfig::default());
+
+        assert_eq!(texts(&kept), vec!["はい", "続けます"]);
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].text, "えー");
+        assert_eq!(removed[0].reason, FilterReason::NoSpeech);
+    }
+
+    /// 同じ内容が max_repeats 回を超えて続くと最初の 1 つだけ残す
+    #[test]
+    fn test_filter_consecutive_repetition() {
+        let config = FilterConfig::default();
+        let mut segments: Vec<TranscriptionSegment> = (0..5)
+            .map(|i| segment("それでは。", i * 1000, (i + 1) * 1000))
+            .collect();
+        segments.push(segment("次へ", 5000, 6000));
+        // max_repeats 回までは残す
+        segments.extend((0..3).map(|i| segment("はい", 6000 + i * 500, 6500 + i * 500)));
+
+        let (kept, removed) = filter_segments(segments, &config);
+
+        assert_eq!(
+            texts(&kept),
+            vec!["それでは。", "次へ", "はい", "はい", "はい"]
+        );
+        assert_eq!(removed.len(), 4);
+        assert!(removed
+            .iter()
+            .all(|segment| segment.reason == FilterReason::Repetition));
+        assert_eq!(removed[0].start_time_ms, 1000);
+    }
+
+    /// セグメント内の繰り返しは 1 回にまとめ、元のテキストを報告する
+    #[test]
+    fn test_filter_collapse_within_segment() {
+        let segments = vec![segment("ありがとうありがとうありがとうありがとう", 0, 3000)];
+        let (kept, removed) = filter_segments(segments, &FilterConfig::default());
+
+        assert_eq!(texts(&kept), vec!["ありがとう"]);
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].text, "ありがとうありがとうありがとうありがとう");
+        assert_eq!(removed[0].reason, FilterReason::Repetition);
+    }
+
+    /// 繰り返しが max_repeats 回以下なら変更しない
+    #[test]
+    fn test_collapse_repetitions() {
+        assert_eq!(collapse_repetitions("はいはいはい", 3), None);
+        assert_eq!(
+            collapse_repetitions("はいはいはいはい", 3),
+            Some("はい".to_string())
+        );
+        assert_eq!(
+            collapse_repetitions("I think the the the the end", 3),
+            Some("I think the end".to_string())
+        );
+        assert_eq!(collapse_repetitions("通常の文章です。", 3), None);
+    }
+
+    /// 除いたセグメントにもチャンネル番号を残す
+    #[test]
+    fn test_filter_keeps_channel() {
+        let mut blocked = segment("ご視聴ありがとうございました", 0, 1000);
+        blocked.channel = Some(1);
+        let (_, removed) = filter_segments(vec![blocked], &FilterConfig::default());
+
+        assert_eq!(removed[0].channel, Some(1));
+        let json = serde_json::to_value(&removed[0]).unwrap();
+        assert_eq!(json["reason"], "blocklist");
+        assert_eq!(json["channel"], 1);
+    }
+}
@@ -0,0 +1,249 @@
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, Request, StatusCode},
+    routing::{get, post},
+    Router,
+};
+use tempfile::TempDir;
+use tower::ServiceExt;
+use WhisperBackendAPI::{
+    config::{Config, GlossaryConfig},
+    glossary::{correct_segments, Glossary, GlossaryStore},
+    handlers::{
+        delete_glossary, get_glossary, list_glossaries, put_glossary, transcribe_basic, AppState,
+    },
+    models::{DecodingParameters, FilterReason, TranscriptionSegment},
+};
+
+#[cfg(test)]
+mod glossary_tests {
+    use super::*;
+
+    fn terms(terms: &[&str]) -> Vec<String> {
+        terms.iter().map(|term| term.to_string()).collect()
+    }
+
+    fn glossary(list: &[&str]) -> Glossary {
+        Glossary::new("test", &terms(list), true, false).unwrap()
+    }
+
+    /// セグメントのテキストを直し、直した後のテキストを返す
+    fn corrected(text: &str, list: &[&str]) -> String {
+        let mut segments = vec![TranscriptionSegment::new(text.to_string(), 0, 1000)];
+        correct_segments(&mut segments, &terms(list));
+        segments.remove(0).text
+    }
+
+    /// 語句は前後の空白を除き、空のものと重複を除く。名前は英数字/-/_ のみ
+    #[test]
+    fn test_new_validates() {
+        let glossary = Glossary::new(
+            "product-names_2",
+            &terms(&[" Kubernetes ", "", "kubernetes", "田中太郎"]),
+            false,
+            false,
+        )
+        .unwrap();
+        assert_eq!(glossary.terms, vec!["Kubernetes", "田中太郎"]);
+
+        assert!(Glossary::new("../etc", &terms(&["a"]), false, false).is_err());
+        assert!(Glossary::new("", &terms(&["a"]), false, false).is_err());
+        assert!(Glossary::new("empty", &terms(&[" "]), false, false).is_err());
+        assert!(Glossary::new("newline", &terms(&["a\nb"]), false, false).is_err());
+    }
+
+    /// 語句をつなげた後にリクエストの初期プロンプトを続け、上限を超える語句は省く
+    #[test]
+    fn test_prompt() {
+        let glossary = glossary(&["Kubernetes", "田中太郎"]);
+        assert_eq!(glossary.prompt(None), "Kubernetes, 田中太郎");
+        assert_eq!(
+            glossary.prompt(Some(" 会議の議事録 ")),
+            "Kubernetes, 田中太郎 会議の議事録"
+        );
+
+        let long_term = "あ".repeat(90);
+        let many: Vec<String> = (0..20).map(|i| format!("{}{}", long_term, i)).collect();
+        let glossary = Glossary::new("many", &many, false, false).unwrap();
+        let prompt = glossary.prompt(Some("文脈"));
+        assert!(prompt.chars().count() <= DecodingParameters::MAX_PROMPT_CHARS);
+        assert!(prompt.ends_with(" 文脈"));
+        assert!(prompt.starts_with(&many[0]));
+    }
+
+    /// 大文字小文字の違いと 1〜2 文字違いを語句の表記に直し、元のテキストを記録する
+    #[test]
+    fn test_correct_near_miss() {
+        let mut segments = vec![
+            TranscriptionSegment::new(" we deployed it on kubernates today".to_string(), 0, 1000),
+            TranscriptionSegment::new(" 担当は田中太朗さんです".to_string(), 1000, 2000),
+            TranscriptionSegment::new(" nothing to fix".to_string(), 2000, 3000),
+        ];
+        let changed = correct_segments(&mut segments, &terms(&["Kubernetes", "田中太郎"]));
+
+        assert_eq!(segments[0].text, " we deployed it on Kubernetes today");
+        assert_eq!(segments[1].text, " 担当は田中太郎さんです");
+        assert_eq!(changed.len(), 2);
+        assert_eq!(changed[0].text, " we deployed it on kubernates today");
+        assert_eq!(changed[0].reason, FilterReason::Glossary);
+    }
+
+    /// 単語の途中や前後の助詞/空白を巻き込まず、短い語句や遠い表記は直さない
+    #[test]
+    fn test_correct_keeps_boundaries() {
+        assert_eq!(
+            corrected("the kubernetesish setup", &["Kubernetes"]),
+            "the kubernetesish setup"
+        );
+        assert_eq!(corrected("は田中太郎です", &["田中太郎"]), "は田中太郎です");
+        assert_eq!(corrected("see the AI team", &["ai"]), "see the AI team");
+        assert_eq!(corrected("Kabanetis", &["Kubernetes"]), "Kabanetis");
+        assert_eq!(
+            corrected("Whispr and whisper.cpp", &["Whisper"]),
+            "Whisper and Whisper.cpp"
+        );
+    }
+
+    /// 登録した用語集はファイルに保存して次回も読み込み、設定の用語集は読み取り専用で優先する
+    #[test]
+    fn test_store_persists() {
+        let dir = TempDir::new().unwrap();
+        let configured = vec![GlossaryConfig {
+            name: "medical".to_string(),
+            terms: terms(&["心電図"]),
+            correct: false,
+        }];
+
+        let store = GlossaryStore::open(dir.path(), &configured);
+        store.put(glossary(&["Kubernetes"])).unwrap();
+        let mut shadowed = glossary(&["上書きされる"]);
+        shadowed.name = "medical".to_string();
+        store.put(shadowed).unwrap();
+
+        let reopened = GlossaryStore::open(dir.path(), &configured);
+        let names: Vec<String> = reopened.list().into_iter().map(|g| g.name).collect();
+        assert_eq!(names, vec!["medical", "test"]);
+        assert!(reopened.get("medical").unwrap().read_only);
+        assert_eq!(reopened.get("test").unwrap().terms, vec!["Kubernetes"]);
+
+        assert!(reopened.delete("test").unwrap());
+        assert!(!reopened.delete("test").unwrap());
+        assert!(GlossaryStore::open(dir.path(), &[]).get("test").is_none());
+    }
+
+    fn app(dir: &TempDir) -> Router {
+        let mut config = Config::default();
+        config.paths.upload_dir = dir.path().to_string_lossy().to_string();
+        config.glossaries = vec![GlossaryConfig {
+            name: "fixed".to_string(),
+            terms: terms(&["固定"]),
+            correct: false,
+        }];
+        Router::new()
+            .route("/glossaries", get(list_glossaries))
+            .route(
+                "/glossaries/{name}",
+                get(get_glossary).put(put_glossary).delete(delete_glossary),
+            )
+            .route("/transcribe", post(transcribe_basic))
+            .with_state(AppState::new(config))
+    }
+
+    async fn send(
+        app: &Router,
+        method: &str,
+        uri: &str,
+        body: Option<&str>,
+    ) -> (StatusCode, serde_json::Value) {
+        let mut request = Request::builder().method(method).uri(uri);
+        if body.is_some() {
+            request = request.header(header::CONTENT_TYPE, "application/json");
+        }
+        let response = app
+            .clone()
+            .oneshot(
+                request
+                    .body(Body::from(body.unwrap_or("").to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or_default())
+    }
+
+    /// 登録/取得/一覧/削除。設定の用語集は変更できず、無い用語集は 404
+    #[tokio::test]
+    async fn test_glossary_endpoints() {
+        let dir = TempDir::new().unwrap();
+        let app = app(&dir);
+
+        let (status, body) = send(
+            &app,
+            "PUT",
+            "/glossaries/product",
+            Some(r#"{"terms": ["Kubernetes", "Grafana"], "correct": true}"#),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["terms"][1], "Grafana");
+        assert!(dir.path().join("glossaries/product.json").exists());
+
+        let (status, body) = send(&app, "GET", "/glossaries", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["glossaries"][0]["name"], "fixed");
+        assert_eq!(body["glossaries"][0]["read_only"], true);
+        assert_eq!(body["glossaries"][1]["name"], "product");
+
+        let (status, _) = send(
+            &app,
+            "PUT",
+            "/glossaries/fixed",
+            Some(r#"{"terms": ["x"]}"#),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = send(
+            &app,
+            "PUT",
+            "/glossaries/bad.name",
+            Some(r#"{"terms": ["x"]}"#),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, _) = send(&app, "DELETE", "/glossaries/product", None).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let (status, body) = send(&app, "GET", "/glossaries/product", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["code"], "GLOSSARY_NOT_FOUND");
+    }
+
+    /// 存在しない用語集を指定した文字起こしは 404
+    #[tokio::test]
+    async fn test_transcribe_unknown_glossary() {
+        let dir = TempDir::new().unwrap();
+        let boundary = "glossary-test-boundary";
+        let body = format!(
+            "--{b}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"a.wav\"\r\n\r\nRIFF\r\n\
+             --{b}\r\nContent-Disposition: form-data; name=\"glossary\"\r\n\r\nmissing\r\n--{b}--\r\n",
+            b = boundary
+        );
+        let response = app(&dir)
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/transcribe")
+                    .header(
+                        header::CONTENT_TYPE,
+                        format!("multipart/form-data; boundary={}", boundary),
+                    )
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
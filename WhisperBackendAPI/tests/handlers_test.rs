@@ -1,15 +1,16 @@
 use axum::{
     body::Body,
     http::{Request, StatusCode},
-    response::Response,
+    response::{IntoResponse, Response},
 };
 use serde_json::Value;
 use std::fs;
+use std::time::{Duration, Instant};
 use tempfile::TempDir;
 use tower::ServiceExt;
 use WhisperBackendAPI::{
     config::Config,
-    handlers::{ApiError, AppState},
+    handlers::{process_transcription_with_timeout, ApiError, AppState, TranscriptionHooks},
     models::{ApiErrorCode, *},
     whisper::WhisperEngine,
 };
@@ -49,9 +50,8 @@ mod handlers_tests {
             assert_eq!(app_state.config.server.host, "127.0.0.1");
             assert_eq!(app_state.config.server.port, 8080);
 
-            // WhisperEngineは初期状態ではNone
-            let engine_guard = app_state.whisper_engine.lock().unwrap();
-            assert!(engine_guard.is_none());
+            // エンジンプールは初期状態ではNone
+            assert!(app_state.engine_pool().is_none());
         }
 
         #[test]
@@ -406,12 +406,14 @@ mod handlers_tests {
             let model_def = &ModelCatalog::default().models["base"];
 
             let model_info = ModelInfo {
+                id: "base".to_string(),
                 name: model_def.name.clone(),
                 file_path: "/test/path/ggml-base.bin".to_string(),
                 size_mb: model_def.size_mb,
                 description: model_def.description.clone(),
                 language_support: model_def.language_support.clone(),
                 is_available: false,
+                is_loaded: false,
             };
 
             assert_eq!(model_info.name, "Whisper Base");
@@ -421,6 +423,46 @@ mod handlers_tests {
                 .language_support
                 .contains(&"multilingual".to_string()));
         }
+
+        /// 存在しないモデルの指定は 404 MODEL_NOT_FOUND
+        #[tokio::test]
+        async fn test_unknown_model_not_found() {
+            let temp_dir = TempDir::new().unwrap();
+            let app_state = create_test_app_state(&temp_dir);
+
+            let error = app_state.load_model("missing").await.unwrap_err();
+            assert!(matches!(error.code, ApiErrorCode::ModelNotFound));
+            assert_eq!(error.status_code(), StatusCode::NOT_FOUND);
+
+            let error = app_state
+                .acquire_engine(Some("../etc/passwd"))
+                .await
+                .err()
+                .unwrap();
+            assert!(matches!(error.code, ApiErrorCode::ModelNotFound));
+
+            // model 未指定で既定モデルが無い場合は 503
+            let error = app_state.acquire_engine(None).await.err().unwrap();
+            assert!(matches!(error.code, ApiErrorCode::ModelNotLoaded));
+        }
+
+        /// 読み込まれていないモデルの破棄は 503 MODEL_NOT_LOADED
+        #[tokio::test]
+        async fn test_unload_model_not_loaded() {
+            let temp_dir = TempDir::new().unwrap();
+            let app_state = create_test_app_state(&temp_dir);
+            fs::write(temp_dir.path().join("models/ggml-base.bin"), b"dummy").unwrap();
+
+            let result = WhisperBackendAPI::handlers::unload_model(
+                axum::extract::State(app_state),
+                axum::extract::Path("base".to_string()),
+            )
+            .await;
+            assert!(matches!(
+                result.err().unwrap().code,
+                ApiErrorCode::ModelNotLoaded
+            ));
+        }
     }
 
     /// エラーレスポンス変換のテスト
@@ -462,6 +504,15 @@ mod handlers_tests {
                     ApiErrorCode::InternalError,
                     StatusCode::INTERNAL_SERVER_ERROR,
                 ),
+                (ApiErrorCode::JobNotFound, StatusCode::NOT_FOUND),
+                (ApiErrorCode::JobNotReady, StatusCode::CONFLICT),
+                (ApiErrorCode::ModelNotFound, StatusCode::NOT_FOUND),
+                (ApiErrorCode::Unauthorized, StatusCode::UNAUTHORIZED),
+                (ApiErrorCode::RateLimited, StatusCode::TOO_MANY_REQUESTS),
+                (ApiErrorCode::QuotaExceeded, StatusCode::TOO_MANY_REQUESTS),
+                (ApiErrorCode::Timeout, StatusCode::GATEWAY_TIMEOUT),
+                (ApiErrorCode::HistoryNotFound, StatusCode::NOT_FOUND),
+                (ApiErrorCode::GlossaryNotFound, StatusCode::NOT_FOUND),
             ];
 
             for (error_code, expected_status) in error_codes_and_statuses {
@@ -537,5 +588,232 @@ mod handlers_tests {
 
             assert_eq!(app_state.config.limits.cleanup_temp_files_after_minutes, 60);
         }
+
+        /// 重みがすべて 0 の小さな ggml モデルを書き出す（whisper.cpp で読み込めるが認識はしない）
+        /// - 語彙は空白の 1 語だけ（残りは whisper.cpp が補う）、エンコーダ/デコーダは 1 層で次元 8
+        fn write_stub_model(path: &std::path::Path) {
+            const STATE: i32 = 8;
+            const N_VOCAB: i32 = 51865;
+            const AUDIO_CTX: i32 = 1500;
+            const TEXT_CTX: i32 = 448;
+            const MELS: i32 = 80;
+
+            let mut bytes = Vec::new();
+            let put = |values: &[i32], bytes: &mut Vec<u8>| {
+                for value in values {
+                    bytes.extend_from_slice(&value.to_le_bytes());
+                }
+            };
+            // magic とハイパーパラメータ（ftype 1 = 行列は f16）
+            put(&[0x67676d6c], &mut bytes);
+            put(
+                &[
+                    N_VOCAB, AUDIO_CTX, STATE, 1, 1, TEXT_CTX, STATE, 1, 1, MELS, 1,
+                ],
+                &mut bytes,
+            );
+            // メルフィルタ（80 x 201）と語彙（suppress_blank が参照する空白の 1 語）
+            put(&[MELS, 201], &mut bytes);
+            bytes.resize(bytes.len() + (MELS * 201 * 4) as usize, 0);
+            put(&[1, 1], &mut bytes);
+            bytes.push(b' ');
+
+            let mut tensors: Vec<(String, Vec<i32>)> = vec![
+                (
+                    "encoder.positional_embedding".into(),
+                    vec![STATE, AUDIO_CTX],
+                ),
+                ("encoder.conv1.weight".into(), vec![3, MELS, STATE]),
+                ("encoder.conv1.bias".into(), vec![1, STATE]),
+                ("encoder.conv2.weight".into(), vec![3, STATE, STATE]),
+                ("encoder.conv2.bias".into(), vec![1, STATE]),
+                ("encoder.ln_post.weight".into(), vec![STATE]),
+                ("encoder.ln_post.bias".into(), vec![STATE]),
+                ("decoder.positional_embedding".into(), vec![STATE, TEXT_CTX]),
+                (
+                    "decoder.token_embedding.weight".into(),
+                    vec![STATE, N_VOCAB],
+                ),
+                ("decoder.ln.weight".into(), vec![STATE]),
+                ("decoder.ln.bias".into(), vec![STATE]),
+            ];
+            let attention = |prefix: &str| -> Vec<(String, Vec<i32>)> {
+                vec![
+                    (format!("{}_ln.weight", prefix), vec![STATE]),
+                    (format!("{}_ln.bias", prefix), vec![STATE]),
+                    (format!("{}.query.weight", prefix), vec![STATE, STATE]),
+                    (format!("{}.query.bias", prefix), vec![STATE]),
+                    (format!("{}.key.weight", prefix), vec![STATE, STATE]),
+                    (format!("{}.value.weight", prefix), vec![STATE, STATE]),
+                    (format!("{}.value.bias", prefix), vec![STATE]),
+                    (format!("{}.out.weight", prefix), vec![STATE, STATE]),
+                    (format!("{}.out.bias", prefix), vec![STATE]),
+                ]
+            };
+            for block in ["encoder.blocks.0", "decoder.blocks.0"] {
+                tensors.extend(vec![
+                    (format!("{}.mlp_ln.weight", block), vec![STATE]),
+                    (format!("{}.mlp_ln.bias", block), vec![STATE]),
+                    (format!("{}.mlp.0.weight", block), vec![STATE, 4 * STATE]),
+                    (format!("{}.mlp.0.bias", block), vec![4 * STATE]),
+                    (format!("{}.mlp.2.weight", block), vec![4 * STATE, STATE]),
+                    (format!("{}.mlp.2.bias", block), vec![STATE]),
+                ]);
+                tensors.extend(attention(&format!("{}.attn", block)));
+            }
+            tensors.extend(attention("decoder.blocks.0.cross_attn"));
+
+            // テンソル: 次元数, 名前の長さ, 型, 形状, 名前, 値（すべて 0）
+            // - 2 次元以上の重み（畳み込み/行列）は f16、それ以外は f32
+            for (name, shape) in tensors {
+                let f16 = shape.len() >= 2 && name.ends_with(".weight");
+                let (ttype, width) = if f16 { (1, 2) } else { (0, 4) };
+                put(&[shape.len() as i32, name.len() as i32, ttype], &mut bytes);
+                put(&shape, &mut bytes);
+                bytes.extend_from_slice(name.as_bytes());
+                let elements: i32 = shape.iter().product();
+                bytes.resize(bytes.len() + (elements * width) as usize, 0);
+            }
+
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, bytes).unwrap();
+        }
+
+        /// 無音の 16kHz モノラル WAV（16-bit PCM）
+        fn silent_wav(seconds: u32) -> Vec<u8> {
+            let data_size = 16000 * 2 * seconds;
+            let mut wav = Vec::new();
+            wav.extend_from_slice(b"RIFF");
+            wav.extend_from_slice(&(36 + data_size).to_le_bytes());
+            wav.extend_from_slice(b"WAVEfmt ");
+            wav.extend_from_slice(&16u32.to_le_bytes());
+            wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+            wav.extend_from_slice(&1u16.to_le_bytes()); // mono
+            wav.extend_from_slice(&16000u32.to_le_bytes());
+            wav.extend_from_slice(&32000u32.to_le_bytes()); // byte rate
+            wav.extend_from_slice(&2u16.to_le_bytes()); // block align
+            wav.extend_from_slice(&16u16.to_le_bytes()); // bits per sample
+            wav.extend_from_slice(b"data");
+            wav.extend_from_slice(&data_size.to_le_bytes());
+            wav.resize(wav.len() + data_size as usize, 0);
+            wav
+        }
+
+        /// 推論が制限時間を超えたら 504（TIMEOUT）を返し、推論を中断してエンジンをプールへ返す
+        #[tokio::test]
+        async fn test_request_timeout_releases_engine() {
+            let temp_dir = TempDir::new().unwrap();
+            let model_path = temp_dir.path().join("models/ggml-stub.bin");
+            write_stub_model(&model_path);
+
+            let mut config = Config::default();
+            config.paths.models_dir = temp_dir.path().join("models").to_string_lossy().into();
+            config.paths.temp_dir = temp_dir.path().to_string_lossy().into();
+            config.paths.upload_dir = temp_dir.path().join("uploads").to_string_lossy().into();
+            config.whisper.model_path = model_path.to_string_lossy().into();
+            config.whisper.default_model = "stub".to_string();
+            config.whisper.language = "ja".to_string();
+            config.whisper.enable_gpu = false;
+            config.performance.whisper_threads = 1;
+            config.performance.max_concurrent_requests = 1;
+            config.performance.request_timeout_seconds = 1;
+            let state = AppState::new(config);
+            state.load_model("stub").await.unwrap();
+            let pool = state.engine_pool().unwrap();
+
+            // 推論が始まったら 3 秒止まる（制限時間より遅い処理）
+            let mut hooks = TranscriptionHooks::default();
+            let mut stalled = false;
+            hooks.inference.on_progress = Some(Box::new(move |_| {
+                if !std::mem::replace(&mut stalled, true) {
+                    std::thread::sleep(Duration::from_secs(3));
+                }
+            }));
+
+            let started = Instant::now();
+            let error = process_transcription_with_timeout(
+                state.clone(),
+                silent_wav(5),
+                "slow.wav".to_string(),
+                TranscribeRequest::default(),
+                None,
+                started,
+                hooks,
+            )
+            .await
+            .expect_err("制限時間を超えたのに成功しました");
+            assert!(matches!(error.code, ApiErrorCode::Timeout));
+            assert_eq!(error.into_response().status(), StatusCode::GATEWAY_TIMEOUT);
+            assert!(started.elapsed() < Duration::from_secs(3));
+
+            // 応答した時点では推論スレッドがまだエンジンを使っている
+            assert!(pool.try_acquire().is_none());
+
+            // 止まっていた処理が戻ると中断され、許可とエンジンがプールへ返る
+            let deadline = Instant::now() + Duration::from_secs(30);
+            while pool.try_acquire().is_none() {
+                assert!(
+                    Instant::now() < deadline,
+                    "エンジンがプールへ返却されません"
+                );
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+            assert_eq!(pool.queued(), 0);
+        }
+
+        /// スタブモデルを 2 つ置いた状態（max_concurrent_requests = 1、待機なし）
+        fn two_model_state(temp_dir: &TempDir, load_models_on_request: bool) -> AppState {
+            let models_dir = temp_dir.path().join("models");
+            write_stub_model(&models_dir.join("ggml-stub-a.bin"));
+            write_stub_model(&models_dir.join("ggml-stub-b.bin"));
+
+            let mut config = Config::default();
+            config.paths.models_dir = models_dir.to_string_lossy().into();
+            config.paths.temp_dir = temp_dir.path().to_string_lossy().into();
+            config.paths.upload_dir = temp_dir.path().join("uploads").to_string_lossy().into();
+            config.whisper.enable_gpu = false;
+            config.whisper.load_models_on_request = load_models_on_request;
+            config.performance.whisper_threads = 1;
+            config.performance.max_concurrent_requests = 1;
+            config.performance.max_queued_requests = 0;
+            AppState::new(config)
+        }
+
+        /// リクエストで読み込まれていないモデルを指定しても読み込まない（設定で許可した場合のみ）
+        #[tokio::test]
+        async fn test_request_does_not_load_model() {
+            let temp_dir = TempDir::new().unwrap();
+            let state = two_model_state(&temp_dir, false);
+
+            let error = state.acquire_engine(Some("stub-a")).await.err().unwrap();
+            assert!(matches!(error.code, ApiErrorCode::ModelNotLoaded));
+            assert!(!state.models.is_loaded("stub-a"));
+
+            state.load_model("stub-a").await.unwrap();
+            assert!(state.acquire_engine(Some("stub-a")).await.is_ok());
+
+            let temp_dir = TempDir::new().unwrap();
+            let state = two_model_state(&temp_dir, true);
+            let (name, _engine) = state.acquire_engine(Some("stub-b")).await.unwrap();
+            assert_eq!(name, "stub-b");
+            assert!(state.models.is_loaded("stub-b"));
+        }
+
+        /// 同時推論数の上限は全モデルの合計に対してかかる
+        #[tokio::test]
+        async fn test_concurrency_limit_shared_across_models() {
+            let temp_dir = TempDir::new().unwrap();
+            let state = two_model_state(&temp_dir, false);
+            state.load_model("stub-a").await.unwrap();
+            state.load_model("stub-b").await.unwrap();
+
+            let (_, engine) = state.acquire_engine(Some("stub-a")).await.unwrap();
+            assert!(state.borrow_idle_engines("stub-b", 1).is_empty());
+            let error = state.acquire_engine(Some("stub-b")).await.err().unwrap();
+            assert!(matches!(error.code, ApiErrorCode::ServerOverloaded));
+
+            drop(engine);
+            assert!(state.acquire_engine(Some("stub-b")).await.is_ok());
+        }
     }
 }
@@ -0,0 +1,450 @@
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, Method, Request, StatusCode},
+    routing::get,
+    Extension, Router,
+};
+use std::sync::Arc;
+use tempfile::TempDir;
+use tower::ServiceExt;
+use WhisperBackendAPI::{
+    audio::ChannelMode,
+    auth::ApiCaller,
+    config::{Config, HistoryConfig},
+    handlers::{delete_history, get_history, get_history_audio, list_history, AppState},
+    history::{HistoryParameters, HistoryQuery, HistoryStore},
+    models::{TranscribeResponse, TranscriptionSegment},
+};
+
+#[cfg(test)]
+mod history_tests {
+    use super::*;
+
+    fn sample_response(text: &str) -> TranscribeResponse {
+        TranscribeResponse {
+            text: text.to_string(),
+            language: Some("ja".to_string()),
+            duration_ms: Some(3000),
+            segments: Some(vec![TranscriptionSegment::new(text.to_string(), 0, 3000)]),
+            processing_time_ms: 250,
+            decoding: None,
+            language_detection: None,
+            model: Some("base".to_string()),
+            filtered: None,
+            corrections: None,
+            history_id: None,
+            failed_chunks: None,
+        }
+    }
+
+    fn parameters() -> HistoryParameters {
+        HistoryParameters {
+            language: Some("ja".to_string()),
+            translate_to_english: false,
+            word_timestamps: false,
+            channel_mode: ChannelMode::Mix,
+            vad: false,
+            filter: true,
+        }
+    }
+
+    fn query(q: Option<&str>) -> HistoryQuery {
+        HistoryQuery {
+            q: q.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    /// 保存したエントリは結果/パラメータ/音声とともに取得できる
+    #[test]
+    fn test_save_and_get() {
+        let dir = TempDir::new().unwrap();
+        let store = HistoryStore::open(dir.path(), &HistoryConfig::default()).unwrap();
+
+        let id = store
+            .save(
+                "meeting.MP3",
+                b"audio-bytes",
+                None,
+                parameters(),
+                sample_response("会議の議事録"),
+            )
+            .unwrap();
+
+        let entry = store.get(&id, None).unwrap();
+        assert_eq!(entry.filename, "meeting.MP3");
+        assert_eq!(entry.audio_file.as_deref(), Some("audio.mp3"));
+        assert_eq!(entry.audio_size_bytes, 11);
+        assert_eq!(entry.parameters, parameters());
+        assert_eq!(entry.result.text, "会議の議事録");
+        assert_eq!(entry.result.segments.as_ref().unwrap().len(), 1);
+
+        let (path, filename) = store.audio_path(&id, None).unwrap();
+        assert_eq!(std::fs::read(path).unwrap(), b"audio-bytes");
+        assert_eq!(filename, "meeting.MP3");
+    }
+
+    /// keep_audio=false の場合は音声を保存しない
+    #[test]
+    fn test_save_without_audio() {
+        let dir = TempDir::new().unwrap();
+        let config = HistoryConfig {
+            keep_audio: false,
+            ..Default::default()
+        };
+        let store = HistoryStore::open(dir.path(), &config).unwrap();
+
+        let id = store
+            .save(
+                "a.wav",
+                b"audio",
+                None,
+                parameters(),
+                sample_response("テスト"),
+            )
+            .unwrap();
+        assert!(store.get(&id, None).unwrap().audio_file.is_none());
+        assert!(store.audio_path(&id, None).is_none());
+        assert!(!store.list(&query(None), None).entries[0].has_audio);
+    }
+
+    /// 本文の検索は大文字小文字を無視し、空白区切りの語句をすべて含むものに絞る
+    #[test]
+    fn test_list_search() {
+        let dir = TempDir::new().unwrap();
+        let store = HistoryStore::open(dir.path(), &HistoryConfig::default()).unwrap();
+        store
+            .save(
+                "1.wav",
+                b"1",
+                None,
+                parameters(),
+                sample_response("Weekly sync: budget review"),
+            )
+            .unwrap();
+        store
+            .save(
+                "2.wav",
+                b"2",
+                None,
+                parameters(),
+                sample_response("予算の確認と weekly 報告"),
+            )
+            .unwrap();
+        store
+            .save("3.wav", b"3", None, parameters(), sample_response("雑談"))
+            .unwrap();
+
+        assert_eq!(store.list(&query(None), None).total, 3);
+        assert_eq!(store.list(&query(Some("WEEKLY")), None).total, 2);
+
+        let found = store.list(&query(Some("weekly 予算")), None);
+        assert_eq!(found.total, 1);
+        assert_eq!(found.entries[0].filename, "2.wav");
+        assert_eq!(store.list(&query(Some("存在しない")), None).total, 0);
+    }
+
+    /// 一覧は limit/offset で区切り、total は区切る前の件数
+    #[test]
+    fn test_list_limit_offset() {
+        let dir = TempDir::new().unwrap();
+        let store = HistoryStore::open(dir.path(), &HistoryConfig::default()).unwrap();
+        for i in 0..5 {
+            store
+                .save(
+                    &format!("{}.wav", i),
+                    b"a",
+                    None,
+                    parameters(),
+                    sample_response("テキスト"),
+                )
+                .unwrap();
+        }
+
+        let page = store.list(
+            &HistoryQuery {
+                limit: Some(2),
+                offset: Some(4),
+                ..Default::default()
+            },
+            None,
+        );
+        assert_eq!(page.total, 5);
+        assert_eq!(page.entries.len(), 1);
+    }
+
+    /// 一覧のプレビューは長い本文を省略する
+    #[test]
+    fn test_list_preview_truncated() {
+        let dir = TempDir::new().unwrap();
+        let store = HistoryStore::open(dir.path(), &HistoryConfig::default()).unwrap();
+        store
+            .save(
+                "long.wav",
+                b"a",
+                None,
+                parameters(),
+                sample_response(&"あ".repeat(500)),
+            )
+            .unwrap();
+
+        let preview = &store.list(&query(None), None).entries[0].preview;
+        assert_eq!(preview.chars().count(), 121);
+        assert!(preview.ends_with('…'));
+    }
+
+    /// 開き直しても保存したエントリが残り、削除するとディレクトリごと消える
+    #[test]
+    fn test_reopen_and_delete() {
+        let dir = TempDir::new().unwrap();
+        let id = {
+            let store = HistoryStore::open(dir.path(), &HistoryConfig::default()).unwrap();
+            store
+                .save(
+                    "a.wav",
+                    b"a",
+                    None,
+                    parameters(),
+                    sample_response("再読み込み"),
+                )
+                .unwrap()
+        };
+
+        let store = HistoryStore::open(dir.path(), &HistoryConfig::default()).unwrap();
+        assert_eq!(store.get(&id, None).unwrap().result.text, "再読み込み");
+
+        assert!(store.delete(&id, None).unwrap());
+        assert!(store.get(&id, None).is_none());
+        assert!(!dir.path().join(&id).exists());
+        assert!(!store.delete(&id, None).unwrap());
+    }
+
+    /// owner を指定すると、そのキーが保存したエントリだけを扱う
+    #[test]
+    fn test_owner_scope() {
+        let dir = TempDir::new().unwrap();
+        let store = HistoryStore::open(dir.path(), &HistoryConfig::default()).unwrap();
+        let id_a = store
+            .save(
+                "a.wav",
+                b"a",
+                Some("team-a".to_string()),
+                parameters(),
+                sample_response("A の会議"),
+            )
+            .unwrap();
+        let id_b = store
+            .save(
+                "b.wav",
+                b"b",
+                Some("team-b".to_string()),
+                parameters(),
+                sample_response("B の会議"),
+            )
+            .unwrap();
+
+        let listed = store.list(&query(None), Some("team-a"));
+        assert_eq!(listed.total, 1);
+        assert_eq!(listed.entries[0].id, id_a);
+        assert_eq!(store.list(&query(None), None).total, 2);
+
+        assert_eq!(
+            store.get(&id_a, Some("team-a")).unwrap().owner.as_deref(),
+            Some("team-a")
+        );
+        assert!(store.get(&id_b, Some("team-a")).is_none());
+        assert!(store.audio_path(&id_b, Some("team-a")).is_none());
+        assert!(!store.delete(&id_b, Some("team-a")).unwrap());
+        assert!(store.get(&id_b, None).is_some());
+        assert!(store.delete(&id_b, Some("team-b")).unwrap());
+    }
+
+    /// entry.json の無いディレクトリ（保存途中）は開くときに削除する
+    #[test]
+    fn test_open_removes_incomplete_entries() {
+        let dir = TempDir::new().unwrap();
+        let incomplete = dir.path().join("incomplete");
+        std::fs::create_dir_all(&incomplete).unwrap();
+        std::fs::write(incomplete.join("audio.wav"), b"a").unwrap();
+
+        let store = HistoryStore::open(dir.path(), &HistoryConfig::default()).unwrap();
+        assert_eq!(store.list(&query(None), None).total, 0);
+        assert!(!incomplete.exists());
+    }
+
+    /// 保存期間を過ぎたエントリは削除される（0 は無期限）
+    #[test]
+    fn test_prune_expired() {
+        let dir = TempDir::new().unwrap();
+        let store = HistoryStore::open(dir.path(), &HistoryConfig::default()).unwrap();
+        let id = store
+            .save("a.wav", b"a", None, parameters(), sample_response("古い"))
+            .unwrap();
+
+        let now = chrono::Utc::now();
+        assert_eq!(store.prune_expired(now + chrono::Duration::days(29)), 0);
+        assert_eq!(store.prune_expired(now + chrono::Duration::days(31)), 1);
+        assert!(store.get(&id, None).is_none());
+        assert!(!dir.path().join(&id).exists());
+
+        let unlimited = HistoryConfig {
+            retention_days: 0,
+            ..Default::default()
+        };
+        let store = HistoryStore::open(dir.path(), &unlimited).unwrap();
+        store
+            .save("b.wav", b"b", None, parameters(), sample_response("無期限"))
+            .unwrap();
+        assert_eq!(
+            store.prune_expired(chrono::Utc::now() + chrono::Duration::days(3650)),
+            0
+        );
+    }
+
+    fn router(state: AppState) -> Router {
+        Router::new()
+            .route("/history", get(list_history))
+            .route("/history/{id}", get(get_history).delete(delete_history))
+            .route("/history/{id}/audio", get(get_history_audio))
+            .with_state(state)
+    }
+
+    fn request(method: Method, uri: &str) -> Request<Body> {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    async fn body_text(response: axum::response::Response) -> String {
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    /// 履歴が無効の場合は 404 を返す
+    #[tokio::test]
+    async fn test_endpoints_when_disabled() {
+        let app = router(AppState::new(Config::default()));
+        let response = app.oneshot(request(Method::GET, "/history")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert!(body_text(response).await.contains("HISTORY_NOT_FOUND"));
+    }
+
+    /// 一覧/検索 → 字幕形式での再ダウンロード → 音声 → 削除
+    #[tokio::test]
+    async fn test_endpoints() {
+        let dir = TempDir::new().unwrap();
+        let store = Arc::new(HistoryStore::open(dir.path(), &HistoryConfig::default()).unwrap());
+        let id = store
+            .save(
+                "meeting.wav",
+                b"RIFF",
+                None,
+                parameters(),
+                sample_response("先週の会議"),
+            )
+            .unwrap();
+        let mut state = AppState::new(Config::default());
+        state.history = Some(store);
+        let app = router(state);
+
+        let response = app
+            .clone()
+            .oneshot(request(Method::GET, "/history?q=%E4%BC%9A%E8%AD%B0"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let list: serde_json::Value = serde_json::from_str(&body_text(response).await).unwrap();
+        assert_eq!(list["total"], 1);
+        assert_eq!(list["entries"][0]["id"], id.as_str());
+
+        let response = app
+            .clone()
+            .oneshot(request(Method::GET, &format!("/history/{}?format=srt", id)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers()[header::CONTENT_DISPOSITION]
+            .to_str()
+            .unwrap()
+            .contains("meeting.srt"));
+        assert!(body_text(response)
+            .await
+            .contains("00:00:00,000 --> 00:00:03,000\n先週の会議"));
+
+        let response = app
+            .clone()
+            .oneshot(request(Method::GET, &format!("/history/{}/audio", id)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body_text(response).await, "RIFF");
+
+        let response = app
+            .clone()
+            .oneshot(request(Method::DELETE, &format!("/history/{}", id)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        let response = app
+            .oneshot(request(Method::GET, &format!("/history/{}", id)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    /// 他のキーが保存した履歴は存在しないものとして扱い、管理者キーはすべて扱える
+    #[tokio::test]
+    async fn test_endpoints_scoped_to_caller() {
+        let dir = TempDir::new().unwrap();
+        let store = Arc::new(HistoryStore::open(dir.path(), &HistoryConfig::default()).unwrap());
+        let id = store
+            .save(
+                "a.wav",
+                b"RIFF",
+                Some("team-a".to_string()),
+                parameters(),
+                sample_response("A の会議"),
+            )
+            .unwrap();
+        let mut state = AppState::new(Config::default());
+        state.history = Some(store);
+        let as_caller = |name: &str, admin: bool| {
+            router(state.clone()).layer(Extension(ApiCaller {
+                name: name.to_string(),
+                admin,
+            }))
+        };
+
+        let other = as_caller("team-b", false);
+        let response = other
+            .clone()
+            .oneshot(request(Method::GET, "/history"))
+            .await
+            .unwrap();
+        let list: serde_json::Value = serde_json::from_str(&body_text(response).await).unwrap();
+        assert_eq!(list["total"], 0);
+        for (method, uri) in [
+            (Method::GET, format!("/history/{}", id)),
+            (Method::GET, format!("/history/{}/audio", id)),
+            (Method::DELETE, format!("/history/{}", id)),
+        ] {
+            let response = other.clone().oneshot(request(method, &uri)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        }
+
+        let response = as_caller("team-a", false)
+            .oneshot(request(Method::GET, &format!("/history/{}", id)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = as_caller("ops", true)
+            .oneshot(request(Method::DELETE, &format!("/history/{}", id)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    }
+}
@@ -39,17 +39,16 @@ mod integration_tests {
     ) -> axum::Json<HealthResponse> {
         let uptime_seconds = state.start_time.elapsed().as_secs();
 
-        let model_loaded = {
-            let engine_guard = state.whisper_engine.lock().unwrap();
-            engine_guard.is_some()
-        };
+        let model_loaded = state.engine_pool().is_some();
 
         axum::Json(HealthResponse {
             status: "healthy".to_string(),
             version: env!("CARGO_PKG_VERSION").to_string(),
             model_loaded,
+            model_name: None,
             uptime_seconds,
             memory_usage_mb: None, // テスト環境ではNone
+            last_cleanup: None,
         })
     }
 
@@ -69,17 +68,19 @@ mod integration_tests {
 
         let mut models = Vec::new();
 
-        for (_key, model_def) in &catalog.models {
+        for (key, model_def) in &catalog.models {
             let file_path = models_dir.join(&model_def.file_name);
             let is_available = file_path.exists();
 
             models.push(ModelInfo {
+                id: key.clone(),
                 name: model_def.name.clone(),
                 file_path: file_path.to_string_lossy().to_string(),
                 size_mb: model_def.size_mb,
                 description: model_def.description.clone(),
                 language_support: model_def.language_support.clone(),
                 is_available,
+                is_loaded: false,
             });
         }
 
@@ -88,6 +89,9 @@ mod integration_tests {
         axum::Json(ModelsResponse {
             models,
             current_model,
+            loaded_models: Vec::new(),
+            memory_used_mb: 0,
+            memory_budget_mb: 0,
         })
     }
 
@@ -551,10 +555,7 @@ mod integration_tests {
         }
 
         // Whisperエンジンの初期状態確認
-        {
-            let engine_guard = app_state.whisper_engine.lock().unwrap();
-            assert!(engine_guard.is_none()); // テスト環境では未初期化
-        }
+        assert!(app_state.engine_pool().is_none()); // テスト環境では未初期化
 
         // 設定値の確認
         assert_eq!(app_state.config.server.host, "127.0.0.1");
@@ -0,0 +1,190 @@
+use axum::extract::State;
+use std::fs::{self, File};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tempfile::TempDir;
+use WhisperBackendAPI::{
+    audio::ChannelMode,
+    config::{Config, HistoryConfig},
+    handlers::{health_check, AppState},
+    history::{HistoryParameters, HistoryStore},
+    janitor::Janitor,
+    models::TranscribeResponse,
+};
+
+#[cfg(test)]
+mod janitor_tests {
+    use super::*;
+
+    const HOUR: Duration = Duration::from_secs(60 * 60);
+
+    /// temp/uploads を一時ディレクトリに置いた設定（60 分より古いファイルを消す）
+    fn test_config(dir: &TempDir) -> Config {
+        let mut config = Config::default();
+        config.paths.temp_dir = dir.path().join("temp").to_string_lossy().into_owned();
+        config.paths.upload_dir = dir.path().join("uploads").to_string_lossy().into_owned();
+        config.limits.cleanup_temp_files_after_minutes = 60;
+        fs::create_dir_all(&config.paths.temp_dir).unwrap();
+        fs::create_dir_all(&config.paths.upload_dir).unwrap();
+        config
+    }
+
+    /// 指定した時間だけ前に更新されたファイルを作る
+    fn write_file(path: &Path, bytes: usize, age: Duration) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, vec![0u8; bytes]).unwrap();
+        File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(SystemTime::now() - age)
+            .unwrap();
+    }
+
+    /// 古いファイルだけを削除し、件数とサイズを記録する
+    #[test]
+    fn test_sweep_removes_old_files() {
+        let dir = TempDir::new().unwrap();
+        let config = test_config(&dir);
+        let temp = Path::new(&config.paths.temp_dir);
+        let uploads = Path::new(&config.paths.upload_dir);
+        write_file(&temp.join("old.wav"), 100, 2 * HOUR);
+        write_file(&temp.join("new.wav"), 100, Duration::ZERO);
+        write_file(&uploads.join("nested/old.mp3"), 50, 3 * HOUR);
+
+        let janitor = Janitor::new(&config, None);
+        assert!(janitor.last_report().is_none());
+        let report = janitor.sweep(SystemTime::now());
+
+        assert_eq!(report.removed_files, 2);
+        assert_eq!(report.removed_bytes, 150);
+        assert_eq!(report.failed, 0);
+        assert!(!temp.join("old.wav").exists());
+        assert!(temp.join("new.wav").exists());
+        assert!(!uploads.join("nested/old.mp3").exists());
+        assert_eq!(janitor.last_report(), Some(report));
+    }
+
+    /// 履歴ディレクトリ（upload_dir/history）と API キーの使用量（upload_dir/auth）は掃除しない
+    #[test]
+    fn test_sweep_skips_history_dir() {
+        let dir = TempDir::new().unwrap();
+        let config = test_config(&dir);
+        let saved = Path::new(&config.paths.upload_dir).join("history/entry-id/audio.wav");
+        let usage = Path::new(&config.paths.upload_dir).join("auth/usage.json");
+        write_file(&saved, 10, 24 * HOUR);
+        write_file(&usage, 10, 24 * HOUR);
+
+        let report = Janitor::new(&config, None).sweep(SystemTime::now());
+        assert_eq!(report.removed_files, 0);
+        assert!(saved.exists());
+        assert!(usage.exists());
+    }
+
+    /// 0 分の設定では掃除しない
+    #[test]
+    fn test_sweep_disabled() {
+        let dir = TempDir::new().unwrap();
+        let mut config = test_config(&dir);
+        config.limits.cleanup_temp_files_after_minutes = 0;
+        let old = Path::new(&config.paths.temp_dir).join("old.wav");
+        write_file(&old, 10, 24 * HOUR);
+
+        let janitor = Janitor::new(&config, None);
+        assert!(janitor.interval().is_none());
+        assert_eq!(janitor.sweep(SystemTime::now()).removed_files, 0);
+        assert!(old.exists());
+    }
+
+    /// 掃除の間隔は保存期間の半分を 1〜10 分に収めたもの
+    #[test]
+    fn test_interval() {
+        let dir = TempDir::new().unwrap();
+        let mut config = test_config(&dir);
+
+        config.limits.cleanup_temp_files_after_minutes = 1;
+        assert_eq!(
+            Janitor::new(&config, None).interval(),
+            Some(Duration::from_secs(60))
+        );
+        config.limits.cleanup_temp_files_after_minutes = 8;
+        assert_eq!(
+            Janitor::new(&config, None).interval(),
+            Some(Duration::from_secs(4 * 60))
+        );
+        config.limits.cleanup_temp_files_after_minutes = 60;
+        assert_eq!(
+            Janitor::new(&config, None).interval(),
+            Some(Duration::from_secs(10 * 60))
+        );
+    }
+
+    /// 保存期間を過ぎた履歴も掃除で削除する
+    #[test]
+    fn test_sweep_prunes_history() {
+        let dir = TempDir::new().unwrap();
+        let config = test_config(&dir);
+        let history_dir = Path::new(&config.paths.upload_dir).join("history");
+        let store = Arc::new(HistoryStore::open(&history_dir, &HistoryConfig::default()).unwrap());
+        let id = store
+            .save(
+                "a.wav",
+                b"a",
+                None,
+                HistoryParameters {
+                    language: None,
+                    translate_to_english: false,
+                    word_timestamps: false,
+                    channel_mode: ChannelMode::Mix,
+                    vad: false,
+                    filter: false,
+                },
+                TranscribeResponse {
+                    text: "古い履歴".to_string(),
+                    language: None,
+                    duration_ms: None,
+                    segments: None,
+                    processing_time_ms: 0,
+                    decoding: None,
+                    language_detection: None,
+                    model: None,
+                    filtered: None,
+                    corrections: None,
+                    history_id: None,
+                    failed_chunks: None,
+                },
+            )
+            .unwrap();
+
+        let janitor = Janitor::new(&config, Some(Arc::clone(&store)));
+        assert_eq!(janitor.sweep(SystemTime::now()).expired_history, 0);
+
+        let report = janitor.sweep(SystemTime::now() + 31 * 24 * HOUR);
+        assert_eq!(report.expired_history, 1);
+        assert!(store.get(&id, None).is_none());
+    }
+
+    /// /health は直近の掃除の結果を返す
+    #[tokio::test]
+    async fn test_health_reports_last_cleanup() {
+        let dir = TempDir::new().unwrap();
+        let config = test_config(&dir);
+        write_file(
+            &Path::new(&config.paths.temp_dir).join("crashed.wav"),
+            42,
+            2 * HOUR,
+        );
+        let state = AppState::new(config);
+
+        let health = health_check(State(state.clone())).await.0;
+        assert!(health.last_cleanup.is_none());
+
+        state.janitor.sweep(SystemTime::now());
+        let health = health_check(State(state)).await.0;
+        let cleanup = health.last_cleanup.unwrap();
+        assert_eq!(cleanup.removed_files, 1);
+        assert_eq!(cleanup.removed_bytes, 42);
+        assert!(!cleanup.finished_at.is_empty());
+    }
+}
@@ -0,0 +1,239 @@
+use axum::{
+    body::Body,
+    http::{Method, Request, StatusCode},
+    routing::get,
+    Extension, Router,
+};
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use tower::ServiceExt;
+use WhisperBackendAPI::{
+    auth::ApiCaller,
+    config::Config,
+    handlers::{cancel_job, get_job, get_job_result, AppState},
+    jobs::{JobOutcome, JobStatus, JobStore},
+    models::{ApiErrorCode, TranscribeResponse},
+};
+
+#[cfg(test)]
+mod jobs_tests {
+    use super::*;
+
+    fn sample_response() -> TranscribeResponse {
+        TranscribeResponse {
+            text: "こんにちは".to_string(),
+            language: Some("ja".to_string()),
+            duration_ms: Some(1000),
+            segments: None,
+            processing_time_ms: 120,
+            decoding: None,
+            language_detection: None,
+            model: None,
+            filtered: None,
+            corrections: None,
+            history_id: None,
+            failed_chunks: None,
+        }
+    }
+
+    /// 登録直後は Queued で結果はまだ取得できない
+    #[test]
+    fn test_job_created_as_queued() {
+        let store = JobStore::new(Duration::from_secs(60));
+        let (id, cancel_flag) = store.create("audio.wav", None);
+
+        let info = store.get(&id, None).unwrap();
+        assert_eq!(info.status, JobStatus::Queued);
+        assert_eq!(info.progress, 0);
+        assert_eq!(info.filename, "audio.wav");
+        assert!(info.started_at.is_none());
+        assert!(!cancel_flag.load(Ordering::SeqCst));
+
+        assert!(matches!(
+            store.outcome(&id, None),
+            Some(JobOutcome::Pending(JobStatus::Queued))
+        ));
+    }
+
+    /// Queued → Running → Completed の遷移と進捗
+    #[test]
+    fn test_job_lifecycle_completed() {
+        let store = JobStore::new(Duration::from_secs(60));
+        let (id, _) = store.create("audio.wav", None);
+
+        store.mark_running(&id);
+        store.set_progress(&id, 40);
+        // 進捗は後退しない
+        store.set_progress(&id, 10);
+
+        let info = store.get(&id, None).unwrap();
+        assert_eq!(info.status, JobStatus::Running);
+        assert_eq!(info.progress, 40);
+        assert!(info.started_at.is_some());
+
+        store.complete(&id, sample_response());
+
+        let info = store.get(&id, None).unwrap();
+        assert_eq!(info.status, JobStatus::Completed);
+        assert_eq!(info.progress, 100);
+        assert!(info.finished_at.is_some());
+
+        match store.outcome(&id, None) {
+            Some(JobOutcome::Completed(response)) => assert_eq!(response.text, "こんにちは"),
+            other => panic!("unexpected outcome: {:?}", other),
+        }
+    }
+
+    /// 失敗したジョブはエラーコードとメッセージを保持する
+    #[test]
+    fn test_job_failed_keeps_error() {
+        let store = JobStore::new(Duration::from_secs(60));
+        let (id, _) = store.create("audio.xyz", None);
+
+        store.fail(
+            &id,
+            ApiErrorCode::UnsupportedFormat,
+            "サポートされていないファイル形式".to_string(),
+            None,
+        );
+
+        let info = store.get(&id, None).unwrap();
+        assert_eq!(info.status, JobStatus::Failed);
+        assert_eq!(info.error.unwrap().code, "UNSUPPORTED_FORMAT");
+
+        match store.outcome(&id, None) {
+            Some(JobOutcome::Failed { code, error }) => {
+                assert!(matches!(code, ApiErrorCode::UnsupportedFormat));
+                assert_eq!(error.error, "サポートされていないファイル形式");
+            }
+            other => panic!("unexpected outcome: {:?}", other),
+        }
+    }
+
+    /// キャンセル後に完了通知が届いても状態は Cancelled のまま
+    #[test]
+    fn test_job_cancel() {
+        let store = JobStore::new(Duration::from_secs(60));
+        let (id, cancel_flag) = store.create("audio.wav", None);
+        store.mark_running(&id);
+
+        let (info, cancelled) = store.cancel(&id, None).unwrap();
+        assert!(cancelled);
+        assert_eq!(info.status, JobStatus::Cancelled);
+        assert!(cancel_flag.load(Ordering::SeqCst));
+
+        store.complete(&id, sample_response());
+        assert_eq!(store.get(&id, None).unwrap().status, JobStatus::Cancelled);
+        assert!(matches!(
+            store.outcome(&id, None),
+            Some(JobOutcome::Cancelled)
+        ));
+
+        // 2回目のキャンセルは状態を変えない
+        let (_, cancelled) = store.cancel(&id, None).unwrap();
+        assert!(!cancelled);
+    }
+
+    /// 終了済みのジョブはキャンセルできない
+    #[test]
+    fn test_job_cancel_after_completion() {
+        let store = JobStore::new(Duration::from_secs(60));
+        let (id, _) = store.create("audio.wav", None);
+        store.complete(&id, sample_response());
+
+        let (info, cancelled) = store.cancel(&id, None).unwrap();
+        assert!(!cancelled);
+        assert_eq!(info.status, JobStatus::Completed);
+    }
+
+    /// 存在しないジョブ
+    #[test]
+    fn test_unknown_job() {
+        let store = JobStore::new(Duration::from_secs(60));
+        assert!(store.get("missing", None).is_none());
+        assert!(store.outcome("missing", None).is_none());
+        assert!(store.cancel("missing", None).is_none());
+    }
+
+    /// owner を指定すると、そのキーが作成したジョブだけを扱う
+    #[test]
+    fn test_job_owner_scope() {
+        let store = JobStore::new(Duration::from_secs(60));
+        let (id, cancel_flag) = store.create("audio.wav", Some("team-a".to_string()));
+        store.complete(&id, sample_response());
+
+        assert!(store.get(&id, Some("team-b")).is_none());
+        assert!(store.outcome(&id, Some("team-b")).is_none());
+        assert!(store.cancel(&id, Some("team-b")).is_none());
+        assert!(store.get(&id, Some("team-a")).is_some());
+        assert!(matches!(
+            store.outcome(&id, Some("team-a")),
+            Some(JobOutcome::Completed(_))
+        ));
+
+        let (running, running_flag) = store.create("b.wav", Some("team-a".to_string()));
+        assert!(store.cancel(&running, Some("team-b")).is_none());
+        assert!(!running_flag.load(Ordering::SeqCst));
+        let (_, cancelled) = store.cancel(&running, Some("team-a")).unwrap();
+        assert!(cancelled);
+        assert!(!cancel_flag.load(Ordering::SeqCst));
+    }
+
+    /// 他のキーが作成したジョブは 404、管理者キーはすべて扱える
+    #[tokio::test]
+    async fn test_job_endpoints_scoped_to_caller() {
+        let state = AppState::new(Config::default());
+        let (id, _) = state.jobs.create("audio.wav", Some("team-a".to_string()));
+        state.jobs.complete(&id, sample_response());
+        let as_caller = |name: &str, admin: bool| {
+            Router::new()
+                .route("/jobs/{id}", get(get_job).delete(cancel_job))
+                .route("/jobs/{id}/result", get(get_job_result))
+                .with_state(state.clone())
+                .layer(Extension(ApiCaller {
+                    name: name.to_string(),
+                    admin,
+                }))
+        };
+        let request = |method: Method, uri: &str| {
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let other = as_caller("team-b", false);
+        for (method, uri) in [
+            (Method::GET, format!("/jobs/{}", id)),
+            (Method::GET, format!("/jobs/{}/result", id)),
+            (Method::DELETE, format!("/jobs/{}", id)),
+        ] {
+            let response = other.clone().oneshot(request(method, &uri)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        }
+
+        for caller in [as_caller("team-a", false), as_caller("ops", true)] {
+            let response = caller
+                .oneshot(request(Method::GET, &format!("/jobs/{}/result", id)))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+    }
+
+    /// 保持期間を過ぎた終了済みジョブは破棄され、実行中のジョブは残る
+    #[test]
+    fn test_job_retention() {
+        let store = JobStore::new(Duration::ZERO);
+        let (finished, _) = store.create("a.wav", None);
+        let (running, _) = store.create("b.wav", None);
+        store.complete(&finished, sample_response());
+        store.mark_running(&running);
+
+        store.prune_expired();
+
+        assert!(store.get(&finished, None).is_none());
+        assert!(store.get(&running, None).is_some());
+    }
+}
@@ -0,0 +1,168 @@
+use axum::{
+    body::{to_bytes, Body},
+    http::{Method, Request, StatusCode},
+    middleware,
+    routing::get,
+    Router,
+};
+use tower::ServiceExt;
+use WhisperBackendAPI::{
+    config::Config,
+    handlers::{get_languages, get_metrics, track_metrics, AppState},
+    metrics::{Metrics, MetricsGauges},
+};
+
+#[cfg(test)]
+mod metrics_tests {
+    use super::*;
+
+    fn render(metrics: &Metrics) -> String {
+        metrics.render(&MetricsGauges::default())
+    }
+
+    /// ヒストグラムのバケットは累積で出力される
+    #[test]
+    fn test_histogram_buckets_are_cumulative() {
+        let metrics = Metrics::new();
+        metrics.record_transcription(400, None);
+        metrics.record_transcription(2_000, None);
+        metrics.record_transcription(700_000, None);
+
+        let out = render(&metrics);
+        assert!(out.contains("whisper_transcription_processing_seconds_bucket{le=\"0.5\"} 1\n"));
+        assert!(out.contains("whisper_transcription_processing_seconds_bucket{le=\"2.5\"} 2\n"));
+        assert!(out.contains("whisper_transcription_processing_seconds_bucket{le=\"600\"} 2\n"));
+        assert!(out.contains("whisper_transcription_processing_seconds_bucket{le=\"+Inf\"} 3\n"));
+        assert!(out.contains("whisper_transcription_processing_seconds_count 3\n"));
+        assert!(out.contains("whisper_transcription_processing_seconds_sum 702.4\n"));
+    }
+
+    /// 境界値は「以下」のバケットに入る
+    #[test]
+    fn test_histogram_bound_is_inclusive() {
+        let metrics = Metrics::new();
+        metrics.record_transcription(1_000, None);
+        let out = render(&metrics);
+        assert!(out.contains("whisper_transcription_processing_seconds_bucket{le=\"0.5\"} 0\n"));
+        assert!(out.contains("whisper_transcription_processing_seconds_bucket{le=\"1\"} 1\n"));
+    }
+
+    /// 音声の長さが分かる場合は実時間比も記録する
+    #[test]
+    fn test_record_transcription_real_time_factor() {
+        let metrics = Metrics::new();
+        metrics.record_transcription(5_000, Some(20_000));
+        metrics.record_transcription(1_000, None);
+
+        let out = render(&metrics);
+        assert!(out.contains("whisper_transcription_audio_seconds_count 1\n"));
+        assert!(out.contains("whisper_transcription_audio_seconds_sum 20\n"));
+        assert!(out.contains("whisper_transcription_real_time_factor_bucket{le=\"0.25\"} 1\n"));
+        assert!(out.contains("whisper_transcription_real_time_factor_bucket{le=\"0.1\"} 0\n"));
+    }
+
+    /// エンジンの空き待ちは処理時間とは別のヒストグラムに記録する
+    #[test]
+    fn test_record_queue_wait_separately() {
+        let metrics = Metrics::new();
+        metrics.record_queue_wait(3_000);
+        metrics.record_transcription(1_000, Some(10_000));
+
+        let out = render(&metrics);
+        assert!(out.contains("# TYPE whisper_engine_queue_wait_seconds histogram\n"));
+        assert!(out.contains("whisper_engine_queue_wait_seconds_sum 3\n"));
+        assert!(out.contains("whisper_engine_queue_wait_seconds_bucket{le=\"2.5\"} 0\n"));
+        assert!(out.contains("whisper_transcription_processing_seconds_sum 1\n"));
+        assert!(out.contains("whisper_transcription_real_time_factor_bucket{le=\"0.1\"} 1\n"));
+    }
+
+    /// リクエスト数・アップロード量・現在値が出力される
+    #[test]
+    fn test_render_counters_and_gauges() {
+        let metrics = Metrics::new();
+        metrics.record_request("POST", "/transcribe", 200);
+        metrics.record_request("POST", "/transcribe", 200);
+        metrics.record_request("POST", "/transcribe", 400);
+        metrics.record_upload(1_024);
+        metrics.record_upload(512);
+
+        let out = metrics.render(&MetricsGauges {
+            active_requests: 2,
+            queued_requests: vec![("base".to_string(), 3), ("large".to_string(), 0)],
+            gpu_enabled: true,
+            uptime_seconds: 42,
+        });
+        assert!(out.contains("# TYPE whisper_http_requests_total counter\n"));
+        assert!(out.contains(
+            "whisper_http_requests_total{method=\"POST\",endpoint=\"/transcribe\",status=\"200\"} 2\n"
+        ));
+        assert!(out.contains(
+            "whisper_http_requests_total{method=\"POST\",endpoint=\"/transcribe\",status=\"400\"} 1\n"
+        ));
+        assert!(out.contains("whisper_upload_bytes_total 1536\n"));
+        assert!(out.contains("whisper_active_requests 2\n"));
+        assert!(out.contains("whisper_queued_requests{model=\"base\"} 3\n"));
+        assert!(out.contains("whisper_models_loaded 2\n"));
+        assert!(out.contains("whisper_gpu_enabled 1\n"));
+        assert!(out.contains("whisper_uptime_seconds 42\n"));
+    }
+
+    /// ラベル値の `"` と `\` はエスケープされる
+    #[test]
+    fn test_label_escaping() {
+        let metrics = Metrics::new();
+        metrics.record_request("GET", "/a\"b\\c", 404);
+
+        let out = render(&metrics);
+        assert!(out.contains("endpoint=\"/a\\\"b\\\\c\""));
+    }
+
+    /// ミドルウェアがルートのパターン別に数え、`/metrics` で出力される
+    #[tokio::test]
+    async fn test_metrics_endpoint_counts_requests() {
+        let state = AppState::new(Config::default());
+        let app = Router::new()
+            .route("/languages", get(get_languages))
+            .route("/metrics", get(get_metrics))
+            .layer(middleware::from_fn_with_state(state.clone(), track_metrics))
+            .with_state(state);
+
+        for uri in ["/languages", "/languages", "/missing"] {
+            app.clone()
+                .oneshot(
+                    Request::builder()
+                        .method(Method::GET)
+                        .uri(uri)
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+        }
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/metrics")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers()["content-type"]
+            .to_str()
+            .unwrap()
+            .starts_with("text/plain; version=0.0.4"));
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let out = String::from_utf8(body.to_vec()).unwrap();
+        assert!(out.contains(
+            "whisper_http_requests_total{method=\"GET\",endpoint=\"/languages\",status=\"200\"} 2\n"
+        ));
+        assert!(out.contains(
+            "whisper_http_requests_total{method=\"GET\",endpoint=\"unmatched\",status=\"404\"} 1\n"
+        ));
+        assert!(out.contains("whisper_models_loaded 0\n"));
+    }
+}
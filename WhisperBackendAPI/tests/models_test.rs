@@ -148,6 +148,81 @@ mod models_tests {
             assert_eq!(cloned.start_time_ms, original.start_time_ms);
             assert_eq!(cloned.end_time_ms, original.end_time_ms);
         }
+
+        /// チャンネル番号があるときだけラベルを付ける
+        #[test]
+        fn test_labelled_text() {
+            let mut segment = TranscriptionSegment::new(" こんにちは ".to_string(), 0, 1000);
+            assert_eq!(segment.labelled_text(), "こんにちは");
+
+            segment.channel = Some(1);
+            assert_eq!(segment.labelled_text(), "[ch1] こんにちは");
+        }
+
+        /// チャンネルごとのセグメントを開始時刻順に 1 本のタイムラインへまとめる
+        #[test]
+        fn test_merge_channel_segments() {
+            let left = vec![
+                TranscriptionSegment::new("A1".to_string(), 0, 1000),
+                TranscriptionSegment::new("A2".to_string(), 3000, 4000),
+            ];
+            let right = vec![
+                TranscriptionSegment::new("B1".to_string(), 0, 500),
+                TranscriptionSegment::new("B2".to_string(), 1500, 2500),
+            ];
+
+            let merged = merge_channel_segments(vec![left, right]);
+            let order: Vec<(&str, Option<usize>)> = merged
+                .iter()
+                .map(|segment| (segment.text.as_str(), segment.channel))
+                .collect();
+
+            assert_eq!(
+                order,
+                vec![
+                    ("A1", Some(0)),
+                    ("B1", Some(1)),
+                    ("B2", Some(1)),
+                    ("A2", Some(0)),
+                ]
+            );
+        }
+    }
+
+    /// ChannelModeのテスト
+    mod channel_mode_tests {
+        use super::*;
+        use WhisperBackendAPI::audio::ChannelMode;
+
+        #[test]
+        fn test_parse() {
+            assert_eq!(ChannelMode::parse("mix"), Some(ChannelMode::Mix));
+            assert_eq!(ChannelMode::parse("mono"), Some(ChannelMode::Mix));
+            assert_eq!(ChannelMode::parse("Left"), Some(ChannelMode::Left));
+            assert_eq!(ChannelMode::parse("right"), Some(ChannelMode::Right));
+            assert_eq!(ChannelMode::parse("2"), Some(ChannelMode::Index(2)));
+            assert_eq!(ChannelMode::parse("SEPARATE"), Some(ChannelMode::Separate));
+            assert!(ChannelMode::parse("center").is_none());
+        }
+
+        #[test]
+        fn test_channel_index() {
+            assert_eq!(ChannelMode::Left.channel_index(2).unwrap(), 0);
+            assert_eq!(ChannelMode::Right.channel_index(2).unwrap(), 1);
+            assert!(ChannelMode::Right.channel_index(1).is_err());
+            assert!(ChannelMode::Index(3).channel_index(2).is_err());
+        }
+
+        #[test]
+        fn test_serde_round_trip() {
+            let request: TranscribeRequest =
+                serde_json::from_str(r#"{"channel_mode": "separate"}"#).unwrap();
+            assert_eq!(request.channel_mode, Some(ChannelMode::Separate));
+
+            let json = serde_json::to_string(&ChannelMode::Index(1)).unwrap();
+            assert_eq!(json, r#""1""#);
+            assert!(serde_json::from_str::<ChannelMode>(r#""center""#).is_err());
+        }
     }
 
     /// ServerStatsのテスト
@@ -491,6 +566,15 @@ mod models_tests {
             assert_eq!(ApiErrorCode::ProcessingFailed.as_str(), "PROCESSING_FAILED");
             assert_eq!(ApiErrorCode::ModelNotLoaded.as_str(), "MODEL_NOT_LOADED");
             assert_eq!(ApiErrorCode::ServerOverloaded.as_str(), "SERVER_OVERLOADED");
+            assert_eq!(ApiErrorCode::Unauthorized.as_str(), "UNAUTHORIZED");
+            assert_eq!(ApiErrorCode::RateLimited.as_str(), "RATE_LIMITED");
+            assert_eq!(ApiErrorCode::QuotaExceeded.as_str(), "QUOTA_EXCEEDED");
+            assert_eq!(ApiErrorCode::Timeout.as_str(), "TIMEOUT");
+            assert_eq!(ApiErrorCode::HistoryNotFound.as_str(), "HISTORY_NOT_FOUND");
+            assert_eq!(
+                ApiErrorCode::GlossaryNotFound.as_str(),
+                "GLOSSARY_NOT_FOUND"
+            );
             assert_eq!(ApiErrorCode::InternalError.as_str(), "INTERNAL_ERROR");
         }
 
@@ -511,8 +595,22 @@ mod models_tests {
         fn test_transcribe_request_default_values() {
             let request = TranscribeRequest {
                 language: None,
+                model: None,
                 translate_to_english: None,
                 include_timestamps: None,
+                word_timestamps: None,
+                format: None,
+                channel_mode: None,
+                vad: None,
+                filter: None,
+                chunking: None,
+                glossary: None,
+                glossary_correction: None,
+                diarize: None,
+                num_speakers: None,
+                callback_url: None,
+                callback_secret: None,
+                decoding: Default::default(),
             };
 
             assert!(request.language.is_none());
@@ -524,8 +622,22 @@ mod models_tests {
         fn test_transcribe_request_with_values() {
             let request = TranscribeRequest {
                 language: Some("ja".to_string()),
+                model: None,
                 translate_to_english: Some(true),
                 include_timestamps: Some(false),
+                word_timestamps: None,
+                format: None,
+                channel_mode: None,
+                vad: None,
+                filter: None,
+                chunking: None,
+                glossary: None,
+                glossary_correction: None,
+                diarize: None,
+                num_speakers: None,
+                callback_url: None,
+                callback_secret: None,
+                decoding: Default::default(),
             };
 
             assert_eq!(request.language, Some("ja".to_string()));
@@ -546,6 +658,13 @@ mod models_tests {
                 duration_ms: Some(2000),
                 segments: Some(segments.clone()),
                 processing_time_ms: 1500,
+                decoding: None,
+                language_detection: None,
+                model: None,
+                filtered: None,
+                corrections: None,
+                history_id: None,
+                failed_chunks: None,
             };
 
             assert_eq!(response.text, "Hello World");
@@ -559,12 +678,14 @@ mod models_tests {
         #[test]
         fn test_model_info() {
             let model_info = ModelInfo {
+                id: "test".to_string(),
                 name: "Test Model".to_string(),
                 file_path: "/path/to/model.bin".to_string(),
                 size_mb: 500,
                 description: "Test description".to_string(),
                 language_support: vec!["en".to_string(), "ja".to_string()],
                 is_available: true,
+                is_loaded: false,
             };
 
             assert_eq!(model_info.name, "Test Model");
@@ -579,26 +700,33 @@ mod models_tests {
         fn test_models_response() {
             let models = vec![
                 ModelInfo {
+                    id: "model-1".to_string(),
                     name: "Model 1".to_string(),
                     file_path: "/path/1.bin".to_string(),
                     size_mb: 100,
                     description: "Description 1".to_string(),
                     language_support: vec!["en".to_string()],
                     is_available: true,
+                    is_loaded: false,
                 },
                 ModelInfo {
+                    id: "model-2".to_string(),
                     name: "Model 2".to_string(),
                     file_path: "/path/2.bin".to_string(),
                     size_mb: 200,
                     description: "Description 2".to_string(),
                     language_support: vec!["ja".to_string()],
                     is_available: false,
+                    is_loaded: false,
                 },
             ];
 
             let response = ModelsResponse {
                 models: models.clone(),
                 current_model: "Model 1".to_string(),
+                loaded_models: Vec::new(),
+                memory_used_mb: 0,
+                memory_budget_mb: 0,
             };
 
             assert_eq!(response.models.len(), 2);
@@ -611,8 +739,10 @@ mod models_tests {
                 status: "healthy".to_string(),
                 version: "1.0.0".to_string(),
                 model_loaded: true,
+                model_name: None,
                 uptime_seconds: 3600,
                 memory_usage_mb: Some(512),
+                last_cleanup: None,
             };
 
             assert_eq!(health.status, "healthy");
@@ -634,6 +764,52 @@ mod models_tests {
             assert_eq!(error.code, "INTERNAL_ERROR");
             assert_eq!(error.details, Some("Stack trace here".to_string()));
         }
+
+        /// SSE イベントの名前と JSON、終了判定
+        #[test]
+        fn test_transcribe_stream_event() {
+            let progress = TranscribeStreamEvent::Progress(42);
+            assert_eq!(progress.name(), "progress");
+            assert_eq!(progress.data().unwrap(), r#"{"progress":42}"#);
+            assert!(!progress.is_final());
+
+            let segment = TranscribeStreamEvent::Segment(TranscriptionSegment::new(
+                "こんにちは".to_string(),
+                0,
+                1500,
+            ));
+            assert_eq!(segment.name(), "segment");
+            let value: serde_json::Value = serde_json::from_str(&segment.data().unwrap()).unwrap();
+            assert_eq!(value["text"], "こんにちは");
+            assert_eq!(value["end_time_ms"], 1500);
+
+            let done = TranscribeStreamEvent::Done(Box::new(TranscribeResponse {
+                text: "こんにちは".to_string(),
+                language: Some("ja".to_string()),
+                duration_ms: Some(1500),
+                segments: None,
+                processing_time_ms: 250,
+                decoding: None,
+                language_detection: None,
+                model: None,
+                filtered: None,
+                corrections: None,
+                history_id: None,
+                failed_chunks: None,
+            }));
+            assert_eq!(done.name(), "done");
+            let value: serde_json::Value = serde_json::from_str(&done.data().unwrap()).unwrap();
+            assert_eq!(value["processing_time_ms"], 250);
+            assert!(done.is_final());
+
+            let error = TranscribeStreamEvent::Error(ErrorResponse {
+                error: "失敗".to_string(),
+                code: "PROCESSING_FAILED".to_string(),
+                details: None,
+            });
+            assert_eq!(error.name(), "error");
+            assert!(error.is_final());
+        }
     }
 
     /// シリアライゼーション/デシリアライゼーションのテスト
@@ -692,8 +868,22 @@ mod models_tests {
         fn test_transcribe_request_json_serialization() {
             let request = TranscribeRequest {
                 language: Some("ja".to_string()),
+                model: None,
                 translate_to_english: Some(false),
                 include_timestamps: Some(true),
+                word_timestamps: None,
+                format: None,
+                channel_mode: None,
+                vad: None,
+                filter: None,
+                chunking: None,
+                glossary: None,
+                glossary_correction: None,
+                diarize: None,
+                num_speakers: None,
+                callback_url: None,
+                callback_secret: None,
+                decoding: Default::default(),
             };
 
             let json = serde_json::to_string(&request).unwrap();
@@ -721,4 +911,294 @@ mod models_tests {
             }
         }
     }
+
+    /// デコードパラメータの検証テスト
+    mod decoding_options_tests {
+        use super::*;
+
+        #[test]
+        fn test_resolve_defaults() {
+            let params = DecodingOptions::default().resolve().unwrap();
+
+            assert_eq!(params, DecodingParameters::default());
+            assert_eq!(params.strategy, "greedy");
+            assert_eq!(params.best_of, 1);
+            assert_eq!(params.temperature, 0.0);
+            assert!(params.suppress_blank);
+        }
+
+        #[test]
+        fn test_resolve_with_values() {
+            let options = DecodingOptions {
+                temperature: Some(0.4),
+                temperature_increment: Some(0.0),
+                beam_size: Some(5),
+                no_speech_threshold: Some(0.3),
+                logprob_threshold: Some(-0.5),
+                initial_prompt: Some("  議事録  ".to_string()),
+                suppress_blank: Some(false),
+                ..Default::default()
+            };
+
+            let params = options.resolve().unwrap();
+            assert_eq!(params.strategy, "beam_search");
+            assert_eq!(params.beam_size, Some(5));
+            assert_eq!(params.temperature, 0.4);
+            assert_eq!(params.temperature_increment, 0.0);
+            assert_eq!(params.no_speech_threshold, 0.3);
+            assert_eq!(params.logprob_threshold, -0.5);
+            assert_eq!(params.initial_prompt.as_deref(), Some("議事録"));
+            assert!(!params.suppress_blank);
+        }
+
+        #[test]
+        fn test_resolve_rejects_out_of_range() {
+            let invalid = [
+                DecodingOptions {
+                    temperature: Some(1.5),
+                    ..Default::default()
+                },
+                DecodingOptions {
+                    temperature: Some(f32::NAN),
+                    ..Default::default()
+                },
+                DecodingOptions {
+                    beam_size: Some(0),
+                    ..Default::default()
+                },
+                DecodingOptions {
+                    best_of: Some(DecodingParameters::MAX_DECODERS + 1),
+                    ..Default::default()
+                },
+                DecodingOptions {
+                    no_speech_threshold: Some(-0.1),
+                    ..Default::default()
+                },
+                DecodingOptions {
+                    logprob_threshold: Some(0.5),
+                    ..Default::default()
+                },
+                DecodingOptions {
+                    initial_prompt: Some("あ".repeat(DecodingParameters::MAX_PROMPT_CHARS + 1)),
+                    ..Default::default()
+                },
+            ];
+
+            for options in invalid {
+                assert!(options.resolve().is_err(), "{:?}", options);
+            }
+        }
+
+        /// 空白だけのプロンプトは未指定扱い
+        #[test]
+        fn test_resolve_blank_prompt() {
+            let options = DecodingOptions {
+                initial_prompt: Some("   ".to_string()),
+                ..Default::default()
+            };
+            assert_eq!(options.resolve().unwrap().initial_prompt, None);
+        }
+
+        /// JSON ではデコードパラメータをリクエストの直下に書ける
+        #[test]
+        fn test_transcribe_request_flattened_decoding() {
+            let request: TranscribeRequest = serde_json::from_str(
+                r#"{"language": "ja", "temperature": 0.2, "no_speech_threshold": 0.5}"#,
+            )
+            .unwrap();
+
+            assert_eq!(request.decoding.temperature, Some(0.2));
+            assert_eq!(request.decoding.no_speech_threshold, Some(0.5));
+            assert_eq!(request.decoding.beam_size, None);
+        }
+    }
+
+    /// 言語検出結果のテスト
+    mod language_detection_tests {
+        use super::*;
+
+        #[test]
+        fn test_from_probabilities_sorted() {
+            let detection = LanguageDetection::from_probabilities(vec![
+                ("en".to_string(), 0.1),
+                ("ja".to_string(), 0.85),
+                ("ko".to_string(), 0.05),
+            ])
+            .unwrap();
+
+            assert_eq!(detection.language, "ja");
+            assert_eq!(detection.probability, 0.85);
+            let order: Vec<&str> = detection
+                .probabilities
+                .iter()
+                .map(|p| p.language.as_str())
+                .collect();
+            assert_eq!(order, vec!["ja", "en", "ko"]);
+        }
+
+        #[test]
+        fn test_from_probabilities_truncated() {
+            let probabilities = (0..50)
+                .map(|i| (format!("l{}", i), i as f32 / 100.0))
+                .collect();
+            let detection = LanguageDetection::from_probabilities(probabilities).unwrap();
+
+            assert_eq!(
+                detection.probabilities.len(),
+                LanguageDetection::TOP_CANDIDATES
+            );
+            assert_eq!(detection.language, "l49");
+        }
+
+        /// NaN は除外し、候補が無ければ None
+        #[test]
+        fn test_from_probabilities_invalid() {
+            assert!(LanguageDetection::from_probabilities(vec![]).is_none());
+            assert!(
+                LanguageDetection::from_probabilities(vec![("en".to_string(), f32::NAN)]).is_none()
+            );
+        }
+
+        /// `/detect-language` のレスポンスは検出結果をトップレベルに展開する
+        #[test]
+        fn test_detect_language_response_json() {
+            let response = DetectLanguageResponse {
+                detection: LanguageDetection::from_probabilities(vec![("ja".to_string(), 0.9)])
+                    .unwrap(),
+                duration_ms: 1000,
+                processing_time_ms: 20,
+            };
+
+            let value = serde_json::to_value(&response).unwrap();
+            assert_eq!(value["language"], "ja");
+            assert_eq!(value["probabilities"][0]["language"], "ja");
+            assert_eq!(value["duration_ms"], 1000);
+        }
+
+        /// 言語検出結果が無い場合は JSON に含めない
+        #[test]
+        fn test_transcribe_response_omits_missing_detection() {
+            let response = TranscribeResponse {
+                text: "hello".to_string(),
+                language: Some("en".to_string()),
+                duration_ms: None,
+                segments: None,
+                processing_time_ms: 0,
+                decoding: None,
+                language_detection: None,
+                model: None,
+                filtered: None,
+                corrections: None,
+                history_id: None,
+                failed_chunks: None,
+            };
+
+            let value = serde_json::to_value(&response).unwrap();
+            assert!(value.get("language_detection").is_none());
+        }
+
+        /// 用語集で直したセグメントは `filtered` ではなく `corrections` に入る
+        #[test]
+        fn test_transcribe_response_separates_corrections() {
+            let segment = TranscriptionSegment::new("ウィスパー".to_string(), 0, 1000);
+            let response = TranscribeResponse {
+                corrections: Some(vec![FilteredSegment::new(&segment, FilterReason::Glossary)]),
+                ..serde_json::from_str(r#"{"text": "Whisper", "processing_time_ms": 0}"#).unwrap()
+            };
+
+            let value = serde_json::to_value(&response).unwrap();
+            assert!(value.get("filtered").is_none());
+            assert_eq!(value["corrections"][0]["text"], "ウィスパー");
+            assert_eq!(value["corrections"][0]["reason"], "glossary");
+        }
+    }
+
+    /// 単語タイムスタンプのテスト
+    mod word_timing_tests {
+        use super::*;
+
+        fn token(text: &[u8], start: u64, end: u64, probability: f32) -> TokenTiming {
+            TokenTiming {
+                bytes: text.to_vec(),
+                start_time_ms: start,
+                end_time_ms: end,
+                probability,
+            }
+        }
+
+        /// 空白で始まるトークンが単語の先頭になり、句読点は直前の単語に含める
+        #[test]
+        fn test_words_from_spaced_tokens() {
+            let tokens = vec![
+                token(b" Hel", 0, 200, 0.8),
+                token(b"lo", 200, 400, 0.6),
+                token(b" world", 450, 900, 0.9),
+                token(b".", 900, 950, 0.5),
+            ];
+
+            let words = WordTiming::from_tokens(&tokens);
+
+            assert_eq!(words.len(), 2);
+            assert_eq!(words[0].word, "Hello");
+            assert_eq!(words[0].start_time_ms, 0);
+            assert_eq!(words[0].end_time_ms, 400);
+            assert!((words[0].probability - 0.7).abs() < 1e-6);
+            assert_eq!(words[1].word, "world.");
+            assert_eq!(words[1].end_time_ms, 950);
+        }
+
+        /// 空白で区切らない日本語は文字（トークン）ごとに単語とする
+        #[test]
+        fn test_words_from_japanese_tokens() {
+            let tokens = vec![
+                token("こん".as_bytes(), 0, 300, 0.9),
+                token("にち".as_bytes(), 300, 600, 0.9),
+                token("は".as_bytes(), 600, 700, 0.9),
+                token("。".as_bytes(), 700, 750, 0.9),
+            ];
+
+            let words: Vec<String> = WordTiming::from_tokens(&tokens)
+                .into_iter()
+                .map(|w| w.word)
+                .collect();
+
+            assert_eq!(words, vec!["こん", "にち", "は。"]);
+        }
+
+        /// マルチバイト文字の途中で分かれたトークンは結合する
+        #[test]
+        fn test_words_from_split_utf8_tokens() {
+            let bytes = "世".as_bytes();
+            let tokens = vec![
+                token(&bytes[..1], 0, 100, 0.4),
+                token(&bytes[1..], 100, 200, 0.8),
+            ];
+
+            let words = WordTiming::from_tokens(&tokens);
+
+            assert_eq!(words.len(), 1);
+            assert_eq!(words[0].word, "世");
+            assert_eq!(words[0].start_time_ms, 0);
+            assert_eq!(words[0].end_time_ms, 200);
+            assert!((words[0].probability - 0.6).abs() < 1e-6);
+        }
+
+        /// 単語情報が無いセグメントは従来どおりの JSON になる
+        #[test]
+        fn test_segment_json_omits_word_fields() {
+            let segment = TranscriptionSegment::new("Test".to_string(), 0, 1000);
+            let value = serde_json::to_value(&segment).unwrap();
+            assert!(value.get("words").is_none());
+            assert!(value.get("avg_logprob").is_none());
+
+            let mut segment = segment;
+            segment.words = Some(WordTiming::from_tokens(&[token(b" Test", 0, 1000, 0.9)]));
+            segment.avg_logprob = Some(-0.2);
+            segment.no_speech_prob = Some(0.01);
+            let value = serde_json::to_value(&segment).unwrap();
+            assert_eq!(value["words"][0]["word"], "Test");
+            assert_eq!(value["words"][0]["end_time_ms"], 1000);
+            assert!(value["no_speech_prob"].is_number());
+        }
+    }
 }
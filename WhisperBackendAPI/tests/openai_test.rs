@@ -0,0 +1,205 @@
+use axum::{http::StatusCode, response::IntoResponse};
+use WhisperBackendAPI::{
+    handlers::{ApiError, OpenAiError},
+    models::{ApiErrorCode, TranscribeResponse, TranscriptionSegment, WordTiming},
+    openai::{self, OpenAiAudioRequest, OpenAiResponseFormat, TimestampGranularity},
+};
+
+#[cfg(test)]
+mod openai_tests {
+    use super::*;
+
+    fn sample_response() -> TranscribeResponse {
+        TranscribeResponse {
+            text: " Hello world.".to_string(),
+            language: Some("ja".to_string()),
+            duration_ms: Some(2500),
+            segments: Some(vec![
+                TranscriptionSegment::new(" Hello".to_string(), 0, 1200),
+                TranscriptionSegment::new(" world.".to_string(), 1200, 2500),
+            ]),
+            processing_time_ms: 100,
+            decoding: None,
+            language_detection: None,
+            model: None,
+            filtered: None,
+            corrections: None,
+            history_id: None,
+            failed_chunks: None,
+        }
+    }
+
+    fn request_with(format: OpenAiResponseFormat) -> OpenAiAudioRequest {
+        OpenAiAudioRequest {
+            response_format: format,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_response_format_parse() {
+        assert_eq!(
+            OpenAiResponseFormat::parse("verbose_json"),
+            Some(OpenAiResponseFormat::VerboseJson)
+        );
+        assert_eq!(
+            OpenAiResponseFormat::parse("SRT"),
+            Some(OpenAiResponseFormat::Srt)
+        );
+        // OpenAI API に無い形式は受け付けない
+        assert_eq!(OpenAiResponseFormat::parse("tsv"), None);
+        assert_eq!(OpenAiResponseFormat::default(), OpenAiResponseFormat::Json);
+    }
+
+    #[test]
+    fn test_timestamp_granularity_parse() {
+        assert_eq!(
+            TimestampGranularity::parse("segment"),
+            Some(TimestampGranularity::Segment)
+        );
+        assert_eq!(
+            TimestampGranularity::parse("word"),
+            Some(TimestampGranularity::Word)
+        );
+        assert_eq!(TimestampGranularity::parse("char"), None);
+    }
+
+    /// whisper-1 は既定モデルの別名
+    #[test]
+    fn test_server_model() {
+        let mut request = OpenAiAudioRequest::default();
+        assert_eq!(request.server_model(), None);
+
+        request.model = Some("whisper-1".to_string());
+        assert_eq!(request.server_model(), None);
+
+        request.model = Some("base".to_string());
+        assert_eq!(request.server_model(), Some("base"));
+    }
+
+    /// json は `{"text": ...}` のみ
+    #[test]
+    fn test_render_json() {
+        let body = openai::render(
+            &sample_response(),
+            &request_with(OpenAiResponseFormat::Json),
+            false,
+        )
+        .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&body).unwrap();
+
+        assert_eq!(value, serde_json::json!({ "text": "Hello world." }));
+    }
+
+    #[test]
+    fn test_render_text_and_subtitles() {
+        let response = sample_response();
+
+        let text =
+            openai::render(&response, &request_with(OpenAiResponseFormat::Text), false).unwrap();
+        assert_eq!(text, "Hello world.\n");
+
+        let srt =
+            openai::render(&response, &request_with(OpenAiResponseFormat::Srt), false).unwrap();
+        assert!(srt.starts_with("1\n00:00:00,000 --> 00:00:01,200\nHello\n"));
+
+        let vtt =
+            openai::render(&response, &request_with(OpenAiResponseFormat::Vtt), false).unwrap();
+        assert!(vtt.starts_with("WEBVTT\n\n00:00:00.000 --> 00:00:01.200\nHello\n"));
+    }
+
+    /// verbose_json は言語を英語名の小文字で返し、セグメントは秒単位
+    #[test]
+    fn test_render_verbose_json() {
+        let body = openai::render(
+            &sample_response(),
+            &request_with(OpenAiResponseFormat::VerboseJson),
+            true,
+        )
+        .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&body).unwrap();
+
+        assert_eq!(value["task"], "translate");
+        assert_eq!(value["language"], "japanese");
+        assert_eq!(value["duration"], 2.5);
+        assert_eq!(value["text"], "Hello world.");
+        assert_eq!(value["segments"][1]["id"], 1);
+        assert_eq!(value["segments"][1]["seek"], 120);
+        assert_eq!(value["segments"][1]["start"], 1.2);
+        assert_eq!(value["segments"][1]["end"], 2.5);
+        assert_eq!(value["segments"][1]["text"], "world.");
+    }
+
+    /// word を指定すると単語をトップレベルの words に返し、segment を含まなければセグメントは省く
+    #[test]
+    fn test_render_verbose_json_words() {
+        let mut response = sample_response();
+        let segments = response.segments.as_mut().unwrap();
+        segments[0].words = Some(vec![WordTiming {
+            word: "Hello".to_string(),
+            start_time_ms: 100,
+            end_time_ms: 900,
+            probability: 0.9,
+        }]);
+        segments[0].avg_logprob = Some(-0.25);
+
+        let mut request = request_with(OpenAiResponseFormat::VerboseJson);
+        request.timestamp_granularities = vec![TimestampGranularity::Word];
+        let body = openai::render(&response, &request, false).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&body).unwrap();
+
+        assert_eq!(value["words"][0]["word"], "Hello");
+        assert_eq!(value["words"][0]["start"], 0.1);
+        assert_eq!(value["words"][0]["end"], 0.9);
+        assert!(value.get("segments").is_none());
+
+        request
+            .timestamp_granularities
+            .push(TimestampGranularity::Segment);
+        let body = openai::render(&response, &request, false).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(value["segments"][0]["avg_logprob"], -0.25);
+        assert!(value["segments"][1].get("avg_logprob").is_none());
+    }
+
+    #[test]
+    fn test_content_types() {
+        assert_eq!(
+            OpenAiResponseFormat::Json.content_type(),
+            "application/json"
+        );
+        assert_eq!(
+            OpenAiResponseFormat::Text.content_type(),
+            "text/plain; charset=utf-8"
+        );
+    }
+
+    /// エラーは OpenAI 形式 `{"error": {"message", "type", "param", "code"}}` で返す
+    #[tokio::test]
+    async fn test_openai_error_response() {
+        let error = OpenAiError(ApiError::new(
+            ApiErrorCode::InvalidInput,
+            "ファイルが見つかりません",
+        ));
+        let response = error.into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(value["error"]["message"], "ファイルが見つかりません");
+        assert_eq!(value["error"]["type"], "invalid_request_error");
+        assert_eq!(value["error"]["code"], "invalid_input");
+        assert!(value["error"]["param"].is_null());
+
+        let error = OpenAiError(ApiError::new(ApiErrorCode::ModelNotLoaded, "未初期化"));
+        let response = error.into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(value["error"]["type"], "server_error");
+    }
+}
@@ -0,0 +1,121 @@
+use std::fs;
+use tempfile::TempDir;
+use WhisperBackendAPI::{
+    config::Config,
+    models::ModelCatalog,
+    registry::{resolve_model, ModelRegistry, RegistryError},
+};
+
+#[cfg(test)]
+mod registry_tests {
+    use super::*;
+
+    /// 予算を超える場合は既定モデル以外を LRU 順に破棄する
+    #[test]
+    fn test_lru_eviction_keeps_default() {
+        let registry = ModelRegistry::new(1000);
+        registry.insert("large", "L", 600, true).unwrap();
+        registry.insert("base", "B", 150, false).unwrap();
+        registry.insert("small", "S", 250, false).unwrap();
+        assert_eq!(registry.total_size_mb(), 1000);
+
+        // base を使うと small が最も長く使われていないモデルになる
+        assert_eq!(registry.get("base").as_deref(), Some(&"B"));
+        let evicted = registry.insert("tiny", "T", 40, false).unwrap();
+
+        assert_eq!(evicted, vec!["small".to_string()]);
+        assert!(registry.is_loaded("large"));
+        assert!(registry.is_loaded("base"));
+        assert!(!registry.is_loaded("small"));
+        assert_eq!(registry.total_size_mb(), 790);
+    }
+
+    /// 既定モデルと合わせて予算を超えるモデルは読み込めない（何も破棄しない）
+    #[test]
+    fn test_exceeds_budget() {
+        let registry = ModelRegistry::new(1000);
+        registry.insert("large", "L", 800, true).unwrap();
+        registry.insert("base", "B", 150, false).unwrap();
+
+        let error = registry.insert("medium", "M", 500, false).unwrap_err();
+        assert_eq!(
+            error,
+            RegistryError::ExceedsBudget {
+                name: "medium".to_string(),
+                size_mb: 500,
+                available_mb: 200,
+            }
+        );
+        assert!(registry.is_loaded("base"));
+    }
+
+    /// 予算 0 は無制限
+    #[test]
+    fn test_unlimited_budget() {
+        let registry = ModelRegistry::new(0);
+        registry.insert("a", 1, 10_000, true).unwrap();
+        assert!(registry.insert("b", 2, 10_000, false).unwrap().is_empty());
+        assert_eq!(registry.loaded().len(), 2);
+    }
+
+    /// 最初に登録したモデルが既定になり、破棄すると既定は未設定になる
+    #[test]
+    fn test_default_model_and_remove() {
+        let registry = ModelRegistry::new(0);
+        assert!(registry.default_model().is_none());
+
+        registry.insert("base", "B", 150, false).unwrap();
+        registry.insert("small", "S", 250, false).unwrap();
+        assert_eq!(registry.default_name().as_deref(), Some("base"));
+        assert_eq!(registry.default_model().as_deref(), Some(&"B"));
+
+        let loaded = registry.loaded();
+        assert_eq!(loaded[0].name, "base");
+        assert!(loaded[0].is_default);
+        assert!(!loaded[1].is_default);
+
+        assert!(registry.remove("base"));
+        assert!(!registry.remove("base"));
+        assert!(registry.default_model().is_none());
+        assert!(registry.get("small").is_some());
+    }
+
+    /// モデル名からファイルパスへの解決
+    #[test]
+    fn test_resolve_model() {
+        let temp_dir = TempDir::new().unwrap();
+        let models_dir = temp_dir.path();
+        fs::write(models_dir.join("ggml-base.bin"), b"base").unwrap();
+        fs::write(models_dir.join("ggml-custom.bin"), b"custom").unwrap();
+        fs::write(models_dir.join("ggml-large-v3-turbo-q5_0.bin"), b"large").unwrap();
+
+        let mut config = Config::default();
+        config.paths.models_dir = models_dir.to_string_lossy().to_string();
+        config.whisper.model_path = models_dir
+            .join("ggml-large-v3-turbo-q5_0.bin")
+            .to_string_lossy()
+            .to_string();
+        config.whisper.default_model = "large-q5_0".to_string();
+        let catalog = ModelCatalog::default();
+
+        // カタログのキー
+        let (name, path) = resolve_model(&config, &catalog, "base").unwrap();
+        assert_eq!(name, "base");
+        assert_eq!(path, models_dir.join("ggml-base.bin"));
+
+        // カタログに無くても ggml-{name}.bin があれば使える
+        let (name, _) = resolve_model(&config, &catalog, "custom").unwrap();
+        assert_eq!(name, "custom");
+
+        // 既定モデルと同じファイルは既定モデル名にまとめる
+        let (name, _) = resolve_model(&config, &catalog, "large-v3-turbo-q5_0").unwrap();
+        assert_eq!(name, "large-q5_0");
+        let (name, _) = resolve_model(&config, &catalog, "large-q5_0").unwrap();
+        assert_eq!(name, "large-q5_0");
+
+        // ファイルが無い/不正な名前
+        assert!(resolve_model(&config, &catalog, "small").is_none());
+        assert!(resolve_model(&config, &catalog, "../base").is_none());
+        assert!(resolve_model(&config, &catalog, "").is_none());
+    }
+}
@@ -133,8 +133,22 @@ mod mock_tests {
         // TranscribeRequest
         let request = TranscribeRequest {
             language: Some("ja".to_string()),
+            model: None,
             translate_to_english: Some(false),
             include_timestamps: Some(true),
+            word_timestamps: None,
+            format: None,
+            channel_mode: None,
+            vad: None,
+            filter: None,
+            chunking: None,
+            glossary: None,
+            glossary_correction: None,
+            diarize: None,
+            num_speakers: None,
+            callback_url: None,
+            callback_secret: None,
+            decoding: Default::default(),
         };
 
         let json = serde_json::to_string(&request).unwrap();
@@ -146,8 +160,10 @@ mod mock_tests {
             status: "healthy".to_string(),
             version: "1.0.0".to_string(),
             model_loaded: false,
+            model_name: None,
             uptime_seconds: 3600,
             memory_usage_mb: Some(256),
+            last_cleanup: None,
         };
 
         let json = serde_json::to_string(&health).unwrap();
@@ -225,14 +241,19 @@ mod mock_tests {
     fn test_json_serialization() {
         let models_response = ModelsResponse {
             models: vec![ModelInfo {
+                id: "test".to_string(),
                 name: "Test Model".to_string(),
                 file_path: "/path/to/model.bin".to_string(),
                 size_mb: 100,
                 description: "Test model description".to_string(),
                 language_support: vec!["en".to_string(), "ja".to_string()],
                 is_available: true,
+                is_loaded: false,
             }],
             current_model: "Test Model".to_string(),
+            loaded_models: Vec::new(),
+            memory_used_mb: 0,
+            memory_budget_mb: 0,
         };
 
         // JSON変換テスト
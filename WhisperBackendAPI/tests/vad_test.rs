@@ -0,0 +1,182 @@
+use WhisperBackendAPI::{
+    config::{Config, VadConfig},
+    models::{TranscriptionSegment, WordTiming},
+    vad::{detect_speech, SpeechRegion, SpeechTimeline},
+};
+
+#[cfg(test)]
+mod vad_tests {
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 16000;
+
+    fn samples(ms: usize) -> usize {
+        ms * SAMPLE_RATE as usize / 1000
+    }
+
+    /// 指定した区間（ミリ秒）だけ 440Hz のサイン波が鳴り、他は無音の音声
+    fn tone_at(total_ms: usize, tones: &[(usize, usize)]) -> Vec<f32> {
+        let mut audio = vec![0.0f32; samples(total_ms)];
+        for &(start, end) in tones {
+            for (i, sample) in audio
+                .iter_mut()
+                .enumerate()
+                .take(samples(end))
+                .skip(samples(start))
+            {
+                *sample = (2.0 * std::f32::consts::PI * 440.0 * i as f32 / SAMPLE_RATE as f32)
+                    .sin()
+                    * 0.3;
+            }
+        }
+        audio
+    }
+
+    fn config_without_padding() -> VadConfig {
+        VadConfig {
+            padding_ms: 0,
+            ..Default::default()
+        }
+    }
+
+    /// 既定では無効、`[vad]` の無い設定ファイルも読み込める
+    #[test]
+    fn test_vad_config_default() {
+        let config = Config::default();
+        assert!(!config.vad.enabled);
+
+        let mut value = toml::Value::try_from(&config).unwrap();
+        value.as_table_mut().unwrap().remove("vad");
+        let loaded: Config = toml::from_str(&toml::to_string(&value).unwrap()).unwrap();
+        assert_eq!(loaded.vad, VadConfig::default());
+    }
+
+    /// 無音に挟まれた発話区間を検出する
+    #[test]
+    fn test_detect_speech_regions() {
+        let audio = tone_at(5000, &[(990, 2010), (3000, 3990)]);
+        let regions = detect_speech(&audio, SAMPLE_RATE, &config_without_padding());
+
+        assert_eq!(regions.len(), 2);
+        // フレーム（30ms）単位で検出される
+        for (region, (start, end)) in regions.iter().zip([(990, 2010), (3000, 3990)]) {
+            assert!(region.start <= samples(start) && samples(start) - region.start < samples(30));
+            assert!(region.end >= samples(end) && region.end - samples(end) < samples(30));
+        }
+    }
+
+    /// 無音のみの音声では区間が無い
+    #[test]
+    fn test_detect_speech_silence() {
+        let audio = tone_at(3000, &[]);
+        assert!(detect_speech(&audio, SAMPLE_RATE, &VadConfig::default()).is_empty());
+    }
+
+    /// 短い無音はつなげ、短い発話は捨てる
+    #[test]
+    fn test_detect_speech_merge_and_drop() {
+        // 300ms の無音（< min_silence_ms）を挟む 2 区間と、90ms の物音（< min_speech_ms）
+        let audio = tone_at(4000, &[(600, 1200), (1500, 2100), (3000, 3090)]);
+        let regions = detect_speech(&audio, SAMPLE_RATE, &config_without_padding());
+
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].start, samples(600));
+        assert_eq!(regions[0].end, samples(2100));
+    }
+
+    /// 余白は音声の範囲内に収め、重なった区間は結合する
+    #[test]
+    fn test_detect_speech_padding() {
+        let config = VadConfig {
+            padding_ms: 300,
+            ..Default::default()
+        };
+        let audio = tone_at(3000, &[(0, 600), (1200, 1800), (2400, 3000)]);
+        let regions = detect_speech(&audio, SAMPLE_RATE, &config);
+
+        assert_eq!(
+            regions,
+            vec![SpeechRegion {
+                start: 0,
+                end: samples(3000)
+            }]
+        );
+    }
+
+    /// 発話区間だけをつなげ、時刻を元の時間軸へ戻す
+    #[test]
+    fn test_speech_timeline_mapping() {
+        let regions = vec![
+            SpeechRegion {
+                start: samples(1000),
+                end: samples(2000),
+            },
+            SpeechRegion {
+                start: samples(5000),
+                end: samples(5500),
+            },
+        ];
+        let timeline = SpeechTimeline::new(regions, SAMPLE_RATE);
+        let audio: Vec<f32> = (0..samples(6000)).map(|i| i as f32).collect();
+
+        let compacted = timeline.compact(&audio);
+        assert_eq!(compacted.len(), samples(1500));
+        assert_eq!(compacted[0], samples(1000) as f32);
+        assert_eq!(compacted[samples(1000)], samples(5000) as f32);
+        assert_eq!(timeline.speech_ms(), 1500);
+
+        assert_eq!(timeline.start_ms(0), 1000);
+        assert_eq!(timeline.start_ms(400), 1400);
+        // 区間の境目は開始なら後ろの区間、終了なら前の区間
+        assert_eq!(timeline.start_ms(1000), 5000);
+        assert_eq!(timeline.end_ms(1000), 2000);
+        assert_eq!(timeline.end_ms(1200), 5200);
+        // 末尾を超える時刻は最後の区間の終わりに収める
+        assert_eq!(timeline.end_ms(9000), 5500);
+    }
+
+    /// セグメントと単語の時刻を元の時間軸へ戻す
+    #[test]
+    fn test_remap_segment() {
+        let timeline = SpeechTimeline::new(
+            vec![
+                SpeechRegion {
+                    start: samples(2000),
+                    end: samples(3000),
+                },
+                SpeechRegion {
+                    start: samples(10000),
+                    end: samples(12000),
+                },
+            ],
+            SAMPLE_RATE,
+        );
+
+        let mut segment = TranscriptionSegment::new("テスト".to_string(), 500, 1500);
+        segment.words = Some(vec![
+            WordTiming {
+                word: "テ".to_string(),
+                start_time_ms: 500,
+                end_time_ms: 1000,
+                probability: 0.9,
+            },
+            WordTiming {
+                word: "スト".to_string(),
+                start_time_ms: 1000,
+                end_time_ms: 1500,
+                probability: 0.8,
+            },
+        ]);
+
+        timeline.remap_segment(&mut segment);
+
+        assert_eq!(segment.start_time_ms, 2500);
+        assert_eq!(segment.end_time_ms, 10500);
+        let words = segment.words.unwrap();
+        assert_eq!((words[0].start_time_ms, words[0].end_time_ms), (2500, 3000));
+        assert_eq!(
+            (words[1].start_time_ms, words[1].end_time_ms),
+            (10000, 10500)
+        );
+    }
+}
@@ -0,0 +1,426 @@
+use axum::{
+    body::{to_bytes, Body, Bytes},
+    extract::State,
+    http::{header, HeaderMap, Request, StatusCode},
+    routing::{get, post},
+    Router,
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tower::ServiceExt;
+use WhisperBackendAPI::{
+    checksum::{hmac_sha256, to_hex},
+    config::{Config, WebhookConfig},
+    handlers::{create_job, get_job, transcribe_basic, transcribe_with_timestamps, AppState},
+    jobs::JobStore,
+    models::{ApiErrorCode, TranscribeResponse},
+    webhook::{
+        backoff, deliver, signature, DeliveryState, WebhookPayload, WebhookTarget, EVENT_HEADER,
+        SIGNATURE_HEADER, TIMESTAMP_HEADER,
+    },
+};
+
+#[cfg(test)]
+mod webhook_tests {
+    use super::*;
+
+    /// 通知を受け取るスタブ（最初の `failures` 回は 503 を返す）
+    #[derive(Clone, Default)]
+    struct Receiver {
+        requests: Arc<Mutex<Vec<(HeaderMap, Bytes)>>>,
+        failures: Arc<AtomicUsize>,
+    }
+
+    async fn receive(
+        State(receiver): State<Receiver>,
+        headers: HeaderMap,
+        body: Bytes,
+    ) -> StatusCode {
+        receiver.requests.lock().unwrap().push((headers, body));
+        let failing = receiver
+            .failures
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+            .is_ok();
+        if failing {
+            StatusCode::SERVICE_UNAVAILABLE
+        } else {
+            StatusCode::NO_CONTENT
+        }
+    }
+
+    /// スタブを起動し、通知先の URL を返す
+    async fn start_receiver(failures: usize) -> (String, Receiver) {
+        let receiver = Receiver::default();
+        receiver.failures.store(failures, Ordering::SeqCst);
+        let app = Router::new()
+            .route("/hook", post(receive))
+            .with_state(receiver.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, receiver)
+    }
+
+    fn fast_retry(max_attempts: u32) -> WebhookConfig {
+        WebhookConfig {
+            max_attempts,
+            initial_backoff_ms: 10,
+            max_backoff_ms: 40,
+            timeout_seconds: 5,
+            // スタブはループバックで待ち受ける
+            allowed_hosts: vec!["127.0.0.1".to_string()],
+        }
+    }
+
+    fn sample_response() -> TranscribeResponse {
+        TranscribeResponse {
+            text: "こんにちは".to_string(),
+            language: Some("ja".to_string()),
+            duration_ms: Some(1000),
+            segments: None,
+            processing_time_ms: 120,
+            decoding: None,
+            language_detection: None,
+            model: None,
+            filtered: None,
+            corrections: None,
+            history_id: None,
+            failed_chunks: None,
+        }
+    }
+
+    /// HMAC-SHA256 は RFC 4231 のテストベクタと一致する（ブロック長より長い鍵を含む）
+    #[test]
+    fn test_hmac_sha256() {
+        assert_eq!(
+            to_hex(&hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        assert_eq!(
+            to_hex(&hmac_sha256(
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First"
+            )),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
+
+    /// 署名は `{timestamp}.{本文}` の HMAC-SHA256 で、送信時刻が変われば変わる
+    #[test]
+    fn test_signature_covers_timestamp() {
+        assert_eq!(
+            signature("Jefe", 1700000000, b"{}"),
+            format!("sha256={}", to_hex(&hmac_sha256(b"Jefe", b"1700000000.{}")))
+        );
+        assert_ne!(
+            signature("Jefe", 1700000000, b"{}"),
+            signature("Jefe", 1700000001, b"{}")
+        );
+    }
+
+    /// 通知先は http/https の URL のみ。再送の待ち時間は倍々で増えて上限で止まる
+    #[tokio::test]
+    async fn test_target_and_backoff() {
+        let config = WebhookConfig::default();
+        assert!(
+            WebhookTarget::new("https://93.184.216.34/hook", Some("key"), &config)
+                .await
+                .is_ok()
+        );
+        assert!(
+            WebhookTarget::new("ftp://93.184.216.34/hook", None, &config)
+                .await
+                .is_err()
+        );
+        assert!(WebhookTarget::new("example.com/hook", None, &config)
+            .await
+            .is_err());
+
+        assert_eq!(backoff(&config, 1), Duration::from_secs(1));
+        assert_eq!(backoff(&config, 2), Duration::from_secs(2));
+        assert_eq!(backoff(&config, 3), Duration::from_secs(4));
+        assert_eq!(backoff(&config, 10), Duration::from_secs(60));
+        assert_eq!(backoff(&config, 100), Duration::from_secs(60));
+    }
+
+    /// 内部向けのアドレスは拒否し、`allowed_hosts` のホスト名/アドレス/CIDR だけ許可する
+    #[tokio::test]
+    async fn test_target_rejects_internal_addresses() {
+        let config = WebhookConfig::default();
+        for url in [
+            "http://127.0.0.1:8080/hook",
+            "http://localhost/hook",
+            "http://169.254.169.254/latest/meta-data/",
+            "http://10.1.2.3/hook",
+            "http://172.16.0.1/hook",
+            "http://192.168.1.10/hook",
+            "http://100.64.0.1/hook",
+            "http://0.0.0.0/hook",
+            "http://[::1]/hook",
+            "http://[fe80::1]/hook",
+            "http://[fd00::1]/hook",
+            "http://[::ffff:127.0.0.1]/hook",
+        ] {
+            let error = WebhookTarget::new(url, None, &config).await.err();
+            assert!(error.is_some(), "{} が許可されました", url);
+        }
+
+        let config = WebhookConfig {
+            allowed_hosts: vec![
+                "localhost".to_string(),
+                "10.0.0.0/8".to_string(),
+                "fd00::1".to_string(),
+            ],
+            ..Default::default()
+        };
+        for url in [
+            "http://localhost/hook",
+            "http://10.1.2.3/hook",
+            "http://[fd00::1]/hook",
+        ] {
+            assert!(
+                WebhookTarget::new(url, None, &config).await.is_ok(),
+                "{} が拒否されました",
+                url
+            );
+        }
+        for url in [
+            "http://192.168.1.10/hook",
+            "http://169.254.169.254/",
+            "http://[fd00::2]/hook",
+        ] {
+            assert!(WebhookTarget::new(url, None, &config).await.is_err());
+        }
+    }
+
+    /// 失敗した送信は再送し、届いたら配信状況を記録する。本文には署名が付く
+    #[tokio::test]
+    async fn test_deliver_retries_until_delivered() {
+        let (url, receiver) = start_receiver(2).await;
+        let jobs = JobStore::new(Duration::from_secs(60));
+        let (id, _) = jobs.create("audio.wav", None);
+        jobs.set_callback(
+            &id,
+            WebhookTarget::new(&url, Some("s3cret"), &fast_retry(5))
+                .await
+                .unwrap(),
+        );
+        assert_eq!(
+            jobs.get(&id, None).unwrap().callback.unwrap().state,
+            DeliveryState::Pending
+        );
+
+        jobs.complete(&id, sample_response());
+        deliver(&jobs, &id, &fast_retry(5)).await;
+
+        let requests = receiver.requests.lock().unwrap().clone();
+        assert_eq!(requests.len(), 3);
+        let (headers, body) = requests.last().unwrap();
+        assert_eq!(headers[EVENT_HEADER], "job.completed");
+        let timestamp: i64 = headers[TIMESTAMP_HEADER].to_str().unwrap().parse().unwrap();
+        assert!((chrono::Utc::now().timestamp() - timestamp).abs() < 60);
+        assert_eq!(
+            headers[SIGNATURE_HEADER],
+            signature("s3cret", timestamp, body).as_str()
+        );
+        let payload: WebhookPayload = serde_json::from_slice(body).unwrap();
+        assert_eq!(payload.job.id, id);
+        assert_eq!(payload.result.unwrap().text, "こんにちは");
+        assert!(payload.job.callback.is_none());
+
+        let status = jobs.get(&id, None).unwrap().callback.unwrap();
+        assert_eq!(status.state, DeliveryState::Delivered);
+        assert_eq!(status.attempts, 3);
+        assert_eq!(status.last_status, Some(204));
+        assert!(status.delivered_at.is_some());
+    }
+
+    /// 最大回数まで届かなければ失敗として記録する。鍵が無ければ署名は付けない
+    #[tokio::test]
+    async fn test_deliver_gives_up() {
+        let (url, receiver) = start_receiver(usize::MAX).await;
+        let jobs = JobStore::new(Duration::from_secs(60));
+        let (id, _) = jobs.create("audio.wav", None);
+        jobs.set_callback(
+            &id,
+            WebhookTarget::new(&url, None, &fast_retry(2))
+                .await
+                .unwrap(),
+        );
+
+        jobs.fail(
+            &id,
+            ApiErrorCode::UnsupportedFormat,
+            "音声を読み込めません".to_string(),
+            None,
+        );
+        deliver(&jobs, &id, &fast_retry(2)).await;
+
+        let requests = receiver.requests.lock().unwrap().clone();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].0.get(SIGNATURE_HEADER).is_none());
+        assert!(requests[0].0.get(TIMESTAMP_HEADER).is_some());
+        let payload: WebhookPayload = serde_json::from_slice(&requests[0].1).unwrap();
+        assert_eq!(payload.event, "job.failed");
+        assert_eq!(payload.job.error.unwrap().code, "UNSUPPORTED_FORMAT");
+
+        let status = jobs.get(&id, None).unwrap().callback.unwrap();
+        assert_eq!(status.state, DeliveryState::Failed);
+        assert_eq!(status.attempts, 2);
+        assert_eq!(status.last_status, Some(503));
+    }
+
+    fn job_form(fields: &[(&str, &str)]) -> Request<Body> {
+        form_request("/jobs", fields)
+    }
+
+    fn form_request(uri: &str, fields: &[(&str, &str)]) -> Request<Body> {
+        let boundary = "webhook-test-boundary";
+        let mut body = format!(
+            "--{b}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"a.wav\"\r\n\r\nRIFF\r\n",
+            b = boundary
+        );
+        for (name, value) in fields {
+            body.push_str(&format!(
+                "--{b}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+                name,
+                value,
+                b = boundary
+            ));
+        }
+        body.push_str(&format!("--{}--\r\n", boundary));
+        Request::builder()
+            .method("POST")
+            .uri(uri)
+            .header(
+                header::CONTENT_TYPE,
+                format!("multipart/form-data; boundary={}", boundary),
+            )
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    /// 同期のエンドポイントでは通知しないため、callback_url / callback_secret は 400 にする
+    #[tokio::test]
+    async fn test_callback_rejected_outside_jobs() {
+        let app = Router::new()
+            .route("/transcribe", post(transcribe_basic))
+            .route(
+                "/transcribe-with-timestamps",
+                post(transcribe_with_timestamps),
+            )
+            .with_state(AppState::new(Config::default()));
+
+        for uri in ["/transcribe", "/transcribe-with-timestamps"] {
+            for field in ["callback_url", "callback_secret"] {
+                let response = app
+                    .clone()
+                    .oneshot(form_request(uri, &[(field, "https://example.com/hook")]))
+                    .await
+                    .unwrap();
+                assert_eq!(
+                    response.status(),
+                    StatusCode::BAD_REQUEST,
+                    "{} {}",
+                    uri,
+                    field
+                );
+                let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                assert_eq!(error["code"], "INVALID_INPUT");
+            }
+        }
+    }
+
+    /// `POST /jobs` の callback_url に、ジョブが終了したら通知が届き、`GET /jobs/{id}` に配信状況が出る
+    #[tokio::test]
+    async fn test_job_callback() {
+        let (url, receiver) = start_receiver(0).await;
+        let config = Config {
+            webhook: fast_retry(3),
+            ..Default::default()
+        };
+        let app = Router::new()
+            .route("/jobs", post(create_job))
+            .route("/jobs/{id}", get(get_job))
+            .with_state(AppState::new(config));
+
+        let response = app
+            .clone()
+            .oneshot(job_form(&[("callback_url", "not a url")]))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = app
+            .clone()
+            .oneshot(job_form(&[("callback_secret", "s3cret")]))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = app
+            .clone()
+            .oneshot(job_form(&[(
+                "callback_url",
+                "http://169.254.169.254/latest/meta-data/",
+            )]))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = app
+            .clone()
+            .oneshot(job_form(&[
+                ("callback_url", &url),
+                ("callback_secret", "s3cret"),
+            ]))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let job: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(job["callback"]["state"], "pending");
+        let id = job["id"].as_str().unwrap().to_string();
+
+        // モデルが無いためジョブは失敗し、その結果が通知される
+        let mut delivered = None;
+        for _ in 0..100 {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .uri(format!("/jobs/{}", id))
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            let job: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            if job["callback"]["state"] != "pending" {
+                delivered = Some(job);
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        let job = delivered.expect("通知が届きませんでした");
+        assert_eq!(job["status"], "failed");
+        assert_eq!(job["callback"]["state"], "delivered");
+        assert_eq!(job["callback"]["attempts"], 1);
+
+        let requests = receiver.requests.lock().unwrap().clone();
+        assert_eq!(requests.len(), 1);
+        let (headers, body) = &requests[0];
+        let timestamp: i64 = headers[TIMESTAMP_HEADER].to_str().unwrap().parse().unwrap();
+        assert!((chrono::Utc::now().timestamp() - timestamp).abs() < 60);
+        assert_eq!(
+            headers[SIGNATURE_HEADER],
+            signature("s3cret", timestamp, body).as_str()
+        );
+        let payload: WebhookPayload = serde_json::from_slice(body).unwrap();
+        assert_eq!(payload.event, "job.failed");
+        assert_eq!(payload.job.id, id);
+        assert!(payload.job.error.is_some());
+    }
+}
@@ -1,7 +1,7 @@
 //! 音声ファイルの読み込み・メタデータ取得・リサンプリングなど、
 //! Whisper 前処理に関わる機能をまとめたモジュール。
 //! - 任意のコンテナ/コーデックを Symphonia でデコード
-//! - 複数チャネルをモノラルへ集約
+//! - 複数チャネルをモノラルへ集約、または指定チャネル/チャネル別に取り出し
 //! - 16kHz など指定サンプリングレートへリサンプリング
 //! - WAV への簡易書き出し（プレビュー用）
 
@@ -10,14 +10,17 @@ use anyhow::Result;
 use rubato::{
     Resampler, SincFixedIn, SincInterpolationParameters, SincInterpolationType, WindowFunction,
 };
+use serde::{Deserialize, Serialize};
 use std::fs::File;
 use std::path::Path;
-use symphonia::core::audio::{AudioBufferRef, Signal};
+use symphonia::core::audio::{AudioBuffer, AudioBufferRef, Signal};
 use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::conv::IntoSample;
 use symphonia::core::formats::FormatOptions;
 use symphonia::core::io::MediaSourceStream;
 use symphonia::core::meta::MetadataOptions;
 use symphonia::core::probe::Hint;
+use symphonia::core::sample::Sample;
 
 /// 音声ファイルの基本メタデータ。
 pub struct AudioMetadata {
@@ -25,6 +28,103 @@ pub struct AudioMetadata {
     pub sample_rate: u32,
 }
 
+/// 多チャネル音声からどのチャネルを使うか。
+/// 設定では `mix` / `left` / `right` / `separate` / チャネル番号（0 始まり）で指定する。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum ChannelMode {
+    /// 全チャネルの単純平均（従来の動作）
+    #[default]
+    Mix,
+    /// 左チャネル（0 番）
+    Left,
+    /// 右チャネル（1 番）
+    Right,
+    /// 指定した番号のチャネル
+    Index(usize),
+    /// チャネルごとに文字起こしし、結果を時刻順にまとめる
+    Separate,
+}
+
+impl ChannelMode {
+    /// 指定値を解釈（大文字小文字は区別しない）。
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        match value.to_ascii_lowercase().as_str() {
+            "mix" | "mono" => Some(ChannelMode::Mix),
+            "left" => Some(ChannelMode::Left),
+            "right" => Some(ChannelMode::Right),
+            "separate" => Some(ChannelMode::Separate),
+            _ => value.parse().ok().map(ChannelMode::Index),
+        }
+    }
+
+    /// 1 本の波形にまとめる場合のモード（`separate` は平均に置き換える）。
+    pub fn mono(self) -> Self {
+        match self {
+            ChannelMode::Separate => ChannelMode::Mix,
+            mode => mode,
+        }
+    }
+
+    /// 単一チャネルを選ぶモードで使うチャネル番号（存在しないチャネルはエラー）。
+    fn channel_index(&self, channels: usize) -> Result<usize> {
+        let index = match self {
+            ChannelMode::Left => 0,
+            ChannelMode::Right => 1,
+            ChannelMode::Index(index) => *index,
+            ChannelMode::Mix | ChannelMode::Separate => return Ok(0),
+        };
+        if index >= channels {
+            return Err(anyhow::anyhow!(
+                "チャネル {} は存在しません（音声のチャネル数: {}）",
+                self,
+                channels
+            ));
+        }
+        Ok(index)
+    }
+
+    /// 出力する波形の本数。
+    fn output_count(&self, channels: usize) -> Result<usize> {
+        match self {
+            ChannelMode::Separate => Ok(channels.max(1)),
+            mode => mode.channel_index(channels).map(|_| 1),
+        }
+    }
+}
+
+impl std::fmt::Display for ChannelMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChannelMode::Mix => write!(f, "mix"),
+            ChannelMode::Left => write!(f, "left"),
+            ChannelMode::Right => write!(f, "right"),
+            ChannelMode::Index(index) => write!(f, "{}", index),
+            ChannelMode::Separate => write!(f, "separate"),
+        }
+    }
+}
+
+impl TryFrom<String> for ChannelMode {
+    type Error = String;
+
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        ChannelMode::parse(&value).ok_or_else(|| {
+            format!(
+                "channel_mode の値が不正です: {}（mix / left / right / separate / チャネル番号）",
+                value
+            )
+        })
+    }
+}
+
+impl From<ChannelMode> for String {
+    fn from(mode: ChannelMode) -> Self {
+        mode.to_string()
+    }
+}
+
 /// 音声処理の中核クラス。コンフィグに基づいてデコードやリサンプリングを行う。
     pub struct AudioProcessor {
         config: Config,
@@ -126,7 +226,39 @@ pub struct AudioMetadata {
     }
 
     /// 音声ファイルを読み込み、モノラル f32 波形に変換して返す（必要に応じて指定レートへリサンプリング）。
+    /// `separate` 設定時はプレビュー用に全チャネルの平均を返す。
     pub fn load_audio_file(&mut self, file_path: &str) -> Result<Vec<f32>> {
+        let mode = self.config.audio.channel_mode.mono();
+        let mut channels = self.decode_channels(file_path, mode, None::<fn(u64, Option<u64>)>)?;
+        Ok(channels.remove(0))
+    }
+
+    /// 進捗コールバック付きで音声ファイルを読み込む。
+    /// コールバックには (処理済みサンプル数, 推定総サンプル数) を渡す。
+    pub fn load_audio_file_with_progress<F>(&mut self, file_path: &str, on_progress: Option<F>) -> Result<Vec<f32>>
+    where
+        F: FnMut(u64, Option<u64>),
+    {
+        let mode = self.config.audio.channel_mode.mono();
+        let mut channels = self.decode_channels(file_path, mode, on_progress)?;
+        Ok(channels.remove(0))
+    }
+
+    /// 設定の `channel_mode` に従ってチャネルごとの波形を返す（進捗コールバック付き）。
+    /// `separate` ならチャネル数分、それ以外は 1 本だけ返す。
+    pub fn load_audio_channels_with_progress<F>(&mut self, file_path: &str, on_progress: Option<F>) -> Result<Vec<Vec<f32>>>
+    where
+        F: FnMut(u64, Option<u64>),
+    {
+        let mode = self.config.audio.channel_mode;
+        self.decode_channels(file_path, mode, on_progress)
+    }
+
+    /// デコードして `mode` に従ったチャネルの波形を取り出し、設定レートへリサンプリングする。
+    fn decode_channels<F>(&self, file_path: &str, mode: ChannelMode, mut on_progress: Option<F>) -> Result<Vec<Vec<f32>>>
+    where
+        F: FnMut(u64, Option<u64>),
+    {
         let path = Path::new(file_path);
         if !path.exists() {
             return Err(anyhow::anyhow!(
@@ -135,6 +267,21 @@ pub struct AudioMetadata {
             ));
         }
 
+        // 進捗を報告する場合のみ、事前に概算の総サンプル数を推定（メタデータから）
+        let total_estimate: Option<u64> = match on_progress {
+            Some(_) => match self.probe_metadata(file_path) {
+                Ok(meta) => {
+                    let total = (meta.duration_seconds.max(0.0) as f64
+                        * meta.sample_rate as f64)
+                        .round()
+                        as u64;
+                    if total > 0 { Some(total) } else { None }
+                }
+                Err(_) => None,
+            },
+            None => None,
+        };
+
         // ファイルを開く
         let file = File::open(path)?;
         let mss = MediaSourceStream::new(Box::new(file), Default::default());
@@ -170,7 +317,9 @@ pub struct AudioMetadata {
         let dec_opts: DecoderOptions = Default::default();
         let mut decoder = symphonia::default::get_codecs().make(&codec_params, &dec_opts)?;
 
-        let mut samples = Vec::new();
+        // 出力先はチャネル数が分かる最初のデコード結果で用意する
+        let mut outputs: Vec<Vec<f32>> = Vec::new();
+        let mut last_reported_pct: i32 = -1;
 
         // パケットをデコード
         loop {
@@ -196,8 +345,31 @@ pub struct AudioMetadata {
 
             match decoder.decode(&packet) {
                 Ok(audio_buf) => {
-                    // 各フレームで全チャネルを平均し、モノラルに変換して蓄積
-                    self.extract_samples_from_buffer(&audio_buf, &mut samples)?;
+                    if outputs.is_empty() {
+                        let channels = audio_buf.spec().channels.count();
+                        outputs = vec![Vec::new(); mode.output_count(channels)?];
+                    }
+                    // チャネルモードに従って f32 波形を取り出して蓄積
+                    extract_samples_from_buffer(&audio_buf, mode, &mut outputs)?;
+                    let processed = outputs[0].len() as u64;
+
+                    // 進捗を 1% 単位で報告
+                    if let Some(total) = total_estimate {
+                        if total > 0 {
+                            let pct = ((processed.min(total)) * 100 / total) as i32;
+                            if pct != last_reported_pct {
+                                last_reported_pct = pct;
+                                if let Some(cb) = on_progress.as_mut() {
+                                    cb(processed.min(total), Some(total));
+                                }
+                            }
+                        } else if let Some(cb) = on_progress.as_mut() {
+                            cb(processed, None);
+                        }
+                    } else if let Some(cb) = on_progress.as_mut() {
+                        // 総量不明
+                        cb(processed, None);
+                    }
                 }
                 Err(symphonia::core::errors::Error::IoError(ref err))
                     if err.kind() == std::io::ErrorKind::UnexpectedEof =>
@@ -209,7 +381,7 @@ pub struct AudioMetadata {
             }
         }
 
-        if samples.is_empty() {
+        if outputs.first().map_or(true, |samples| samples.is_empty()) {
             return Err(anyhow::anyhow!("音声データが空です"));
         }
 
@@ -220,178 +392,29 @@ pub struct AudioMetadata {
             .or(codec_params.sample_rate)
             .ok_or_else(|| anyhow::anyhow!("サンプリングレートが取得できません"))? as f64;
 
-        // 既にモノラルへ変換済み
-        let mono_samples = samples;
-
-        // 16kHzなど設定レートにリサンプル
+        // 16kHzなど設定レートにチャネルごとリサンプル
         let target_sample_rate = self.config.audio.sample_rate as f64;
-        let resampled = if (original_sample_rate - target_sample_rate).abs() > 1.0 {
-            self.resample_audio(mono_samples, original_sample_rate, target_sample_rate)?
-        } else {
-            mono_samples
-        };
+        let resampled = outputs
+            .into_iter()
+            .map(|samples| {
+                if (original_sample_rate - target_sample_rate).abs() > 1.0 {
+                    self.resample_audio(samples, original_sample_rate, target_sample_rate)
+                } else {
+                    Ok(samples)
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
 
         println!(
-            "音声ファイル読み込み完了: {} samples, {}Hz -> {}Hz",
+            "音声ファイル読み込み完了: {} samples x {}ch, {}Hz -> {}Hz",
+            resampled[0].len(),
             resampled.len(),
             original_sample_rate,
             target_sample_rate
         );
 
         Ok(resampled)
-        }
-
-        /// 進捗コールバック付きで音声ファイルを読み込む。
-        /// コールバックには (処理済みサンプル数, 推定総サンプル数) を渡す。
-        pub fn load_audio_file_with_progress<F>(&mut self, file_path: &str, mut on_progress: Option<F>) -> Result<Vec<f32>>
-        where
-            F: FnMut(u64, Option<u64>),
-        {
-            let path = Path::new(file_path);
-            if !path.exists() {
-                return Err(anyhow::anyhow!(
-                    "音声ファイルが見つかりません: {}",
-                    file_path
-                ));
-            }
-
-            // 事前に概算の総サンプル数を推定（メタデータから）
-            let total_estimate: Option<u64> = match self.probe_metadata(file_path) {
-                Ok(meta) => {
-                    let total = (meta.duration_seconds.max(0.0) as f64
-                        * meta.sample_rate as f64)
-                        .round()
-                        as u64;
-                    if total > 0 { Some(total) } else { None }
-                }
-                Err(_) => None,
-            };
-
-            // ファイルを開く
-            let file = File::open(path)?;
-            let mss = MediaSourceStream::new(Box::new(file), Default::default());
-
-            // フォーマットヒントを設定
-            let mut hint = Hint::new();
-            if let Some(extension) = path.extension() {
-                if let Some(extension_str) = extension.to_str() {
-                    hint.with_extension(extension_str);
-                }
-            }
-
-            // フォーマットを推定
-            let meta_opts: MetadataOptions = Default::default();
-            let fmt_opts: FormatOptions = Default::default();
-
-            let probed = symphonia::default::get_probe().format(&hint, mss, &fmt_opts, &meta_opts)?;
-
-            let mut format = probed.format;
-
-            // 最初のオーディオトラックを見つける
-            let (track_id, codec_params) = {
-                let track = format
-                    .tracks()
-                    .iter()
-                    .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
-                    .ok_or_else(|| anyhow::anyhow!("音声トラックが見つかりません"))?;
-
-                (track.id, track.codec_params.clone())
-            };
-
-            // デコーダーを作成
-            let dec_opts: DecoderOptions = Default::default();
-            let mut decoder = symphonia::default::get_codecs().make(&codec_params, &dec_opts)?;
-
-            let mut samples = Vec::new();
-            let mut last_reported_pct: i32 = -1;
-
-            // パケットをデコード
-            loop {
-                let packet = match format.next_packet() {
-                    Ok(packet) => packet,
-                    Err(symphonia::core::errors::Error::ResetRequired) => {
-                        // リセットが必要な場合
-                        break;
-                    }
-                    Err(symphonia::core::errors::Error::IoError(ref err))
-                        if err.kind() == std::io::ErrorKind::UnexpectedEof =>
-                    {
-                        // ファイル終端
-                        break;
-                    }
-                    Err(err) => return Err(anyhow::anyhow!("パケット読み込みエラー: {}", err)),
-                };
-
-                // 正しいトラックのパケットのみを処理
-                if packet.track_id() != track_id {
-                    continue;
-                }
-
-                match decoder.decode(&packet) {
-                    Ok(audio_buf) => {
-                        // 各フレームで全チャネルを平均し、モノラルに変換して蓄積
-                        self.extract_samples_from_buffer(&audio_buf, &mut samples)?;
-                        let processed = samples.len() as u64;
-
-                        // 進捗を 1% 単位で報告
-                        if let Some(total) = total_estimate {
-                            if total > 0 {
-                                let pct = ((processed.min(total)) * 100 / total) as i32;
-                                if pct != last_reported_pct {
-                                    last_reported_pct = pct;
-                                    if let Some(cb) = on_progress.as_mut() {
-                                        cb(processed.min(total), Some(total));
-                                    }
-                                }
-                            } else if let Some(cb) = on_progress.as_mut() {
-                                cb(processed, None);
-                            }
-                        } else if let Some(cb) = on_progress.as_mut() {
-                            // 総量不明
-                            cb(processed, None);
-                        }
-                    }
-                    Err(symphonia::core::errors::Error::IoError(ref err))
-                        if err.kind() == std::io::ErrorKind::UnexpectedEof =>
-                    {
-                        // デコード終了
-                        break;
-                    }
-                    Err(err) => return Err(anyhow::anyhow!("デコードエラー: {}", err)),
-                }
-            }
-
-            if samples.is_empty() {
-                return Err(anyhow::anyhow!("音声データが空です"));
-            }
-
-            // サンプリングレートを取得（デコーダが提供する値を優先）
-            let original_sample_rate = decoder
-                .codec_params()
-                .sample_rate
-                .or(codec_params.sample_rate)
-                .ok_or_else(|| anyhow::anyhow!("サンプリングレートが取得できません"))? as f64;
-
-            // 既にモノラルへ変換済み
-            let mono_samples = samples;
-
-            // 16kHzなど設定レートにリサンプル
-            let target_sample_rate = self.config.audio.sample_rate as f64;
-            let resampled = if (original_sample_rate - target_sample_rate).abs() > 1.0 {
-                self.resample_audio(mono_samples, original_sample_rate, target_sample_rate)?
-            } else {
-                mono_samples
-            };
-
-            println!(
-                "音声ファイル読み込み完了: {} samples, {}Hz -> {}Hz",
-                resampled.len(),
-                original_sample_rate,
-                target_sample_rate
-            );
-
-            Ok(resampled)
-        }
+    }
 
     /// 入力ファイルを読み込み、モノラル16bit PCM WAV で保存（プレビュー用）。
     pub fn decode_to_wav_file(&mut self, src_path: &str, dst_path: &str) -> Result<()> {
@@ -412,71 +435,6 @@ pub struct AudioMetadata {
         Ok(())
     }
 
-    /// デコード済みバッファから f32 モノラル波形を抽出（各サンプル形式を正規化）。
-    fn extract_samples_from_buffer(
-        &self,
-        audio_buf: &AudioBufferRef,
-        samples: &mut Vec<f32>,
-    ) -> Result<()> {
-        match audio_buf {
-            AudioBufferRef::F32(buf) => {
-                let ch = buf.spec().channels.count();
-                let frames = buf.frames();
-                for i in 0..frames {
-                    let mut sum = 0.0f32;
-                    for c in 0..ch {
-                        sum += buf.chan(c)[i];
-                    }
-                    samples.push(sum / ch as f32);
-                }
-            }
-            AudioBufferRef::S32(buf) => {
-                let ch = buf.spec().channels.count();
-                let frames = buf.frames();
-                for i in 0..frames {
-                    let mut sum = 0.0f32;
-                    for c in 0..ch {
-                        sum += buf.chan(c)[i] as f32 / i32::MAX as f32;
-                    }
-                    samples.push(sum / ch as f32);
-                }
-            }
-            AudioBufferRef::S16(buf) => {
-                let ch = buf.spec().channels.count();
-                let frames = buf.frames();
-                for i in 0..frames {
-                    let mut sum = 0.0f32;
-                    for c in 0..ch {
-                        sum += buf.chan(c)[i] as f32 / i16::MAX as f32;
-                    }
-                    samples.push(sum / ch as f32);
-                }
-            }
-            _ => return Err(anyhow::anyhow!("サポートされていない音声フォーマットです")),
-        }
-        Ok(())
-    }
-
-    /// 多チャネル波形を単純平均でモノラル化。
-    fn convert_to_mono(&self, samples: Vec<f32>, channels: usize) -> Vec<f32> {
-        if channels == 1 {
-            return samples;
-        }
-
-        let mono_len = samples.len() / channels;
-        let mut mono_samples = Vec::with_capacity(mono_len);
-
-        for i in 0..mono_len {
-            let mut sum = 0.0;
-            for ch in 0..channels {
-                sum += samples[i * channels + ch];
-            }
-            mono_samples.push(sum / channels as f32);
-        }
-
-        mono_samples
-    }
-
     /// SincFixed（rubato）で音質と負荷のバランスを取りつつリサンプリング。
     fn resample_audio(
         &self,
@@ -515,6 +473,56 @@ pub struct AudioMetadata {
     }
 }
 
+/// デコード済みバッファから `mode` に従って f32 波形を抽出（各サンプル形式を正規化）。
+fn extract_samples_from_buffer(
+    audio_buf: &AudioBufferRef,
+    mode: ChannelMode,
+    outputs: &mut [Vec<f32>],
+) -> Result<()> {
+    match audio_buf {
+        AudioBufferRef::U8(buf) => extract_channels(buf, mode, outputs),
+        AudioBufferRef::U16(buf) => extract_channels(buf, mode, outputs),
+        AudioBufferRef::U24(buf) => extract_channels(buf, mode, outputs),
+        AudioBufferRef::U32(buf) => extract_channels(buf, mode, outputs),
+        AudioBufferRef::S8(buf) => extract_channels(buf, mode, outputs),
+        AudioBufferRef::S16(buf) => extract_channels(buf, mode, outputs),
+        AudioBufferRef::S24(buf) => extract_channels(buf, mode, outputs),
+        AudioBufferRef::S32(buf) => extract_channels(buf, mode, outputs),
+        AudioBufferRef::F32(buf) => extract_channels(buf, mode, outputs),
+        AudioBufferRef::F64(buf) => extract_channels(buf, mode, outputs),
+    }
+}
+
+/// `mix` は全チャネルの単純平均、`left`/`right`/番号指定はそのチャネルのみ、
+/// `separate` はチャネルごとに別の出力へ追記する。
+fn extract_channels<S>(buf: &AudioBuffer<S>, mode: ChannelMode, outputs: &mut [Vec<f32>]) -> Result<()>
+where
+    S: Sample + IntoSample<f32>,
+{
+    let ch = buf.spec().channels.count();
+    match mode {
+        ChannelMode::Mix => {
+            for i in 0..buf.frames() {
+                let mut sum = 0.0f32;
+                for c in 0..ch {
+                    sum += buf.chan(c)[i].into_sample();
+                }
+                outputs[0].push(sum / ch as f32);
+            }
+        }
+        ChannelMode::Separate => {
+            for (c, output) in outputs.iter_mut().enumerate().take(ch) {
+                output.extend(buf.chan(c).iter().map(|&sample| IntoSample::<f32>::into_sample(sample)));
+            }
+        }
+        mode => {
+            let c = mode.channel_index(ch)?;
+            outputs[0].extend(buf.chan(c).iter().map(|&sample| IntoSample::<f32>::into_sample(sample)));
+        }
+    }
+    Ok(())
+}
+
 /// 非依存ライブラリでの簡易WAV書き出し（モノラル16bit）。
 fn write_wav_mono_16(path: &str, sample_rate: u32, samples: &[f32]) -> Result<()> {
     use std::fs::File;
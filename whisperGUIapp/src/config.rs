@@ -3,6 +3,7 @@
 //! - `Config::save()` で保存先に書き出し
 //! - `ensure_directories()` で必要なディレクトリを作成
 
+use crate::audio::ChannelMode;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::fs;
@@ -47,6 +48,10 @@ pub struct AudioConfig {
     pub sample_rate: u32,
     pub channels: u32,
     pub buffer_size: usize,
+    /// 多チャネル音声の扱い（mix / left / right / チャネル番号 / separate）
+    /// separate ではチャネルごとに文字起こしし、`[chN]` 付きで時刻順にまとめる
+    #[serde(default)]
+    pub channel_mode: ChannelMode,
 }
 
 /// GUI 表示に関する設定。
@@ -165,6 +170,7 @@ impl Default for Config {
                 sample_rate: 16000,
                 channels: 1,
                 buffer_size: 4096,
+                channel_mode: ChannelMode::default(),
             },
             gui: GuiConfig {
                 window_width: 800.0,
@@ -13,10 +13,10 @@ use whisperGUIapp::config;
 use whisperGUIapp::models;
 use whisperGUIapp::whisper;
 
-use whisperGUIapp::audio::AudioProcessor;
+use whisperGUIapp::audio::{AudioProcessor, ChannelMode};
 use whisperGUIapp::config::Config;
 use whisperGUIapp::models::MODEL_CATALOG;
-use whisperGUIapp::whisper::WhisperEngine;
+use whisperGUIapp::whisper::{merge_channel_segments, WhisperEngine};
 use whisperGUIapp::utils;
 
 use serde::{Deserialize, Serialize};
@@ -109,12 +109,20 @@ fn emit_task_progress(app: &tauri::AppHandle, payload: &TaskProgressPayload) {
 }
 
 /// 同一ファイルの二重読み込みを避けるための簡易キャッシュ。
+/// 取り出したチャネルが変わると波形も変わるため、チャネルモードも一致を確認する。
 #[derive(Clone)]
 struct CachedAudio {
     path: String,
+    channel_mode: ChannelMode,
     data: Vec<f32>,
 }
 
+impl CachedAudio {
+    fn matches(&self, path: &str, channel_mode: ChannelMode) -> bool {
+        self.path == path && self.channel_mode == channel_mode.mono()
+    }
+}
+
 // Tauri コマンドハンドラー
 /// ファイルダイアログで音声/動画ファイルを選択する。
 #[tauri::command]
@@ -199,7 +207,7 @@ async fn prepare_preview_wav(path: String, state: State<'_, AppState>, app: taur
     let audio_data = {
         if let Ok(cache_guard) = state.cached_audio.lock() {
             if let Some(cached) = cache_guard.as_ref() {
-                if cached.path == path {
+                if cached.matches(&path, config.audio.channel_mode) {
                     used_cache = true;
                     cached.data.clone()
                 } else {
@@ -234,7 +242,11 @@ async fn prepare_preview_wav(path: String, state: State<'_, AppState>, app: taur
 
         // キャッシュに保存
         if let Ok(mut cache) = state.cached_audio.lock() {
-            *cache = Some(CachedAudio { path: path.clone(), data: data.clone() });
+            *cache = Some(CachedAudio {
+                path: path.clone(),
+                channel_mode: config.audio.channel_mode.mono(),
+                data: data.clone(),
+            });
         }
         data
     };
@@ -252,6 +264,7 @@ async fn prepare_preview_wav(path: String, state: State<'_, AppState>, app: taur
             .map_err(|_| "キャッシュへの保存に失敗しました")?;
         *cache = Some(CachedAudio {
             path: path.clone(),
+            channel_mode: config.audio.channel_mode.mono(),
             data: audio_data.clone(),
         });
     }
@@ -378,12 +391,14 @@ async fn start_transcription(
         ).await;
     }
 
-    // 可能ならキャッシュを再利用
+    // 可能ならキャッシュを再利用（separate はチャネルごとの波形が必要なため常に読み込む）
+    let channel_mode = config_snapshot.audio.channel_mode;
+    let separate = channel_mode == ChannelMode::Separate;
     let mut use_cache = false;
     let audio_data: Vec<f32> = {
         if let Ok(cache_guard) = state.cached_audio.lock() {
             if let Some(cached) = cache_guard.as_ref() {
-                if cached.path == audio_path {
+                if !separate && cached.matches(&audio_path, channel_mode) {
                     use_cache = true;
                     cached.data.clone()
                 } else {
@@ -420,7 +435,7 @@ async fn start_transcription(
             phase: "done".to_string(),
             message: Some("読み込み完了".to_string()),
         });
-        audio_data
+        vec![audio_data]
     } else {
         // 音声ファイルの読み込み（未キャッシュ）
         let mut processor = AudioProcessor::new(&config_snapshot)
@@ -444,7 +459,7 @@ async fn start_transcription(
         let app_for_progress = app.clone();
         let fname_for_progress = fname.clone();
         let data = processor
-            .load_audio_file_with_progress(&audio_path, Some(move |done, total| {
+            .load_audio_channels_with_progress(&audio_path, Some(move |done, total| {
                 emit_task_progress(&app_for_progress, &TaskProgressPayload {
                     task: "file-read".to_string(),
                     filename: fname_for_progress.clone(),
@@ -456,12 +471,15 @@ async fn start_transcription(
             }))
             .map_err(|e| format!("音声読み込みエラー: {}", e))?;
 
-        // キャッシュへ保存
-        if let Ok(mut cache) = state.cached_audio.lock() {
-            *cache = Some(CachedAudio {
-                path: audio_path.clone(),
-                data: data.clone(),
-            });
+        // キャッシュへ保存（1 本にまとめた波形のみ）
+        if !separate {
+            if let Ok(mut cache) = state.cached_audio.lock() {
+                *cache = Some(CachedAudio {
+                    path: audio_path.clone(),
+                    channel_mode: channel_mode.mono(),
+                    data: data[0].clone(),
+                });
+            }
         }
 
         // 進捗100%（完了）
@@ -505,9 +523,20 @@ async fn start_transcription(
                     "" | "auto" => None,
                     other => Some(other),
                 };
-                engine
-                    .transcribe_with_timestamps(&audio_data, translate_to_english, lang_opt)
-                    .map_err(|e| format!("文字起こしに失敗しました: {}", e))
+                // チャネルごとに文字起こし（separate 以外は 1 本だけ）
+                let per_channel = audio_data
+                    .iter()
+                    .map(|samples| {
+                        engine
+                            .transcribe_with_timestamps(samples, translate_to_english, lang_opt)
+                            .map_err(|e| format!("文字起こしに失敗しました: {}", e))
+                    })
+                    .collect::<Result<Vec<_>, String>>()?;
+                if separate {
+                    Ok(merge_channel_segments(per_channel))
+                } else {
+                    Ok(per_channel.into_iter().next().unwrap_or_default())
+                }
             }
             None => Err("Whisperエンジンが初期化されていません".to_string()),
         }?
@@ -562,11 +591,14 @@ async fn transcribe_via_remote(
     // 1) cached_audio の path が一致し、preview.wav が存在すれば preview.wav を使う
     // 2) 1が無くても cached_audio の path が一致すれば、remote_upload.wav を生成して使う
     // 3) 2も無い場合は拡張子で許容されていれば元ファイル、そうでなければWAVへ変換
+    // separate はサーバ側でチャネルを分けるため、モノラル化済みのキャッシュは使わない
+    let channel_mode = config.audio.channel_mode;
     let orig_path = std::path::Path::new(audio_path);
+    let cached_audio = cached_audio.filter(|_| channel_mode != ChannelMode::Separate);
     let upload_path: std::path::PathBuf = if let Some(cached) = &cached_audio {
-        if cached.path == audio_path && preview_path.exists() {
+        if cached.matches(audio_path, channel_mode) && preview_path.exists() {
             preview_path
-        } else if cached.path == audio_path {
+        } else if cached.matches(audio_path, channel_mode) {
             let target = temp_dir.join("remote_upload.wav");
             // キャッシュ済みサンプルを直接WAV化（16kHz mono）
             let processor = AudioProcessor::new(config)
@@ -699,6 +731,9 @@ async fn transcribe_via_remote(
         if !lang_trim.is_empty() && lang_trim != "auto" {
             form = form.text("language", lang_trim.to_string());
         }
+        if channel_mode == ChannelMode::Separate {
+            form = form.text("channel_mode", channel_mode.to_string());
+        }
 
         let client = reqwest::blocking::Client::builder()
             .timeout(std::time::Duration::from_secs(request_timeout_secs))
@@ -771,6 +806,11 @@ async fn transcribe_via_remote(
                 .get("text")
                 .and_then(|v| v.as_str())
                 .unwrap_or("");
+            // separate のときはチャネル番号を付ける
+            let text = match seg.get("channel").and_then(|v| v.as_u64()) {
+                Some(channel) => format!("[ch{}] {}", channel, text.trim()),
+                None => text.to_string(),
+            };
             let (start_ms, end_ms) = if let (Some(s), Some(e)) = (
                 seg.get("start_time_ms").and_then(|v| v.as_u64()),
                 seg.get("end_time_ms").and_then(|v| v.as_u64()),
@@ -204,6 +204,24 @@ impl WhisperEngine {
     }
 }
 
+/// チャネルごとのセグメントを開始時刻順に 1 本へまとめ、テキストに `[chN]` を付ける。
+pub fn merge_channel_segments(channels: Vec<Vec<TranscriptionSegment>>) -> Vec<TranscriptionSegment> {
+    let mut merged: Vec<(usize, TranscriptionSegment)> = channels
+        .into_iter()
+        .enumerate()
+        .flat_map(|(channel, segments)| segments.into_iter().map(move |segment| (channel, segment)))
+        .collect();
+    merged.sort_by_key(|(channel, segment)| (segment.start_time_ms, *channel));
+
+    merged
+        .into_iter()
+        .map(|(channel, mut segment)| {
+            segment.text = format!("[ch{}] {}", channel, segment.text);
+            segment
+        })
+        .collect()
+}
+
 /// 1 セグメント分の認識結果。
 #[derive(Debug, Clone)]
 pub struct TranscriptionSegment {
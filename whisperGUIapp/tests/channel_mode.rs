@@ -0,0 +1,49 @@
+use whisperGUIapp::audio::ChannelMode;
+use whisperGUIapp::config::Config;
+use whisperGUIapp::whisper::{merge_channel_segments, TranscriptionSegment};
+
+#[test]
+fn default_channel_mode_is_mix() {
+    let cfg = Config::default();
+    assert_eq!(cfg.audio.channel_mode, ChannelMode::Mix);
+}
+
+#[test]
+fn channel_mode_is_read_from_toml() {
+    let mut cfg = Config::default();
+    cfg.audio.channel_mode = ChannelMode::Index(1);
+    let toml_str = toml::to_string(&cfg).unwrap();
+    assert!(toml_str.contains("channel_mode = \"1\""));
+
+    let toml_str = toml_str.replace("channel_mode = \"1\"", "channel_mode = \"separate\"");
+    let cfg: Config = toml::from_str(&toml_str).unwrap();
+    assert_eq!(cfg.audio.channel_mode, ChannelMode::Separate);
+}
+
+#[test]
+fn parse_channel_mode_values() {
+    assert_eq!(ChannelMode::parse("Left"), Some(ChannelMode::Left));
+    assert_eq!(ChannelMode::parse("right"), Some(ChannelMode::Right));
+    assert_eq!(ChannelMode::parse("mono"), Some(ChannelMode::Mix));
+    assert_eq!(ChannelMode::parse("2"), Some(ChannelMode::Index(2)));
+    assert_eq!(ChannelMode::parse("center"), None);
+    assert_eq!(ChannelMode::Separate.mono(), ChannelMode::Mix);
+}
+
+fn segment(text: &str, start_time_ms: u64) -> TranscriptionSegment {
+    TranscriptionSegment {
+        text: text.to_string(),
+        start_time_ms,
+        end_time_ms: start_time_ms + 500,
+    }
+}
+
+#[test]
+fn separate_segments_are_merged_by_start_time() {
+    let merged = merge_channel_segments(vec![
+        vec![segment("A1", 0), segment("A2", 3000)],
+        vec![segment("B1", 0), segment("B2", 1500)],
+    ]);
+    let texts: Vec<&str> = merged.iter().map(|s| s.text.as_str()).collect();
+    assert_eq!(texts, vec!["[ch0] A1", "[ch1] B1", "[ch1] B2", "[ch0] A2"]);
+}
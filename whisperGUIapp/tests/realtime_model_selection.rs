@@ -26,7 +26,7 @@ fn make_base_config(models_dir: &str, model_path: &str) -> Config {
             remote_server_endpoint: "/transcribe-with-timestamps".to_string(),
             request_timeout_secs: 10,
         },
-        audio: AudioConfig { sample_rate: 16000, channels: 1, buffer_size: 4096 },
+        audio: AudioConfig { sample_rate: 16000, channels: 1, buffer_size: 4096, channel_mode: Default::default() },
         gui: GuiConfig { window_width: 800.0, window_height: 600.0, window_title: "t".to_string(), theme: "Light".to_string() },
         performance: PerformanceConfig { audio_threads: 2, whisper_threads: 2, use_gpu: false },
         paths: PathsConfig { models_dir: models_dir.to_string(), output_dir: "output".to_string(), temp_dir: "temp".to_string() },